@@ -0,0 +1,22 @@
+;; An example git-rare plugin reporting hashes that start and end with the
+;; same character as Uncommon. Load it with:
+;;
+;;     git rare --plugin examples/plugins/bookends.wat
+;;
+;; See src/plugin.rs for the interface plugins implement.
+(module
+  (memory (export "memory") 1)
+  (data (i32.const 0) "Starts and ends with the same character")
+
+  ;; The hash is written after the explanation.
+  (func (export "alloc") (param $len i32) (result i32)
+    (i32.const 64))
+
+  (func (export "classify") (param $ptr i32) (param $len i32) (result i32)
+    (i32.eq
+      (i32.load8_u (local.get $ptr))
+      (i32.load8_u (i32.sub (i32.add (local.get $ptr) (local.get $len)) (i32.const 1)))))
+
+  ;; The explanation is the 39 bytes at offset 0.
+  (func (export "explanation") (result i64)
+    (i64.const 39)))
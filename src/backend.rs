@@ -0,0 +1,1899 @@
+//! Where the commit history is read from, chosen with `--backend`.
+
+use std::collections::{HashMap, HashSet};
+use std::fmt::{self, Display, Formatter};
+use std::io::{BufRead, BufReader, Read, Split, Write};
+use std::path::{Path, PathBuf};
+use std::process::{Child, ChildStdout, Command, Stdio};
+use std::sync::Arc;
+use std::thread::JoinHandle;
+
+use anyhow::{bail, Context, Result};
+use chrono::{DateTime, FixedOffset};
+use clap::ValueEnum;
+use regex::Regex;
+use xshell::{cmd, Shell};
+
+use crate::timings::{Phase, Timings};
+use crate::ObjectKind;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+pub enum Backend {
+    /// Run `git log` and parse its output.
+    Git,
+    /// Walk the commit graph in-process, without needing `git` installed.
+    #[cfg(feature = "libgit2")]
+    Libgit2,
+}
+
+impl Default for Backend {
+    /// The library when git-rare is built with it, `git` otherwise.
+    fn default() -> Self {
+        #[cfg(feature = "libgit2")]
+        return Backend::Libgit2;
+        #[cfg(not(feature = "libgit2"))]
+        Backend::Git
+    }
+}
+
+impl Backend {
+    /// The backend to read `repo`, or the current directory, with. libgit2
+    /// can't open repositories using the SHA-256 object format, so git is
+    /// run for those instead.
+    #[cfg_attr(not(feature = "libgit2"), allow(unused_variables))]
+    pub fn for_repo(self, repo: Option<&Path>) -> Self {
+        match self {
+            Backend::Git => Backend::Git,
+            #[cfg(feature = "libgit2")]
+            Backend::Libgit2 => match open(repo) {
+                Err(e) if is_unsupported_format(&e) => Backend::Git,
+                _ => Backend::Libgit2,
+            },
+        }
+    }
+
+    /// The backend to read the commits `options` selects in `repo` with,
+    /// like [`Backend::for_repo`]. libgit2 can't limit a walk to paths with
+    /// the history simplification `git log` does, so git is run for that.
+    pub fn for_log(self, repo: Option<&Path>, options: &LogOptions) -> Self {
+        if options.paths.is_empty() {
+            self.for_repo(repo)
+        } else {
+            Backend::Git
+        }
+    }
+}
+
+/// Whether `error` is libgit2 refusing a repository's object format, as set
+/// by its `extensions.objectformat`.
+#[cfg(feature = "libgit2")]
+fn is_unsupported_format(error: &anyhow::Error) -> bool {
+    error.chain().any(|e| {
+        e.downcast_ref::<git2::Error>().is_some_and(|e| {
+            e.code() == git2::ErrorCode::Invalid && e.message().contains("object format")
+        })
+    })
+}
+
+/// The dates to scan commits between, from `--since` and `--until`. Both
+/// ends are included, like `git log` includes them.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct DateRange {
+    pub since: Option<DateTime<FixedOffset>>,
+    pub until: Option<DateTime<FixedOffset>>,
+}
+
+impl DateRange {
+    /// Whether the range lets every commit through.
+    pub fn is_unbounded(&self) -> bool {
+        self.since.is_none() && self.until.is_none()
+    }
+
+    pub fn contains(&self, datetime: &DateTime<FixedOffset>) -> bool {
+        self.since.is_none_or(|since| *datetime >= since)
+            && self.until.is_none_or(|until| *datetime <= until)
+    }
+
+    /// The `git log` options that select the range.
+    fn git_options(&self) -> Vec<String> {
+        let since = self
+            .since
+            .map(|since| format!("--since={}", since.to_rfc3339()));
+        let until = self
+            .until
+            .map(|until| format!("--until={}", until.to_rfc3339()));
+        since.into_iter().chain(until).collect()
+    }
+}
+
+/// Which commits to read by whether they're merges.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Merges {
+    #[default]
+    Include,
+    Exclude,
+    Only,
+}
+
+impl Merges {
+    /// Whether a commit with `parents` parents is read.
+    #[cfg_attr(not(feature = "libgit2"), allow(dead_code))]
+    pub fn allows(self, parents: usize) -> bool {
+        match self {
+            Merges::Include => true,
+            Merges::Exclude => parents <= 1,
+            Merges::Only => parents > 1,
+        }
+    }
+}
+
+/// Which of the walked commits a backend reads.
+#[derive(Clone, Debug, Default)]
+pub struct LogOptions {
+    pub dates: DateRange,
+    /// Patterns matched against a commit's `Name <email>` author, like
+    /// `git log --author`. A commit matching any of them is read.
+    pub authors: Vec<Regex>,
+    /// Patterns matched against the lines of a commit's message, like
+    /// `git log --grep`. A commit matching any of them is read, or all of
+    /// them with `all_match`.
+    pub greps: Vec<Regex>,
+    pub all_match: bool,
+    /// Whether the commits whose message matches are left out instead, like
+    /// `git log --invert-grep`.
+    pub invert_grep: bool,
+    /// Pathspecs the commits must modify, passed to `git log` after `--`.
+    pub paths: Vec<String>,
+    /// How many of the most recent commits to read, after filtering them.
+    pub max_count: Option<usize>,
+    /// Whether only the first parent of merges is followed.
+    pub first_parent: bool,
+    pub merges: Merges,
+    /// Whether authors are read by the canonical names and emails the
+    /// repository's `.mailmap` gives them, both to show and to filter by.
+    pub mailmap: bool,
+    /// Whether commits are read by their committer and commit date instead
+    /// of their author and author date.
+    pub committer: bool,
+    /// Whether `dates` are compared to the author date instead of the
+    /// commit date, which is the one `git log` filters by.
+    pub author_dates: bool,
+}
+
+impl LogOptions {
+    /// Whether all of the walked history passes the filters, though only
+    /// `max_count` of its commits may be read.
+    pub fn is_unfiltered(&self) -> bool {
+        self.dates.is_unbounded()
+            && self.authors.is_empty()
+            && self.greps.is_empty()
+            && self.paths.is_empty()
+            && !self.first_parent
+            && self.merges == Merges::Include
+    }
+
+    /// Whether annotated tag objects can be read along with the commits.
+    /// They have a tagger rather than an author, and don't modify any paths
+    /// or merge anything. Their messages aren't searched either, like
+    /// `git log --grep` doesn't search them.
+    pub fn allows_tag_objects(&self) -> bool {
+        self.authors.is_empty()
+            && self.greps.is_empty()
+            && self.paths.is_empty()
+            && self.merges != Merges::Only
+    }
+
+    pub fn matches_author(&self, author: &str) -> bool {
+        self.authors.is_empty() || self.authors.iter().any(|regex| regex.is_match(author))
+    }
+
+    /// Whether a commit with `message` is read, matching each pattern
+    /// against its lines one at a time like git does.
+    #[cfg_attr(not(feature = "libgit2"), allow(dead_code))]
+    pub fn matches_message(&self, message: &str) -> bool {
+        if self.greps.is_empty() {
+            return true;
+        }
+        let matches = |regex: &Regex| message.lines().any(|line| regex.is_match(line));
+        let matched = if self.all_match {
+            self.greps.iter().all(matches)
+        } else {
+            self.greps.iter().any(matches)
+        };
+        matched != self.invert_grep
+    }
+
+    /// Whether the dates are compared to the author date, which `git log`
+    /// can't filter by, so its commits are filtered as they're read.
+    fn filters_author_dates(&self) -> bool {
+        self.author_dates && !self.dates.is_unbounded()
+    }
+
+    /// Whether the commits `git log` prints are filtered as they're read:
+    /// by author date, or by author, which it would match with its own
+    /// regular expressions rather than [`Regex`]'s.
+    fn filters_git_log(&self) -> bool {
+        self.filters_author_dates() || !self.authors.is_empty()
+    }
+
+    /// The `git log` options that select the commits. The patterns of
+    /// `greps` are read as extended regular expressions, which are closest
+    /// to [`Regex`]'s, while authors are left to [`GitLog`] to match.
+    fn git_options(&self) -> Vec<String> {
+        let mut options = vec![];
+        if !self.filters_author_dates() {
+            options.extend(self.dates.git_options());
+        }
+        // When git doesn't filter all of them, it can't count the commits
+        // left either.
+        if let Some(max_count) = self.max_count.filter(|_| !self.filters_git_log()) {
+            options.push(format!("--max-count={max_count}"));
+        }
+        if self.first_parent {
+            options.push("--first-parent".to_string());
+        }
+        match self.merges {
+            Merges::Include => {}
+            Merges::Exclude => options.push("--no-merges".to_string()),
+            Merges::Only => options.push("--merges".to_string()),
+        }
+        // `log.mailmap` may be set either way, so it's always overridden.
+        options.push(
+            if self.mailmap {
+                "--use-mailmap"
+            } else {
+                "--no-use-mailmap"
+            }
+            .to_string(),
+        );
+        if !self.greps.is_empty() {
+            options.push("--extended-regexp".to_string());
+        }
+        options.extend(
+            self.greps
+                .iter()
+                .map(|regex| format!("--grep={}", regex.as_str())),
+        );
+        if self.all_match {
+            options.push("--all-match".to_string());
+        }
+        if self.invert_grep {
+            options.push("--invert-grep".to_string());
+        }
+        options
+    }
+}
+
+/// A commit read by a backend, before it is classified.
+pub struct LogEntry {
+    pub hash: String,
+    pub author: String,
+    /// The author's email, or the committer's with [`LogOptions::committer`].
+    /// Tags have none.
+    pub email: String,
+    pub datetime: DateTime<FixedOffset>,
+    pub message: String,
+}
+
+/// The commits read by a backend, newest first. They're only read as the
+/// entries are iterated, so a long history is never held in memory at once.
+pub struct Log {
+    /// How many commits the history has, when that's known before reading
+    /// them. Fewer are read when [`LogOptions::max_count`] is set.
+    pub len: Option<usize>,
+    pub entries: Box<dyn Iterator<Item = Result<LogEntry>>>,
+}
+
+/// Runs `git log` in `repo`, or the current directory, reading its commits
+/// as it prints them. The `revisions` are passed to it as is, so git reports
+/// the invalid ones, and so are the pathspecs. When scanning all of HEAD, its
+/// commits are counted first with `git rev-list`. The time spent waiting
+/// for git and parsing what it prints is added to `timings`.
+pub fn git_log(
+    repo: Option<&Path>,
+    revisions: &[String],
+    options: &LogOptions,
+    timings: &Arc<Timings>,
+) -> Result<Log> {
+    let sh = git_shell(repo)?;
+    let len = if revisions.is_empty() && options.is_unfiltered() {
+        // When this fails, so will `git log`, with a better message.
+        cmd!(sh, "git rev-list --count HEAD --")
+            .quiet()
+            .ignore_stderr()
+            .read()
+            .ok()
+            .and_then(|count| count.parse().ok())
+    } else {
+        None
+    };
+    read_git_log(&sh, revisions, &[], options, timings, len)
+}
+
+/// Runs `git log` in the shell's directory like [`git_log`], writing the
+/// `stdin` revisions to it after the `revisions` it's given, for when
+/// there may be too many to pass as arguments. `len` is how many commits it
+/// will read, when that's known.
+fn read_git_log(
+    sh: &Shell,
+    revisions: &[String],
+    stdin: &[String],
+    options: &LogOptions,
+    timings: &Arc<Timings>,
+    len: Option<usize>,
+) -> Result<Log> {
+    // Get the logs with their fields separated by NULs, which no name or
+    // email can contain:
+    // Hash\0Date\0Author\0Email\0Message\x1e
+    // e83c5163316f89bfbde7d9ab23ca2e25604af290\02024-09-28T17:45:47+00:00\0John Doe\0john@example.com\0Fix #12\x1e
+    // where `%aN` is the author's name after applying `.mailmap`, and `%c`
+    // the committer's in place of `%a`. With authors to match, the message
+    // starts with the author's `Name <email>` and a NUL, whoever is shown.
+    // Each field is its own argument, so nothing is quoted for a shell on
+    // any platform. Messages are re-encoded as UTF-8 whatever
+    // `i18n.logOutputEncoding` says, since Git for Windows may be set up for
+    // the console's code page. The trailing `--` keeps a revision from being
+    // taken for a path.
+    let shown = match (options.committer, options.mailmap) {
+        (false, true) => "%aI%x00%aN%x00%aE",
+        (false, false) => "%aI%x00%an%x00%ae",
+        (true, true) => "%cI%x00%cN%x00%cE",
+        (true, false) => "%cI%x00%cn%x00%ce",
+    };
+    let identity = match (options.authors.is_empty(), options.mailmap) {
+        (true, _) => "",
+        (false, true) => "%aN <%aE>%x00",
+        (false, false) => "%an <%ae>%x00",
+    };
+    let format = format!("--pretty=format:%H%x00{shown}%x00{identity}%B%x1e");
+    let mut child = Command::new("git")
+        .current_dir(sh.current_dir())
+        .args(["log", "--encoding=UTF-8", &format])
+        .args(options.git_options())
+        .args(revisions)
+        .args(if stdin.is_empty() {
+            None
+        } else {
+            Some("--stdin")
+        })
+        .arg("--")
+        .args(&options.paths)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .context("failed to run git log")?;
+    let stderr = read_stderr(&mut child);
+    let mut pipe = child.stdin.take().expect("stdin is piped");
+    let input = stdin
+        .iter()
+        .map(|revision| format!("{revision}\n"))
+        .collect::<String>();
+    // Written while the log is read, so neither pipe fills up. Git stops
+    // reading when it fails, which it reports itself.
+    std::thread::spawn(move || {
+        let _ = pipe.write_all(input.as_bytes());
+    });
+    let stdout = child.stdout.take().expect("stdout is piped");
+    let entries = GitLog {
+        child,
+        stderr: Some(stderr),
+        records: BufReader::new(stdout).split(crate::RECORD_SEPARATOR as u8),
+        options: options.clone(),
+        done: false,
+        timings: Arc::clone(timings),
+    };
+    if !options.filters_git_log() {
+        return Ok(Log {
+            len,
+            entries: Box::new(entries),
+        });
+    }
+    let (dates, filters_dates) = (options.dates, options.filters_author_dates());
+    let entries = entries.filter(move |entry| {
+        entry.as_ref().map_or(true, |entry| {
+            !filters_dates || dates.contains(&entry.datetime)
+        })
+    });
+    Ok(Log {
+        len,
+        entries: match options.max_count {
+            Some(max_count) => Box::new(entries.take(max_count)),
+            None => Box::new(entries),
+        },
+    })
+}
+
+/// The commits printed by a running `git log`, in the format read by
+/// [`crate::parse_log`].
+struct GitLog {
+    child: Child,
+    stderr: Option<JoinHandle<String>>,
+    records: Split<BufReader<ChildStdout>>,
+    /// The options whose [`LogOptions::authors`] the commits are matched
+    /// against, when the format starts their messages with their author.
+    options: LogOptions,
+    done: bool,
+    timings: Arc<Timings>,
+}
+
+impl GitLog {
+    /// Waits for git to exit, failing with its error when it didn't succeed.
+    fn finish(&mut self) -> Result<()> {
+        let status = self.child.wait()?;
+        let stderr = self.stderr.take().map(JoinHandle::join);
+        if !status.success() {
+            bail!("{}", stderr.and_then(Result::ok).unwrap_or_default().trim());
+        }
+        Ok(())
+    }
+}
+
+impl Iterator for GitLog {
+    type Item = Result<LogEntry>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while !self.done {
+            match self.timings.time(Phase::Git, || self.records.next()) {
+                Some(Ok(record)) => {
+                    let _parsing = self.timings.measure(Phase::Parsing);
+                    let record = String::from_utf8_lossy(&record);
+                    if let Some((hash, datetime, author, email, message)) =
+                        crate::split_commit(record.trim_start())
+                    {
+                        let message = if self.options.authors.is_empty() {
+                            message
+                        } else {
+                            let Some((identity, message)) = message.split_once('\0') else {
+                                continue;
+                            };
+                            if !self.options.matches_author(identity) {
+                                continue;
+                            }
+                            message
+                        };
+                        return Some(Ok(LogEntry {
+                            hash: hash.to_string(),
+                            author,
+                            email: email.to_string(),
+                            datetime,
+                            message: message.to_string(),
+                        }));
+                    }
+                }
+                Some(Err(e)) => {
+                    self.done = true;
+                    return Some(Err(e.into()));
+                }
+                None => {
+                    self.done = true;
+                    return self.finish().err().map(Err);
+                }
+            }
+        }
+        None
+    }
+}
+
+impl Drop for GitLog {
+    fn drop(&mut self) {
+        if !self.done {
+            // The rest of the log won't be read.
+            let _ = self.child.kill();
+        }
+        let _ = self.child.wait();
+    }
+}
+
+/// Reads what `child` prints on stderr on a thread of its own, so git can't
+/// stall on a full pipe while its stdout is still being read. The thread
+/// returns it all once git closes the pipe, or exits.
+fn read_stderr(child: &mut Child) -> JoinHandle<String> {
+    let mut pipe = child.stderr.take().expect("stderr is piped");
+    std::thread::spawn(move || {
+        let mut stderr = vec![];
+        let _ = pipe.read_to_end(&mut stderr);
+        String::from_utf8_lossy(&stderr).into_owned()
+    })
+}
+
+/// Checks that git can be found on `PATH` the way commands are looked up,
+/// as `git.exe` on Windows, so its absence is reported as such rather than
+/// as a failure to run one of them.
+fn check_git() -> Result<()> {
+    let name = format!("git{}", std::env::consts::EXE_SUFFIX);
+    let found = std::env::var_os("PATH")
+        .iter()
+        .flat_map(std::env::split_paths)
+        .any(|dir| dir.join(&name).is_file());
+    if found {
+        return Ok(());
+    }
+    let install = if cfg!(windows) {
+        "install Git for Windows and add its `cmd` directory, like \
+         C:\\Program Files\\Git\\cmd, to PATH"
+    } else {
+        "install git or add its directory to PATH"
+    };
+    let instead = if cfg!(feature = "libgit2") {
+        ", or pass --backend libgit2 to read the repository without it"
+    } else {
+        ""
+    };
+    bail!("{name} wasn't found on PATH: {install}{instead}");
+}
+
+/// A shell in `repo`, or the current directory, checked to be in a git
+/// repository.
+fn git_shell(repo: Option<&Path>) -> Result<Shell> {
+    check_git()?;
+    let sh = Shell::new()?;
+    if let Some(repo) = repo {
+        check_dir(repo)?;
+        sh.change_dir(repo);
+    }
+    let found = cmd!(sh, "git rev-parse --git-dir")
+        .quiet()
+        .ignore_status()
+        .ignore_stdout()
+        .output()?;
+    if !found.status.success() {
+        let detail = String::from_utf8_lossy(&found.stderr).trim().to_string();
+        if !detail.contains("not a git repository") {
+            bail!("{detail}");
+        }
+        return Err(NotARepository::new(repo, detail).into());
+    }
+    Ok(sh)
+}
+
+/// The git directory that `repo`, or the current directory, shares with all
+/// of its worktrees, where what isn't a worktree's own is kept.
+pub fn git_common_dir(repo: Option<&Path>) -> Result<PathBuf> {
+    let sh = git_shell(repo)?;
+    let dir = cmd!(sh, "git rev-parse --git-common-dir").quiet().read()?;
+    // It's relative to the directory git was run in, unless it's elsewhere.
+    Ok(sh.current_dir().join(dir))
+}
+
+/// The directory git runs the hooks of `repo`, or the current directory,
+/// from: `core.hooksPath` when it's set, or the common git directory's
+/// `hooks`. Since git is the one running them, it's asked even with the
+/// libgit2 backend.
+pub fn git_hooks_dir(repo: Option<&Path>) -> Result<PathBuf> {
+    let sh = git_shell(repo)?;
+    let dir = cmd!(sh, "git rev-parse --git-path hooks").quiet().read()?;
+    Ok(sh.current_dir().join(dir))
+}
+
+/// The URL of the remote `name` of `repo`, or the current directory, or
+/// `None` when it has no such remote.
+pub fn git_remote_url(repo: Option<&Path>, name: &str) -> Result<Option<String>> {
+    let sh = git_shell(repo)?;
+    let output = cmd!(sh, "git remote get-url {name}")
+        .quiet()
+        .ignore_status()
+        .ignore_stderr()
+        .output()?;
+    let url = String::from_utf8_lossy(&output.stdout);
+    Ok(output.status.success().then(|| url.trim().to_string()))
+}
+
+/// Calls `f` with the hash and type of every object in `repo`, or the
+/// current directory, until it returns false. The objects are streamed from
+/// `git cat-file` rather than buffered, since there can be millions.
+pub fn git_objects(
+    repo: Option<&Path>,
+    mut f: impl FnMut(String, ObjectKind) -> bool,
+) -> Result<()> {
+    let sh = git_shell(repo)?;
+    let mut child = Command::new("git")
+        .current_dir(sh.current_dir())
+        .args([
+            "cat-file",
+            "--batch-all-objects",
+            "--batch-check=%(objectname) %(objecttype)",
+            "--unordered",
+        ])
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .context("failed to run git cat-file")?;
+    let stderr = read_stderr(&mut child);
+    let stdout = child.stdout.take().expect("stdout is piped");
+    let mut stopped = false;
+    for line in BufReader::new(stdout).lines() {
+        let line = line?;
+        let Some((hash, object)) = line
+            .split_once(' ')
+            .and_then(|(hash, kind)| Some((hash, ObjectKind::from_type(kind)?)))
+        else {
+            continue;
+        };
+        if !f(hash.to_string(), object) {
+            stopped = true;
+            break;
+        }
+    }
+    if stopped {
+        // Nothing more is read, so there's no use in listing the rest.
+        let _ = child.kill();
+    }
+    let status = child.wait()?;
+    let stderr = stderr.join().unwrap_or_default();
+    if !stopped && !status.success() {
+        bail!("{}", stderr.trim());
+    }
+    Ok(())
+}
+
+/// Finds a path at which each of the `hashes` of trees and blobs is
+/// referenced from some ref, by streaming `git rev-list --objects --all`
+/// until all of them are found.
+pub fn git_object_paths(
+    repo: Option<&Path>,
+    hashes: &HashSet<String>,
+) -> Result<HashMap<String, String>> {
+    let mut paths = HashMap::new();
+    if hashes.is_empty() {
+        return Ok(paths);
+    }
+    let sh = git_shell(repo)?;
+    let mut child = Command::new("git")
+        .current_dir(sh.current_dir())
+        .args(["rev-list", "--objects", "--all"])
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn()
+        .context("failed to run git rev-list")?;
+    let stdout = child.stdout.take().expect("stdout is piped");
+    for line in BufReader::new(stdout).lines() {
+        let line = line?;
+        if let Some((hash, path)) = line.split_once(' ') {
+            if hashes.contains(hash) {
+                paths
+                    .entry(hash.to_string())
+                    .or_insert_with(|| path.to_string());
+                if paths.len() == hashes.len() {
+                    break;
+                }
+            }
+        }
+    }
+    let _ = child.kill();
+    child.wait()?;
+    Ok(paths)
+}
+
+/// Whether `repo`, or the current directory, is a shallow clone, whose
+/// history stops short at the commits it was cloned to the depth of.
+pub fn git_is_shallow(repo: Option<&Path>) -> Result<bool> {
+    let sh = git_shell(repo)?;
+    let shallow = cmd!(sh, "git rev-parse --is-shallow-repository")
+        .quiet()
+        .read()?;
+    Ok(shallow == "true")
+}
+
+/// The commits the reflogs in `repo`, or the current directory, record that
+/// no ref or HEAD can reach anymore.
+pub fn git_unreachable(repo: Option<&Path>) -> Result<HashSet<String>> {
+    let sh = git_shell(repo)?;
+    let hashes = cmd!(sh, "git rev-list --reflog --not --all")
+        .quiet()
+        .read()?;
+    Ok(hashes.lines().map(str::to_string).collect())
+}
+
+/// The commits in `repo`, or the current directory, that no ref or HEAD can
+/// reach, even if a reflog still records them, as streamed from
+/// `git fsck --unreachable --no-reflogs`. Only its connectivity is checked,
+/// which is all it needs to read.
+pub fn git_lost(repo: Option<&Path>) -> Result<Vec<String>> {
+    let sh = git_shell(repo)?;
+    let mut child = Command::new("git")
+        .current_dir(sh.current_dir())
+        .args([
+            "fsck",
+            "--unreachable",
+            "--no-reflogs",
+            "--connectivity-only",
+            "--no-progress",
+        ])
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .context("failed to run git fsck")?;
+    let stderr = read_stderr(&mut child);
+    let stdout = child.stdout.take().expect("stdout is piped");
+    let mut hashes = vec![];
+    for line in BufReader::new(stdout).lines() {
+        if let Some(hash) = line?.strip_prefix("unreachable commit ") {
+            hashes.push(hash.to_string());
+        }
+    }
+    let status = child.wait()?;
+    let stderr = stderr.join().unwrap_or_default();
+    if !status.success() {
+        bail!("{}", stderr.trim());
+    }
+    Ok(hashes)
+}
+
+/// Creates a `refs/rare/<hash>` ref for each of the commits `hashes` in
+/// `repo`, or the current directory, so `git gc` keeps them.
+pub fn git_rescue(repo: Option<&Path>, hashes: &[String]) -> Result<()> {
+    let sh = git_shell(repo)?;
+    let updates = hashes
+        .iter()
+        .map(|hash| format!("update refs/rare/{hash} {hash}\n"))
+        .collect::<String>();
+    cmd!(sh, "git update-ref --stdin")
+        .stdin(updates)
+        .quiet()
+        .run()?;
+    Ok(())
+}
+
+/// The note under `notes_ref` on each of the objects `hashes` in `repo`, or
+/// the current directory, if it has one. The notes are listed, and those of
+/// `hashes` read, by a single git each however many there are.
+pub fn git_notes(
+    repo: Option<&Path>,
+    notes_ref: &str,
+    hashes: &[String],
+) -> Result<Vec<Option<String>>> {
+    let sh = git_shell(repo)?;
+    // A line of the blob of each note and the object it's on.
+    let listed = cmd!(sh, "git notes --ref={notes_ref} list")
+        .quiet()
+        .read()?;
+    let blobs = listed
+        .lines()
+        .filter_map(|line| {
+            let (blob, object) = line.split_once(' ')?;
+            Some((object, blob))
+        })
+        .collect::<HashMap<&str, &str>>();
+    let wanted = hashes
+        .iter()
+        .filter_map(|hash| blobs.get(hash.as_str()))
+        .map(|blob| format!("{blob}\n"))
+        .collect::<String>();
+    let notes = if wanted.is_empty() {
+        HashMap::new()
+    } else {
+        let output = cmd!(sh, "git cat-file --batch")
+            .stdin(wanted)
+            .quiet()
+            .output()?;
+        parse_batch(&output.stdout)
+    };
+    Ok(hashes
+        .iter()
+        .map(|hash| {
+            let blob = blobs.get(hash.as_str())?;
+            notes.get(*blob).cloned()
+        })
+        .collect())
+}
+
+/// The contents of the objects `git cat-file --batch` prints, by their hash:
+/// a line of the hash, type and size of each, followed by that many bytes
+/// and a newline.
+fn parse_batch(output: &[u8]) -> HashMap<String, String> {
+    let mut contents = HashMap::new();
+    let mut rest = output;
+    while let Some(end) = rest.iter().position(|&b| b == b'\n') {
+        let header = String::from_utf8_lossy(&rest[..end]);
+        let mut fields = header.split(' ');
+        let (Some(hash), Some(_), Some(Ok(size))) = (
+            fields.next(),
+            fields.next(),
+            fields.next().map(str::parse::<usize>),
+        ) else {
+            // A missing object has no contents to skip.
+            rest = &rest[end + 1..];
+            continue;
+        };
+        let Some(content) = rest.get(end + 1..end + 1 + size) else {
+            break;
+        };
+        contents.insert(
+            hash.to_string(),
+            String::from_utf8_lossy(content).into_owned(),
+        );
+        rest = rest.get(end + 2 + size..).unwrap_or_default();
+    }
+    contents
+}
+
+/// Writes each of `notes`, a hash and the text of its note, under
+/// `notes_ref` in `repo`, or the current directory, replacing any note
+/// the object had. They're written by one `git fast-import`, in a single
+/// commit on top of `notes_ref`, as the committer git is set up with.
+pub fn git_write_notes(
+    repo: Option<&Path>,
+    notes_ref: &str,
+    notes: &[(String, String)],
+) -> Result<()> {
+    if notes.is_empty() {
+        return Ok(());
+    }
+    let sh = git_shell(repo)?;
+    let committer = cmd!(sh, "git var GIT_COMMITTER_IDENT").quiet().read()?;
+    let message = "Notes added by 'git-rare annotate'\n";
+    let mut stream = format!(
+        "commit {notes_ref}\ncommitter {committer}\ndata {}\n{message}",
+        message.len()
+    );
+    let parent = cmd!(sh, "git rev-parse --verify --quiet {notes_ref}")
+        .quiet()
+        .ignore_status()
+        .read()?;
+    if !parent.is_empty() {
+        stream.push_str(&format!("from {parent}\n"));
+    }
+    for (hash, text) in notes {
+        stream.push_str(&format!("N inline {hash}\ndata {}\n{text}\n", text.len()));
+    }
+    cmd!(sh, "git fast-import --quiet")
+        .stdin(stream)
+        .quiet()
+        .run()?;
+    Ok(())
+}
+
+/// Creates a lightweight tag for each of `tags`, a name and the hash it
+/// points to, in `repo`, or the current directory, moving any tag that
+/// already has the name.
+pub fn git_create_tags(repo: Option<&Path>, tags: &[(String, String)]) -> Result<()> {
+    let sh = git_shell(repo)?;
+    let updates = tags
+        .iter()
+        .map(|(name, hash)| format!("update refs/tags/{name} {hash}\n"))
+        .collect::<String>();
+    cmd!(sh, "git update-ref --stdin")
+        .stdin(updates)
+        .quiet()
+        .run()?;
+    Ok(())
+}
+
+/// Whether HEAD in `repo`, or the current directory, is a merge commit.
+pub fn git_head_is_merge(repo: Option<&Path>) -> Result<bool> {
+    let sh = git_shell(repo)?;
+    let output = cmd!(sh, "git rev-parse --verify --quiet HEAD^2")
+        .quiet()
+        .ignore_status()
+        .output()?;
+    Ok(output.status.success())
+}
+
+/// Whether a rebase is under way in `repo`, or the current directory, which
+/// leaves a `rebase-merge` or `rebase-apply` directory in the git directory.
+pub fn git_is_rebasing(repo: Option<&Path>) -> Result<bool> {
+    let sh = git_shell(repo)?;
+    for state in ["rebase-merge", "rebase-apply"] {
+        let path = cmd!(sh, "git rev-parse --git-path {state}")
+            .quiet()
+            .read()?;
+        if sh.current_dir().join(path).exists() {
+            return Ok(true);
+        }
+    }
+    Ok(false)
+}
+
+/// Adds `trailer` to the message of HEAD in `repo`, or the current
+/// directory, like `git interpret-trailers` adds it, by amending HEAD. Only
+/// the message changes, not what was staged since, and the hooks that check
+/// a message aren't run, though the post-commit hook is, with the `env`
+/// given. Returns false without amending when the message already has a
+/// trailer with the same key.
+pub fn git_amend_trailer(repo: Option<&Path>, trailer: &str, env: (&str, &str)) -> Result<bool> {
+    let sh = git_shell(repo)?;
+    let message = cmd!(sh, "git log -1 --encoding=UTF-8 --format=%B HEAD")
+        .quiet()
+        .read()?;
+    let trailers = cmd!(sh, "git interpret-trailers --parse")
+        .stdin(&message)
+        .quiet()
+        .read()?;
+    let key = trailer.split_once(':').map_or(trailer, |(key, _)| key);
+    if trailers.lines().any(|line| {
+        line.split_once(':')
+            .is_some_and(|(k, _)| k.eq_ignore_ascii_case(key))
+    }) {
+        return Ok(false);
+    }
+    let message = cmd!(sh, "git interpret-trailers --trailer {trailer}")
+        .stdin(&message)
+        .quiet()
+        .read()?;
+    cmd!(
+        sh,
+        "git commit --quiet --amend --only --allow-empty --no-verify --cleanup=verbatim -F -"
+    )
+    .env(env.0, env.1)
+    .stdin(format!("{message}\n"))
+    .quiet()
+    .run()?;
+    Ok(true)
+}
+
+/// A submodule of a repository, or of one of its submodules.
+pub struct Submodule {
+    /// Where it's checked out, relative to the directory it was listed in.
+    pub path: PathBuf,
+    /// Whether it's checked out, so it can be scanned.
+    pub initialized: bool,
+}
+
+/// The submodules of `repo`, or the current directory, and theirs, as
+/// `git submodule status --recursive` lists them. A bare repository has
+/// none checked out.
+pub fn git_submodules(repo: Option<&Path>) -> Result<Vec<Submodule>> {
+    let sh = git_shell(repo)?;
+    // A bare repository has no work tree to check submodules out in, and
+    // git refuses to list them.
+    let bare = cmd!(sh, "git rev-parse --is-bare-repository")
+        .quiet()
+        .read()?;
+    if bare == "true" {
+        return Ok(vec![]);
+    }
+    let status = cmd!(sh, "git submodule status --recursive")
+        .quiet()
+        .read()?;
+    // Each line is a state, the checked out commit, the path and, for
+    // those checked out, its description in parentheses.
+    let submodules = status
+        .lines()
+        .filter_map(|line| {
+            let initialized = !line.starts_with('-');
+            let (_, rest) = line.get(1..)?.split_once(' ')?;
+            let path = match rest.rfind(" (") {
+                Some(end) if initialized && rest.ends_with(')') => &rest[..end],
+                _ => rest,
+            };
+            Some(Submodule {
+                path: PathBuf::from(path),
+                initialized,
+            })
+        })
+        .collect();
+    Ok(submodules)
+}
+
+/// The submodules of `repo`, or the current directory, and theirs, like
+/// [`git_submodules`].
+#[cfg(feature = "libgit2")]
+pub fn libgit2_submodules(repo: Option<&Path>) -> Result<Vec<Submodule>> {
+    let dir = std::fs::canonicalize(repo.unwrap_or(Path::new(".")))?;
+    let repo = open(repo)?;
+    let mut submodules = vec![];
+    add_submodules(&repo, &dir, &mut submodules)?;
+    Ok(submodules)
+}
+
+/// Adds the submodules of `repo` and theirs to `submodules`, with their
+/// paths relative to `dir` when they're inside it. A bare repository has
+/// none checked out.
+#[cfg(feature = "libgit2")]
+fn add_submodules(
+    repo: &git2::Repository,
+    dir: &Path,
+    submodules: &mut Vec<Submodule>,
+) -> Result<()> {
+    let Some(workdir) = repo.workdir() else {
+        return Ok(());
+    };
+    let workdir = std::fs::canonicalize(workdir)?;
+    for submodule in repo.submodules()? {
+        let checkout = workdir.join(submodule.path());
+        let path = checkout
+            .strip_prefix(dir)
+            .map_or_else(|_| checkout.clone(), Path::to_path_buf);
+        // One that isn't checked out has no repository to open.
+        let opened = submodule.open().ok();
+        submodules.push(Submodule {
+            path,
+            initialized: opened.is_some(),
+        });
+        if let Some(opened) = opened {
+            add_submodules(&opened, dir, submodules)?;
+        }
+    }
+    Ok(())
+}
+
+/// A commit a stash is made of, named like `git stash list` names the stash,
+/// with `^2` after it for the commit of its index and `^3` for that of its
+/// untracked files.
+pub struct StashCommit {
+    pub name: String,
+    pub hash: String,
+}
+
+/// The commits of every stash in `repo`, or the current directory, newest
+/// stash first.
+pub fn git_stashes(repo: Option<&Path>) -> Result<Vec<StashCommit>> {
+    let sh = git_shell(repo)?;
+    let stashed = cmd!(sh, "git rev-parse --verify --quiet refs/stash")
+        .quiet()
+        .ignore_status()
+        .ignore_stdout()
+        .output()?;
+    if !stashed.status.success() {
+        return Ok(vec![]);
+    }
+    let format = "%H %P";
+    let stashes = cmd!(sh, "git log --walk-reflogs --format={format} refs/stash --")
+        .quiet()
+        .read()?;
+    Ok(stash_commits(
+        stashes
+            .lines()
+            .map(|line| line.split(' ').map(str::to_string).collect()),
+    ))
+}
+
+/// Names the commits of each stash, given as its hash followed by its
+/// parents'. The first parent is the commit the stash was made on, which
+/// isn't part of it, and a commit shared by several stashes is only listed
+/// for the newest.
+fn stash_commits(stashes: impl Iterator<Item = Vec<String>>) -> Vec<StashCommit> {
+    let mut seen = HashSet::new();
+    let mut commits = vec![];
+    for (i, hashes) in stashes.enumerate() {
+        let stash = format!("stash@{{{i}}}");
+        let named = hashes
+            .into_iter()
+            .enumerate()
+            .filter_map(|(j, hash)| match j {
+                0 => Some((stash.clone(), hash)),
+                1 => None,
+                _ => Some((format!("{stash}^{j}"), hash)),
+            });
+        for (name, hash) in named {
+            if seen.insert(hash.clone()) {
+                commits.push(StashCommit { name, hash });
+            }
+        }
+    }
+    commits
+}
+
+/// The letter `git log` prints for the signature of each of the commits
+/// `hashes` in `repo`, or the current directory, as [`crate::parse_signatures`]
+/// reads them. Checking a signature runs gpg, or ssh-keygen for SSH
+/// signatures, which is slow, so only the commits asked about are.
+pub fn git_signatures(repo: Option<&Path>, hashes: &[String]) -> Result<HashMap<String, char>> {
+    if hashes.is_empty() {
+        return Ok(HashMap::new());
+    }
+    let sh = git_shell(repo)?;
+    let input = hashes
+        .iter()
+        .map(|hash| format!("{hash}\n"))
+        .collect::<String>();
+    let output = cmd!(sh, "git log --no-walk=unsorted --stdin --format=%H%x00%G?")
+        .quiet()
+        .stdin(input)
+        .read()?;
+    Ok(crate::parse_signatures(&output))
+}
+
+/// Reads the commits `hashes` in `repo`, or the current directory, like
+/// [`git_log`] but without walking their history.
+pub fn git_commits(
+    repo: Option<&Path>,
+    hashes: &[String],
+    options: &LogOptions,
+    timings: &Arc<Timings>,
+) -> Result<Log> {
+    if hashes.is_empty() {
+        return Ok(Log {
+            len: Some(0),
+            entries: Box::new(std::iter::empty()),
+        });
+    }
+    let sh = git_shell(repo)?;
+    let revisions = ["--no-walk=unsorted".to_string()];
+    read_git_log(&sh, &revisions, hashes, options, timings, None)
+}
+
+/// The most recent tag reachable from HEAD in `repo`, or the current
+/// directory, as `git describe --tags --abbrev=0` finds it, or `None` when no
+/// tag is.
+pub fn git_latest_tag(repo: Option<&Path>) -> Result<Option<String>> {
+    let sh = git_shell(repo)?;
+    let output = cmd!(sh, "git describe --tags --abbrev=0")
+        .quiet()
+        .ignore_status()
+        .output()?;
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    if !output.status.success() {
+        if stderr.contains("No names found") || stderr.contains("No tags can describe") {
+            return Ok(None);
+        }
+        bail!("{}", stderr.trim());
+    }
+    Ok(Some(
+        String::from_utf8_lossy(&output.stdout).trim().to_string(),
+    ))
+}
+
+/// Whether `repo`, or the current directory, has a tag named `tag`.
+pub fn git_has_tag(repo: Option<&Path>, tag: &str) -> Result<bool> {
+    let sh = git_shell(repo)?;
+    let reference = format!("refs/tags/{tag}");
+    let output = cmd!(sh, "git rev-parse --verify --quiet {reference}")
+        .quiet()
+        .ignore_status()
+        .ignore_stdout()
+        .output()?;
+    Ok(output.status.success())
+}
+
+/// Lists the annotated tags in `repo`, or the current directory, in the
+/// format of [`git_log`], with the tag's name in place of the author and the
+/// tagger date in place of the author date.
+pub fn git_tags(repo: Option<&Path>) -> Result<String> {
+    let sh = Shell::new()?;
+    if let Some(repo) = repo {
+        sh.change_dir(repo);
+    }
+    // Lightweight tags only point at commits, which the walk already
+    // covers, so they print nothing. A tag has no email in place of the
+    // author's, so the field is left empty.
+    let format = "%(if:equals=tag)%(objecttype)%(then)\
+                  %(objectname)%00%(taggerdate:iso-strict)%00%(refname:short)%00%00%(contents)%1e\
+                  %(end)";
+    let output = cmd!(sh, "git for-each-ref refs/tags --format={format}")
+        .quiet()
+        .ignore_status()
+        .output()?;
+    if !output.status.success() {
+        bail!("{}", String::from_utf8_lossy(&output.stderr).trim());
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
+
+/// Walks the history of HEAD, or of the `revisions`, in the repository
+/// containing `repo`, or the current directory, newest commit first like
+/// `git log`. A repository without commits has no history rather than being
+/// an error. The time spent reading the repository is added to `timings`
+/// like [`git_log`] adds git's.
+#[cfg(feature = "libgit2")]
+pub fn libgit2_log(
+    repo: Option<&Path>,
+    revisions: &[String],
+    options: &LogOptions,
+    timings: &Arc<Timings>,
+) -> Result<Log> {
+    use anyhow::Context;
+    use git2::ErrorCode;
+
+    // Each commit is only read once, so caching them would only hold the
+    // whole history in memory.
+    git2::opts::enable_caching(false);
+    let repo = open(repo)?;
+    // The default sorting is git's: parents are queued by commit date as
+    // their children are shown.
+    let mut walk = repo.revwalk()?;
+    if options.first_parent {
+        walk.simplify_first_parent()?;
+    }
+    if revisions.is_empty() {
+        if let Err(e) = repo.head() {
+            if e.code() == ErrorCode::UnbornBranch {
+                return Ok(Log {
+                    len: Some(0),
+                    entries: Box::new(std::iter::empty()),
+                });
+            }
+            return Err(e).context("failed to resolve HEAD");
+        }
+        walk.push(peel(&repo, "HEAD")?)?;
+    }
+    // Like git's, each `--exclude` applies to the next ref set.
+    let mut excluded = vec![];
+    for revision in revisions {
+        if let Some(name) = revision.strip_prefix("--exclude=") {
+            excluded.push(name);
+            continue;
+        }
+        push_revision(&repo, &mut walk, revision, &excluded)?;
+        if revision.starts_with("--") {
+            excluded.clear();
+        }
+    }
+    // Only the ids are walked up front, and each commit is read as it's
+    // iterated.
+    let oids = timings.time(Phase::Git, || {
+        walk.collect::<Result<Vec<git2::Oid>, git2::Error>>()
+    })?;
+    let len = options.is_unfiltered().then_some(oids.len());
+    let mailmap = options.mailmap.then(|| repo.mailmap()).transpose()?;
+    let entries = Libgit2Log {
+        repo,
+        oids: oids.into_iter(),
+        options: options.clone(),
+        mailmap,
+        timings: Arc::clone(timings),
+    };
+    Ok(Log {
+        len,
+        entries: match options.max_count {
+            Some(max_count) => Box::new(entries.take(max_count)),
+            None => Box::new(entries),
+        },
+    })
+}
+
+/// The commits of a revwalk, read from the repository one at a time and
+/// skipped when `options` leaves them out.
+#[cfg(feature = "libgit2")]
+struct Libgit2Log {
+    repo: git2::Repository,
+    oids: std::vec::IntoIter<git2::Oid>,
+    options: LogOptions,
+    /// The repository's `.mailmap`, when `options` applies it.
+    mailmap: Option<git2::Mailmap>,
+    timings: Arc<Timings>,
+}
+
+#[cfg(feature = "libgit2")]
+impl Libgit2Log {
+    fn read(&self, oid: git2::Oid) -> Result<Option<LogEntry>> {
+        let timings = &self.timings;
+        let commit = timings.time(Phase::Git, || self.repo.find_commit(oid))?;
+        let _parsing = timings.measure(Phase::Parsing);
+        if !self.options.merges.allows(commit.parent_count()) {
+            return Ok(None);
+        }
+        let message = String::from_utf8_lossy(commit.message_bytes());
+        if !self.options.matches_message(&message) {
+            return Ok(None);
+        }
+        let (author, committer) = match &self.mailmap {
+            Some(mailmap) => (
+                commit.author_with_mailmap(mailmap)?,
+                commit.committer_with_mailmap(mailmap)?,
+            ),
+            None => (commit.author(), commit.committer()),
+        };
+        let shown = if self.options.committer {
+            &committer
+        } else {
+            &author
+        };
+        let dated = if self.options.author_dates {
+            &author
+        } else {
+            &committer
+        };
+        let dates = &self.options.dates;
+        if !dates.is_unbounded() && !dates.contains(&signature_time(dated, oid)?) {
+            return Ok(None);
+        }
+        if !self.options.authors.is_empty() {
+            let identity = format!(
+                "{} <{}>",
+                String::from_utf8_lossy(author.name_bytes()),
+                String::from_utf8_lossy(author.email_bytes())
+            );
+            if !self.options.matches_author(&identity) {
+                return Ok(None);
+            }
+        }
+        Ok(Some(LogEntry {
+            hash: commit.id().to_string(),
+            author: String::from_utf8_lossy(shown.name_bytes()).into_owned(),
+            email: String::from_utf8_lossy(shown.email_bytes()).into_owned(),
+            datetime: signature_time(shown, oid)?,
+            message: message.into_owned(),
+        }))
+    }
+}
+
+#[cfg(feature = "libgit2")]
+impl Iterator for Libgit2Log {
+    type Item = Result<LogEntry>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let oid = self.oids.next()?;
+            if let Some(entry) = self.read(oid).transpose() {
+                return Some(entry);
+            }
+        }
+    }
+}
+
+/// Opens the repository containing `repo`, or the current directory.
+#[cfg(feature = "libgit2")]
+fn open(repo: Option<&Path>) -> Result<git2::Repository> {
+    use anyhow::Context;
+    use git2::{ErrorCode, Repository};
+
+    let opened = match repo {
+        Some(path) => {
+            check_dir(path)?;
+            Repository::discover(path)
+        }
+        None => Repository::open_from_env(),
+    };
+    match opened {
+        Ok(repo) => Ok(repo),
+        Err(e) if e.code() == ErrorCode::NotFound => {
+            Err(NotARepository::new(repo, e.message().to_string()).into())
+        }
+        Err(e) => Err(e).context("failed to open the repository"),
+    }
+}
+
+/// When `signature` was made, in its own time zone.
+#[cfg(feature = "libgit2")]
+fn signature_time(signature: &git2::Signature, id: git2::Oid) -> Result<DateTime<FixedOffset>> {
+    let when = signature.when();
+    FixedOffset::east_opt(when.offset_minutes() * 60)
+        .and_then(|offset| {
+            DateTime::from_timestamp(when.seconds(), 0).map(|d| d.with_timezone(&offset))
+        })
+        .ok_or_else(|| anyhow::anyhow!("object {id}: date is out of range"))
+}
+
+/// The git directory that `repo`, or the current directory, shares with all
+/// of its worktrees, like [`git_common_dir`].
+#[cfg(feature = "libgit2")]
+pub fn libgit2_common_dir(repo: Option<&Path>) -> Result<PathBuf> {
+    Ok(open(repo)?.commondir().to_path_buf())
+}
+
+/// The URL of the remote `name` of `repo`, or the current directory, like
+/// [`git_remote_url`].
+#[cfg(feature = "libgit2")]
+pub fn libgit2_remote_url(repo: Option<&Path>, name: &str) -> Result<Option<String>> {
+    let repo = open(repo)?;
+    let url = match repo.find_remote(name) {
+        Ok(remote) => remote.url().ok().map(str::to_string),
+        Err(e) if e.code() == git2::ErrorCode::NotFound => None,
+        Err(e) => return Err(e.into()),
+    };
+    Ok(url)
+}
+
+/// Whether `repo`, or the current directory, is a shallow clone, like
+/// [`git_is_shallow`].
+#[cfg(feature = "libgit2")]
+pub fn libgit2_is_shallow(repo: Option<&Path>) -> Result<bool> {
+    Ok(open(repo)?.is_shallow())
+}
+
+/// The commits no ref or HEAD can reach anymore, like [`git_unreachable`].
+#[cfg(feature = "libgit2")]
+pub fn libgit2_unreachable(repo: Option<&Path>) -> Result<HashSet<String>> {
+    let repo = open(repo)?;
+    let mut walk = repo.revwalk()?;
+    for oid in reflog_commits(&repo)? {
+        walk.push(oid)?;
+    }
+    walk.hide_glob("*")?;
+    for oid in heads(&repo)? {
+        walk.hide(oid)?;
+    }
+    walk.map(|oid| Ok(oid?.to_string())).collect()
+}
+
+/// The commits no ref or HEAD can reach, like [`git_lost`], found by walking
+/// everything they can and then listing every object in the repository.
+#[cfg(feature = "libgit2")]
+pub fn libgit2_lost(repo: Option<&Path>) -> Result<Vec<String>> {
+    let repo = open(repo)?;
+    let mut walk = repo.revwalk()?;
+    walk.push_glob("*")?;
+    for oid in heads(&repo)? {
+        walk.push(oid)?;
+    }
+    let reachable = walk.collect::<Result<HashSet<git2::Oid>, git2::Error>>()?;
+    let odb = repo.odb()?;
+    // An object can be both loose and packed, and is listed once for each.
+    let mut lost = HashSet::new();
+    let mut failed = None;
+    odb.foreach(|oid| {
+        if reachable.contains(oid) || lost.contains(oid) {
+            return true;
+        }
+        match odb.read_header(*oid) {
+            Ok((_, git2::ObjectType::Commit)) => {
+                lost.insert(*oid);
+                true
+            }
+            Ok(_) => true,
+            Err(e) => {
+                failed = Some(e);
+                false
+            }
+        }
+    })?;
+    if let Some(e) = failed {
+        return Err(e.into());
+    }
+    Ok(lost.into_iter().map(|oid| oid.to_string()).collect())
+}
+
+/// Creates a ref for each of the commits `hashes`, like [`git_rescue`].
+#[cfg(feature = "libgit2")]
+pub fn libgit2_rescue(repo: Option<&Path>, hashes: &[String]) -> Result<()> {
+    let repo = open(repo)?;
+    for hash in hashes {
+        let oid = git2::Oid::from_str(hash)?;
+        repo.reference(&format!("refs/rare/{hash}"), oid, true, "git-rare: rescue")?;
+    }
+    Ok(())
+}
+
+/// The note under `notes_ref` on each of `hashes`, like [`git_notes`].
+#[cfg(feature = "libgit2")]
+pub fn libgit2_notes(
+    repo: Option<&Path>,
+    notes_ref: &str,
+    hashes: &[String],
+) -> Result<Vec<Option<String>>> {
+    let repo = open(repo)?;
+    let mut notes = vec![];
+    for hash in hashes {
+        let oid = git2::Oid::from_str(hash)?;
+        match repo.find_note(Some(notes_ref), oid) {
+            Ok(note) => notes.push(Some(note.message().unwrap_or_default().to_string())),
+            Err(e) if e.code() == git2::ErrorCode::NotFound => notes.push(None),
+            Err(e) => return Err(e.into()),
+        }
+    }
+    Ok(notes)
+}
+
+/// Writes each of `notes` under `notes_ref`, like [`git_write_notes`].
+#[cfg(feature = "libgit2")]
+pub fn libgit2_write_notes(
+    repo: Option<&Path>,
+    notes_ref: &str,
+    notes: &[(String, String)],
+) -> Result<()> {
+    let repo = open(repo)?;
+    let signature = repo.signature()?;
+    for (hash, text) in notes {
+        let oid = git2::Oid::from_str(hash)?;
+        repo.note(&signature, &signature, Some(notes_ref), oid, text, true)?;
+    }
+    Ok(())
+}
+
+/// Creates a lightweight tag for each of `tags`, like [`git_create_tags`].
+#[cfg(feature = "libgit2")]
+pub fn libgit2_create_tags(repo: Option<&Path>, tags: &[(String, String)]) -> Result<()> {
+    let repo = open(repo)?;
+    for (name, hash) in tags {
+        let oid = git2::Oid::from_str(hash)?;
+        repo.reference(
+            &format!("refs/tags/{name}"),
+            oid,
+            true,
+            "git-rare: annotate",
+        )?;
+    }
+    Ok(())
+}
+
+/// The commits of every stash, like [`git_stashes`].
+#[cfg(feature = "libgit2")]
+pub fn libgit2_stashes(repo: Option<&Path>) -> Result<Vec<StashCommit>> {
+    let repo = open(repo)?;
+    // A missing reflog reads as an empty one.
+    let reflog = repo.reflog("refs/stash")?;
+    let mut stashes = vec![];
+    for entry in reflog.iter() {
+        let commit = repo.find_commit(entry.id_new())?;
+        let hashes = std::iter::once(commit.id())
+            .chain(commit.parent_ids())
+            .map(|oid| oid.to_string())
+            .collect();
+        stashes.push(hashes);
+    }
+    Ok(stash_commits(stashes.into_iter()))
+}
+
+/// Reads the commits `hashes` like [`git_commits`].
+#[cfg(feature = "libgit2")]
+pub fn libgit2_commits(
+    repo: Option<&Path>,
+    hashes: &[String],
+    options: &LogOptions,
+    timings: &Arc<Timings>,
+) -> Result<Log> {
+    let repo = open(repo)?;
+    let oids = hashes
+        .iter()
+        .map(|hash| git2::Oid::from_str(hash))
+        .collect::<Result<Vec<git2::Oid>, git2::Error>>()?;
+    let mailmap = options.mailmap.then(|| repo.mailmap()).transpose()?;
+    let entries = Libgit2Log {
+        repo,
+        oids: oids.into_iter(),
+        options: options.clone(),
+        mailmap,
+        timings: Arc::clone(timings),
+    };
+    Ok(Log {
+        len: None,
+        entries: match options.max_count {
+            Some(max_count) => Box::new(entries.take(max_count)),
+            None => Box::new(entries),
+        },
+    })
+}
+
+/// The commits recorded in the reflogs of HEAD and every ref, before and
+/// after each change, like `git log --reflog` starts walking from.
+#[cfg(feature = "libgit2")]
+fn reflog_commits(repo: &git2::Repository) -> Result<Vec<git2::Oid>> {
+    let mut names = vec!["HEAD".to_string()];
+    for name in repo.references()?.names() {
+        names.push(name?.to_string());
+    }
+    let mut reflogs = vec![];
+    for name in names {
+        reflogs.push(repo.reflog(&name)?);
+    }
+    // Each worktree has a HEAD of its own, and git reads all of them.
+    for worktree in other_worktrees(repo)? {
+        reflogs.push(worktree.reflog("HEAD")?);
+    }
+    let mut oids = vec![];
+    for reflog in reflogs {
+        for entry in reflog.iter() {
+            for oid in [entry.id_old(), entry.id_new()] {
+                // Entries outlive the commits they record once those are
+                // pruned.
+                if !oid.is_zero() && repo.find_commit(oid).is_ok() {
+                    oids.push(oid);
+                }
+            }
+        }
+    }
+    Ok(oids)
+}
+
+/// The worktrees `repo` shares its refs with, other than its own: the main
+/// one, when `repo` is a linked worktree, and every linked one besides. Those
+/// whose directory was deleted without `git worktree prune` are skipped.
+#[cfg(feature = "libgit2")]
+fn other_worktrees(repo: &git2::Repository) -> Result<Vec<git2::Repository>> {
+    let own = repo.path().canonicalize()?;
+    let mut worktrees = vec![];
+    if repo.is_worktree() {
+        worktrees.push(git2::Repository::open(repo.commondir())?);
+    }
+    for name in repo.worktrees()?.iter() {
+        // Names that aren't UTF-8 can't be looked up.
+        let Some(name) = name? else {
+            continue;
+        };
+        let worktree = repo.find_worktree(name)?;
+        if worktree.validate().is_err() {
+            continue;
+        }
+        let worktree = git2::Repository::open_from_worktree(&worktree)?;
+        if worktree.path().canonicalize()? != own {
+            worktrees.push(worktree);
+        }
+    }
+    Ok(worktrees)
+}
+
+/// The commits at the HEAD of `repo` and of every other worktree, which
+/// `git log --all` walks along with the refs.
+#[cfg(feature = "libgit2")]
+fn heads(repo: &git2::Repository) -> Result<Vec<git2::Oid>> {
+    let worktrees = other_worktrees(repo)?;
+    // An unborn HEAD has no commit to walk from.
+    Ok(std::iter::once(repo)
+        .chain(&worktrees)
+        .filter_map(|repo| repo.head().and_then(|head| head.peel_to_commit()).ok())
+        .map(|commit| commit.id())
+        .collect())
+}
+
+/// The most recent tag reachable from HEAD in `repo`, or the current
+/// directory, like [`git_latest_tag`].
+#[cfg(feature = "libgit2")]
+pub fn libgit2_latest_tag(repo: Option<&Path>) -> Result<Option<String>> {
+    use git2::{DescribeFormatOptions, DescribeOptions, ErrorClass, ErrorCode};
+
+    let repo = open(repo)?;
+    let describe = match repo.describe(DescribeOptions::new().describe_tags()) {
+        Ok(describe) => describe,
+        // It fails with a describe error when there's no tag to describe
+        // HEAD with.
+        Err(e) if e.code() == ErrorCode::NotFound || e.class() == ErrorClass::Describe => {
+            return Ok(None)
+        }
+        Err(e) => return Err(e.into()),
+    };
+    let tag = describe.format(Some(DescribeFormatOptions::new().abbreviated_size(0)))?;
+    Ok(Some(tag))
+}
+
+/// Whether `repo`, or the current directory, has a tag named `tag`.
+#[cfg(feature = "libgit2")]
+pub fn libgit2_has_tag(repo: Option<&Path>, tag: &str) -> Result<bool> {
+    use git2::ErrorCode;
+
+    let repo = open(repo)?;
+    let exists = match repo.find_reference(&format!("refs/tags/{tag}")) {
+        Ok(_) => true,
+        Err(e) if matches!(e.code(), ErrorCode::NotFound | ErrorCode::InvalidSpec) => false,
+        Err(e) => return Err(e.into()),
+    };
+    Ok(exists)
+}
+
+/// Lists the annotated tags in `repo`, or the current directory, like
+/// [`git_tags`]. Tags without a tagger have no date and are left out.
+#[cfg(feature = "libgit2")]
+pub fn libgit2_tags(repo: Option<&Path>) -> Result<Vec<LogEntry>> {
+    let repo = open(repo)?;
+    let mut entries = vec![];
+    for name in repo.tag_names(None)?.iter() {
+        // Names that aren't UTF-8 can't be shown in place of an author.
+        let Some(name) = name? else {
+            continue;
+        };
+        let reference = repo.find_reference(&format!("refs/tags/{name}"))?;
+        let Some(tag) = reference.target().and_then(|oid| repo.find_tag(oid).ok()) else {
+            continue;
+        };
+        let Some(tagger) = tag.tagger() else {
+            continue;
+        };
+        entries.push(LogEntry {
+            hash: tag.id().to_string(),
+            author: name.to_string(),
+            email: String::new(),
+            datetime: signature_time(&tagger, tag.id())?,
+            message: String::from_utf8_lossy(tag.message_bytes().unwrap_or_default()).into_owned(),
+        });
+    }
+    Ok(entries)
+}
+
+/// Calls `f` with every object in `repo`, or the current directory, like
+/// [`git_objects`].
+#[cfg(feature = "libgit2")]
+pub fn libgit2_objects(
+    repo: Option<&Path>,
+    mut f: impl FnMut(String, ObjectKind) -> bool,
+) -> Result<()> {
+    let repo = open(repo)?;
+    let odb = repo.odb()?;
+    // An object can be both loose and packed, and is listed once for each.
+    let mut seen = HashSet::new();
+    let mut failed = None;
+    odb.foreach(|oid| {
+        if !seen.insert(*oid) {
+            return true;
+        }
+        match odb.read_header(*oid) {
+            Ok((_, kind)) => match ObjectKind::from_git2(kind) {
+                Some(object) => f(oid.to_string(), object),
+                None => true,
+            },
+            Err(e) => {
+                failed = Some(e);
+                false
+            }
+        }
+    })?;
+    match failed {
+        Some(e) => Err(e.into()),
+        None => Ok(()),
+    }
+}
+
+/// Finds the paths of `hashes` like [`git_object_paths`], walking the trees
+/// of every commit reachable from a ref.
+#[cfg(feature = "libgit2")]
+pub fn libgit2_object_paths(
+    repo: Option<&Path>,
+    hashes: &HashSet<String>,
+) -> Result<HashMap<String, String>> {
+    use git2::{TreeWalkMode, TreeWalkResult};
+
+    let mut paths = HashMap::new();
+    if hashes.is_empty() {
+        return Ok(paths);
+    }
+    let repo = open(repo)?;
+    let mut walk = repo.revwalk()?;
+    walk.push_glob("*")?;
+    if repo.head().is_ok() {
+        walk.push_head()?;
+    }
+    // Subtrees shared between commits only need walking once.
+    let mut walked = HashSet::new();
+    for oid in walk {
+        let tree = repo.find_commit(oid?)?.tree()?;
+        if !walked.insert(tree.id()) {
+            continue;
+        }
+        if hashes.contains(&tree.id().to_string()) {
+            paths
+                .entry(tree.id().to_string())
+                .or_insert_with(String::new);
+        }
+        tree.walk(TreeWalkMode::PreOrder, |root, entry| {
+            let id = entry.id();
+            let hash = id.to_string();
+            if hashes.contains(&hash) {
+                let name = String::from_utf8_lossy(entry.name_bytes());
+                paths.entry(hash).or_insert_with(|| format!("{root}{name}"));
+            }
+            if entry.kind() == Some(git2::ObjectType::Tree) && !walked.insert(id) {
+                return TreeWalkResult::Skip;
+            }
+            TreeWalkResult::Ok
+        })?;
+        if paths.len() == hashes.len() {
+            break;
+        }
+    }
+    Ok(paths)
+}
+
+/// Adds a revision as `git log` reads it to the walk: `a..b`, `a...b`, `^a`,
+/// a single revision or one of `--branches`, `--remotes`, `--all`, `--tags`
+/// and `--reflog`. The refs named in `excluded` are left out of `--all`;
+/// unlike git's `--exclude`, they aren't patterns.
+#[cfg(feature = "libgit2")]
+fn push_revision(
+    repo: &git2::Repository,
+    walk: &mut git2::Revwalk,
+    revision: &str,
+    excluded: &[&str],
+) -> Result<()> {
+    use git2::RevparseMode;
+
+    // Walking the same commit from several refs still lists it once.
+    match revision {
+        "--branches" => return Ok(walk.push_glob("heads")?),
+        "--remotes" => return Ok(walk.push_glob("remotes")?),
+        "--tags" => return Ok(walk.push_glob("tags")?),
+        "--reflog" => {
+            for oid in reflog_commits(repo)? {
+                walk.push(oid)?;
+            }
+            return Ok(());
+        }
+        "--all" if excluded.is_empty() => {
+            walk.push_glob("*")?;
+            for oid in heads(repo)? {
+                walk.push(oid)?;
+            }
+            return Ok(());
+        }
+        "--all" => {
+            for reference in repo.references()? {
+                let reference = reference?;
+                if reference.name().is_ok_and(|name| excluded.contains(&name)) {
+                    continue;
+                }
+                // Refs to trees and blobs are skipped, as with a glob.
+                if let Ok(commit) = reference.peel_to_commit() {
+                    walk.push(commit.id())?;
+                }
+            }
+            for oid in heads(repo)? {
+                walk.push(oid)?;
+            }
+            return Ok(());
+        }
+        _ if revision.starts_with('-') => {
+            bail!("'{revision}' is only supported with --backend git")
+        }
+        _ => {}
+    }
+    if let Some(hidden) = revision.strip_prefix('^') {
+        walk.hide(peel(repo, hidden)?)?;
+        return Ok(());
+    }
+    let spec = repo
+        .revparse(revision)
+        .map_err(|e| anyhow::anyhow!("bad revision '{revision}': {}", e.message()))?;
+    let commit = |object: Option<&git2::Object>| -> Result<Option<git2::Oid>> {
+        Ok(object
+            .map(|o| o.peel_to_commit())
+            .transpose()?
+            .map(|c| c.id()))
+    };
+    let (from, to) = (commit(spec.from())?, commit(spec.to())?);
+    if spec.mode().contains(RevparseMode::MERGE_BASE) {
+        let (from, to) = (
+            from.expect("ranges have two ends"),
+            to.expect("ranges have two ends"),
+        );
+        walk.push(from)?;
+        walk.push(to)?;
+        walk.hide(repo.merge_base(from, to)?)?;
+    } else if spec.mode().contains(RevparseMode::RANGE) {
+        walk.hide(from.expect("ranges have two ends"))?;
+        walk.push(to.expect("ranges have two ends"))?;
+    } else {
+        walk.push(from.expect("a single revision is the start of its spec"))?;
+    }
+    Ok(())
+}
+
+/// Resolves `revision` to a commit. Peeling lets an annotated tag start the
+/// walk, as it does for `git log`.
+#[cfg(feature = "libgit2")]
+fn peel(repo: &git2::Repository, revision: &str) -> Result<git2::Oid> {
+    let object = repo
+        .revparse_single(revision)
+        .map_err(|e| anyhow::anyhow!("bad revision '{revision}': {}", e.message()))?;
+    Ok(object.peel_to_commit()?.id())
+}
+
+/// Checks that a `--repo` path exists, since both backends would otherwise
+/// report it as not being a repository.
+fn check_dir(path: &Path) -> Result<()> {
+    if !path.is_dir() {
+        bail!("'{}' does not exist or is not a directory", path.display());
+    }
+    Ok(())
+}
+
+/// The error for `repo`, or the current directory, not being in a git
+/// repository, which git-rare exits with status 2 for.
+#[derive(Debug)]
+pub struct NotARepository {
+    repo: Option<PathBuf>,
+    /// Why git or libgit2 couldn't find the repository, shown with
+    /// `--verbose`.
+    pub detail: String,
+}
+
+impl NotARepository {
+    fn new(repo: Option<&Path>, detail: String) -> Self {
+        Self {
+            repo: repo.map(Path::to_path_buf),
+            detail,
+        }
+    }
+}
+
+impl Display for NotARepository {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        if let Some(path) = &self.repo {
+            return write!(f, "'{}' is not a git repository", path.display());
+        }
+        // Both git and libgit2 only look for `$GIT_DIR` when it's set, as it
+        // is by `--git-dir`.
+        match std::env::var_os("GIT_DIR") {
+            Some(git_dir) => write!(
+                f,
+                "'{}' is not a git directory",
+                Path::new(&git_dir).display()
+            ),
+            None => write!(
+                f,
+                "not a git repository (or any parent up to /): \
+                 run inside a repo or pass --repo <path>"
+            ),
+        }
+    }
+}
+
+impl std::error::Error for NotARepository {}
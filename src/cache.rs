@@ -0,0 +1,212 @@
+//! Classifications kept between scans, so that rescanning a history only
+//! classifies the commits it gained since.
+//!
+//! Each walk's commits are written to a file of their own under the
+//! repository's common git directory, which all of its worktrees share, named
+//! by a key of everything that affects how they're walked and classified. A
+//! history that only grew is walked in the same order next time, after its
+//! new commits, so the file is streamed alongside the walk rather than held
+//! in memory: a commit is looked up in it when it's the next one there.
+
+use std::fs::{self, File};
+use std::io::{BufReader, BufWriter, Write};
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+use bincode::Options;
+use chrono::{DateTime, FixedOffset};
+use serde::{Deserialize, Serialize};
+
+use crate::{Commit, Rarity};
+
+/// Changed whenever what's written to the files is.
+const FORMAT: u32 = 2;
+
+/// The directory the caches of a repository are kept in, given its common git
+/// directory.
+pub fn dir(common_dir: &Path) -> PathBuf {
+    common_dir.join("git-rare").join("cache")
+}
+
+/// How entries are encoded. A file that's been corrupted can't make one be
+/// read into more memory than any commit needs.
+fn encoding() -> impl Options {
+    bincode::DefaultOptions::new().with_limit(1 << 20)
+}
+
+#[derive(Serialize, Deserialize)]
+struct Header {
+    format: u32,
+    key: u64,
+}
+
+/// A commit as it was classified, with what its classification depends on
+/// besides its hash.
+#[derive(Serialize, Deserialize)]
+struct Entry {
+    hash: String,
+    ordinal: usize,
+    author: String,
+    datetime: DateTime<FixedOffset>,
+    rarity: Rarity,
+}
+
+/// The classifications of the last scan with the same key, read as the walk
+/// goes, and those of this one, written as it goes.
+pub struct Cache {
+    path: PathBuf,
+    temp: PathBuf,
+    previous: Option<BufReader<File>>,
+    /// The next of the last scan's commits, not yet walked past.
+    next: Option<Entry>,
+    /// Where this scan's are written, until they're finished.
+    writer: Option<BufWriter<File>>,
+    written: usize,
+    reused: usize,
+}
+
+/// How many of a scan's commits were classified, and how many of their
+/// classifications were read from the cache instead.
+#[derive(Clone, Copy, Default)]
+pub struct Stats {
+    pub classified: usize,
+    pub reused: usize,
+}
+
+impl Cache {
+    /// Opens the cache with `key` in `dir`, creating it. A cache that can't
+    /// be read, or was written by another version, is as good as none.
+    pub fn open(dir: &Path, key: u64) -> Result<Self> {
+        fs::create_dir_all(dir).with_context(|| format!("failed to create {}", dir.display()))?;
+        let path = dir.join(format!("{key:016x}.bin"));
+        let temp = dir.join(format!("{key:016x}.{}.tmp", std::process::id()));
+        let previous = File::open(&path)
+            .ok()
+            .and_then(|file| check_header(BufReader::new(file), key));
+        let file =
+            File::create(&temp).with_context(|| format!("failed to create {}", temp.display()))?;
+        let mut writer = BufWriter::new(file);
+        encoding().serialize_into(
+            &mut writer,
+            &Header {
+                format: FORMAT,
+                key,
+            },
+        )?;
+        let mut cache = Self {
+            path,
+            temp,
+            previous,
+            next: None,
+            writer: Some(writer),
+            written: 0,
+            reused: 0,
+        };
+        cache.advance();
+        Ok(cache)
+    }
+
+    fn advance(&mut self) {
+        self.next = self
+            .previous
+            .as_mut()
+            .and_then(|reader| encoding().deserialize_from(reader).ok());
+        if self.next.is_none() {
+            self.previous = None;
+        }
+    }
+
+    /// The rarity the commit was classified with in the last scan, if it
+    /// was walked to the same place in history then. Commits the history
+    /// gained aren't there, and the last scan's commits that numbered
+    /// positions were walked past without being found, like one amended
+    /// away, are skipped.
+    pub fn lookup(
+        &mut self,
+        hash: &str,
+        author: &str,
+        datetime: &DateTime<FixedOffset>,
+        ordinal: usize,
+    ) -> Option<Rarity> {
+        while self
+            .next
+            .as_ref()
+            .is_some_and(|next| next.ordinal > ordinal)
+        {
+            self.advance();
+        }
+        if self.next.as_ref()?.hash != hash {
+            return None;
+        }
+        let next = self.next.take()?;
+        self.advance();
+        let same = next.ordinal == ordinal
+            && next.author == author
+            && next.datetime == *datetime
+            && next.datetime.offset() == datetime.offset();
+        if same {
+            self.reused += 1;
+        }
+        same.then_some(next.rarity)
+    }
+
+    /// Writes how `commit`, at `ordinal`, was classified this time.
+    pub fn record(&mut self, commit: &Commit, ordinal: usize) -> Result<()> {
+        let entry = Entry {
+            hash: commit.hash.clone(),
+            ordinal,
+            author: commit.author.clone(),
+            datetime: commit.datetime,
+            rarity: commit.rarity.clone(),
+        };
+        if let Some(writer) = &mut self.writer {
+            encoding().serialize_into(writer, &entry)?;
+        }
+        self.written += 1;
+        Ok(())
+    }
+
+    pub fn stats(&self) -> Stats {
+        Stats {
+            classified: self.written - self.reused,
+            reused: self.reused,
+        }
+    }
+
+    /// Replaces the last scan's classifications with this one's.
+    pub fn finish(mut self) -> Result<()> {
+        // Files can't be replaced while they're open on Windows.
+        self.previous = None;
+        if let Some(mut writer) = self.writer.take() {
+            writer.flush()?;
+        }
+        fs::rename(&self.temp, &self.path)
+            .with_context(|| format!("failed to write {}", self.path.display()))
+    }
+}
+
+impl Drop for Cache {
+    /// Deletes what was written, unless it replaced the last scan's, like
+    /// when the scan failed partway.
+    fn drop(&mut self) {
+        drop(self.writer.take());
+        let _ = fs::remove_file(&self.temp);
+    }
+}
+
+/// `reader`, past its header, if that's one of this format and `key`.
+fn check_header(mut reader: BufReader<File>, key: u64) -> Option<BufReader<File>> {
+    let header: Header = encoding().deserialize_from(&mut reader).ok()?;
+    (header.format == FORMAT && header.key == key).then_some(reader)
+}
+
+/// Deletes every cache of a repository, given its common git directory.
+/// Returns whether there were any.
+pub fn clear(common_dir: &Path) -> Result<bool> {
+    let dir = dir(common_dir);
+    if !dir.exists() {
+        return Ok(false);
+    }
+    fs::remove_dir_all(&dir).with_context(|| format!("failed to delete {}", dir.display()))?;
+    Ok(true)
+}
@@ -0,0 +1,113 @@
+//! Colors of the table in a terminal: each tier has one, which its name in the
+//! Tier column and the characters its matches depend on in the hash are
+//! shown in.
+
+use std::io::IsTerminal;
+use std::ops::Range;
+
+use clap::ValueEnum;
+
+use crate::RarityTier;
+
+const RESET: &str = "\x1b[0m";
+
+/// When to color the table.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default, ValueEnum)]
+pub enum When {
+    /// When printing to a terminal, unless `NO_COLOR` is set.
+    #[default]
+    Auto,
+    Always,
+    Never,
+}
+
+impl When {
+    /// Whether to color what's printed, to a file with `--output` when
+    /// `to_file` is set or else to stdout.
+    pub fn enabled(self, to_file: bool) -> bool {
+        let no_color = std::env::var_os("NO_COLOR").is_some_and(|value| !value.is_empty());
+        self.colors(!to_file && std::io::stdout().is_terminal(), no_color)
+    }
+
+    /// Whether to color what's printed to a terminal when `terminal` is set,
+    /// with `NO_COLOR` set when `no_color` is. Asking for colors with
+    /// `always` outweighs `NO_COLOR`.
+    fn colors(self, terminal: bool, no_color: bool) -> bool {
+        match self {
+            Self::Always => true,
+            Self::Never => false,
+            Self::Auto => terminal && !no_color,
+        }
+    }
+}
+
+/// The escape sequence that starts the color of `tier`, or `None` for Common,
+/// which isn't colored.
+fn start(tier: &RarityTier) -> Option<&'static str> {
+    match tier {
+        RarityTier::Common => None,
+        // Yellow, magenta and bold red.
+        RarityTier::Uncommon => Some("\x1b[33m"),
+        RarityTier::Rare => Some("\x1b[35m"),
+        RarityTier::Legendary => Some("\x1b[1;31m"),
+    }
+}
+
+/// `text` in the color of `tier`.
+pub fn paint(text: &str, tier: &RarityTier) -> String {
+    match start(tier) {
+        Some(start) => format!("{start}{text}{RESET}"),
+        None => text.to_string(),
+    }
+}
+
+/// `hash` with the characters in `spans` in the color of `tier`.
+pub fn highlight(hash: &str, spans: &[Range<usize>], tier: &RarityTier) -> String {
+    let Some(start) = start(tier) else {
+        return hash.to_string();
+    };
+    let mut colored = String::new();
+    let mut inside = false;
+    for (i, c) in hash.char_indices() {
+        let now_inside = spans.iter().any(|span| span.contains(&i));
+        if now_inside != inside {
+            colored.push_str(if now_inside { start } else { RESET });
+            inside = now_inside;
+        }
+        colored.push(c);
+    }
+    if inside {
+        colored.push_str(RESET);
+    }
+    colored
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn spans_are_colored_in_the_tier_color() {
+        assert_eq!(
+            highlight("abcdef", &[1..2, 2..3], &RarityTier::Uncommon),
+            "a\x1b[33mbc\x1b[0mdef"
+        );
+        assert_eq!(
+            highlight("abcdef", &[0..1, 4..6], &RarityTier::Rare),
+            "\x1b[35ma\x1b[0mbcd\x1b[35mef\x1b[0m"
+        );
+        assert_eq!(
+            highlight("abcdef", &[1..3, 4..5], &RarityTier::Common),
+            "abcdef"
+        );
+    }
+
+    #[test]
+    fn auto_colors_a_terminal_without_no_color() {
+        assert!(When::Auto.colors(true, false));
+        assert!(!When::Auto.colors(true, true));
+        assert!(!When::Auto.colors(false, false));
+        assert!(When::Always.colors(false, true));
+        assert!(!When::Never.colors(true, false));
+    }
+}
@@ -0,0 +1,483 @@
+//! Loading of the optional git-rare config file.
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use anyhow::{anyhow, Context, Result};
+use clap::ValueEnum;
+use serde::Deserialize;
+
+use crate::custom_rule::{self, CustomRule};
+use crate::{RarityTier, RuleMatch, TableStyle};
+
+/// Settings read from the config file. Command line flags take precedence
+/// over anything set here.
+#[derive(Default, Debug)]
+pub struct Config {
+    /// Equivalent of `--min-run`.
+    pub min_run: Option<usize>,
+    /// Rules from `[[rules]]` entries, evaluated alongside the built-in ones.
+    pub rules: Vec<CustomRule>,
+    /// Digit strings from `lucky_numbers`, like a birthday, to look for in
+    /// hashes.
+    pub lucky_numbers: Vec<String>,
+    /// Entries of `[overrides]`, by rule name.
+    pub overrides: HashMap<String, RuleOverride>,
+    /// Entries of `[emoji]`: the symbol `--emoji` shows a tier with, in
+    /// place of its default.
+    pub emoji: HashMap<RarityTier, String>,
+    /// Equivalent of `--style`.
+    pub style: Option<TableStyle>,
+}
+
+/// A change to the matches of a built-in rule, from `[overrides]`. Remapping
+/// a rule to Common keeps it out of the default view.
+#[derive(Clone, Debug, PartialEq)]
+pub struct RuleOverride {
+    pub tier: Option<RarityTier>,
+    pub probability: Option<f64>,
+}
+
+impl RuleOverride {
+    pub fn apply(&self, rule_match: RuleMatch) -> RuleMatch {
+        RuleMatch {
+            tier: self.tier.clone().unwrap_or(rule_match.tier),
+            percentage: self.probability.or(rule_match.percentage),
+            ..rule_match
+        }
+    }
+}
+
+#[derive(Deserialize, Default)]
+#[serde(default, deny_unknown_fields)]
+struct RawConfig {
+    min_run: Option<usize>,
+    /// Kept as raw values so errors can name the offending rule.
+    rules: Vec<toml::Value>,
+    lucky_numbers: Vec<String>,
+    /// Kept as raw values since an override is either a tier or a table.
+    overrides: HashMap<String, toml::Value>,
+    emoji: HashMap<String, String>,
+    style: Option<String>,
+}
+
+/// A table entry in `[overrides]`.
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct OverrideConfig {
+    tier: Option<String>,
+    probability: Option<f64>,
+}
+
+/// A `[[rules]]` entry.
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct RuleConfig {
+    /// Only needed to name the rule in errors, which read it from the raw
+    /// value so that it is available even when deserialization fails.
+    #[allow(dead_code)]
+    name: String,
+    pattern: String,
+    #[serde(default)]
+    kind: PatternKind,
+    tier: String,
+    explanation: String,
+    probability: Option<f64>,
+}
+
+/// How a rule's `pattern` is matched against a hash.
+#[derive(Deserialize, Default, Clone, Copy)]
+#[serde(rename_all = "lowercase")]
+enum PatternKind {
+    #[default]
+    Regex,
+    Prefix,
+    Suffix,
+    Contains,
+}
+
+impl RuleConfig {
+    fn into_rule(self) -> Result<CustomRule, String> {
+        let pattern = match self.kind {
+            PatternKind::Regex => self.pattern,
+            PatternKind::Prefix => format!("^{}", regex::escape(&self.pattern)),
+            PatternKind::Suffix => format!("{}$", regex::escape(&self.pattern)),
+            PatternKind::Contains => regex::escape(&self.pattern),
+        };
+        validate_probability(self.probability)?;
+        Ok(CustomRule {
+            tier: custom_rule::parse_tier(&self.tier)?,
+            regex: custom_rule::parse_regex(&pattern)?,
+            explanation: Some(self.explanation),
+            percentage: self.probability,
+        })
+    }
+}
+
+impl Config {
+    /// Loads the config from `path`, or from the default location when no
+    /// path is given. A missing default config is not an error.
+    pub fn load(path: Option<&Path>) -> Result<Self> {
+        match path {
+            Some(path) => Self::read(path),
+            None => match default_path() {
+                Some(path) if path.is_file() => Self::read(&path),
+                _ => Ok(Self::default()),
+            },
+        }
+    }
+
+    fn read(path: &Path) -> Result<Self> {
+        let contents = fs::read_to_string(path)
+            .with_context(|| format!("failed to read config file {}", path.display()))?;
+        Self::parse(&contents).with_context(|| format!("invalid config file {}", path.display()))
+    }
+
+    fn parse(contents: &str) -> Result<Self> {
+        let raw: RawConfig = toml::from_str(contents)?;
+        if let Some(run) = raw.min_run {
+            crate::validate_min_run(run).map_err(|e| anyhow!("min_run: {e}"))?;
+        }
+        let rules = raw
+            .rules
+            .into_iter()
+            .enumerate()
+            .map(|(i, value)| parse_rule(i, value))
+            .collect::<Result<Vec<CustomRule>>>()?;
+        for number in &raw.lucky_numbers {
+            crate::validate_lucky_number(number).map_err(|e| anyhow!("lucky_numbers: {e}"))?;
+        }
+        let overrides = raw
+            .overrides
+            .into_iter()
+            .map(|(name, value)| {
+                let rule_override = parse_override(&name, value)?;
+                Ok((name, rule_override))
+            })
+            .collect::<Result<HashMap<String, RuleOverride>>>()?;
+        let emoji = raw
+            .emoji
+            .into_iter()
+            .map(|(tier, symbol)| {
+                let tier = custom_rule::parse_tier(&tier).map_err(|e| anyhow!("emoji: {e}"))?;
+                Ok((tier, symbol))
+            })
+            .collect::<Result<HashMap<RarityTier, String>>>()?;
+        let style = raw
+            .style
+            .map(|style| {
+                TableStyle::from_str(&style, true).map_err(|_| {
+                    let valid = TableStyle::value_variants()
+                        .iter()
+                        .filter_map(|s| s.to_possible_value())
+                        .map(|v| v.get_name().to_string())
+                        .collect::<Vec<String>>();
+                    anyhow!(
+                        "style: invalid style '{style}', expected one of: {}",
+                        valid.join(", ")
+                    )
+                })
+            })
+            .transpose()?;
+        Ok(Self {
+            min_run: raw.min_run,
+            rules,
+            lucky_numbers: raw.lucky_numbers,
+            overrides,
+            emoji,
+            style,
+        })
+    }
+}
+
+fn parse_rule(index: usize, value: toml::Value) -> Result<CustomRule> {
+    let name = value
+        .get("name")
+        .and_then(|name| name.as_str())
+        .map(|name| format!("rule '{name}'"))
+        .unwrap_or_else(|| format!("rule #{}", index + 1));
+    RuleConfig::deserialize(value)
+        .map_err(|e| e.to_string())
+        .and_then(RuleConfig::into_rule)
+        .map_err(|e| anyhow!("{name}: {}", e.trim_end()))
+}
+
+/// Parses the `[overrides]` entry for the rule `name`, which is either a tier
+/// or a table with an optional `tier` and `probability`.
+fn parse_override(name: &str, value: toml::Value) -> Result<RuleOverride> {
+    crate::parse_rule_name(name).map_err(|e| anyhow!("overrides: {e}"))?;
+    let parsed = match value {
+        toml::Value::String(tier) => custom_rule::parse_tier(&tier).map(|tier| RuleOverride {
+            tier: Some(tier),
+            probability: None,
+        }),
+        value => OverrideConfig::deserialize(value)
+            .map_err(|e| e.to_string())
+            .and_then(|o| {
+                validate_probability(o.probability)?;
+                Ok(RuleOverride {
+                    tier: o.tier.as_deref().map(custom_rule::parse_tier).transpose()?,
+                    probability: o.probability,
+                })
+            }),
+    };
+    parsed.map_err(|e| anyhow!("overrides: {name}: {}", e.trim_end()))
+}
+
+fn validate_probability(probability: Option<f64>) -> Result<(), String> {
+    match probability {
+        Some(p) if !(p > 0.0 && p <= 1.0) => Err(format!("probability must be in (0, 1], got {p}")),
+        _ => Ok(()),
+    }
+}
+
+/// `$XDG_CONFIG_HOME/git-rare/config.toml`, falling back to `~/.config`.
+fn default_path() -> Option<PathBuf> {
+    let config_dir = std::env::var_os("XDG_CONFIG_HOME")
+        .map(PathBuf::from)
+        .or_else(|| std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")))?;
+    Some(config_dir.join("git-rare").join("config.toml"))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn parses_min_run() {
+        let config = Config::parse("min_run = 12").unwrap();
+        assert_eq!(config.min_run, Some(12));
+        let config = Config::parse("").unwrap();
+        assert_eq!(config.min_run, None);
+        assert!(config.rules.is_empty());
+    }
+
+    #[test]
+    fn rejects_invalid_min_run() {
+        let err = Config::parse("min_run = 2").unwrap_err();
+        assert!(err.to_string().contains("min_run"), "{err}");
+    }
+
+    #[test]
+    fn parses_lucky_numbers() {
+        let config = Config::parse(r#"lucky_numbers = ["19900412", "070415"]"#).unwrap();
+        assert_eq!(config.lucky_numbers, ["19900412", "070415"]);
+        assert!(Config::parse("").unwrap().lucky_numbers.is_empty());
+    }
+
+    #[test]
+    fn rejects_invalid_lucky_numbers() {
+        let err = Config::parse(r#"lucky_numbers = ["0412"]"#).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "lucky_numbers: lucky number must be at least 6 digits, got '0412'"
+        );
+        let err = Config::parse(r#"lucky_numbers = ["1990-04-12"]"#).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "lucky_numbers: lucky number must be digits only, got '1990-04-12'"
+        );
+    }
+
+    #[test]
+    fn rejects_unknown_keys() {
+        assert!(Config::parse("min_runs = 12").is_err());
+    }
+
+    #[test]
+    fn missing_explicit_config_is_an_error() {
+        assert!(Config::load(Some(Path::new("/nonexistent/git-rare.toml"))).is_err());
+    }
+
+    #[test]
+    fn parses_rules() {
+        let config = Config::parse(
+            r#"
+            [[rules]]
+            name = "leet"
+            pattern = "^1337"
+            tier = "rare"
+            explanation = "Starts with 1337: '1337' at offset 0"
+            probability = 1.5e-5
+
+            [[rules]]
+            name = "code"
+            pattern = "c0de"
+            kind = "suffix"
+            tier = "uncommon"
+            explanation = "Ends with c0de: 'c0de' at offset 36"
+            "#,
+        )
+        .unwrap();
+        let [leet, code] = &config.rules[..] else {
+            panic!("expected two rules");
+        };
+        let found = leet
+            .rule_match("1337a91c5d48b2f6e1a9e73cb5d284e6f10a9c3b")
+            .unwrap();
+        assert!(found.tier == RarityTier::Rare);
+        assert_eq!(
+            found.explanation,
+            "Starts with 1337: '1337' at offset 0: '1337' at offset 0"
+        );
+        assert_eq!(found.percentage, Some(1.5e-5));
+        let found = code
+            .rule_match("7e3a91c5d48b2f6e1a9e73cb5d284e6f10a9c0de")
+            .unwrap();
+        assert_eq!(
+            found.explanation,
+            "Ends with c0de: 'c0de' at offset 36: 'c0de' at offset 36"
+        );
+        assert_eq!(found.percentage, None);
+        assert!(code
+            .rule_match("c0de91c5d48b2f6e1a9e73cb5d284e6f10a9c3b7")
+            .is_none());
+    }
+
+    #[test]
+    fn literal_patterns_are_escaped() {
+        let config = Config::parse(
+            r#"
+            [[rules]]
+            name = "dots"
+            pattern = "a.b"
+            kind = "contains"
+            tier = "rare"
+            explanation = "Contains a.b"
+            "#,
+        )
+        .unwrap();
+        assert!(config.rules[0].rule_match("7e3aab").is_none());
+        assert!(config.rules[0].rule_match("7e3a.b").is_some());
+    }
+
+    #[test]
+    fn errors_name_the_offending_rule() {
+        let rule = |body: &str| {
+            let config = format!("[[rules]]\nname = \"bad\"\n{body}");
+            Config::parse(&config).unwrap_err().to_string()
+        };
+        let err = rule("pattern = \"1\"\ntier = \"epic\"\nexplanation = \"x\"");
+        assert!(err.starts_with("rule 'bad': invalid tier 'epic'"), "{err}");
+        let err = rule("pattern = \"(1\"\ntier = \"rare\"\nexplanation = \"x\"");
+        assert!(err.starts_with("rule 'bad': invalid regex '(1'"), "{err}");
+        let err = rule("pattern = \"1\"\ntier = \"rare\"");
+        assert!(
+            err.starts_with("rule 'bad': missing field `explanation`"),
+            "{err}"
+        );
+        let err = rule("pattern = \"1\"\ntier = \"rare\"\nexplanation = \"x\"\nprobability = 2.0");
+        assert!(err.starts_with("rule 'bad': probability"), "{err}");
+        let err = Config::parse("[[rules]]\npattern = \"1\"")
+            .unwrap_err()
+            .to_string();
+        assert!(err.starts_with("rule #1: missing field `name`"), "{err}");
+    }
+
+    #[test]
+    fn parses_overrides() {
+        let config = Config::parse(
+            r#"
+            [overrides]
+            starts_digits = "common"
+            hexspeak = { tier = "legendary", probability = 1e-9 }
+            palindrome = { probability = 0.5 }
+            "#,
+        )
+        .unwrap();
+        assert_eq!(
+            config.overrides["starts_digits"],
+            RuleOverride {
+                tier: Some(RarityTier::Common),
+                probability: None,
+            }
+        );
+        assert_eq!(
+            config.overrides["hexspeak"],
+            RuleOverride {
+                tier: Some(RarityTier::Legendary),
+                probability: Some(1e-9),
+            }
+        );
+        assert_eq!(config.overrides["palindrome"].tier, None);
+        assert!(Config::parse("").unwrap().overrides.is_empty());
+    }
+
+    #[test]
+    fn parses_emoji() {
+        let config = Config::parse(
+            r#"
+            [emoji]
+            rare = "R"
+            Legendary = "👑"
+            "#,
+        )
+        .unwrap();
+        assert_eq!(
+            config.emoji,
+            HashMap::from([
+                (RarityTier::Rare, "R".to_string()),
+                (RarityTier::Legendary, "👑".to_string()),
+            ])
+        );
+        let err = Config::parse("[emoji]\nepic = \"x\"")
+            .unwrap_err()
+            .to_string();
+        assert!(err.starts_with("emoji: invalid tier 'epic'"), "{err}");
+    }
+
+    #[test]
+    fn parses_style() {
+        let config = Config::parse("style = \"PSQL\"").unwrap();
+        assert_eq!(config.style, Some(TableStyle::Psql));
+        assert_eq!(Config::parse("").unwrap().style, None);
+        let err = Config::parse("style = \"fancy\"").unwrap_err().to_string();
+        assert!(
+            err.starts_with("style: invalid style 'fancy', expected one of: rounded, "),
+            "{err}"
+        );
+    }
+
+    #[test]
+    fn errors_name_the_overridden_rule() {
+        let err = Config::parse(
+            "[overrides]
+starts_digit = \"common\"",
+        )
+        .unwrap_err()
+        .to_string();
+        assert!(
+            err.starts_with("overrides: unknown rule 'starts_digit', expected one of: "),
+            "{err}"
+        );
+        assert!(err.contains("starts_digits"), "{err}");
+        let err = Config::parse(
+            "[overrides]
+hexspeak = \"epic\"",
+        )
+        .unwrap_err()
+        .to_string();
+        assert!(
+            err.starts_with("overrides: hexspeak: invalid tier 'epic'"),
+            "{err}"
+        );
+        let err = Config::parse(
+            "[overrides]
+hexspeak = { probability = 0.0 }",
+        )
+        .unwrap_err()
+        .to_string();
+        assert!(err.starts_with("overrides: hexspeak: probability"), "{err}");
+        let err = Config::parse(
+            "[overrides]
+hexspeak = { tier = \"rare\", odds = 0.1 }",
+        )
+        .unwrap_err()
+        .to_string();
+        assert!(
+            err.starts_with("overrides: hexspeak: unknown field `odds`"),
+            "{err}"
+        );
+    }
+}
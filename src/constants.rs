@@ -0,0 +1,85 @@
+//! Digits of famous numeric constants that can appear in a hash.
+
+/// The shortest run of a constant's leading digits worth reporting.
+pub const MIN_DIGITS: usize = 8;
+
+/// Names and leading digits of each constant, without the decimal point.
+pub const CONSTANTS: &[(&str, &str)] = &[
+    ("π", "3141592653589793238462643383279502884197"),
+    ("e", "2718281828459045235360287471352662497757"),
+    ("√2", "1414213562373095048801688724209698078569"),
+    ("φ", "1618033988749894848204586834365638117720"),
+    (
+        "the Fibonacci sequence",
+        "1123581321345589144233377610987159725844",
+    ),
+];
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ConstantMatch {
+    pub name: &'static str,
+    /// The leading digits of the constant that were found.
+    pub digits: &'static str,
+    pub offset: usize,
+}
+
+/// Finds the longest run of a constant's leading digits in `hash`, preferring
+/// matches at the start of the hash when several have the same length.
+pub fn find(hash: &str) -> Option<ConstantMatch> {
+    CONSTANTS
+        .iter()
+        .filter_map(|&(name, digits)| {
+            (MIN_DIGITS..=digits.len().min(hash.len()))
+                .rev()
+                .find_map(|len| {
+                    let digits = &digits[..len];
+                    let offset = if hash.starts_with(digits) {
+                        0
+                    } else {
+                        hash.find(digits)?
+                    };
+                    Some(ConstantMatch {
+                        name,
+                        digits,
+                        offset,
+                    })
+                })
+        })
+        .min_by_key(|m| (std::cmp::Reverse(m.digits.len()), m.offset))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn finds_prefix_matches() {
+        let found = find("314159265a91c5d48b2f6e1a9e73cb5d284e6f10").unwrap();
+        assert_eq!(found.name, "π");
+        assert_eq!(found.digits, "314159265");
+        assert_eq!(found.offset, 0);
+    }
+
+    #[test]
+    fn finds_interior_matches() {
+        let found = find("7e3a91c5d4112358132134e1a9e73cb5d284e6f1").unwrap();
+        assert_eq!(found.name, "the Fibonacci sequence");
+        assert_eq!(found.digits, "112358132134");
+        assert_eq!(found.offset, 10);
+    }
+
+    #[test]
+    fn ignores_seven_digit_near_misses() {
+        assert_eq!(find("2718281a91c5d48b2f6e1a9e73cb5d284e6f10a9"), None);
+        assert_eq!(find("7e3a91c5d41414213e1a9e73cb5d284e6f10a9c3"), None);
+        // Eight digits, but not the leading ones.
+        assert_eq!(find("7e3a91c5d41592653e1a9e73cb5d284e6f10a9c3"), None);
+    }
+
+    #[test]
+    fn longest_match_wins() {
+        let found = find("16180339a91c5d48b2f6e2718281828ae6f10a9c").unwrap();
+        assert_eq!(found.name, "e");
+        assert_eq!(found.digits, "2718281828");
+    }
+}
@@ -0,0 +1,127 @@
+//! User-supplied regex rules, given with `--rule <tier>:<regex>` or defined in
+//! the config file.
+
+use std::str::FromStr;
+
+use clap::ValueEnum;
+use regex::Regex;
+
+use crate::{RarityTier, RuleMatch, Span};
+
+#[derive(Clone, Debug)]
+pub struct CustomRule {
+    pub tier: RarityTier,
+    pub regex: Regex,
+    /// Explanation to report, defaulting to the regex source.
+    pub explanation: Option<String>,
+    pub percentage: Option<f64>,
+}
+
+impl FromStr for CustomRule {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (tier, pattern) = s
+            .split_once(':')
+            .ok_or_else(|| format!("expected <tier>:<regex>, got '{s}'"))?;
+        Ok(Self {
+            tier: parse_tier(tier)?,
+            regex: parse_regex(pattern)?,
+            explanation: None,
+            percentage: None,
+        })
+    }
+}
+
+impl CustomRule {
+    pub fn rule_match(&self, hash: &str) -> Option<RuleMatch> {
+        self.regex.find(hash).map(|found| {
+            let explanation = self
+                .explanation
+                .as_deref()
+                .unwrap_or_else(|| self.regex.as_str());
+            let span = Span::new(found.start(), found.as_str());
+            RuleMatch {
+                tier: self.tier.clone(),
+                percentage: self.percentage,
+                // A pattern can match nothing at all, which there's no point
+                // showing.
+                explanation: if found.is_empty() {
+                    explanation.to_string()
+                } else {
+                    span.explain(explanation)
+                },
+                span: Some(span),
+            }
+        })
+    }
+}
+
+pub fn parse_regex(pattern: &str) -> Result<Regex, String> {
+    Regex::new(pattern).map_err(|e| format!("invalid regex '{pattern}': {e}"))
+}
+
+/// Parses a tier name case-insensitively, listing the valid names on error.
+pub fn parse_tier(tier: &str) -> Result<RarityTier, String> {
+    RarityTier::from_str(tier, true).map_err(|_| {
+        let valid = RarityTier::value_variants()
+            .iter()
+            .filter_map(|t| t.to_possible_value())
+            .map(|v| v.get_name().to_string())
+            .collect::<Vec<String>>();
+        format!(
+            "invalid tier '{tier}', expected one of: {}",
+            valid.join(", ")
+        )
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn parses_tier_and_regex() {
+        let rule = "rare:^1337".parse::<CustomRule>().unwrap();
+        assert!(rule.tier == RarityTier::Rare);
+        assert_eq!(rule.regex.as_str(), "^1337");
+        let rule = "Uncommon:(dead|beef){2}".parse::<CustomRule>().unwrap();
+        assert!(rule.tier == RarityTier::Uncommon);
+    }
+
+    #[test]
+    fn keeps_colons_in_the_regex() {
+        let rule = "legendary:a:b".parse::<CustomRule>().unwrap();
+        assert_eq!(rule.regex.as_str(), "a:b");
+    }
+
+    #[test]
+    fn rejects_invalid_tiers() {
+        let err = "epic:^1337".parse::<CustomRule>().unwrap_err();
+        assert_eq!(
+            err,
+            "invalid tier 'epic', expected one of: common, uncommon, rare, legendary"
+        );
+        assert!("^1337: '1337' at offset 0".parse::<CustomRule>().is_err());
+    }
+
+    #[test]
+    fn rejects_invalid_regexes() {
+        let err = "rare:(dead".parse::<CustomRule>().unwrap_err();
+        assert!(err.starts_with("invalid regex '(dead'"), "{err}");
+    }
+
+    #[test]
+    fn matches_hashes() {
+        let rule = "rare:^1337".parse::<CustomRule>().unwrap();
+        let found = rule
+            .rule_match("1337a91c5d48b2f6e1a9e73cb5d284e6f10a9c3b")
+            .unwrap();
+        assert_eq!(found.explanation, "^1337: '1337' at offset 0");
+        assert!(found.tier == RarityTier::Rare);
+        assert_eq!(found.percentage, None);
+        assert!(rule
+            .rule_match("7e3a91c5d48b2f6e1a9e73cb5d284e6f10a91337")
+            .is_none());
+    }
+}
@@ -0,0 +1,67 @@
+//! The symbols `--emoji` shows before each tier in the Tier column.
+
+use std::collections::HashMap;
+
+use crate::RarityTier;
+
+/// The symbol a tier is shown with when the config gives it none.
+fn default_symbol(tier: &RarityTier) -> &'static str {
+    match tier {
+        RarityTier::Common => "⚪",
+        RarityTier::Uncommon => "🟢",
+        RarityTier::Rare => "💎",
+        RarityTier::Legendary => "🌟",
+    }
+}
+
+/// The symbol a tier is shown with in a table limited to ASCII, which can't
+/// be relied on to show an emoji.
+fn ascii_symbol(tier: &RarityTier) -> &'static str {
+    match tier {
+        RarityTier::Common => "-",
+        RarityTier::Uncommon => "+",
+        RarityTier::Rare => "*",
+        RarityTier::Legendary => "!",
+    }
+}
+
+/// The symbol of `tier`: the one the `[emoji]` of the config gives it, or its
+/// default. When the table is limited to `ascii`, a symbol that isn't ASCII
+/// is replaced by a character standing for the tier.
+pub fn symbol<'a>(
+    tier: &RarityTier,
+    symbols: &'a HashMap<RarityTier, String>,
+    ascii: bool,
+) -> &'a str {
+    let symbol = symbols
+        .get(tier)
+        .map_or(default_symbol(tier), String::as_str);
+    if ascii && !symbol.is_ascii() {
+        ascii_symbol(tier)
+    } else {
+        symbol
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn configured_symbols_replace_the_defaults() {
+        let symbols = HashMap::from([(RarityTier::Rare, "R".to_string())]);
+        assert_eq!(symbol(&RarityTier::Rare, &symbols, false), "R");
+        assert_eq!(symbol(&RarityTier::Legendary, &symbols, false), "🌟");
+    }
+
+    #[test]
+    fn ascii_tables_fall_back_to_characters() {
+        let symbols = HashMap::from([
+            (RarityTier::Rare, "R".to_string()),
+            (RarityTier::Uncommon, "🍀".to_string()),
+        ]);
+        assert_eq!(symbol(&RarityTier::Rare, &symbols, true), "R");
+        assert_eq!(symbol(&RarityTier::Uncommon, &symbols, true), "+");
+        assert_eq!(symbol(&RarityTier::Common, &symbols, true), "-");
+    }
+}
@@ -0,0 +1,517 @@
+//! Reading the history of a repository on GitHub through its REST API, given
+//! with `--github owner/name`, so it needn't be cloned.
+//!
+//! Commits are listed a page at a time from `GET /repos/{owner}/{name}/commits`
+//! as they're read. A `GITHUB_TOKEN` in the environment is sent along, which
+//! raises the rate limit and gives access to private repositories.
+
+use std::thread;
+use std::time::Duration;
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, FixedOffset, SecondsFormat, Utc};
+use serde::Deserialize;
+
+use crate::backend::{Log, LogEntry, LogOptions};
+
+const API_URL: &str = "https://api.github.com";
+
+/// Most commits the API lists on one page.
+const PAGE_SIZE: usize = 100;
+
+/// How many times a request is made before giving up on it.
+const MAX_ATTEMPTS: u32 = 4;
+
+/// Longest wait for the rate limit to reset before giving up instead.
+const MAX_WAIT: Duration = Duration::from_secs(60);
+
+/// Checks that `value` names a repository like `owner/name`.
+pub fn parse_repo(value: &str) -> Result<String, String> {
+    let valid = |part: &str| {
+        !part.is_empty()
+            && part
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'))
+    };
+    match value.split_once('/') {
+        Some((owner, name)) if valid(owner) && valid(name) => Ok(value.to_string()),
+        _ => Err(format!("'{value}' isn't a repository like owner/name")),
+    }
+}
+
+/// Lists the commits of `repo` newest first, like [`crate::backend::git_log`]
+/// lists those of a local one. The dates and `max_count` are passed to the
+/// API where it can apply them, and the other filters are applied as the
+/// commits are read.
+pub fn log(repo: &str, options: &LogOptions) -> Result<Log> {
+    let agent = ureq::AgentBuilder::new()
+        .user_agent(concat!("git-rare/", env!("CARGO_PKG_VERSION")))
+        .build();
+    let pages = Pages {
+        agent,
+        repo: repo.to_string(),
+        token: std::env::var("GITHUB_TOKEN").ok().filter(|t| !t.is_empty()),
+        next: Some(first_page(repo, options)),
+        page: vec![].into_iter(),
+        options: options.clone(),
+    };
+    Ok(Log {
+        len: None,
+        entries: match options.max_count {
+            Some(max_count) => Box::new(pages.take(max_count)),
+            None => Box::new(pages),
+        },
+    })
+}
+
+/// The URL of the first page of commits `options` selects.
+fn first_page(repo: &str, options: &LogOptions) -> String {
+    let format = |date: DateTime<FixedOffset>| {
+        date.with_timezone(&Utc)
+            .to_rfc3339_opts(SecondsFormat::Secs, true)
+    };
+    // The API filters by commit date, so an author date window can only be
+    // narrowed down to the commits committed since it opened: a commit is
+    // committed after it's authored, though maybe after the window closed.
+    let until = options.dates.until.filter(|_| !options.author_dates);
+    // A page only needs to be as long as the commits wanted, when each of
+    // those listed is one of them.
+    let filtered = !options.authors.is_empty()
+        || !options.greps.is_empty()
+        || options.merges != Default::default();
+    let per_page = match options.max_count {
+        Some(max_count) if !filtered && until == options.dates.until => {
+            max_count.clamp(1, PAGE_SIZE)
+        }
+        _ => PAGE_SIZE,
+    };
+    let mut url = format!("{API_URL}/repos/{repo}/commits?per_page={per_page}");
+    if let Some(since) = options.dates.since {
+        url.push_str(&format!("&since={}", format(since)));
+    }
+    if let Some(until) = until {
+        url.push_str(&format!("&until={}", format(until)));
+    }
+    url
+}
+
+/// The commits of a repository, read from the API a page at a time.
+struct Pages {
+    agent: ureq::Agent,
+    repo: String,
+    token: Option<String>,
+    /// The URL of the next page, until the last one has been read.
+    next: Option<String>,
+    /// What's left of the page being read.
+    page: std::vec::IntoIter<ApiCommit>,
+    options: LogOptions,
+}
+
+impl Pages {
+    /// Reads the page at `url`, waiting out rate limits and retrying server
+    /// errors a few times, and returns its commits with the link to the
+    /// next one.
+    fn fetch(&self, url: &str) -> Result<(Vec<ApiCommit>, Option<String>)> {
+        let mut attempt = 0;
+        loop {
+            attempt += 1;
+            let mut request = self
+                .agent
+                .get(url)
+                .set("Accept", "application/vnd.github+json")
+                .set("X-GitHub-Api-Version", "2022-11-28");
+            if let Some(token) = &self.token {
+                request = request.set("Authorization", &format!("Bearer {token}"));
+            }
+            let response = match request.call() {
+                Ok(response) => response,
+                // An empty repository has no commits, which the API reports
+                // as a conflict.
+                Err(ureq::Error::Status(409, _)) => return Ok((vec![], None)),
+                Err(ureq::Error::Status(status, response)) => {
+                    let header = |name: &str| response.header(name).map(str::to_string);
+                    let now = Utc::now().timestamp();
+                    if let Some(delay) = retry_delay(status, header, attempt, now) {
+                        thread::sleep(delay);
+                        continue;
+                    }
+                    return Err(self.status_error(status, response));
+                }
+                Err(e) => {
+                    return Err(e).with_context(|| format!("failed to reach {API_URL}"));
+                }
+            };
+            let next = response.header("Link").and_then(next_link);
+            let body = response
+                .into_string()
+                .context("failed to read the GitHub API's response")?;
+            return Ok((parse_commits(&body)?, next));
+        }
+    }
+
+    /// The error for a request the API refused with `status`.
+    fn status_error(&self, status: u16, response: ureq::Response) -> anyhow::Error {
+        let repo = &self.repo;
+        let exhausted = response.header("x-ratelimit-remaining") == Some("0");
+        let message = response
+            .into_string()
+            .ok()
+            .and_then(|body| serde_json::from_str::<ApiError>(&body).ok())
+            .map(|error| error.message)
+            .unwrap_or_default();
+        match status {
+            403 | 429 if exhausted => anyhow::anyhow!(
+                "the GitHub API's rate limit is used up; set GITHUB_TOKEN to raise it"
+            ),
+            404 => anyhow::anyhow!(
+                "GitHub repository {repo} not found; set GITHUB_TOKEN if it's private"
+            ),
+            _ => anyhow::anyhow!("the GitHub API failed with {status} for {repo}: {message}"),
+        }
+    }
+
+    /// The entry for `commit`, or `None` when the options leave it out.
+    fn entry(&self, commit: ApiCommit) -> Option<LogEntry> {
+        let options = &self.options;
+        if !options.merges.allows(commit.parents.len()) {
+            return None;
+        }
+        let ApiDetail {
+            author,
+            committer,
+            message,
+        } = commit.commit;
+        let author = author?;
+        let identity = format!("{} <{}>", author.name, author.email);
+        if !options.matches_author(&identity) || !options.matches_message(&message) {
+            return None;
+        }
+        let dated = if options.author_dates {
+            Some(&author)
+        } else {
+            committer.as_ref()
+        };
+        if !options.dates.is_unbounded() && !options.dates.contains(&dated?.date) {
+            return None;
+        }
+        let shown = if options.committer {
+            committer?
+        } else {
+            author
+        };
+        Some(LogEntry {
+            hash: commit.sha,
+            author: shown.name,
+            email: shown.email,
+            datetime: shown.date,
+            message,
+        })
+    }
+}
+
+impl Iterator for Pages {
+    type Item = Result<LogEntry>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(commit) = self.page.next() {
+                match self.entry(commit) {
+                    Some(entry) => return Some(Ok(entry)),
+                    None => continue,
+                }
+            }
+            let url = self.next.take()?;
+            match self.fetch(&url) {
+                Ok((commits, next)) => {
+                    self.page = commits.into_iter();
+                    self.next = next;
+                }
+                Err(e) => return Some(Err(e)),
+            }
+        }
+    }
+}
+
+/// A commit as the API lists it.
+#[derive(Deserialize)]
+struct ApiCommit {
+    sha: String,
+    commit: ApiDetail,
+    parents: Vec<ApiParent>,
+}
+
+#[derive(Deserialize)]
+struct ApiDetail {
+    /// Missing for commits whose signature git can't parse.
+    author: Option<ApiSignature>,
+    committer: Option<ApiSignature>,
+    message: String,
+}
+
+#[derive(Deserialize)]
+struct ApiSignature {
+    name: String,
+    email: String,
+    date: DateTime<FixedOffset>,
+}
+
+#[derive(Deserialize)]
+struct ApiParent {}
+
+/// The body of a refused request.
+#[derive(Deserialize)]
+struct ApiError {
+    message: String,
+}
+
+/// Parses a page of commits.
+fn parse_commits(body: &str) -> Result<Vec<ApiCommit>> {
+    serde_json::from_str(body).context("the GitHub API's response isn't a list of commits")
+}
+
+/// The URL of the next page in a `Link` header, like
+/// `<https://api.github.com/...&page=2>; rel="next", <...>; rel="last"`.
+fn next_link(header: &str) -> Option<String> {
+    header.split(',').find_map(|link| {
+        let (url, params) = link.split_once(';')?;
+        params
+            .split(';')
+            .any(|param| param.trim() == r#"rel="next""#)
+            .then(|| url.trim().trim_start_matches('<').trim_end_matches('>'))
+            .map(str::to_string)
+    })
+}
+
+/// How long to wait before retrying the `attempt`th request, which failed
+/// with `status`, or `None` when it shouldn't be retried. `header` reads the
+/// response's headers, and `now` is the time in seconds since the epoch.
+fn retry_delay(
+    status: u16,
+    header: impl Fn(&str) -> Option<String>,
+    attempt: u32,
+    now: i64,
+) -> Option<Duration> {
+    if attempt >= MAX_ATTEMPTS {
+        return None;
+    }
+    let seconds = |name: &str| header(name)?.trim().parse::<i64>().ok();
+    let wait = |seconds: i64| {
+        let delay = Duration::from_secs(seconds.max(1).unsigned_abs());
+        (delay <= MAX_WAIT).then_some(delay)
+    };
+    // The secondary rate limits say how long to back off for.
+    if let Some(retry_after) = seconds("retry-after") {
+        return wait(retry_after);
+    }
+    let backoff = Duration::from_secs(1 << (attempt - 1));
+    match status {
+        403 | 429 if header("x-ratelimit-remaining").as_deref() == Some("0") => {
+            wait(seconds("x-ratelimit-reset")? - now)
+        }
+        429 | 500..=599 => Some(backoff),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::collections::HashMap;
+
+    use chrono::DateTime;
+    use regex::Regex;
+
+    use super::*;
+    use crate::backend::{DateRange, Merges};
+
+    const PAGE: &str = include_str!("../tests/fixtures/github/commits.json");
+
+    fn pages(options: LogOptions) -> Pages {
+        Pages {
+            agent: ureq::agent(),
+            repo: "octocat/hello".to_string(),
+            token: None,
+            next: None,
+            page: parse_commits(PAGE).unwrap().into_iter(),
+            options,
+        }
+    }
+
+    fn hashes(options: LogOptions) -> Vec<String> {
+        pages(options)
+            .map(|entry| entry.unwrap().hash[..7].to_string())
+            .collect()
+    }
+
+    fn headers(headers: &[(&str, &str)]) -> impl Fn(&str) -> Option<String> {
+        let headers = headers
+            .iter()
+            .map(|(name, value)| (name.to_string(), value.to_string()))
+            .collect::<HashMap<String, String>>();
+        move |name| headers.get(name).cloned()
+    }
+
+    fn date(date: &str) -> Option<DateTime<FixedOffset>> {
+        Some(DateTime::parse_from_rfc3339(date).unwrap())
+    }
+
+    #[test]
+    fn commits_are_read_from_a_recorded_page() {
+        let entries = pages(LogOptions::default())
+            .collect::<Result<Vec<LogEntry>>>()
+            .unwrap();
+        assert_eq!(entries.len(), 4);
+        assert_eq!(entries[0].hash, "7fd1a60b01f91b314f59955a4e4d4e80d8edf11d");
+        assert_eq!(entries[0].author, "The Octocat");
+        assert_eq!(
+            entries[0].datetime,
+            date("2024-03-06T15:06:50-08:00").unwrap()
+        );
+        assert_eq!(
+            entries[0].message,
+            "Merge pull request #6 from topic\n\nTidy up"
+        );
+        // Shown by the author, not the committer.
+        assert_eq!(entries[2].author, "Jane Roe");
+    }
+
+    #[test]
+    fn filters_are_applied_to_the_page() {
+        let merges = |merges| LogOptions {
+            merges,
+            ..LogOptions::default()
+        };
+        assert_eq!(hashes(merges(Merges::Only)), ["7fd1a60"]);
+        assert_eq!(hashes(merges(Merges::Exclude)).len(), 3);
+
+        let authors = LogOptions {
+            authors: vec![Regex::new("jane@").unwrap()],
+            ..LogOptions::default()
+        };
+        assert_eq!(hashes(authors), ["553c207"]);
+
+        // Each line of a message is matched on its own.
+        let greps = |all_match, invert_grep| LogOptions {
+            greps: vec![
+                Regex::new("^(Tidy|Update)").unwrap(),
+                Regex::new("up").unwrap(),
+            ],
+            all_match,
+            invert_grep,
+            ..LogOptions::default()
+        };
+        assert_eq!(hashes(greps(false, false)), ["7fd1a60", "7629413"]);
+        assert_eq!(hashes(greps(true, false)), ["7fd1a60"]);
+        assert_eq!(hashes(greps(false, true)), ["553c207", "c3d0be4"]);
+        assert_eq!(hashes(greps(true, true)), ["7629413", "553c207", "c3d0be4"]);
+
+        let dates = LogOptions {
+            dates: DateRange {
+                since: date("2024-02-01T00:00:00Z"),
+                until: date("2024-03-01T00:00:00Z"),
+            },
+            ..LogOptions::default()
+        };
+        // Jane's commit was authored in February but committed in March.
+        assert_eq!(hashes(dates.clone()), ["7629413"]);
+        let authored = LogOptions {
+            author_dates: true,
+            ..dates
+        };
+        assert_eq!(hashes(authored), ["7629413", "553c207"]);
+    }
+
+    #[test]
+    fn responses_that_arent_commits_are_errors() {
+        assert!(parse_commits("[]").unwrap().is_empty());
+        assert!(parse_commits(r#"{"message": "Not Found"}"#).is_err());
+    }
+
+    #[test]
+    fn options_are_passed_as_query_parameters() {
+        let url = |options: &LogOptions| first_page("octocat/hello", options);
+        let base = "https://api.github.com/repos/octocat/hello/commits";
+        assert_eq!(url(&LogOptions::default()), format!("{base}?per_page=100"));
+
+        let recent = LogOptions {
+            max_count: Some(5),
+            ..LogOptions::default()
+        };
+        assert_eq!(url(&recent), format!("{base}?per_page=5"));
+
+        let window = LogOptions {
+            dates: DateRange {
+                since: date("2024-01-01T09:00:00+09:00"),
+                until: date("2024-02-01T00:00:00Z"),
+            },
+            max_count: Some(5),
+            ..LogOptions::default()
+        };
+        assert_eq!(
+            url(&window),
+            format!("{base}?per_page=5&since=2024-01-01T00:00:00Z&until=2024-02-01T00:00:00Z")
+        );
+        // The window may close before a commit authored in it is committed.
+        let authored = LogOptions {
+            author_dates: true,
+            ..window
+        };
+        assert_eq!(
+            url(&authored),
+            format!("{base}?per_page=100&since=2024-01-01T00:00:00Z")
+        );
+    }
+
+    #[test]
+    fn next_page_is_read_from_the_link_header() {
+        let header = r#"<https://api.github.com/repositories/1/commits?page=2>; rel="next", <https://api.github.com/repositories/1/commits?page=9>; rel="last""#;
+        assert_eq!(
+            next_link(header).as_deref(),
+            Some("https://api.github.com/repositories/1/commits?page=2")
+        );
+        let last = r#"<https://api.github.com/repositories/1/commits?page=1>; rel="prev", <https://api.github.com/repositories/1/commits?page=1>; rel="first""#;
+        assert_eq!(next_link(last), None);
+    }
+
+    #[test]
+    fn rate_limits_are_waited_out_when_they_reset_soon() {
+        let now = 1_700_000_000;
+        let limited = |reset: i64| {
+            headers(&[
+                ("x-ratelimit-remaining", "0"),
+                ("x-ratelimit-reset", &(now + reset).to_string()),
+            ])
+        };
+        assert_eq!(
+            retry_delay(403, limited(30), 1, now),
+            Some(Duration::from_secs(30))
+        );
+        assert_eq!(retry_delay(403, limited(3600), 1, now), None);
+        assert_eq!(
+            retry_delay(403, headers(&[("retry-after", "5")]), 1, now),
+            Some(Duration::from_secs(5))
+        );
+        // A refusal that isn't a rate limit is final.
+        assert_eq!(retry_delay(403, headers(&[]), 1, now), None);
+        assert_eq!(retry_delay(404, headers(&[]), 1, now), None);
+    }
+
+    #[test]
+    fn server_errors_are_retried_with_backoff() {
+        let delay = |attempt| retry_delay(502, headers(&[]), attempt, 0);
+        assert_eq!(delay(1), Some(Duration::from_secs(1)));
+        assert_eq!(delay(2), Some(Duration::from_secs(2)));
+        assert_eq!(delay(3), Some(Duration::from_secs(4)));
+        assert_eq!(delay(MAX_ATTEMPTS), None);
+    }
+
+    #[test]
+    fn repositories_are_named_by_owner_and_name() {
+        assert_eq!(
+            parse_repo("rust-lang/rust.vim").unwrap(),
+            "rust-lang/rust.vim"
+        );
+        for invalid in ["rust", "rust-lang/", "/rust", "a/b/c", "a b/c"] {
+            assert!(parse_repo(invalid).is_err(), "{invalid}");
+        }
+    }
+}
@@ -0,0 +1,76 @@
+//! A built-in dictionary of hexspeak words that can appear in a hash.
+
+/// Hexspeak words of at least six characters. Shorter words are common
+/// enough to show up in most repositories.
+pub const WORDS: &[&str] = &[
+    "8badf00d",
+    "baadf00d",
+    "badc0de",
+    "badc0ffee",
+    "c0ffee",
+    "cafebabe",
+    "cafed00d",
+    "dec0de",
+    "decafbad",
+    "deadbeef",
+    "deadc0de",
+    "defaced",
+    "facade",
+    "feedface",
+    "0ddba11",
+];
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct HexspeakMatch {
+    pub word: &'static str,
+    pub offset: usize,
+}
+
+/// Finds the longest hexspeak word in `hash`, preferring the earliest one
+/// when several words of the same length match.
+pub fn find(hash: &str) -> Option<HexspeakMatch> {
+    WORDS
+        .iter()
+        .filter_map(|word| hash.find(word).map(|offset| HexspeakMatch { word, offset }))
+        .min_by_key(|m| (std::cmp::Reverse(m.word.len()), m.offset))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn finds_word_and_offset() {
+        let found = find("7e3a91c5d48bdeadbeef73cb5d284e6f10a9c3b7").unwrap();
+        assert_eq!(found.word, "deadbeef");
+        assert_eq!(found.offset, 12);
+    }
+
+    #[test]
+    fn finds_word_with_leet_digits() {
+        let found = find("c0ffee91c5d48b2f6e1a9e73cb5d284e6f10a9c3").unwrap();
+        assert_eq!(found.word, "c0ffee");
+        assert_eq!(found.offset, 0);
+    }
+
+    #[test]
+    fn ignores_hashes_without_words() {
+        assert_eq!(find("7e3a91c5d48b2f6e1a9e73cb5d284e6f10a9c3b7"), None);
+        // Close, but not quite.
+        assert_eq!(find("7e3a91c5deadbeaf1a9e73cb5d284e6f10a9c3b7"), None);
+    }
+
+    #[test]
+    fn longest_overlapping_word_wins() {
+        // "badc0de" and "badc0ffee" overlap here, as do "c0ffee" and "badc0ffee".
+        let found = find("7e3a91badc0ffee1a9e73cbadc0de6f10a9c3b7").unwrap();
+        assert_eq!(found.word, "badc0ffee");
+        assert_eq!(found.offset, 6);
+    }
+
+    #[test]
+    fn earliest_word_wins_on_equal_length() {
+        let found = find("7e3a91cafebabe1a9e73cbdeadbeef10a9c3b7e3").unwrap();
+        assert_eq!(found.word, "cafebabe");
+    }
+}
@@ -0,0 +1,103 @@
+//! The post-commit hook `git-rare hook install` writes, which announces how
+//! rare each new commit is with `git-rare check`, or with
+//! `git-rare post-commit --amend-trailer`, which also records it in a
+//! trailer of the commit's message.
+//!
+//! The hook is marked as git-rare's, so that it's the only one replaced or
+//! removed without being asked to. Git runs it from `core.hooksPath` when
+//! that's set, and finds git-rare on `PATH` like it finds git itself.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use anyhow::{bail, Context, Result};
+
+use crate::backend;
+
+/// The line that marks a hook as git-rare's.
+const MARKER: &str = "# Installed by `git-rare hook install`";
+
+/// Set while `git-rare post-commit --amend-trailer` amends a commit, so the
+/// hook the amend runs in turn knows not to.
+pub const AMENDING: &str = "GIT_RARE_AMENDING";
+
+/// What the hook runs, with `amend_trailer` to add a trailer to each commit
+/// that isn't Common. It's a shell script even on Windows, where Git for
+/// Windows runs hooks with its own shell.
+fn script(amend_trailer: bool) -> String {
+    let command = if amend_trailer {
+        "git-rare post-commit --amend-trailer"
+    } else {
+        "git-rare check HEAD --quiet-common"
+    };
+    format!(
+        "#!/bin/sh\n\
+         {MARKER}; `git-rare hook uninstall` removes it.\n\
+         {command}\n"
+    )
+}
+
+/// Whether there's a post-commit hook, and whose it is.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Status {
+    Missing,
+    Installed,
+    /// A hook git-rare didn't write, which it leaves alone.
+    Other,
+}
+
+/// The path of the post-commit hook of `repo`, or the current directory,
+/// and whose it is.
+pub fn status(repo: Option<&Path>) -> Result<(PathBuf, Status)> {
+    let path = backend::git_hooks_dir(repo)?.join("post-commit");
+    let status = match fs::read(&path) {
+        Ok(contents) if String::from_utf8_lossy(&contents).contains(MARKER) => Status::Installed,
+        Ok(_) => Status::Other,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Status::Missing,
+        Err(e) => return Err(e).with_context(|| format!("failed to read {}", path.display())),
+    };
+    Ok((path, status))
+}
+
+/// Writes the post-commit hook of `repo`, or the current directory, like
+/// [`script`] with `amend_trailer`, replacing one git-rare didn't write only
+/// with `force`. Returns its path.
+pub fn install(repo: Option<&Path>, force: bool, amend_trailer: bool) -> Result<PathBuf> {
+    let (path, status) = status(repo)?;
+    if status == Status::Other && !force {
+        bail!(
+            "a post-commit hook already exists at {}; pass --force to replace it",
+            path.display()
+        );
+    }
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir).with_context(|| format!("failed to create {}", dir.display()))?;
+    }
+    fs::write(&path, script(amend_trailer))
+        .with_context(|| format!("failed to write {}", path.display()))?;
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        fs::set_permissions(&path, fs::Permissions::from_mode(0o755))
+            .with_context(|| format!("failed to make {} executable", path.display()))?;
+    }
+    Ok(path)
+}
+
+/// Removes the post-commit hook of `repo`, or the current directory, if
+/// git-rare wrote it. Returns its path and whether there was one to remove.
+pub fn uninstall(repo: Option<&Path>) -> Result<(PathBuf, bool)> {
+    let (path, status) = status(repo)?;
+    match status {
+        Status::Missing => Ok((path, false)),
+        Status::Other => bail!(
+            "the post-commit hook at {} wasn't installed by git-rare, so it's left alone",
+            path.display()
+        ),
+        Status::Installed => {
+            fs::remove_file(&path)
+                .with_context(|| format!("failed to remove {}", path.display()))?;
+            Ok((path, true))
+        }
+    }
+}
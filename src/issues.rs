@@ -0,0 +1,62 @@
+//! Parsing of issue and pull request references in commit messages.
+
+use std::sync::OnceLock;
+
+use regex::Regex;
+
+/// A reference like `#123`, `GH-123` or `!45`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct IssueRef<'a> {
+    /// The reference as written, like `GH-123`.
+    pub text: &'a str,
+    pub number: &'a str,
+}
+
+/// Finds every issue reference in `message`, in order of appearance.
+pub fn references(message: &str) -> Vec<IssueRef<'_>> {
+    static PATTERN: OnceLock<Regex> = OnceLock::new();
+    let pattern = PATTERN.get_or_init(|| {
+        Regex::new(r"(?:^|[^\w])((?:#|(?i:GH-)|!)([0-9]+))\b").expect("valid pattern")
+    });
+    pattern
+        .captures_iter(message)
+        .filter_map(|captures| {
+            Some(IssueRef {
+                text: captures.get(1)?.as_str(),
+                number: captures.get(2)?.as_str(),
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn numbers(message: &str) -> Vec<&str> {
+        references(message).iter().map(|r| r.number).collect()
+    }
+
+    #[test]
+    fn finds_every_kind_of_reference() {
+        let found = references("Fixes #4217, see GH-88 and !45");
+        assert_eq!(found.len(), 3);
+        assert_eq!(found[0].text, "#4217");
+        assert_eq!(found[1].text, "GH-88");
+        assert_eq!(found[2].text, "!45");
+    }
+
+    #[test]
+    fn finds_references_in_the_body() {
+        assert_eq!(
+            numbers("Tidy up\n\nCloses #12\nRefs gh-3456"),
+            ["12", "3456"]
+        );
+    }
+
+    #[test]
+    fn ignores_things_that_only_look_like_references() {
+        assert!(numbers("C#12 and abc#123 and #12ab").is_empty());
+        assert!(numbers("Nothing to see here").is_empty());
+    }
+}
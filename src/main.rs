@@ -1,17 +1,68 @@
+use std::borrow::Cow;
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{HashMap, HashSet};
 use std::fmt::{Display, Error, Formatter};
+use std::hash::{Hash, Hasher};
+use std::io::{BufRead, IsTerminal};
+use std::ops::Range;
+use std::path::{Path, PathBuf};
 use std::string::ToString;
+use std::sync::Arc;
 use std::time::Instant;
 
-use anyhow::Result;
-use chrono::{DateTime, FixedOffset};
-use clap::{Parser, ValueEnum};
+use anyhow::{bail, Context, Result};
+use chrono::format::{Item, StrftimeItems};
+use chrono::{
+    DateTime, Duration, FixedOffset, Local, Months, NaiveDate, NaiveDateTime, NaiveTime, Utc,
+};
+use clap::{ArgGroup, Parser, Subcommand, ValueEnum};
 use rayon::prelude::*;
+use serde::{Deserialize, Serialize};
 use strum_macros::Display;
-use tabled::{settings::Style, Table, Tabled};
-use xshell::{cmd, Shell};
+use tabled::builder::Builder;
+use tabled::settings::location::ByColumnName;
+use tabled::settings::object::Segment;
+use tabled::settings::Style;
+use tabled::{Table, Tabled};
+
+mod backend;
+mod cache;
+mod color;
+mod config;
+mod constants;
+mod custom_rule;
+mod emoji;
+#[cfg(feature = "github")]
+mod github;
+mod hexspeak;
+mod hook;
+mod issues;
+mod memes;
+mod odds;
+mod output;
+mod pager;
+mod patterns;
+#[cfg(feature = "plugins")]
+mod plugin;
+mod prime;
+mod probability;
+mod sort;
+mod timings;
+mod width;
+mod words;
+
+use backend::{Backend, DateRange, LogEntry, LogOptions, Merges, NotARepository};
+use cache::Cache;
+use config::RuleOverride;
+use custom_rule::CustomRule;
+use output::{Column, Format, Output};
+use patterns::PatternSet;
+use sort::Sortable;
+use timings::{Phase, Timings};
 
 #[derive(Parser)]
 #[command(author, version, about, long_about = None)]
+#[command(group(ArgGroup::new("abbreviated").multiple(true)))]
 struct CliArgs {
     #[arg(short, long, conflicts_with_all=["count"], help="Show all commits")]
     all: bool,
@@ -19,9 +70,633 @@ struct CliArgs {
     only: Option<RarityTier>,
     #[arg(short, long, help = "Show commit count")]
     count: bool,
+    #[arg(
+        long,
+        value_enum,
+        default_value_t,
+        help = "Print a table to read, or a document for other tools to parse"
+    )]
+    format: Format,
+    #[arg(
+        long,
+        value_name = "CHAR",
+        value_parser = output::parse_delimiter,
+        default_value = ",",
+        help = "Separate the fields of --format csv with CHAR, like '\\t' for tab-separated values"
+    )]
+    delimiter: u8,
+    #[arg(
+        long,
+        value_name = "PATH",
+        help = "Write what's found to PATH instead of stdout, replacing it once the scan is done"
+    )]
+    output: Option<PathBuf>,
+    #[arg(
+        long,
+        value_enum,
+        value_delimiter = ',',
+        ignore_case = true,
+        value_name = "COLUMNS",
+        help = "Show only these columns of commits, in this order, in the table, CSV and Markdown"
+    )]
+    columns: Option<Vec<Column>>,
+    #[arg(
+        long,
+        value_enum,
+        value_name = "KEY",
+        conflicts_with_all = ["count", "near_misses"],
+        help = "Sort what's found by date, newest first, by author, or by tier or percentage, rarest first"
+    )]
+    sort_by: Option<sort::SortKey>,
+    #[arg(
+        long,
+        conflicts_with_all = ["count", "near_misses"],
+        help = "Print what's found the other way round, oldest first without --sort-by"
+    )]
+    reverse: bool,
+    #[arg(
+        long,
+        conflicts_with_all = ["count", "near_misses"],
+        help = "Print a table per tier, rarest first, or the commits of each tier together"
+    )]
+    group_by_tier: bool,
+    #[arg(
+        long,
+        visible_alias = "porcelain",
+        conflicts_with = "format",
+        help = "Print a line of tab-separated fields per commit for scripts, like --format plain"
+    )]
+    plain: bool,
+    #[arg(
+        long,
+        value_enum,
+        default_value_t,
+        value_name = "WHEN",
+        help = "Color the table's tiers and the matches in each hash, only in a terminal with auto"
+    )]
+    color: color::When,
+    #[arg(
+        long,
+        help = "Show each tier in the table after its symbol, which the config's [emoji] can change"
+    )]
+    emoji: bool,
+    #[arg(
+        long,
+        help = "Show dates in the table as how long ago they were, like git's --date=relative"
+    )]
+    relative_dates: bool,
+    #[arg(
+        long,
+        conflicts_with = "columns",
+        help = "Show the first line of each commit's message, cut short in the table"
+    )]
+    show_subject: bool,
+    #[arg(
+        long,
+        conflicts_with = "columns",
+        help = "Show each commit's author email in the table, which the other formats always have"
+    )]
+    show_email: bool,
+    #[arg(
+        long,
+        conflicts_with_all = ["count", "near_misses", "stdin", "objects", "lost"],
+        help = "Show whether each commit's signature is good, which needs git, and gpg to check it"
+    )]
+    show_signatures: bool,
+    #[arg(
+        long,
+        value_name = "N",
+        value_parser = parse_subject_width,
+        help = "Cut subjects in the table to N characters, ellipsis included [default: 50]"
+    )]
+    subject_width: Option<usize>,
+    #[arg(
+        long,
+        value_name = "N",
+        value_parser = parse_max_width,
+        help = "Cut long explanations and authors short to fit the table in N columns [default: the terminal's width]"
+    )]
+    max_width: Option<usize>,
+    #[arg(
+        long,
+        value_name = "FORMAT",
+        value_parser = parse_date_format,
+        conflicts_with = "relative_dates",
+        help = "Show dates in the table in this strftime FORMAT, like '%Y-%m-%d %H:%M'"
+    )]
+    date_format: Option<String>,
+    #[arg(
+        long,
+        conflicts_with = "local",
+        help = "Show dates in the table in UTC rather than the offset they were made at"
+    )]
+    utc: bool,
+    #[arg(
+        long,
+        help = "Show dates in the table in the local time zone rather than the offset they were made at"
+    )]
+    local: bool,
+    #[arg(
+        long,
+        value_enum,
+        help = "Draw the table in this style [default: rounded in a terminal, else ascii]"
+    )]
+    style: Option<TableStyle>,
+    #[arg(
+        long,
+        help = "Don't page a table too long for the terminal with $GIT_PAGER or $PAGER"
+    )]
+    no_pager: bool,
+    #[arg(
+        long,
+        help = "Don't end the table with a line counting what it shows of each tier"
+    )]
+    no_footer: bool,
+    #[arg(
+        long,
+        conflicts_with = "no_footer",
+        help = "End --format csv with a record counting what it shows, like the table's footer"
+    )]
+    footer: bool,
+    #[arg(
+        short,
+        long,
+        help = "Don't print how long the scan took after the table"
+    )]
+    quiet: bool,
+    #[arg(
+        long,
+        help = "Print how long each phase of the scan took to stderr, and how many commits a second it got through"
+    )]
+    timings: bool,
+    #[arg(long, help = "Start --format plain with a line naming its fields")]
+    header: bool,
+    #[arg(long, value_name = "N", value_parser = parse_min_run, help = "Length of the digit and letter runs to look for [default: 9]")]
+    min_run: Option<usize>,
+    #[arg(
+        long = "rule",
+        value_name = "TIER:REGEX",
+        help = "Also report hashes matching REGEX with the given tier"
+    )]
+    rules: Vec<CustomRule>,
+    #[arg(
+        long,
+        value_name = "PATH",
+        help = "Also report hashes containing the literal patterns listed in PATH, one per line"
+    )]
+    patterns_file: Option<PathBuf>,
+    #[arg(
+        long = "disable-rule",
+        value_name = "NAME",
+        value_parser = parse_rule_name,
+        help = "Skip the built-in rule with the given name"
+    )]
+    disabled_rules: Vec<String>,
+    #[arg(
+        short = 'C',
+        long,
+        value_name = "PATH",
+        help = "Scan the repository at PATH instead of the current directory, repeatable"
+    )]
+    repo: Vec<PathBuf>,
+    #[arg(
+        long,
+        value_name = "PATH",
+        help = "Also scan the repositories listed in PATH, one per line"
+    )]
+    repos_file: Option<PathBuf>,
+    #[arg(
+        long,
+        conflicts_with_all = ["git_dir", "stdin", "objects", "lost"],
+        help = "Also scan the submodules of each repository, and theirs"
+    )]
+    recurse_submodules: bool,
+    #[cfg(feature = "github")]
+    #[arg(
+        long,
+        value_name = "OWNER/NAME",
+        value_parser = github::parse_repo,
+        conflicts_with_all = [
+            "repo", "repos_file", "git_dir", "recurse_submodules", "backend", "branches", "remotes",
+            "all_refs", "tags", "reflog", "stashes", "lost", "objects", "stdin", "revisions", "path",
+            "pathspecs", "first_parent", "since_tag", "show_signatures",
+        ],
+        help = "Scan a repository on GitHub through its API instead of a local one, without cloning it"
+    )]
+    github: Option<String>,
+    #[arg(
+        long,
+        value_name = "PATH",
+        conflicts_with_all = ["repo", "repos_file"],
+        help = "Scan the repository whose .git directory is at PATH, like `git --git-dir`, \
+                or that a linked worktree's .git file at PATH points to"
+    )]
+    git_dir: Option<PathBuf>,
+    #[arg(
+        long,
+        value_enum,
+        default_value_t = Backend::default(),
+        help = "How to read the commit history"
+    )]
+    backend: Backend,
+    #[arg(long, value_name = "PATH", help = "Path to the config file")]
+    config: Option<PathBuf>,
+    #[cfg(feature = "plugins")]
+    #[arg(
+        long = "plugin",
+        value_name = "PATH",
+        help = "Also report the matches of the WebAssembly rule plugin at PATH"
+    )]
+    plugins: Vec<PathBuf>,
+    #[arg(long, help = "Also look for meme numbers like 1337 and 42069")]
+    memes: bool,
+    #[arg(
+        long,
+        help = "Also look for words and initials spelled with 0, 1, 5 and 7 for o, l, s and t"
+    )]
+    leet: bool,
+    #[arg(
+        long,
+        group = "abbreviated",
+        help = "Classify commits by their abbreviated hash instead of the full one"
+    )]
+    short_hash_rules: bool,
+    #[arg(
+        long,
+        group = "abbreviated",
+        help = "Show abbreviated hashes, longer where they'd share a prefix, still classifying the full ones"
+    )]
+    short: bool,
+    #[arg(long, value_name = "N", value_parser = parse_abbrev, requires = "abbreviated", help = "Length of the abbreviated hash [default: 7 with --short-hash-rules, 12 with --short]")]
+    abbrev: Option<usize>,
+    #[arg(long, conflicts_with_all = ["all", "only", "count"], help = "Show the common commits that came closest to a rule")]
+    near_misses: bool,
+    #[arg(
+        short = 'n',
+        long,
+        value_name = "N",
+        default_value_t = 10,
+        requires = "near_misses",
+        help = "Number of near misses to show"
+    )]
+    limit: usize,
+    #[arg(long, help = "Scan every local branch instead of only HEAD")]
+    branches: bool,
+    #[arg(long, help = "Scan every remote-tracking branch instead of only HEAD")]
+    remotes: bool,
+    #[arg(
+        long,
+        help = "Scan every ref and HEAD, like `git log --all`, except the stashes"
+    )]
+    all_refs: bool,
+    #[arg(
+        long,
+        help = "Also scan every tag, including the hashes of annotated tags"
+    )]
+    tags: bool,
+    #[arg(
+        long,
+        help = "Also scan every commit the reflogs record, including those no ref can reach anymore"
+    )]
+    reflog: bool,
+    #[arg(
+        long,
+        help = "Also scan the commits of every stash, including those of their index and untracked files"
+    )]
+    stashes: bool,
+    #[arg(
+        long,
+        conflicts_with_all = [
+            "objects", "branches", "remotes", "all_refs", "tags", "reflog", "stashes", "revisions",
+            "first_parent",
+        ],
+        help = "Scan only the commits no ref can reach anymore, which `git gc` will delete"
+    )]
+    lost: bool,
+    #[arg(
+        long,
+        requires = "lost",
+        conflicts_with_all = ["count", "near_misses"],
+        help = "Create a refs/rare/<hash> ref for each lost commit found, so `git gc` keeps it"
+    )]
+    rescue: bool,
+    #[arg(
+        long,
+        conflicts_with_all = [
+            "all", "count", "near_misses", "tags", "reflog", "stashes", "since", "until", "author", "grep", "path",
+            "pathspecs", "first_parent", "no_merges", "merges_only",
+        ],
+        help = "Classify the hash of every object, including trees and blobs, instead of the commits"
+    )]
+    objects: bool,
+    #[arg(
+        long,
+        value_name = "N",
+        help = "Scan only the most recent N commits, or the first N objects with --objects"
+    )]
+    max_count: Option<usize>,
+    #[arg(
+        long,
+        help = "Follow only the first parent of merges, like `git log --first-parent`"
+    )]
+    first_parent: bool,
+    #[arg(long, help = "Leave out merge commits")]
+    no_merges: bool,
+    #[arg(long, conflicts_with = "no_merges", help = "Scan only merge commits")]
+    merges_only: bool,
+    #[arg(
+        long,
+        help = "Show and match authors as they were recorded, without applying .mailmap"
+    )]
+    no_mailmap: bool,
+    #[arg(
+        long,
+        help = "Show the committer and commit date instead of the author and author date"
+    )]
+    use_committer: bool,
+    #[arg(
+        long,
+        conflicts_with = "use_committer",
+        help = "Compare --since and --until to the author date instead of the commit date"
+    )]
+    author_date: bool,
+    #[arg(
+        long,
+        conflicts_with_all = [
+            "repo", "repos_file", "git_dir", "backend", "branches", "remotes", "all_refs", "tags", "reflog",
+            "stashes", "lost", "objects", "since", "until", "grep", "revisions", "path", "pathspecs",
+            "first_parent", "no_merges", "merges_only",
+        ],
+        help = "Classify the hashes read from stdin, one per line, instead of a repository's"
+    )]
+    stdin: bool,
+    #[arg(
+        long,
+        value_name = "DATE",
+        value_parser = parse_date,
+        help = "Scan only commits committed at or after DATE, like 2024-01-31 or \"2 weeks ago\""
+    )]
+    since: Option<DateTime<FixedOffset>>,
+    #[arg(
+        long,
+        value_name = "DATE",
+        value_parser = parse_date,
+        help = "Scan only commits committed at or before DATE"
+    )]
+    until: Option<DateTime<FixedOffset>>,
+    #[arg(
+        long,
+        value_name = "TAG",
+        num_args = 0..=1,
+        conflicts_with_all = [
+            "branches", "remotes", "all_refs", "revisions", "stdin", "objects", "lost",
+        ],
+        help = "Scan only the commits since TAG, or since the most recent tag reachable from HEAD"
+    )]
+    since_tag: Option<Option<String>>,
+    #[arg(
+        long,
+        value_name = "PATTERN",
+        value_parser = custom_rule::parse_regex,
+        help = "Scan only commits whose author name or email matches the regex PATTERN, repeatable"
+    )]
+    author: Vec<regex::Regex>,
+    #[arg(
+        long,
+        value_name = "PATTERN",
+        value_parser = custom_rule::parse_regex,
+        help = "Scan only commits with a line of their message matching the regex PATTERN, \
+                like `git log --grep`, repeatable"
+    )]
+    grep: Vec<regex::Regex>,
+    #[arg(
+        long,
+        requires = "grep",
+        help = "Scan only commits whose message matches every --grep pattern"
+    )]
+    all_match: bool,
+    #[arg(
+        long,
+        requires = "grep",
+        help = "Scan only commits whose message doesn't match the --grep patterns"
+    )]
+    invert_grep: bool,
+    #[arg(
+        long,
+        value_name = "PATH",
+        help = "Scan only commits that modify PATH, a pathspec like `git log -- PATH` takes, \
+                repeatable. History is simplified like git simplifies it for paths, so \
+                merges and branches that didn't change PATH can be skipped"
+    )]
+    path: Vec<String>,
+    #[arg(
+        long,
+        help = "Fail instead of scanning a shallow clone, which is missing some of its history"
+    )]
+    require_full_history: bool,
+    #[arg(short, long, help = "Print what is being scanned to stderr")]
+    verbose: bool,
+    #[arg(
+        long,
+        help = "Classify every commit again instead of reusing the classifications kept from \
+                earlier scans"
+    )]
+    no_cache: bool,
+    #[arg(
+        value_name = "REVISION",
+        help = "Scan only these revisions, like `main..topic`, instead of all of HEAD"
+    )]
+    revisions: Vec<String>,
+    #[arg(
+        last = true,
+        value_name = "PATHSPEC",
+        help = "Scan only commits that modify these paths, like --path"
+    )]
+    pathspecs: Vec<String>,
+    #[command(subcommand)]
+    command: Option<Command>,
 }
 
-#[derive(Tabled)]
+#[derive(Subcommand)]
+enum Command {
+    /// Manage the classifications kept between scans
+    Cache {
+        #[command(subcommand)]
+        action: CacheAction,
+    },
+    /// Manage the post-commit hook that announces how rare each new commit is
+    Hook {
+        #[command(subcommand)]
+        action: HookAction,
+    },
+    /// Announce how rare a single commit is, without scanning its history
+    Check {
+        #[arg(default_value = "HEAD", help = "The commit to check")]
+        revision: String,
+        #[arg(long, help = "Print nothing for a Common commit")]
+        quiet_common: bool,
+    },
+    /// Write a note under refs/notes/rare on each commit the scan finds that
+    /// isn't Common, with its tier, explanation and probability
+    Annotate {
+        #[arg(
+            long,
+            help = "Also create a lightweight tag rare/<short hash> for each of them"
+        )]
+        tags: bool,
+        #[arg(
+            long,
+            help = "List the notes and tags that would be written without writing them"
+        )]
+        dry_run: bool,
+    },
+    /// Announce how rare HEAD is, like the post-commit hook does, printing
+    /// nothing for a Common commit
+    PostCommit {
+        #[arg(
+            long,
+            help = "Amend a commit that isn't Common to add a `Rarity:` trailer to its message, \
+                    which changes its hash"
+        )]
+        amend_trailer: bool,
+    },
+}
+
+#[derive(Subcommand)]
+enum CacheAction {
+    /// Delete the classifications kept for the repository
+    Clear,
+}
+
+#[derive(Subcommand)]
+enum HookAction {
+    /// Write a post-commit hook that runs `git-rare check HEAD --quiet-common`
+    Install {
+        #[arg(long, help = "Replace a post-commit hook git-rare didn't install")]
+        force: bool,
+        #[arg(
+            long,
+            help = "Run `git-rare post-commit --amend-trailer` instead, adding a `Rarity:` \
+                    trailer to each commit that isn't Common"
+        )]
+        amend_trailer: bool,
+    },
+    /// Remove the post-commit hook, if git-rare installed it
+    Uninstall,
+    /// Show whether the post-commit hook is installed
+    Status,
+}
+
+impl CliArgs {
+    /// Sorts what's found as `--sort-by` and `--reverse` ask, and with
+    /// `--group-by-tier` puts those of each tier together.
+    fn sort<T: Sortable>(&self, found: &mut [T]) {
+        sort::sort(found, self.sort_by, self.reverse);
+        if self.group_by_tier {
+            sort::group(found);
+        }
+    }
+
+    /// The length of the hashes shown with `--short`.
+    fn short_abbrev(&self) -> Option<usize> {
+        self.short.then(|| self.abbrev.unwrap_or(DEFAULT_SHORT))
+    }
+
+    /// Whether commits are shown with their subject, with `--show-subject`
+    /// or when `--columns` asks for it.
+    fn shows_subject(&self) -> bool {
+        let chosen = self.columns.iter().flatten().any(|c| *c == Column::Subject);
+        self.show_subject || chosen
+    }
+
+    /// Whether the table shows the author's email, with `--show-email` or
+    /// when `--columns` asks for it.
+    fn shows_email(&self) -> bool {
+        let chosen = self.columns.iter().flatten().any(|c| *c == Column::Email);
+        self.show_email || chosen
+    }
+
+    /// The columns after a commit's rarity, and its subject, that are shown.
+    fn labels(&self) -> Labels {
+        Labels {
+            reflog: self.reflog,
+            stashes: self.stashes,
+            subject: self.shows_subject(),
+        }
+    }
+
+    /// The dates to scan between, checked to be in order.
+    fn dates(&self) -> Result<DateRange> {
+        if let (Some(since), Some(until)) = (self.since, self.until) {
+            if until < since {
+                bail!("--until {until} is before --since {since}");
+            }
+        }
+        Ok(DateRange {
+            since: self.since,
+            until: self.until,
+        })
+    }
+
+    /// Which of the walked commits to scan.
+    fn log_options(&self) -> Result<LogOptions> {
+        Ok(LogOptions {
+            dates: self.dates()?,
+            authors: self.author.clone(),
+            greps: self.grep.clone(),
+            all_match: self.all_match,
+            invert_grep: self.invert_grep,
+            paths: self.path.iter().chain(&self.pathspecs).cloned().collect(),
+            max_count: self.max_count,
+            first_parent: self.first_parent,
+            merges: if self.no_merges {
+                Merges::Exclude
+            } else if self.merges_only {
+                Merges::Only
+            } else {
+                Merges::Include
+            },
+            mailmap: !self.no_mailmap,
+            committer: self.use_committer,
+            author_dates: self.author_date,
+        })
+    }
+
+    /// Which commits [`print_scans`] shows, so the rest needn't be kept.
+    fn keep(&self) -> impl Fn(&Commit) -> bool + Sync {
+        let (only, all, count, near_misses) =
+            (self.only.clone(), self.all, self.count, self.near_misses);
+        move |commit| match &only {
+            Some(only) => commit.rarity.tier == *only,
+            None if all => true,
+            None if count => false,
+            None => (commit.rarity.tier == RarityTier::Common) == near_misses,
+        }
+    }
+
+    /// The revisions to walk, with the ref sets given by flags as the
+    /// `git log` options that select them. Empty for all of HEAD. The stash
+    /// is left out of every ref, since `--stashes` reads stashes instead.
+    /// Tags are scanned as well as HEAD, unless revisions are given.
+    fn walk(&self) -> Vec<String> {
+        let ref_sets = [
+            (self.branches, &["--branches"][..]),
+            (self.remotes, &["--remotes"]),
+            (self.all_refs, &["--exclude=refs/stash", "--all"]),
+            (self.tags, &["--tags"]),
+            (self.reflog, &["--reflog"]),
+        ];
+        ref_sets
+            .into_iter()
+            .filter(|(given, _)| *given)
+            .flat_map(|(_, options)| options.iter().map(|option| option.to_string()))
+            .chain(self.revisions.iter().cloned())
+            .chain((self.tags && self.revisions.is_empty()).then(|| "HEAD".to_string()))
+            .collect()
+    }
+}
+
+#[derive(Tabled, Clone, Default, Serialize)]
 struct Count {
     #[tabled(rename = "Total")]
     total: usize,
@@ -31,240 +706,6982 @@ struct Count {
     uncommon: usize,
     #[tabled(rename = "Rare")]
     rare: usize,
+    #[tabled(rename = "Legendary")]
+    legendary: usize,
+}
+
+/// A row tagged with the repository it came from, when scanning several.
+#[derive(Tabled)]
+struct RepoRow<T: Tabled> {
+    #[tabled(rename = "Repository")]
+    repo: String,
+    #[tabled(inline)]
+    row: T,
+}
+
+impl Count {
+    /// The sum of the counts of several repositories.
+    fn total(counts: &[Self]) -> Self {
+        Self {
+            total: counts.iter().map(|c| c.total).sum(),
+            common: counts.iter().map(|c| c.common).sum(),
+            uncommon: counts.iter().map(|c| c.uncommon).sum(),
+            rare: counts.iter().map(|c| c.rare).sum(),
+            legendary: counts.iter().map(|c| c.legendary).sum(),
+        }
+    }
+
+    /// Adds the counts of `other` to these.
+    fn add(&mut self, other: &Self) {
+        *self = Self::total(&[self.clone(), other.clone()]);
+    }
+
+    fn from_commits(commits: &[Commit]) -> Self {
+        Self::from_rarities(&commits.iter().map(|c| &c.rarity).collect::<Vec<&Rarity>>())
+    }
+
+    fn from_rarities(rarities: &[&Rarity]) -> Self {
+        let tier_count = |tier: RarityTier| rarities.par_iter().filter(|r| r.tier == tier).count();
+        Self {
+            total: rarities.len(),
+            common: tier_count(RarityTier::Common),
+            uncommon: tier_count(RarityTier::Uncommon),
+            rare: tier_count(RarityTier::Rare),
+            legendary: tier_count(RarityTier::Legendary),
+        }
+    }
+}
+
+/// What a table shows, for the footer after it and the `shown` of the
+/// machine formats: how many of each tier, and how likely they'd all turn
+/// up, when each has a probability.
+#[derive(Clone, Serialize)]
+struct Shown {
+    #[serde(flatten)]
+    count: Count,
+    /// Unknown when it's too small for a float, though its odds are not.
+    probability: Option<f64>,
+    /// The logarithm of the probability, which is known however small it is.
+    #[serde(skip)]
+    log10: Option<f64>,
+}
+
+impl Shown {
+    fn new<T: Sortable>(rows: &[T]) -> Self {
+        let tier_count = |tier: RarityTier| rows.iter().filter(|row| *row.tier() == tier).count();
+        let probability = rows
+            .iter()
+            .map(Sortable::probability)
+            .product::<Option<f64>>()
+            .filter(|p| *p > 0.0 && !rows.is_empty());
+        // Added up as logarithms, it can't get too small for a float.
+        let log10 = rows
+            .iter()
+            .map(|row| row.probability().map(f64::log10))
+            .sum::<Option<f64>>()
+            .filter(|log10| log10.is_finite() && !rows.is_empty());
+        Self {
+            count: Count {
+                total: rows.len(),
+                common: tier_count(RarityTier::Common),
+                uncommon: tier_count(RarityTier::Uncommon),
+                rare: tier_count(RarityTier::Rare),
+                legendary: tier_count(RarityTier::Legendary),
+            },
+            probability,
+            log10,
+        }
+    }
+
+    /// The probability of all of them, as the CSV record of the footer has
+    /// it: with an exponent when it's too small for a float.
+    fn display_probability(&self) -> String {
+        match (self.probability, self.log10) {
+            (None, Some(log10)) => odds::scientific(log10),
+            (probability, _) => display_percentage(&probability),
+        }
+    }
+
+    /// The line after the table, like "7 commits shown: 5 Uncommon, 2 Rare",
+    /// with the odds of them all when they're known.
+    fn footer(&self, noun: &str) -> String {
+        let count = &self.count;
+        let plural = if count.total == 1 { "" } else { "s" };
+        let tiers = [
+            (count.common, RarityTier::Common),
+            (count.uncommon, RarityTier::Uncommon),
+            (count.rare, RarityTier::Rare),
+            (count.legendary, RarityTier::Legendary),
+        ]
+        .iter()
+        .filter(|(n, _)| *n > 0)
+        .map(|(n, tier)| format!("{n} {tier}"))
+        .collect::<Vec<String>>()
+        .join(", ");
+        let footer = format!("{} {noun}{plural} shown: {tiers}", count.total);
+        match self.log10 {
+            Some(_) => format!("{footer} ({} together)", odds::display_log10(self.log10)),
+            None => footer,
+        }
+    }
 }
 
-#[derive(Tabled, Clone, PartialEq)]
+#[derive(Clone, PartialEq, Serialize, Deserialize)]
 struct Rarity {
-    #[tabled(rename = "Explanation")]
-    explanation: String,
-    #[tabled(rename = "Percentage")]
-    percentage: f64,
-    #[tabled(rename = "Tier")]
+    matches: Vec<RuleMatch>,
+    percentage: Option<f64>,
+    tier: RarityTier,
+}
+
+/// Its columns, with the percentage twice: as it is, and as the odds the
+/// table shows in its place unless `--columns` asks for it.
+impl Tabled for Rarity {
+    const LENGTH: usize = 4;
+
+    fn fields(&self) -> Vec<Cow<'_, str>> {
+        vec![
+            display_explanations(&self.matches).into(),
+            display_percentage(&self.percentage).into(),
+            odds::display(&self.percentage).into(),
+            self.tier.to_string().into(),
+        ]
+    }
+
+    fn headers() -> Vec<Cow<'static, str>> {
+        ["Explanation", "Percentage", "Odds", "Tier"]
+            .map(Cow::Borrowed)
+            .to_vec()
+    }
+}
+
+impl Rarity {
+    /// The stretches of the hash the matches rarer than Common depend on.
+    fn spans(&self) -> Vec<Range<usize>> {
+        self.matches
+            .iter()
+            .filter(|m| m.tier > RarityTier::Common)
+            .filter_map(|m| m.span.as_ref().map(Span::range))
+            .collect()
+    }
+
+    /// Builds a rarity whose tier and percentage come from the rarest of
+    /// `matches`, or a common rarity when nothing matched.
+    ///
+    /// Independent matches multiply: their probabilities are combined, and
+    /// when the two rarest share a tier the commit is escalated to the next
+    /// one, so two Uncommon matches make a Rare commit.
+    fn from_matches(mut matches: Vec<RuleMatch>) -> Self {
+        matches.sort_by(|a, b| {
+            b.tier
+                .cmp(&a.tier)
+                .then(sort_percentage(a).total_cmp(&sort_percentage(b)))
+        });
+        let (tier, percentage) = match independent_matches(&matches).as_slice() {
+            [] => (RarityTier::Common, None),
+            [rarest] => (rarest.tier.clone(), rarest.percentage),
+            independent @ [rarest, next, ..] => {
+                let tier = if rarest.tier == next.tier {
+                    rarest.tier.escalated()
+                } else {
+                    rarest.tier.clone()
+                };
+                let percentage = independent
+                    .iter()
+                    .map(|m| m.percentage)
+                    .product::<Option<f64>>()
+                    .or(rarest.percentage);
+                (tier, percentage)
+            }
+        };
+        Self {
+            matches,
+            percentage,
+            tier,
+        }
+    }
+}
+
+/// The rarest of `matches`, which must be sorted, followed by every other
+/// match that shares no characters with the ones before it. Matches without
+/// a span can't be shown to be independent, and common matches never count.
+fn independent_matches(matches: &[RuleMatch]) -> Vec<&RuleMatch> {
+    let Some((rarest, rest)) = matches.split_first() else {
+        return vec![];
+    };
+    let mut independent = vec![rarest];
+    if rarest.span.is_none() {
+        return independent;
+    }
+    for m in rest {
+        let disjoint = |other: &&RuleMatch| {
+            let (Some(a), Some(b)) = (&m.span, &other.span) else {
+                return false;
+            };
+            let (a, b) = (a.range(), b.range());
+            a.end <= b.start || b.end <= a.start
+        };
+        if m.tier > RarityTier::Common && independent.iter().all(disjoint) {
+            independent.push(m);
+        }
+    }
+    independent
+}
+
+/// Orders matches with an unknown percentage after the known ones.
+fn sort_percentage(rule_match: &RuleMatch) -> f64 {
+    rule_match.percentage.unwrap_or(f64::INFINITY)
+}
+
+/// How many significant digits a probability is shown with, which leaves
+/// out the error that multiplying floats adds in the last few.
+const PROBABILITY_DIGITS: i32 = 12;
+
+fn display_percentage(percentage: &Option<f64>) -> String {
+    percentage.map(display_probability).unwrap_or_default()
+}
+
+/// `p` as a decimal without an exponent, to [`PROBABILITY_DIGITS`]
+/// significant digits and without the zeros that end it.
+fn display_probability(p: f64) -> String {
+    if !p.is_normal() {
+        return p.to_string();
+    }
+    let decimals = (PROBABILITY_DIGITS - 1 - p.abs().log10().floor() as i32).max(0) as usize;
+    let decimal = format!("{p:.decimals$}");
+    if decimal.contains('.') {
+        decimal
+            .trim_end_matches('0')
+            .trim_end_matches('.')
+            .to_string()
+    } else {
+        decimal
+    }
+}
+
+/// `text` cut to `width` characters, ending in an ellipsis when it's cut.
+fn truncate(text: &str, width: usize) -> String {
+    match text.char_indices().nth(width) {
+        Some(_) => {
+            let kept = text.chars().take(width - 1).collect::<String>();
+            format!("{}…", kept.trim_end())
+        }
+        None => text.to_string(),
+    }
+}
+
+fn display_explanations(matches: &[RuleMatch]) -> String {
+    matches
+        .iter()
+        .map(|m| m.explanation.as_str())
+        .collect::<Vec<&str>>()
+        .join("; ")
+}
+
+/// A single rule that a hash satisfied.
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
+struct RuleMatch {
     tier: RarityTier,
+    /// Probability of a random hash matching, when it can be known.
+    percentage: Option<f64>,
+    /// What the rule found, with the text it matched and where.
+    explanation: String,
+    /// The part of the hash the match depends on, when it can be pinned
+    /// down to one stretch of characters.
+    span: Option<Span>,
+}
+
+/// A stretch of a hash that a rule matched.
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
+struct Span {
+    offset: usize,
+    text: String,
+}
+
+impl Span {
+    fn new(offset: usize, text: impl Into<String>) -> Self {
+        Self {
+            offset,
+            text: text.into(),
+        }
+    }
+
+    /// The characters of `hash` in `range`.
+    fn of(hash: &str, range: Range<usize>) -> Self {
+        Self::new(range.start, &hash[range])
+    }
+
+    fn range(&self) -> Range<usize> {
+        self.offset..self.offset + self.text.len()
+    }
+
+    /// `explanation` followed by this span, for a rule whose explanation
+    /// doesn't already say what it matched and where.
+    fn explain(&self, explanation: impl Display) -> String {
+        format!("{explanation}: {self}")
+    }
+}
+
+impl Display for Span {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), Error> {
+        write!(f, "'{}' at offset {}", self.text, self.offset)
+    }
+}
+
+/// How close a hash came to satisfying a rule that looks for a number of
+/// characters, like "8/9 leading digits".
+#[derive(Clone, Copy, PartialEq, Debug)]
+struct MatchStrength {
+    found: usize,
+    needed: usize,
+    what: &'static str,
+}
+
+impl Display for MatchStrength {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), Error> {
+        write!(f, "{}/{} {}", self.found, self.needed, self.what)
+    }
 }
 
-#[derive(Tabled, Display, Clone, PartialEq, ValueEnum)]
+impl MatchStrength {
+    /// Returns `None` when nothing at all was found.
+    fn new(found: usize, needed: usize, what: &'static str) -> Option<Self> {
+        (found > 0).then_some(Self {
+            found,
+            needed,
+            what,
+        })
+    }
+
+    fn is_match(&self) -> bool {
+        self.found >= self.needed
+    }
+
+    /// How much of the rule was satisfied, from 0 to 1 for near misses.
+    fn closeness(&self) -> f64 {
+        self.found as f64 / self.needed as f64
+    }
+}
+
+/// The rule a common commit came closest to matching, for `--near-misses`.
+#[derive(Tabled)]
+struct NearMiss {
+    #[tabled(rename = "Author")]
+    author: String,
+    #[tabled(rename = "Datetime")]
+    datetime: DateTime<FixedOffset>,
+    #[tabled(rename = "Hash")]
+    hash: String,
+    #[tabled(rename = "Near Miss")]
+    strength: MatchStrength,
+    #[tabled(rename = "Closeness", display_with = "display_closeness")]
+    closeness: f64,
+}
+
+impl NearMiss {
+    fn from_commit(commit: &Commit, min_run: usize) -> Option<Self> {
+        let strength = Commit::get_near_miss(&commit.hash, min_run)?;
+        Some(Self {
+            author: commit.author.clone(),
+            datetime: commit.datetime,
+            hash: commit.hash.clone(),
+            strength,
+            closeness: strength.closeness(),
+        })
+    }
+
+    /// The `limit` common commits that came closest to a rule, closest first.
+    fn from_commits(commits: &[Commit], min_run: usize, limit: usize) -> Vec<Self> {
+        let mut near_misses = commits
+            .par_iter()
+            .filter(|c| c.rarity.tier == RarityTier::Common)
+            .filter_map(|c| Self::from_commit(c, min_run))
+            .collect::<Vec<Self>>();
+        near_misses.sort_by(|a, b| b.closeness.total_cmp(&a.closeness));
+        near_misses.truncate(limit);
+        near_misses
+    }
+}
+
+fn display_closeness(closeness: &f64) -> String {
+    format!("{:.0}%", closeness * 100.0)
+}
+
+/// Rarity tiers, ordered from most to least common: Common, Uncommon, Rare
+/// and Legendary. `--sort-by tier` sorts the rarest first, and those of a
+/// tier by their probability.
+#[derive(
+    Tabled,
+    Display,
+    Clone,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    Hash,
+    Debug,
+    ValueEnum,
+    Serialize,
+    Deserialize,
+)]
 enum RarityTier {
     Common,
     Uncommon,
     Rare,
+    Legendary,
+}
+
+impl RarityTier {
+    /// The next rarer tier, or Legendary when there is none.
+    fn escalated(&self) -> Self {
+        match self {
+            Self::Common => Self::Uncommon,
+            Self::Uncommon => Self::Rare,
+            Self::Rare | Self::Legendary => Self::Legendary,
+        }
+    }
 }
 
 #[derive(Tabled, Clone)]
 struct Commit {
+    /// The author, or the tag's name for a tag object.
     #[tabled(rename = "Author")]
     author: String,
+    /// The author's email, shown with `--show-email`. Empty for a tag.
+    #[tabled(rename = "Email")]
+    email: String,
     #[tabled(rename = "Datetime")]
     datetime: DateTime<FixedOffset>,
     #[tabled(rename = "Hash")]
     hash: String,
+    /// The first line of its message, shown with `--show-subject`.
+    #[tabled(rename = "Subject")]
+    subject: String,
     #[tabled(inline)]
     rarity: Rarity,
+    /// The letter `git log` prints for its signature with `%G?`, read only
+    /// with `--show-signatures`.
+    #[tabled(rename = "Signature", display_with = "display_signature")]
+    signature: Option<char>,
+    #[tabled(skip)]
+    object: ObjectKind,
+    /// Whether only a reflog still records the commit, with `--reflog`.
+    #[tabled(skip)]
+    unreachable: bool,
+    /// The stash the commit is part of, with `--stashes`.
+    #[tabled(skip)]
+    stash: Option<String>,
 }
 
-impl Commit {
-    fn new(hash: String, author: String, datetime: DateTime<FixedOffset>) -> Self {
-        Self {
-            author,
-            datetime,
-            hash: String::from(&hash),
-            rarity: Self::get_rarity(&hash),
-        }
+/// A hash read with `--stdin` without the author and date of its commit.
+#[derive(Tabled)]
+struct HashRow {
+    #[tabled(rename = "Hash")]
+    hash: String,
+    #[tabled(inline)]
+    rarity: Rarity,
+}
+
+impl Sortable for Commit {
+    fn tier(&self) -> &RarityTier {
+        &self.rarity.tier
     }
 
-    fn get_rarity(hash: &str) -> Rarity {
-        match hash {
-            _ if UncommonExpl::is_starts_nine_digits(hash) => Rarity {
-                tier: RarityTier::Uncommon,
-                percentage: 0.01,
-                explanation: UncommonExpl::StartsNineDigits.to_string(),
-            },
-            _ if UncommonExpl::is_ends_nine_digits(hash) => Rarity {
-                tier: RarityTier::Uncommon,
-                percentage: 0.01,
-                explanation: UncommonExpl::EndsNineDigits.to_string(),
-            },
-            _ if UncommonExpl::is_contains_nine_continuous_digits(hash) => Rarity {
-                tier: RarityTier::Uncommon,
-                percentage: 0.01,
-                explanation: UncommonExpl::ContainsNineContDigits.to_string(),
-            },
-            _ if RareExpl::is_starts_nine_letters(hash) => Rarity {
-                tier: RarityTier::Rare,
-                percentage: 0.001,
-                explanation: RareExpl::StartsNineLetters.to_string(),
-            },
-            _ if RareExpl::is_ends_nine_letters(hash) => Rarity {
-                tier: RarityTier::Rare,
-                percentage: 0.001,
-                explanation: RareExpl::EndsNineLetters.to_string(),
-            },
-            _ if RareExpl::is_contains_nine_continuous_letters(hash) => Rarity {
-                tier: RarityTier::Rare,
-                percentage: 0.001,
-                explanation: RareExpl::ContainsNineContLetters.to_string(),
-            },
-            _ => Rarity {
-                tier: RarityTier::Common,
-                percentage: 0.99,
-                explanation: String::from(""),
-            },
-        }
+    fn probability(&self) -> Option<f64> {
+        self.rarity.percentage
     }
-}
 
-enum UncommonExpl {
-    StartsNineDigits,
-    EndsNineDigits,
-    ContainsNineContDigits,
-}
+    fn author(&self) -> Option<&str> {
+        Some(&self.author)
+    }
 
-impl Display for UncommonExpl {
-    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), Error> {
-        match self {
-            Self::StartsNineDigits => write!(f, "Starts with nine digits"),
-            Self::EndsNineDigits => write!(f, "Ends with nine digits"),
-            Self::ContainsNineContDigits => write!(f, "Contains nine continuous digits"),
-        }
+    fn email(&self) -> Option<&str> {
+        Some(&self.email)
     }
-}
 
-impl UncommonExpl {
-    fn is_starts_nine_digits(hash: &str) -> bool {
-        hash.chars().take(9).all(|c| c.is_ascii_digit())
+    fn datetime(&self) -> Option<&DateTime<FixedOffset>> {
+        Some(&self.datetime)
     }
+}
 
-    fn is_ends_nine_digits(hash: &str) -> bool {
-        hash.chars().rev().take(9).all(|c| c.is_ascii_digit())
+impl Sortable for HashRow {
+    fn tier(&self) -> &RarityTier {
+        &self.rarity.tier
     }
 
-    fn is_contains_nine_continuous_digits(hash: &str) -> bool {
-        hash.chars().collect::<String>().contains("999999999")
+    fn probability(&self) -> Option<f64> {
+        self.rarity.percentage
     }
 }
 
-enum RareExpl {
-    StartsNineLetters,
-    EndsNineLetters,
-    ContainsNineContLetters,
+impl Sortable for ObjectMatch {
+    fn tier(&self) -> &RarityTier {
+        &self.rarity.tier
+    }
+
+    fn probability(&self) -> Option<f64> {
+        self.rarity.percentage
+    }
 }
 
-impl Display for RareExpl {
-    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), Error> {
-        match self {
-            Self::StartsNineLetters => write!(f, "Starts with nine letters"),
-            Self::EndsNineLetters => write!(f, "Ends with nine letters"),
-            Self::ContainsNineContLetters => write!(f, "Contains nine continuous letters"),
+impl From<Commit> for HashRow {
+    fn from(commit: Commit) -> Self {
+        Self {
+            hash: commit.hash,
+            rarity: commit.rarity,
         }
     }
 }
 
-impl RareExpl {
-    fn is_starts_nine_letters(hash: &str) -> bool {
-        hash.chars().take(9).all(|c| c.is_ascii_alphabetic())
-    }
-    fn is_ends_nine_letters(hash: &str) -> bool {
-        hash.chars().rev().take(9).all(|c| c.is_ascii_alphabetic())
+/// A line read with `--stdin`, which may have the fields of [`parse_commit`]
+/// after its hash.
+enum HashLine {
+    Commit(Commit),
+    Hash(HashRow),
+}
+
+/// The type of git object a hash belongs to.
+#[derive(Display, Clone, Copy, PartialEq, Eq, Debug, Serialize)]
+#[serde(rename_all = "lowercase")]
+enum ObjectKind {
+    Commit,
+    Tag,
+    Tree,
+    Blob,
+}
+
+impl ObjectKind {
+    /// Parses an object type as git prints it.
+    fn from_type(name: &str) -> Option<Self> {
+        match name {
+            "commit" => Some(Self::Commit),
+            "tag" => Some(Self::Tag),
+            "tree" => Some(Self::Tree),
+            "blob" => Some(Self::Blob),
+            _ => None,
+        }
     }
-    fn is_contains_nine_continuous_letters(hash: &str) -> bool {
-        hash.chars().collect::<String>().contains("abcdefghi")
+
+    #[cfg(feature = "libgit2")]
+    fn from_git2(kind: git2::ObjectType) -> Option<Self> {
+        match kind {
+            git2::ObjectType::Commit => Some(Self::Commit),
+            git2::ObjectType::Tag => Some(Self::Tag),
+            git2::ObjectType::Tree => Some(Self::Tree),
+            git2::ObjectType::Blob => Some(Self::Blob),
+            _ => None,
+        }
     }
 }
 
-fn parse_commit(line: &str) -> Option<Commit> {
-    let mut parts = line.split_whitespace();
-    let hash = parts.next();
-    let datetime = parts.next();
-    let author = parts.collect::<Vec<&str>>().join(" ");
-    match (hash, author, datetime) {
-        (Some(hash), author, Some(datetime)) => {
-            let datetime = DateTime::parse_from_rfc3339(datetime).ok()?;
-            Some(Commit::new(hash.to_string(), author.to_string(), datetime))
-        }
-        _ => None,
+/// An object whose hash matched a rule, for `--objects`.
+#[derive(Tabled)]
+struct ObjectMatch {
+    #[tabled(rename = "Object")]
+    object: ObjectKind,
+    #[tabled(rename = "Hash")]
+    hash: String,
+    /// Where a tree or blob is referenced from, when it could be found.
+    #[tabled(rename = "Path")]
+    path: String,
+    #[tabled(inline)]
+    rarity: Rarity,
+}
+
+/// A row with the type of its object, shown when `--tags` mixes tag objects
+/// in with the commits.
+#[derive(Tabled)]
+struct ObjectRow {
+    #[tabled(rename = "Object")]
+    object: ObjectKind,
+    #[tabled(inline)]
+    commit: Commit,
+}
+
+/// A row marking the commits no ref can reach anymore, shown when `--reflog`
+/// scans them.
+#[derive(Tabled)]
+struct ReflogRow<T: Tabled> {
+    #[tabled(inline)]
+    row: T,
+    #[tabled(rename = "Reflog")]
+    reachability: &'static str,
+}
+
+impl<T: Tabled> ReflogRow<T> {
+    fn new(row: T, commit: &Commit) -> Self {
+        let reachability = if commit.unreachable {
+            "unreachable"
+        } else {
+            ""
+        };
+        Self { row, reachability }
     }
 }
 
-fn print_table<T>(commits: &Vec<T>, start_time: Instant) -> Result<()>
-where
-    T: Tabled,
-{
-    let mut table = Table::new(commits);
-    table.with(Style::rounded());
-    println!("{table}");
-    let duration = start_time.elapsed();
-    println!("This operation took {:?}", duration);
-    Ok(())
+/// A row naming the stash a commit is part of, shown when `--stashes` scans
+/// them.
+#[derive(Tabled)]
+struct StashRow<T: Tabled> {
+    #[tabled(inline)]
+    row: T,
+    #[tabled(rename = "Stash")]
+    stash: String,
 }
 
-fn main() -> Result<()> {
-    let start_time = Instant::now();
-    let args = CliArgs::parse();
-    let sh = Shell::new()?;
-
-    // Get the logs in a format:
-    // Hash Date Author
-    // e83c5163316f89bfbde7d9ab23ca2e25604af290 2024-09-28T17:45:47+00:00 John Doe
-    let raw_output = cmd!(sh, "git log --pretty=format:'%H %aI %an'").read()?;
-    if raw_output.is_empty() {
-        println!("No commits found.");
-        return Ok(());
+impl<T: Tabled> StashRow<T> {
+    fn new(row: T, commit: &Commit) -> Self {
+        let stash = commit.stash.clone().unwrap_or_default();
+        Self { row, stash }
     }
-    // TODO: paginate and batch process commits.
-    // If there are hundreds of thousands of commits this may be a bottleneck.
-    let commits: Vec<Commit> = raw_output.par_lines().filter_map(parse_commit).collect();
+}
 
-    if args.all && args.only.is_none() {
-        print_table(&commits, start_time)
-    } else if let Some(only) = args.only {
-        let only_commits = commits
-            .par_iter()
-            .filter(|c| c.rarity.tier == only)
-            .cloned()
-            .collect::<Vec<Commit>>();
-        if only_commits.is_empty() {
-            println!("No {} commits found.", only);
-            return Ok(());
-        }
-        return print_table(&only_commits, start_time);
-    } else if args.count {
-        let count = Count {
-            total: commits.len(),
-            common: commits
-                .par_iter()
-                .filter(|c| c.rarity.tier == RarityTier::Common)
-                .count(),
-            uncommon: commits
-                .par_iter()
-                .filter(|c| c.rarity.tier == RarityTier::Uncommon)
-                .count(),
-            rare: commits
-                .par_iter()
-                .filter(|c| c.rarity.tier == RarityTier::Rare)
-                .count(),
-        };
-        return print_table(&vec![count], start_time);
-    } else {
-        let not_common_commits = commits
-            .par_iter()
-            .filter(|c| c.rarity.tier != RarityTier::Common)
-            .cloned()
-            .collect::<Vec<Commit>>();
+/// Everything a rule may inspect when classifying a commit.
+struct CommitContext<'a> {
+    hash: &'a str,
+    author: &'a str,
+    /// Author date, or `None` for an object that has no date.
+    datetime: Option<&'a DateTime<FixedOffset>>,
+    /// Full commit message, subject and body.
+    message: &'a str,
+    /// Position in history, counting the root commit as 1, or 0 when only
+    /// some revisions are scanned.
+    ordinal: usize,
+}
 
-        if not_common_commits.is_empty() {
-            println!("No uncommon or rare commits found.");
-            return Ok(());
-        }
-        return print_table(&not_common_commits, start_time);
+/// A classification rule with a stable name, returning everything it matched
+/// in a commit.
+trait Rule: Send + Sync {
+    /// The snake_case name given to `--disable-rule` and `[overrides]`.
+    fn name(&self) -> &str;
+    fn matches(&self, commit: &CommitContext, options: &ClassifyOptions) -> Vec<RuleMatch>;
+}
+
+type RuleFn = fn(&CommitContext, &ClassifyOptions) -> Vec<RuleMatch>;
+
+/// One of the entries of [`RULES`].
+struct BuiltinRule {
+    name: &'static str,
+    matches: RuleFn,
+}
+
+impl Rule for BuiltinRule {
+    fn name(&self) -> &str {
+        self.name
+    }
+
+    fn matches(&self, commit: &CommitContext, options: &ClassifyOptions) -> Vec<RuleMatch> {
+        (self.matches)(commit, options)
     }
 }
 
-#[cfg(test)]
-mod test {
-    use crate::*;
-    #[test]
-    fn verify_app() {
+/// Builds the rules a commit is classified with, leaving out the `disabled`
+/// ones. All of them are evaluated and the rarest match wins, so the order
+/// only affects how ties are listed.
+fn rule_registry(disabled: &[String]) -> Vec<Box<dyn Rule>> {
+    RULES
+        .iter()
+        .filter(|(name, _)| !disabled.iter().any(|d| d == name))
+        .map(|&(name, matches)| Box::new(BuiltinRule { name, matches }) as Box<dyn Rule>)
+        .collect()
+}
+
+/// Every built-in rule, by name.
+const RULES: &[(&str, RuleFn)] = &[
+    ("leading_zeros", |c, _| {
+        LeadingZerosExpl::from_hash(c.hash)
+            .rule_match()
+            .into_iter()
+            .collect()
+    }),
+    ("repeated_char", |c, _| {
+        RepeatedCharExpl::from_hash(c.hash)
+            .rule_match(c.hash.len())
+            .into_iter()
+            .collect()
+    }),
+    ("hexspeak", |c, _| {
+        HexspeakExpl::from_hash(c.hash)
+            .and_then(|e| e.rule_match(c.hash.len()))
+            .into_iter()
+            .collect()
+    }),
+    ("constant", |c, _| {
+        ConstantExpl::from_hash(c.hash)
+            .and_then(|e| e.rule_match(c.hash.len()))
+            .into_iter()
+            .collect()
+    }),
+    ("word", |c, o| {
+        WordExpl::from_hash(c.hash, o.leet)
+            .and_then(|e| e.rule_match(c.hash.len()))
+            .into_iter()
+            .collect()
+    }),
+    ("palindrome", |c, _| {
+        PalindromeExpl::from_hash(c.hash)
+            .rule_match(c.hash)
+            .into_iter()
+            .collect()
+    }),
+    ("pandigital", |c, _| {
+        PandigitalExpl::from_hash(c.hash)
+            .rule_match(c.hash)
+            .into_iter()
+            .collect()
+    }),
+    ("prime", |c, _| {
+        PrimeExpl::from_hash(c.hash)
+            .and_then(|e| e.rule_match())
+            .into_iter()
+            .collect()
+    }),
+    ("sequence", |c, _| {
+        SequenceExpl::from_hash(c.hash)
+            .and_then(|e| e.rule_match(c.hash.len()))
+            .into_iter()
+            .collect()
+    }),
+    ("monotonic", |c, _| {
+        MonotonicExpl::from_hash(c.hash)
+            .and_then(|e| e.rule_match(c.hash))
+            .into_iter()
+            .collect()
+    }),
+    ("pattern", |c, _| {
+        [2, 3]
+            .into_iter()
+            .filter_map(|period| PatternExpl::from_hash(c.hash, period)?.rule_match(c.hash.len()))
+            .collect()
+    }),
+    ("alternating", |c, _| {
+        AlternatingExpl::from_hash(c.hash)
+            .rule_match(c.hash)
+            .into_iter()
+            .collect()
+    }),
+    ("binary", |c, _| {
+        BinaryExpl::from_hash(c.hash)
+            .rule_match(c.hash.len())
+            .into_iter()
+            .collect()
+    }),
+    ("sandwich", |c, _| {
+        SandwichExpl::from_hash(c.hash)
+            .rule_match()
+            .into_iter()
+            .collect()
+    }),
+    ("initials", |c, o| {
+        InitialsExpl::from_commit(c, o.leet)
+            .and_then(|e| e.rule_match())
+            .into_iter()
+            .collect()
+    }),
+    ("date", |c, _| {
+        DateExpl::from_commit(c)
+            .and_then(|e| e.rule_match(c.hash.len()))
+            .into_iter()
+            .collect()
+    }),
+    ("ordinal", |c, _| {
+        OrdinalExpl::from_commit(c)
+            .and_then(|e| e.rule_match())
+            .into_iter()
+            .collect()
+    }),
+    ("issue", |c, _| {
+        IssueExpl::from_commit(c)
+            .and_then(|e| e.rule_match(c.hash.len()))
+            .into_iter()
+            .collect()
+    }),
+    ("lucky_number", |c, o| {
+        o.lucky_numbers
+            .iter()
+            .filter_map(|number| {
+                LuckyNumberExpl::from_hash(c.hash, number)?.rule_match(c.hash.len())
+            })
+            .collect()
+    }),
+    ("timestamp", |c, _| {
+        TimestampExpl::from_commit(c)
+            .and_then(|e| e.rule_match(c.hash.len()))
+            .into_iter()
+            .collect()
+    }),
+    ("meme", |c, o| {
+        o.memes
+            .then(|| MemeExpl::from_hash(c.hash)?.rule_match(c.hash.len()))
+            .flatten()
+            .into_iter()
+            .collect()
+    }),
+    ("all_digits", |c, _| {
+        Commit::get_all_match(c.hash, CharClass::Digit)
+            .into_iter()
+            .collect()
+    }),
+    ("starts_digits", |c, o| {
+        Commit::get_run_match(c.hash, CharClass::Digit, RunPosition::Start, o.min_run)
+            .into_iter()
+            .collect()
+    }),
+    ("ends_digits", |c, o| {
+        Commit::get_run_match(c.hash, CharClass::Digit, RunPosition::End, o.min_run)
+            .into_iter()
+            .collect()
+    }),
+    ("contains_digits", |c, o| {
+        Commit::get_run_match(c.hash, CharClass::Digit, RunPosition::Anywhere, o.min_run)
+            .into_iter()
+            .collect()
+    }),
+    ("all_letters", |c, _| {
+        Commit::get_all_match(c.hash, CharClass::Letter)
+            .into_iter()
+            .collect()
+    }),
+    ("starts_letters", |c, o| {
+        Commit::get_run_match(c.hash, CharClass::Letter, RunPosition::Start, o.min_run)
+            .into_iter()
+            .collect()
+    }),
+    ("ends_letters", |c, o| {
+        Commit::get_run_match(c.hash, CharClass::Letter, RunPosition::End, o.min_run)
+            .into_iter()
+            .collect()
+    }),
+    ("contains_letters", |c, o| {
+        Commit::get_run_match(c.hash, CharClass::Letter, RunPosition::Anywhere, o.min_run)
+            .into_iter()
+            .collect()
+    }),
+    ("custom_rules", |c, o| {
+        o.rules
+            .iter()
+            .filter_map(|rule| rule.rule_match(c.hash))
+            .collect()
+    }),
+    ("patterns_file", |c, o| {
+        o.patterns
+            .as_ref()
+            .map_or_else(Vec::new, |patterns| patterns.matches(c.hash))
+    }),
+    #[cfg(feature = "plugins")]
+    ("plugins", |c, o| {
+        o.plugins
+            .iter()
+            .filter_map(|plugin| plugin.rule_match(c.hash))
+            .collect()
+    }),
+];
+
+/// Settings that affect how commits are classified.
+struct ClassifyOptions {
+    /// The rules to evaluate, without those given to `--disable-rule`.
+    registry: Vec<Box<dyn Rule>>,
+    /// Length of the digit and letter runs the run rules look for.
+    min_run: usize,
+    /// Rules given with `--rule`, evaluated after the built-in ones.
+    rules: Vec<CustomRule>,
+    /// Lucky numbers from the config file.
+    lucky_numbers: Vec<String>,
+    /// Tier and probability overrides from the config file, by rule name.
+    overrides: HashMap<String, RuleOverride>,
+    /// Patterns read from `--patterns-file`.
+    patterns: Option<PatternSet>,
+    /// Plugins given with `--plugin`, evaluated after the custom rules and
+    /// patterns.
+    #[cfg(feature = "plugins")]
+    plugins: Vec<plugin::Plugin>,
+    /// Whether the meme number pack given with `--memes` is enabled.
+    memes: bool,
+    /// Whether the word and initials rules also look for leet spellings,
+    /// given with `--leet`.
+    leet: bool,
+    /// Length of the abbreviated hash to classify instead of the full one,
+    /// when `--short-hash-rules` is given.
+    short_hash: Option<usize>,
+    /// Whether all of HEAD's history is scanned, so commits can be numbered
+    /// by their position in it. Only some of it is with revisions given.
+    whole_history: bool,
+    /// Whether the classifications of walked commits are kept between scans,
+    /// unless `--no-cache` is given.
+    cache: bool,
+}
+
+impl Default for ClassifyOptions {
+    fn default() -> Self {
+        Self {
+            registry: rule_registry(&[]),
+            min_run: DEFAULT_MIN_RUN,
+            rules: vec![],
+            lucky_numbers: vec![],
+            overrides: HashMap::new(),
+            patterns: None,
+            #[cfg(feature = "plugins")]
+            plugins: vec![],
+            memes: false,
+            leet: false,
+            short_hash: None,
+            whole_history: true,
+            cache: false,
+        }
+    }
+}
+
+impl Commit {
+    fn new(
+        hash: String,
+        author: String,
+        email: String,
+        datetime: DateTime<FixedOffset>,
+        message: &str,
+        ordinal: usize,
+        options: &ClassifyOptions,
+    ) -> Self {
+        let rarity = Self::get_rarity(
+            &CommitContext {
+                hash: &hash,
+                author: &author,
+                datetime: Some(&datetime),
+                message,
+                ordinal,
+            },
+            options,
+        );
+        Self::with_rarity(hash, author, email, datetime, message, rarity)
+    }
+
+    /// A commit already classified, like one read from the cache.
+    fn with_rarity(
+        hash: String,
+        author: String,
+        email: String,
+        datetime: DateTime<FixedOffset>,
+        message: &str,
+        rarity: Rarity,
+    ) -> Self {
+        Self {
+            author,
+            email,
+            datetime,
+            hash,
+            subject: message
+                .lines()
+                .next()
+                .unwrap_or_default()
+                .trim()
+                .to_string(),
+            rarity,
+            signature: None,
+            object: ObjectKind::Commit,
+            unreachable: false,
+            stash: None,
+        }
+    }
+
+    /// Classifies a hash on its own, for objects that aren't commits. Rules
+    /// that look at a commit's date, author or message can't match.
+    fn get_hash_rarity(hash: &str, options: &ClassifyOptions) -> Rarity {
+        let context = CommitContext {
+            hash,
+            author: "",
+            datetime: None,
+            message: "",
+            ordinal: 0,
+        };
+        Self::get_rarity(&context, options)
+    }
+
+    fn get_rarity(commit: &CommitContext, options: &ClassifyOptions) -> Rarity {
+        let hash = commit.hash;
+        if let Some(abbrev) = options.short_hash {
+            return Rarity::from_matches(ShortHashExpl::get_matches(hash, abbrev));
+        }
+        let matches = options
+            .registry
+            .iter()
+            .flat_map(|rule| {
+                let matches = rule.matches(commit, options);
+                match options.overrides.get(rule.name()) {
+                    Some(rule_override) => matches
+                        .into_iter()
+                        .map(|m| rule_override.apply(m))
+                        .collect(),
+                    None => matches,
+                }
+            })
+            .collect();
+        Rarity::from_matches(matches)
+    }
+
+    /// The rule `hash` came closest to matching without matching it,
+    /// preferring the first rule on ties.
+    fn get_near_miss(hash: &str, min_run: usize) -> Option<MatchStrength> {
+        let runs = [CharClass::Digit, CharClass::Letter]
+            .into_iter()
+            .flat_map(|class| RunRule::for_class(class, min_run))
+            .map(|rule| rule.strength(hash));
+        let others = [
+            LeadingZerosExpl::from_hash(hash).strength(),
+            RepeatedCharExpl::from_hash(hash).strength(),
+            PalindromeExpl::from_hash(hash).strength(),
+            SequenceExpl::from_hash(hash).and_then(|e| e.strength()),
+            AlternatingExpl::from_hash(hash).strength(),
+        ];
+        runs.chain(others)
+            .flatten()
+            .filter(|s| !s.is_match())
+            .reduce(|best, s| {
+                if s.closeness() > best.closeness() {
+                    s
+                } else {
+                    best
+                }
+            })
+    }
+
+    /// The match of the rule for hashes made up entirely of `class`.
+    fn get_all_match(hash: &str, class: CharClass) -> Option<RuleMatch> {
+        if hash.is_empty() || !hash.chars().all(|c| class.contains(c)) {
+            return None;
+        }
+        let explanation = match class {
+            CharClass::Digit => LegendaryExpl::AllDigits,
+            CharClass::Letter => LegendaryExpl::AllLetters,
+        };
+        let span = Span::new(0, hash);
+        Some(RuleMatch {
+            tier: RarityTier::Legendary,
+            percentage: Some(probability::all_of(class.probability(), hash.len())),
+            explanation: span.explain(explanation),
+            span: Some(span),
+        })
+    }
+
+    /// The match of the run rule for `class` at `position`, unless a more
+    /// specific rule matched: an all-digit hash does not also start with
+    /// digits, and a hash starting with digits does not also contain them.
+    fn get_run_match(
+        hash: &str,
+        class: CharClass,
+        position: RunPosition,
+        min_run: usize,
+    ) -> Option<RuleMatch> {
+        if Self::get_all_match(hash, class).is_some() {
+            return None;
+        }
+        let [starts, ends, contains] = RunRule::for_class(class, min_run);
+        match position {
+            RunPosition::Start => starts.rule_match(hash),
+            RunPosition::End => ends.rule_match(hash),
+            RunPosition::Anywhere => {
+                let at_edge = starts.rule_match(hash).is_some() || ends.rule_match(hash).is_some();
+                if at_edge {
+                    None
+                } else {
+                    contains.rule_match(hash)
+                }
+            }
+        }
+    }
+}
+
+/// The kind of character a [`RunRule`] counts.
+#[derive(Clone, Copy, PartialEq, Debug)]
+enum CharClass {
+    Digit,
+    Letter,
+}
+
+impl CharClass {
+    fn contains(self, c: char) -> bool {
+        match self {
+            Self::Digit => c.is_ascii_digit(),
+            Self::Letter => c.is_ascii_alphabetic(),
+        }
+    }
+
+    fn probability(self) -> f64 {
+        match self {
+            Self::Digit => probability::DIGIT,
+            Self::Letter => probability::LETTER,
+        }
+    }
+
+    /// The tier of a run of exactly the minimum length.
+    fn base_tier(self) -> RarityTier {
+        match self {
+            Self::Digit => RarityTier::Uncommon,
+            Self::Letter => RarityTier::Rare,
+        }
+    }
+}
+
+impl Display for CharClass {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), Error> {
+        match self {
+            Self::Digit => write!(f, "digits"),
+            Self::Letter => write!(f, "letters"),
+        }
+    }
+}
+
+/// Where in the hash a [`RunRule`] measures its run.
+#[derive(Clone, Copy, PartialEq, Debug)]
+enum RunPosition {
+    Start,
+    End,
+    Anywhere,
+}
+
+/// Length at which a run is bumped one tier above its base tier.
+const RUN_BUMP_LEN: usize = 12;
+
+/// Length at which a run reaches the top tier.
+const RUN_TOP_LEN: usize = 16;
+
+/// A rule grading a run of one class of characters by its length.
+struct RunRule {
+    char_class: CharClass,
+    position: RunPosition,
+    /// Minimum run length for each tier, in ascending order.
+    thresholds: Vec<(usize, RarityTier)>,
+}
+
+impl RunRule {
+    /// A rule matching runs of at least `min_run` characters at the base
+    /// tier of `char_class`, one tier higher from [`RUN_BUMP_LEN`] and
+    /// Legendary from [`RUN_TOP_LEN`].
+    fn new(char_class: CharClass, position: RunPosition, min_run: usize) -> Self {
+        let base = char_class.base_tier();
+        Self {
+            char_class,
+            position,
+            thresholds: vec![
+                (min_run, base.clone()),
+                (RUN_BUMP_LEN.max(min_run), base.escalated()),
+                (RUN_TOP_LEN.max(min_run), RarityTier::Legendary),
+            ],
+        }
+    }
+
+    /// The start, end and anywhere rules for `char_class`.
+    fn for_class(char_class: CharClass, min_run: usize) -> [Self; 3] {
+        [RunPosition::Start, RunPosition::End, RunPosition::Anywhere]
+            .map(|position| Self::new(char_class, position, min_run))
+    }
+
+    fn min_len(&self) -> usize {
+        self.thresholds[0].0
+    }
+
+    /// The span of the run this rule measures in `hash`.
+    fn span(&self, hash: &str) -> Range<usize> {
+        let class = self.char_class;
+        match self.position {
+            RunPosition::Start => 0..hash.chars().take_while(|&c| class.contains(c)).count(),
+            RunPosition::End => {
+                let found = hash
+                    .chars()
+                    .rev()
+                    .take_while(|&c| class.contains(c))
+                    .count();
+                hash.len() - found..hash.len()
+            }
+            RunPosition::Anywhere => longest_run_span(hash, |c| class.contains(c)),
+        }
+    }
+
+    fn tier(&self, length: usize) -> Option<RarityTier> {
+        self.thresholds
+            .iter()
+            .rev()
+            .find(|(min, _)| length >= *min)
+            .map(|(_, tier)| tier.clone())
+    }
+
+    fn strength(&self, hash: &str) -> Option<MatchStrength> {
+        let what = match (self.position, self.char_class) {
+            (RunPosition::Start, CharClass::Digit) => "leading digits",
+            (RunPosition::End, CharClass::Digit) => "trailing digits",
+            (RunPosition::Anywhere, CharClass::Digit) => "digits in a row",
+            (RunPosition::Start, CharClass::Letter) => "leading letters",
+            (RunPosition::End, CharClass::Letter) => "trailing letters",
+            (RunPosition::Anywhere, CharClass::Letter) => "letters in a row",
+        };
+        MatchStrength::new(self.span(hash).len(), self.min_len(), what)
+    }
+
+    fn rule_match(&self, hash: &str) -> Option<RuleMatch> {
+        let span = self.span(hash);
+        let length = span.len();
+        let p = self.char_class.probability();
+        let percentage = match self.position {
+            RunPosition::Start | RunPosition::End => probability::all_of(p, length),
+            RunPosition::Anywhere => probability::run(p, length, hash.len()),
+        };
+        let explanation = RunExpl {
+            char_class: self.char_class,
+            position: self.position,
+            length,
+        };
+        let span = Span::of(hash, span);
+        Some(RuleMatch {
+            tier: self.tier(length)?,
+            percentage: Some(percentage),
+            explanation: span.explain(explanation),
+            span: Some(span),
+        })
+    }
+}
+
+struct RunExpl {
+    char_class: CharClass,
+    position: RunPosition,
+    length: usize,
+}
+
+impl Display for RunExpl {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), Error> {
+        let Self {
+            char_class, length, ..
+        } = self;
+        match self.position {
+            RunPosition::Start => write!(f, "Starts with {length} {char_class}"),
+            RunPosition::End => write!(f, "Ends with {length} {char_class}"),
+            RunPosition::Anywhere => write!(f, "Contains a run of {length} {char_class}"),
+        }
+    }
+}
+
+enum LegendaryExpl {
+    AllDigits,
+    AllLetters,
+}
+
+impl Display for LegendaryExpl {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), Error> {
+        match self {
+            Self::AllDigits => write!(f, "Consists entirely of digits"),
+            Self::AllLetters => write!(f, "Consists entirely of letters"),
+        }
+    }
+}
+
+/// Hashes mined with a proof-of-work style prefix of leading zeros.
+struct LeadingZerosExpl(usize);
+
+impl Display for LeadingZerosExpl {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), Error> {
+        write!(f, "Starts with {} zeros", self.0)
+    }
+}
+
+impl LeadingZerosExpl {
+    fn from_hash(hash: &str) -> Self {
+        Self(hash.chars().take_while(|&c| c == '0').count())
+    }
+
+    fn tier(&self) -> Option<RarityTier> {
+        match self.0 {
+            0..=5 => None,
+            6..=7 => Some(RarityTier::Uncommon),
+            8..=9 => Some(RarityTier::Rare),
+            _ => Some(RarityTier::Legendary),
+        }
+    }
+
+    fn strength(&self) -> Option<MatchStrength> {
+        MatchStrength::new(self.0, 6, "leading zeros")
+    }
+
+    fn rule_match(&self) -> Option<RuleMatch> {
+        let span = Span::new(0, "0".repeat(self.0));
+        Some(RuleMatch {
+            tier: self.tier()?,
+            percentage: Some(probability::exact(self.0)),
+            explanation: span.explain(self),
+            span: Some(span),
+        })
+    }
+}
+
+/// Hashes containing a run of a single repeated character, like `ffffffff`.
+struct RepeatedCharExpl {
+    character: char,
+    length: usize,
+    offset: usize,
+}
+
+impl Display for RepeatedCharExpl {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), Error> {
+        write!(
+            f,
+            "Contains {} consecutive '{}' characters",
+            self.length, self.character
+        )
+    }
+}
+
+impl RepeatedCharExpl {
+    /// Finds the longest run of a repeated character, preferring the first
+    /// one when several runs share the same length.
+    fn from_hash(hash: &str) -> Self {
+        let mut longest = Self {
+            character: ' ',
+            length: 0,
+            offset: 0,
+        };
+        let mut chars = hash.chars().peekable();
+        let mut offset = 0;
+        while let Some(c) = chars.next() {
+            let mut length = 1;
+            while chars.next_if_eq(&c).is_some() {
+                length += 1;
+            }
+            if length > longest.length {
+                longest = Self {
+                    character: c,
+                    length,
+                    offset,
+                };
+            }
+            offset += length;
+        }
+        longest
+    }
+
+    fn tier(&self) -> Option<RarityTier> {
+        match self.length {
+            0..=6 => None,
+            7..=8 => Some(RarityTier::Uncommon),
+            _ => Some(RarityTier::Rare),
+        }
+    }
+
+    fn strength(&self) -> Option<MatchStrength> {
+        MatchStrength::new(self.length, 7, "repeated characters")
+    }
+
+    fn rule_match(&self, hash_len: usize) -> Option<RuleMatch> {
+        let span = Span::new(self.offset, self.character.to_string().repeat(self.length));
+        Some(RuleMatch {
+            tier: self.tier()?,
+            percentage: Some(probability::repeated(self.length, hash_len)),
+            explanation: span.explain(self),
+            span: Some(span),
+        })
+    }
+}
+
+/// Hashes containing a famous hexspeak word like `deadbeef`.
+struct HexspeakExpl(hexspeak::HexspeakMatch);
+
+impl Display for HexspeakExpl {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), Error> {
+        write!(
+            f,
+            "Contains hexspeak '{}' at offset {}",
+            self.0.word, self.0.offset
+        )
+    }
+}
+
+impl HexspeakExpl {
+    fn from_hash(hash: &str) -> Option<Self> {
+        hexspeak::find(hash).map(Self)
+    }
+
+    fn tier(&self) -> RarityTier {
+        match self.0.word.len() {
+            0..=7 => RarityTier::Uncommon,
+            _ => RarityTier::Rare,
+        }
+    }
+
+    fn rule_match(&self, hash_len: usize) -> Option<RuleMatch> {
+        Some(RuleMatch {
+            tier: self.tier(),
+            percentage: Some(probability::contains(self.0.word, hash_len)),
+            explanation: self.to_string(),
+            span: Some(Span::new(self.0.offset, self.0.word)),
+        })
+    }
+}
+
+/// Hashes starting with the author's initials, when those can be spelled in
+/// hex.
+struct InitialsExpl(String);
+
+impl Display for InitialsExpl {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), Error> {
+        write!(f, "Starts with author initials '{}'", self.0)
+    }
+}
+
+impl InitialsExpl {
+    /// A single initial starts one in sixteen hashes.
+    const MIN_LEN: usize = 2;
+
+    /// Spells the initials of each word in the author's name in hex,
+    /// skipping those that can't be, and checks for them at the start.
+    fn from_commit(commit: &CommitContext, leet: bool) -> Option<Self> {
+        let initials = commit
+            .author
+            .split_whitespace()
+            .filter_map(|word| word.chars().next())
+            .filter_map(|c| words::spell_char(c.to_ascii_lowercase(), leet))
+            .collect::<String>();
+        (initials.len() >= Self::MIN_LEN && commit.hash.starts_with(&initials))
+            .then_some(Self(initials))
+    }
+
+    fn tier(&self) -> RarityTier {
+        match self.0.len() {
+            0..=3 => RarityTier::Uncommon,
+            _ => RarityTier::Rare,
+        }
+    }
+
+    fn rule_match(&self) -> Option<RuleMatch> {
+        Some(RuleMatch {
+            tier: self.tier(),
+            percentage: Some(probability::exact(self.0.len())),
+            explanation: self.to_string(),
+            span: Some(Span::new(0, &self.0)),
+        })
+    }
+}
+
+/// Hashes whose first characters contain the commit's position in history.
+struct OrdinalExpl {
+    ordinal: String,
+    offset: usize,
+}
+
+impl Display for OrdinalExpl {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), Error> {
+        write!(
+            f,
+            "Contains its position in history, {}, at offset {}",
+            self.ordinal, self.offset
+        )
+    }
+}
+
+impl OrdinalExpl {
+    /// Number of leading characters the ordinal is looked for in.
+    const PREFIX_LEN: usize = 12;
+
+    fn from_commit(commit: &CommitContext) -> Option<Self> {
+        let ordinal = commit.ordinal.to_string();
+        // Positions under 100 turn up by chance in most repositories.
+        if ordinal.len() < 3 {
+            return None;
+        }
+        let prefix = commit.hash.get(..Self::PREFIX_LEN).unwrap_or(commit.hash);
+        Some(Self {
+            offset: prefix.find(&ordinal)?,
+            ordinal,
+        })
+    }
+
+    fn tier(&self) -> RarityTier {
+        match self.ordinal.len() {
+            0..=4 => RarityTier::Uncommon,
+            5..=6 => RarityTier::Rare,
+            _ => RarityTier::Legendary,
+        }
+    }
+
+    fn rule_match(&self) -> Option<RuleMatch> {
+        Some(RuleMatch {
+            tier: self.tier(),
+            percentage: Some(probability::contains(&self.ordinal, Self::PREFIX_LEN)),
+            explanation: self.to_string(),
+            span: Some(Span::new(self.offset, &self.ordinal)),
+        })
+    }
+}
+
+/// Hashes containing an issue number referenced in the commit message.
+struct IssueExpl {
+    /// The reference as written, like `#4217`.
+    reference: String,
+    number: String,
+    offset: usize,
+}
+
+impl Display for IssueExpl {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), Error> {
+        write!(
+            f,
+            "Contains the issue number it references as '{}'",
+            self.reference
+        )
+    }
+}
+
+impl IssueExpl {
+    /// Shorter numbers show up by chance too often to be interesting.
+    const MIN_LEN: usize = 4;
+
+    /// Finds the longest referenced number in the hash, preferring the
+    /// first reference on ties.
+    fn from_commit(commit: &CommitContext) -> Option<Self> {
+        issues::references(commit.message)
+            .into_iter()
+            .filter(|r| r.number.len() >= Self::MIN_LEN)
+            .filter_map(|r| {
+                Some(Self {
+                    offset: commit.hash.find(r.number)?,
+                    reference: r.text.to_string(),
+                    number: r.number.to_string(),
+                })
+            })
+            .reduce(|best, e| {
+                if e.number.len() > best.number.len() {
+                    e
+                } else {
+                    best
+                }
+            })
+    }
+
+    fn tier(&self) -> RarityTier {
+        match self.number.len() {
+            0..=5 => RarityTier::Uncommon,
+            _ => RarityTier::Rare,
+        }
+    }
+
+    fn rule_match(&self, hash_len: usize) -> Option<RuleMatch> {
+        let span = Span::new(self.offset, &self.number);
+        Some(RuleMatch {
+            tier: self.tier(),
+            percentage: Some(probability::contains(&self.number, hash_len)),
+            explanation: span.explain(self),
+            span: Some(span),
+        })
+    }
+}
+
+/// Hashes containing one of the lucky numbers from the config file.
+struct LuckyNumberExpl {
+    number: String,
+    offset: usize,
+}
+
+impl Display for LuckyNumberExpl {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), Error> {
+        write!(
+            f,
+            "Contains your lucky number {} at offset {}",
+            self.number, self.offset
+        )
+    }
+}
+
+impl LuckyNumberExpl {
+    /// Shorter numbers show up by chance too often to be interesting.
+    const MIN_LEN: usize = 6;
+
+    fn from_hash(hash: &str, number: &str) -> Option<Self> {
+        Some(Self {
+            offset: hash.find(number)?,
+            number: number.to_string(),
+        })
+    }
+
+    fn tier(&self) -> Option<RarityTier> {
+        match self.number.len() {
+            0..=5 => None,
+            6..=7 => Some(RarityTier::Uncommon),
+            _ => Some(RarityTier::Rare),
+        }
+    }
+
+    fn rule_match(&self, hash_len: usize) -> Option<RuleMatch> {
+        Some(RuleMatch {
+            tier: self.tier()?,
+            percentage: Some(probability::contains(&self.number, hash_len)),
+            explanation: self.to_string(),
+            span: Some(Span::new(self.offset, &self.number)),
+        })
+    }
+}
+
+/// Hashes containing a meme number like 1337, when `--memes` is enabled.
+struct MemeExpl(memes::MemeMatch);
+
+impl Display for MemeExpl {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), Error> {
+        let memes::MemeMatch {
+            digits,
+            name,
+            position,
+            count,
+            ..
+        } = &self.0;
+        match (position, count) {
+            (memes::Position::Start, 1) => write!(f, "Starts with {digits} ({name})"),
+            (memes::Position::End, 1) => write!(f, "Ends with {digits} ({name})"),
+            (_, 1) => write!(f, "Contains {digits} ({name})"),
+            _ => write!(f, "Contains {digits} ({name}) {count} times"),
+        }
+    }
+}
+
+impl MemeExpl {
+    /// Picks the rarest meme in the hash, preferring longer ones on ties.
+    fn from_hash(hash: &str) -> Option<Self> {
+        memes::find(hash)
+            .into_iter()
+            .map(Self)
+            .filter_map(|e| Some((e.tier()?, e)))
+            .max_by_key(|(tier, e)| (tier.clone(), e.0.digits.len()))
+            .map(|(_, e)| e)
+    }
+
+    fn tier(&self) -> Option<RarityTier> {
+        let long = self.0.digits.len() >= memes::MIN_ANYWHERE_LEN;
+        let stands_out = self.0.position != memes::Position::Inside || self.0.count > 1;
+        match (long, stands_out) {
+            // A short meme somewhere in the middle is too likely to report.
+            (false, false) => None,
+            // A short meme at either end or repeated, or a long one anywhere.
+            (false, true) | (true, false) => Some(RarityTier::Uncommon),
+            // A long meme at either end or repeated.
+            (true, true) => Some(RarityTier::Rare),
+        }
+    }
+
+    fn rule_match(&self, hash_len: usize) -> Option<RuleMatch> {
+        let percentage = match (self.0.position, self.0.count) {
+            (memes::Position::Inside, 1) => Some(probability::contains(self.0.digits, hash_len)),
+            (_, 1) => Some(probability::exact(self.0.digits.len())),
+            // There's no closed form for repeats, so leave it blank.
+            _ => None,
+        };
+        // Repeats are spread over the hash, so they have no single span.
+        let span = (self.0.count == 1).then(|| Span::new(self.0.offset, self.0.digits));
+        Some(RuleMatch {
+            tier: self.tier()?,
+            percentage,
+            explanation: span
+                .as_ref()
+                .map_or_else(|| self.to_string(), |span| span.explain(self)),
+            span,
+        })
+    }
+}
+
+/// Hashes spelling an English word from the embedded wordlist.
+struct WordExpl(words::WordMatch);
+
+impl Display for WordExpl {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), Error> {
+        if self.0.offset == 0 {
+            write!(f, "Starts with the word '{}'", self.0.word)?;
+        } else {
+            write!(f, "Contains the word '{}'", self.0.word)?;
+        }
+        if self.0.is_leet() {
+            write!(f, " as '{}'", self.0.spelling)?;
+        }
+        if self.0.offset != 0 {
+            write!(f, " at offset {}", self.0.offset)?;
+        }
+        Ok(())
+    }
+}
+
+impl WordExpl {
+    fn from_hash(hash: &str, leet: bool) -> Option<Self> {
+        words::find(hash, leet).map(Self)
+    }
+
+    fn tier(&self) -> RarityTier {
+        match self.0.word.len() {
+            0..=7 => RarityTier::Uncommon,
+            8..=9 => RarityTier::Rare,
+            _ => RarityTier::Legendary,
+        }
+    }
+
+    fn rule_match(&self, hash_len: usize) -> Option<RuleMatch> {
+        let percentage = if self.0.offset == 0 {
+            probability::exact(self.0.spelling.len())
+        } else {
+            probability::contains(self.0.spelling, hash_len)
+        };
+        Some(RuleMatch {
+            tier: self.tier(),
+            percentage: Some(percentage),
+            explanation: self.to_string(),
+            span: Some(Span::new(self.0.offset, self.0.spelling)),
+        })
+    }
+}
+
+/// Hashes containing the leading digits of a famous constant like π.
+struct ConstantExpl(constants::ConstantMatch);
+
+impl Display for ConstantExpl {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), Error> {
+        let position = if self.0.offset == 0 {
+            "Starts with"
+        } else {
+            "Contains"
+        };
+        write!(
+            f,
+            "{position} the first {} digits of {}",
+            self.0.digits.len(),
+            self.0.name
+        )
+    }
+}
+
+impl ConstantExpl {
+    fn from_hash(hash: &str) -> Option<Self> {
+        constants::find(hash).map(Self)
+    }
+
+    fn tier(&self) -> RarityTier {
+        match self.0.digits.len() {
+            0..=9 => RarityTier::Rare,
+            _ => RarityTier::Legendary,
+        }
+    }
+
+    fn rule_match(&self, hash_len: usize) -> Option<RuleMatch> {
+        let percentage = if self.0.offset == 0 {
+            probability::exact(self.0.digits.len())
+        } else {
+            probability::contains(self.0.digits, hash_len)
+        };
+        let span = Span::new(self.0.offset, self.0.digits);
+        Some(RuleMatch {
+            tier: self.tier(),
+            percentage: Some(percentage),
+            explanation: span.explain(self),
+            span: Some(span),
+        })
+    }
+}
+
+/// Hashes starting with a prime number of 9 to 12 digits.
+struct PrimeExpl(u64);
+
+impl Display for PrimeExpl {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), Error> {
+        write!(
+            f,
+            "Starts with the {}-digit prime {}",
+            self.digits(),
+            self.0
+        )
+    }
+}
+
+impl PrimeExpl {
+    /// Finds the longest prime prefix. The primality test only runs when
+    /// the hash already starts with nine digits.
+    fn from_hash(hash: &str) -> Option<Self> {
+        let digits = hash.chars().take_while(|c| c.is_ascii_digit()).count();
+        if digits < 9 || hash.starts_with('0') {
+            return None;
+        }
+        (9..=digits.min(12))
+            .rev()
+            .filter_map(|len| hash[..len].parse().ok())
+            .find(|&n| prime::is_prime(n))
+            .map(Self)
+    }
+
+    fn digits(&self) -> usize {
+        self.0.to_string().len()
+    }
+
+    fn rule_match(&self) -> Option<RuleMatch> {
+        Some(RuleMatch {
+            tier: RarityTier::Rare,
+            percentage: probability::prime_prefix(self.digits()),
+            explanation: self.to_string(),
+            span: Some(Span::new(0, self.0.to_string())),
+        })
+    }
+}
+
+/// Hashes whose first 16 characters use (nearly) every hex digit.
+struct PandigitalExpl(usize);
+
+impl Display for PandigitalExpl {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), Error> {
+        if self.0 == PANDIGITAL_LEN {
+            write!(f, "First {PANDIGITAL_LEN} characters are pandigital")
+        } else {
+            write!(
+                f,
+                "First {PANDIGITAL_LEN} characters contain {} distinct hex digits",
+                self.0
+            )
+        }
+    }
+}
+
+/// Number of leading characters the pandigital rule inspects, one for each
+/// hex digit.
+const PANDIGITAL_LEN: usize = 16;
+
+impl PandigitalExpl {
+    /// Counts the distinct hex digits in the first 16 characters.
+    fn from_hash(hash: &str) -> Self {
+        let seen = hash
+            .chars()
+            .take(PANDIGITAL_LEN)
+            .filter_map(|c| c.to_digit(16))
+            .fold(0u16, |seen, digit| seen | 1 << digit);
+        Self(seen.count_ones() as usize)
+    }
+
+    fn tier(&self) -> Option<RarityTier> {
+        match self.0 {
+            0..=14 => None,
+            15 => Some(RarityTier::Uncommon),
+            _ => Some(RarityTier::Rare),
+        }
+    }
+
+    fn rule_match(&self, hash: &str) -> Option<RuleMatch> {
+        let span = Span::of(hash, 0..PANDIGITAL_LEN);
+        Some(RuleMatch {
+            tier: self.tier()?,
+            percentage: Some(probability::distinct(self.0, PANDIGITAL_LEN)),
+            explanation: span.explain(self),
+            span: Some(span),
+        })
+    }
+}
+
+/// Hashes whose first characters read the same forwards and backwards.
+struct PalindromeExpl {
+    length: usize,
+    /// Whether the whole hash is a palindrome.
+    whole: bool,
+}
+
+impl Display for PalindromeExpl {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), Error> {
+        if self.whole {
+            write!(f, "Is a palindrome")
+        } else {
+            write!(f, "First {} characters form a palindrome", self.length)
+        }
+    }
+}
+
+impl PalindromeExpl {
+    /// Finds the longest prefix of `hash` that is a palindrome.
+    fn from_hash(hash: &str) -> Self {
+        let chars = hash.chars().collect::<Vec<char>>();
+        let length = (1..=chars.len())
+            .rev()
+            .find(|&n| chars[..n].iter().eq(chars[..n].iter().rev()))
+            .unwrap_or(0);
+        Self {
+            length,
+            whole: length > 0 && length == chars.len(),
+        }
+    }
+
+    fn tier(&self) -> Option<RarityTier> {
+        match self.length {
+            0..=8 => None,
+            9..=12 => Some(RarityTier::Uncommon),
+            _ if self.whole => Some(RarityTier::Legendary),
+            _ => Some(RarityTier::Rare),
+        }
+    }
+
+    fn strength(&self) -> Option<MatchStrength> {
+        MatchStrength::new(self.length, 9, "palindromic leading characters")
+    }
+
+    fn rule_match(&self, hash: &str) -> Option<RuleMatch> {
+        // Only the first half of a palindrome is free; the rest mirrors it.
+        let span = Span::of(hash, 0..self.length);
+        Some(RuleMatch {
+            tier: self.tier()?,
+            percentage: Some(probability::palindrome(self.length)),
+            explanation: span.explain(self),
+            span: Some(span),
+        })
+    }
+}
+
+/// Hashes containing consecutive hex values in order, like `6789abc`.
+struct SequenceExpl {
+    sequence: String,
+    ascending: bool,
+    offset: usize,
+}
+
+impl Display for SequenceExpl {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), Error> {
+        let direction = if self.ascending {
+            "ascending"
+        } else {
+            "descending"
+        };
+        write!(
+            f,
+            "Contains {direction} sequence '{}' at offset {}",
+            self.sequence, self.offset
+        )
+    }
+}
+
+impl SequenceExpl {
+    /// Finds the longest run where each hex value is exactly one more (or
+    /// one less) than the previous, preferring the first on ties.
+    fn from_hash(hash: &str) -> Option<Self> {
+        let values = hash
+            .chars()
+            .map(|c| c.to_digit(16).map(|v| v as i32))
+            .collect::<Vec<Option<i32>>>();
+        let mut longest: Option<(usize, usize, bool)> = None;
+        for (step, ascending) in [(1, true), (-1, false)] {
+            let mut start = 0;
+            for i in 1..=values.len() {
+                let continues = i < values.len()
+                    && matches!((values[i - 1], values[i]), (Some(a), Some(b)) if b - a == step);
+                if continues {
+                    continue;
+                }
+                let len = i - start;
+                if longest.is_none_or(|(s, l, _)| len > l || (len == l && start < s)) {
+                    longest = Some((start, len, ascending));
+                }
+                start = i;
+            }
+        }
+        longest.map(|(start, len, ascending)| Self {
+            sequence: hash[start..start + len].to_string(),
+            ascending,
+            offset: start,
+        })
+    }
+
+    fn tier(&self) -> Option<RarityTier> {
+        match self.sequence.len() {
+            0..=6 => None,
+            7..=8 => Some(RarityTier::Uncommon),
+            _ => Some(RarityTier::Rare),
+        }
+    }
+
+    fn strength(&self) -> Option<MatchStrength> {
+        MatchStrength::new(self.sequence.len(), 7, "sequential characters")
+    }
+
+    fn rule_match(&self, hash_len: usize) -> Option<RuleMatch> {
+        Some(RuleMatch {
+            tier: self.tier()?,
+            percentage: Some(probability::sequence(self.sequence.len(), hash_len)),
+            explanation: self.to_string(),
+            span: Some(Span::new(self.offset, &self.sequence)),
+        })
+    }
+}
+
+/// Hashes with a run of characters whose hex values never decrease, like
+/// `0133478aacdef`.
+struct MonotonicExpl {
+    length: usize,
+    offset: usize,
+    /// Whether every value is greater than the one before it.
+    strict: bool,
+}
+
+impl Display for MonotonicExpl {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), Error> {
+        let kind = if self.strict {
+            "strictly increasing"
+        } else {
+            "non-decreasing"
+        };
+        write!(f, "Contains a {kind} run of {} characters", self.length)
+    }
+}
+
+impl MonotonicExpl {
+    /// Finds the rarest of the longest strictly increasing and the longest
+    /// non-decreasing runs.
+    fn from_hash(hash: &str) -> Option<Self> {
+        let values = hash
+            .chars()
+            .map(|c| c.to_digit(16))
+            .collect::<Option<Vec<u32>>>()?;
+        [true, false]
+            .into_iter()
+            .map(|strict| Self::longest(&values, strict))
+            .filter(|e| e.tier().is_some())
+            .min_by(|a, b| {
+                b.tier().cmp(&a.tier()).then(
+                    a.probability(hash.len())
+                        .total_cmp(&b.probability(hash.len())),
+                )
+            })
+    }
+
+    /// The first longest run, strict or not.
+    fn longest(values: &[u32], strict: bool) -> Self {
+        let mut longest = Self {
+            length: 0,
+            offset: 0,
+            strict,
+        };
+        let mut start = 0;
+        for i in 1..=values.len() {
+            let continues = i < values.len()
+                && if strict {
+                    values[i] > values[i - 1]
+                } else {
+                    values[i] >= values[i - 1]
+                };
+            if continues {
+                continue;
+            }
+            if i - start > longest.length {
+                longest.length = i - start;
+                longest.offset = start;
+            }
+            start = i;
+        }
+        longest
+    }
+
+    fn tier(&self) -> Option<RarityTier> {
+        match (self.strict, self.length) {
+            (true, 0..=9) | (false, 0..=11) => None,
+            (true, 10..=11) => Some(RarityTier::Rare),
+            (true, _) => Some(RarityTier::Legendary),
+            (false, 12..=13) => Some(RarityTier::Uncommon),
+            (false, _) => Some(RarityTier::Rare),
+        }
+    }
+
+    fn probability(&self, hash_len: usize) -> f64 {
+        probability::monotonic(self.length, hash_len, self.strict)
+    }
+
+    fn rule_match(&self, hash: &str) -> Option<RuleMatch> {
+        let span = Span::of(hash, self.offset..self.offset + self.length);
+        Some(RuleMatch {
+            tier: self.tier()?,
+            percentage: Some(self.probability(hash.len())),
+            explanation: span.explain(self),
+            span: Some(span),
+        })
+    }
+}
+
+/// Hashes alternating between digits and letters, like `a1b2c3d4e5`.
+struct AlternatingExpl {
+    length: usize,
+    offset: usize,
+}
+
+impl Display for AlternatingExpl {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), Error> {
+        write!(
+            f,
+            "Alternates between digits and letters for {} characters at offset {}",
+            self.length, self.offset
+        )
+    }
+}
+
+impl AlternatingExpl {
+    /// Finds the longest alternating run, preferring the first on ties.
+    fn from_hash(hash: &str) -> Self {
+        let classes = hash
+            .chars()
+            .map(|c| c.is_ascii_digit())
+            .collect::<Vec<bool>>();
+        let mut longest = Self {
+            length: 0,
+            offset: 0,
+        };
+        let mut start = 0;
+        for i in 1..=classes.len() {
+            if i < classes.len() && classes[i] != classes[i - 1] {
+                continue;
+            }
+            if i - start > longest.length {
+                longest = Self {
+                    length: i - start,
+                    offset: start,
+                };
+            }
+            start = i;
+        }
+        longest
+    }
+
+    fn tier(&self) -> Option<RarityTier> {
+        match self.length {
+            0..=9 => None,
+            10..=13 => Some(RarityTier::Uncommon),
+            _ => Some(RarityTier::Rare),
+        }
+    }
+
+    fn strength(&self) -> Option<MatchStrength> {
+        MatchStrength::new(self.length, 10, "alternating digits and letters")
+    }
+
+    fn rule_match(&self, hash: &str) -> Option<RuleMatch> {
+        Some(RuleMatch {
+            tier: self.tier()?,
+            percentage: Some(probability::alternating(self.length, hash.len())),
+            explanation: self.to_string(),
+            span: Some(Span::of(hash, self.offset..self.offset + self.length)),
+        })
+    }
+}
+
+/// Hashes that start and end with the same characters.
+struct SandwichExpl(String);
+
+impl Display for SandwichExpl {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), Error> {
+        write!(f, "Starts and ends with '{}'", self.0)
+    }
+}
+
+impl SandwichExpl {
+    /// Longest shared prefix and suffix worth measuring.
+    const MAX_LEN: usize = 20;
+
+    /// Finds the longest prefix that is also a suffix. The two are kept
+    /// from overlapping, since otherwise any repetitive hash would match
+    /// itself.
+    fn from_hash(hash: &str) -> Self {
+        let max = Self::MAX_LEN.min(hash.len() / 2);
+        let len = (1..=max)
+            .rev()
+            .find(|&n| hash[..n] == hash[hash.len() - n..])
+            .unwrap_or(0);
+        Self(hash[..len].to_string())
+    }
+
+    fn tier(&self) -> Option<RarityTier> {
+        match self.0.len() {
+            0..=3 => None,
+            4..=5 => Some(RarityTier::Uncommon),
+            _ => Some(RarityTier::Rare),
+        }
+    }
+
+    fn rule_match(&self) -> Option<RuleMatch> {
+        Some(RuleMatch {
+            tier: self.tier()?,
+            percentage: Some(probability::exact(self.0.len())),
+            explanation: self.to_string(),
+            // The match is split between both ends of the hash.
+            span: None,
+        })
+    }
+}
+
+/// Hashes with a stretch that looks like binary, like `0110100110`.
+struct BinaryExpl {
+    digits: String,
+    offset: usize,
+}
+
+impl Display for BinaryExpl {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), Error> {
+        if self.offset == 0 {
+            write!(f, "Starts with binary-looking '{}'", self.digits)
+        } else {
+            write!(
+                f,
+                "Contains binary-looking '{}' at offset {}",
+                self.digits, self.offset
+            )
+        }
+    }
+}
+
+impl BinaryExpl {
+    /// Finds the longest run of zeros and ones, preferring the first on ties.
+    fn from_hash(hash: &str) -> Self {
+        let span = longest_run_span(hash, |c| c == '0' || c == '1');
+        Self {
+            digits: hash[span.clone()].to_string(),
+            offset: span.start,
+        }
+    }
+
+    fn tier(&self) -> Option<RarityTier> {
+        match self.digits.len() {
+            0..=8 => None,
+            9..=11 => Some(RarityTier::Uncommon),
+            _ => Some(RarityTier::Rare),
+        }
+    }
+
+    fn rule_match(&self, hash_len: usize) -> Option<RuleMatch> {
+        let len = self.digits.len();
+        let percentage = if self.offset == 0 {
+            probability::all_of(probability::BINARY, len)
+        } else {
+            probability::run(probability::BINARY, len, hash_len)
+        };
+        Some(RuleMatch {
+            tier: self.tier()?,
+            percentage: Some(percentage),
+            explanation: self.to_string(),
+            span: Some(Span::new(self.offset, &self.digits)),
+        })
+    }
+}
+
+/// Hashes containing a short unit repeated several times, like `5a5a5a5a`.
+struct PatternExpl {
+    unit: String,
+    repetitions: usize,
+    offset: usize,
+}
+
+impl Display for PatternExpl {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), Error> {
+        write!(
+            f,
+            "Pattern '{}' repeated {} times",
+            self.unit, self.repetitions
+        )
+    }
+}
+
+impl PatternExpl {
+    /// Finds the longest stretch of `hash` that repeats with the given
+    /// period. Units that themselves consist of one repeated character are
+    /// skipped, since those are reported as repeated characters instead.
+    fn from_hash(hash: &str, period: usize) -> Option<Self> {
+        let chars = hash.chars().collect::<Vec<char>>();
+        let mut longest: Option<(usize, usize)> = None;
+        let mut start = 0;
+        while start + period < chars.len() {
+            let mut end = start + period;
+            while end < chars.len() && chars[end] == chars[end - period] {
+                end += 1;
+            }
+            let unit = &chars[start..start + period];
+            let uniform = unit.iter().all(|&c| c == unit[0]);
+            if !uniform && longest.is_none_or(|(_, len)| end - start > len) {
+                longest = Some((start, end - start));
+            }
+            start = (end - period + 1).max(start + 1);
+        }
+        longest.map(|(start, len)| Self {
+            unit: chars[start..start + period].iter().collect(),
+            repetitions: len / period,
+            offset: start,
+        })
+    }
+
+    fn tier(&self) -> Option<RarityTier> {
+        match (self.unit.len(), self.repetitions) {
+            (2, 4..=5) => Some(RarityTier::Uncommon),
+            (2, 6..) => Some(RarityTier::Rare),
+            (3, 3..) => Some(RarityTier::Rare),
+            _ => None,
+        }
+    }
+
+    fn rule_match(&self, hash_len: usize) -> Option<RuleMatch> {
+        let period = self.unit.len();
+        let span = Span::new(self.offset, self.unit.repeat(self.repetitions));
+        Some(RuleMatch {
+            tier: self.tier()?,
+            percentage: Some(probability::periodic(
+                period,
+                period * self.repetitions,
+                hash_len,
+            )),
+            explanation: span.explain(self),
+            span: Some(span),
+        })
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+enum DateForm {
+    Full,
+    ShortYear,
+    MonthDay,
+}
+
+impl DateForm {
+    fn format(&self) -> &'static str {
+        match self {
+            Self::Full => "%Y%m%d",
+            Self::ShortYear => "%y%m%d",
+            Self::MonthDay => "%m%d",
+        }
+    }
+
+    fn label(&self) -> &'static str {
+        match self {
+            Self::Full => "YYYYMMDD",
+            Self::ShortYear => "YYMMDD",
+            Self::MonthDay => "MMDD",
+        }
+    }
+}
+
+/// Hashes containing the digits of the date the commit was made.
+struct DateExpl {
+    form: DateForm,
+    digits: String,
+    offset: usize,
+}
+
+impl Display for DateExpl {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), Error> {
+        write!(
+            f,
+            "Contains its commit date as {} '{}' at offset {}",
+            self.form.label(),
+            self.digits,
+            self.offset
+        )
+    }
+}
+
+impl DateExpl {
+    /// Finds the longest form of the commit date contained in the hash.
+    fn from_commit(commit: &CommitContext) -> Option<Self> {
+        let datetime = commit.datetime?;
+        [DateForm::Full, DateForm::ShortYear, DateForm::MonthDay]
+            .into_iter()
+            .find_map(|form| {
+                let digits = datetime.format(form.format()).to_string();
+                let offset = commit.hash.find(&digits)?;
+                Some(Self {
+                    form,
+                    digits,
+                    offset,
+                })
+            })
+    }
+
+    fn tier(&self) -> RarityTier {
+        match self.form {
+            DateForm::Full => RarityTier::Legendary,
+            DateForm::ShortYear => RarityTier::Rare,
+            DateForm::MonthDay => RarityTier::Uncommon,
+        }
+    }
+
+    fn rule_match(&self, hash_len: usize) -> Option<RuleMatch> {
+        Some(RuleMatch {
+            tier: self.tier(),
+            percentage: Some(probability::contains(&self.digits, hash_len)),
+            explanation: self.to_string(),
+            span: Some(Span::new(self.offset, &self.digits)),
+        })
+    }
+}
+
+/// Hashes containing the decimal Unix timestamp of the commit.
+struct TimestampExpl {
+    digits: String,
+    full: bool,
+    offset: usize,
+}
+
+impl Display for TimestampExpl {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), Error> {
+        if self.full {
+            write!(
+                f,
+                "Contains its Unix timestamp '{}' at offset {}",
+                self.digits, self.offset
+            )
+        } else {
+            write!(
+                f,
+                "Contains {} digits of its Unix timestamp '{}' at offset {}",
+                self.digits.len(),
+                self.digits,
+                self.offset
+            )
+        }
+    }
+}
+
+impl TimestampExpl {
+    /// Shortest prefix or suffix of the timestamp worth reporting.
+    const MIN_PARTIAL_LEN: usize = 7;
+
+    /// Finds the full timestamp, or else its longest prefix or suffix, in
+    /// the hash.
+    fn from_commit(commit: &CommitContext) -> Option<Self> {
+        let timestamp = commit.datetime?.timestamp().to_string();
+        if let Some(offset) = commit.hash.find(&timestamp) {
+            return Some(Self {
+                digits: timestamp,
+                full: true,
+                offset,
+            });
+        }
+        for len in (Self::MIN_PARTIAL_LEN..timestamp.len()).rev() {
+            for digits in [&timestamp[..len], &timestamp[timestamp.len() - len..]] {
+                if let Some(offset) = commit.hash.find(digits) {
+                    return Some(Self {
+                        digits: digits.to_string(),
+                        full: false,
+                        offset,
+                    });
+                }
+            }
+        }
+        None
+    }
+
+    fn tier(&self) -> RarityTier {
+        if self.full {
+            RarityTier::Rare
+        } else {
+            RarityTier::Uncommon
+        }
+    }
+
+    fn rule_match(&self, hash_len: usize) -> Option<RuleMatch> {
+        Some(RuleMatch {
+            tier: self.tier(),
+            percentage: Some(probability::contains(&self.digits, hash_len)),
+            explanation: self.to_string(),
+            span: Some(Span::new(self.offset, &self.digits)),
+        })
+    }
+}
+
+/// Rules evaluated against just the abbreviated hash with
+/// `--short-hash-rules`, since that's the part most people see.
+#[derive(Clone, Copy)]
+enum ShortHashExpl {
+    AllDigits,
+    AllLetters,
+    AllSame,
+    Palindrome,
+    Sequence,
+}
+
+impl Display for ShortHashExpl {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), Error> {
+        match self {
+            Self::AllDigits => write!(f, "Short hash is all digits"),
+            Self::AllLetters => write!(f, "Short hash is all letters"),
+            Self::AllSame => write!(f, "Short hash is all the same character"),
+            Self::Palindrome => write!(f, "Short hash is a palindrome"),
+            Self::Sequence => write!(f, "Short hash is a sequence"),
+        }
+    }
+}
+
+impl ShortHashExpl {
+    const ALL: [Self; 5] = [
+        Self::AllDigits,
+        Self::AllLetters,
+        Self::AllSame,
+        Self::Palindrome,
+        Self::Sequence,
+    ];
+
+    /// Matches of every short hash rule against the first `abbrev`
+    /// characters of `hash`.
+    fn get_matches(hash: &str, abbrev: usize) -> Vec<RuleMatch> {
+        let short = &hash[..abbrev.min(hash.len())];
+        Self::ALL
+            .into_iter()
+            .filter(|rule| rule.is_match(short))
+            .map(|rule| rule.rule_match(short))
+            .collect()
+    }
+
+    fn is_match(self, short: &str) -> bool {
+        match self {
+            Self::AllDigits => short.chars().all(|c| c.is_ascii_digit()),
+            Self::AllLetters => short.chars().all(|c| c.is_ascii_alphabetic()),
+            Self::AllSame => short.chars().all(|c| short.starts_with(c)),
+            Self::Palindrome => short.chars().eq(short.chars().rev()),
+            Self::Sequence => {
+                SequenceExpl::from_hash(short).is_some_and(|e| e.sequence.len() == short.len())
+            }
+        }
+    }
+
+    fn tier(self) -> RarityTier {
+        match self {
+            Self::AllDigits | Self::AllLetters | Self::Palindrome => RarityTier::Uncommon,
+            Self::AllSame | Self::Sequence => RarityTier::Rare,
+        }
+    }
+
+    /// Probability of a short hash of `len` characters matching.
+    fn probability(self, len: usize) -> f64 {
+        match self {
+            Self::AllDigits => probability::all_of(probability::DIGIT, len),
+            Self::AllLetters => probability::all_of(probability::LETTER, len),
+            Self::AllSame => probability::exact(len.saturating_sub(1)),
+            Self::Palindrome => probability::palindrome(len),
+            Self::Sequence => probability::sequence(len, len),
+        }
+    }
+
+    fn rule_match(self, short: &str) -> RuleMatch {
+        let span = Span::new(0, short);
+        RuleMatch {
+            tier: self.tier(),
+            percentage: Some(self.probability(short.len())),
+            explanation: span.explain(self),
+            span: Some(span),
+        }
+    }
+}
+
+/// Default length of the abbreviated hash for `--short-hash-rules`, matching
+/// git's own default.
+const DEFAULT_ABBREV: usize = 7;
+
+/// Default width of the subjects `--show-subject` shows.
+const DEFAULT_SUBJECT_WIDTH: usize = 50;
+
+/// Default length of the hashes `--short` shows.
+const DEFAULT_SHORT: usize = 12;
+
+/// The lengths to abbreviate each of `hashes` to: `abbrev`, or as long as it
+/// takes to tell it apart from the others when they share a longer prefix,
+/// like git abbreviates a hash only in the set of hashes given.
+fn abbreviations<'a>(
+    hashes: impl IntoIterator<Item = &'a str>,
+    abbrev: usize,
+) -> HashMap<&'a str, usize> {
+    let mut sorted = hashes.into_iter().collect::<Vec<&str>>();
+    sorted.sort_unstable();
+    sorted.dedup();
+    // Those sharing a prefix in common with a hash are next to it once
+    // sorted, and the one beside it shares the longest.
+    let shared = sorted
+        .windows(2)
+        .map(|pair| {
+            pair[0]
+                .bytes()
+                .zip(pair[1].bytes())
+                .take_while(|(a, b)| a == b)
+                .count()
+        })
+        .collect::<Vec<usize>>();
+    sorted
+        .iter()
+        .enumerate()
+        .map(|(i, hash)| {
+            let before = i.checked_sub(1).map_or(0, |i| shared[i]);
+            let after = shared.get(i).copied().unwrap_or_default();
+            let len = abbrev.max(before.max(after) + 1).min(hash.len());
+            (*hash, len)
+        })
+        .collect()
+}
+
+/// Parses and validates an `--abbrev` value.
+fn parse_abbrev(value: &str) -> Result<usize, String> {
+    let abbrev = value
+        .parse::<usize>()
+        .map_err(|_| format!("'{value}' is not a number"))?;
+    if (4..=HASH_LEN).contains(&abbrev) {
+        Ok(abbrev)
+    } else {
+        Err(format!(
+            "abbreviated length must be between 4 and {HASH_LEN}, got {abbrev}"
+        ))
+    }
+}
+
+/// Default length of the digit and letter runs the run rules look for.
+const DEFAULT_MIN_RUN: usize = 9;
+
+/// Describes the dates commits are scanned between, or `None` when they
+/// aren't restricted.
+fn describe_dates(dates: &DateRange) -> Option<String> {
+    match (dates.since, dates.until) {
+        (Some(since), Some(until)) => Some(format!("from {since} to {until}")),
+        (Some(since), None) => Some(format!("since {since}")),
+        (None, Some(until)) => Some(format!("until {until}")),
+        (None, None) => None,
+    }
+}
+
+/// Parses a `--since` or `--until` value: an RFC 3339 date, a date and
+/// optional time in UTC like `2024-01-31 12:00`, or a time relative to now
+/// like `3 weeks ago`.
+fn parse_date(value: &str) -> Result<DateTime<FixedOffset>, String> {
+    let value = value.trim();
+    let utc = |naive: NaiveDateTime| naive.and_utc().fixed_offset();
+    if let Ok(datetime) = DateTime::parse_from_rfc3339(value) {
+        return Ok(datetime);
+    }
+    for format in ["%Y-%m-%d %H:%M:%S", "%Y-%m-%d %H:%M"] {
+        if let Ok(naive) = NaiveDateTime::parse_from_str(value, format) {
+            return Ok(utc(naive));
+        }
+    }
+    if let Ok(date) = NaiveDate::parse_from_str(value, "%Y-%m-%d") {
+        return Ok(utc(date.and_time(NaiveTime::MIN)));
+    }
+    parse_relative_date(value, Utc::now().fixed_offset()).ok_or_else(|| {
+        format!(
+            "invalid date '{value}', expected a date like 2024-01-31, \
+             2024-01-31T12:00:00+02:00 or 2 weeks ago"
+        )
+    })
+}
+
+/// Parses a time like `3 weeks ago` relative to `now`.
+fn parse_relative_date(value: &str, now: DateTime<FixedOffset>) -> Option<DateTime<FixedOffset>> {
+    let mut words = value.split_whitespace();
+    let (count, unit) = (words.next()?.parse::<u32>().ok()?, words.next()?);
+    if words.next()? != "ago" || words.next().is_some() {
+        return None;
+    }
+    let count = i64::from(count);
+    let seconds = |unit: i64| {
+        // `Duration` counts milliseconds, and panics past them.
+        let seconds = count.checked_mul(unit).filter(|s| *s <= i64::MAX / 1000)?;
+        now.checked_sub_signed(Duration::seconds(seconds))
+    };
+    let months = |months: i64| now.checked_sub_months(Months::new(u32::try_from(months).ok()?));
+    match unit.strip_suffix('s').unwrap_or(unit) {
+        "second" => seconds(1),
+        "minute" => seconds(60),
+        "hour" => seconds(60 * 60),
+        "day" => seconds(24 * 60 * 60),
+        "week" => seconds(7 * 24 * 60 * 60),
+        "month" => months(count),
+        "year" => months(count.checked_mul(12)?),
+        _ => None,
+    }
+}
+
+/// Parses and validates a `--min-run` value.
+fn parse_min_run(value: &str) -> Result<usize, String> {
+    let run = value
+        .parse::<usize>()
+        .map_err(|_| format!("'{value}' is not a number"))?;
+    validate_min_run(run)
+}
+
+/// Checks that a `--date-format` value is one chrono can format any date
+/// with, so a bad one fails before the scan rather than on every row.
+fn parse_date_format(value: &str) -> Result<String, String> {
+    if StrftimeItems::new(value).any(|item| item == Item::Error) {
+        return Err(format!("'{value}' is not a valid strftime format"));
+    }
+    Ok(value.to_string())
+}
+
+/// Parses and validates a `--subject-width` value, which leaves room for at
+/// least a character before the ellipsis.
+fn parse_subject_width(value: &str) -> Result<usize, String> {
+    match value.parse::<usize>() {
+        Ok(width) if width >= 2 => Ok(width),
+        Ok(width) => Err(format!("subject width must be at least 2, got {width}")),
+        Err(_) => Err(format!("'{value}' is not a number")),
+    }
+}
+
+/// Parses a `--max-width` value, which a table can't be narrower than.
+fn parse_max_width(value: &str) -> Result<usize, String> {
+    match value.parse::<usize>() {
+        Ok(width) if width > 0 => Ok(width),
+        Ok(_) => Err("the width must be at least 1".to_string()),
+        Err(_) => Err(format!("'{value}' is not a number")),
+    }
+}
+
+/// Checks that `value` names one of the [`RULES`].
+fn parse_rule_name(value: &str) -> Result<String, String> {
+    if RULES.iter().any(|(name, _)| *name == value) {
+        return Ok(value.to_string());
+    }
+    let names = RULES.iter().map(|(name, _)| *name).collect::<Vec<&str>>();
+    Err(format!(
+        "unknown rule '{value}', expected one of: {}",
+        names.join(", ")
+    ))
+}
+
+fn validate_lucky_number(number: &str) -> Result<(), String> {
+    if !number.chars().all(|c| c.is_ascii_digit()) {
+        Err(format!("lucky number must be digits only, got '{number}'"))
+    } else if number.len() < LuckyNumberExpl::MIN_LEN {
+        Err(format!(
+            "lucky number must be at least {} digits, got '{number}'",
+            LuckyNumberExpl::MIN_LEN
+        ))
+    } else {
+        Ok(())
+    }
+}
+
+fn validate_min_run(run: usize) -> Result<usize, String> {
+    if (4..=HASH_LEN).contains(&run) {
+        Ok(run)
+    } else {
+        Err(format!(
+            "run length must be between 4 and {HASH_LEN}, got {run}"
+        ))
+    }
+}
+
+/// Number of characters in a SHA-1 hash.
+const HASH_LEN: usize = 40;
+
+/// Number of characters in a SHA-256 hash, in a repository created with
+/// `git init --object-format=sha256`.
+const SHA256_HASH_LEN: usize = 64;
+
+/// Whether `hash` is a full SHA-1 or SHA-256 hash.
+fn is_full_hash(hash: &str) -> bool {
+    matches!(hash.len(), HASH_LEN | SHA256_HASH_LEN) && hash.chars().all(|c| c.is_ascii_hexdigit())
+}
+
+/// Position of the first longest run of characters matching `pred`.
+fn longest_run_span(hash: &str, pred: impl Fn(char) -> bool) -> Range<usize> {
+    let mut longest = 0..0;
+    let mut start = 0;
+    for (i, c) in hash.char_indices() {
+        if !pred(c) {
+            start = i + 1;
+        } else if i + 1 - start > longest.len() {
+            longest = start..i + 1;
+        }
+    }
+    longest
+}
+
+/// Separates the fields of a commit in the `git log` output, since a name can
+/// have any other character in it.
+const FIELD_SEPARATOR: char = '\0';
+
+/// Ends each commit in the `git log` output, since messages span lines.
+const RECORD_SEPARATOR: char = '\x1e';
+
+/// Parses the `git log` output, which lists the newest commit first, and
+/// numbers each commit by its position from the root commit.
+fn parse_log(output: &str, options: &ClassifyOptions) -> Vec<Commit> {
+    let records = output
+        .split(RECORD_SEPARATOR)
+        .map(str::trim_start)
+        .filter(|record| !record.is_empty())
+        .collect::<Vec<&str>>();
+    let total = records.len();
+    records
+        .par_iter()
+        .enumerate()
+        .filter_map(|(i, record)| parse_commit(record, ordinal(total, i, options), options))
+        .collect()
+}
+
+/// Position in history of the `i`th of `total` commits listed newest first.
+fn ordinal(total: usize, i: usize, options: &ClassifyOptions) -> usize {
+    if options.whole_history {
+        total - i
+    } else {
+        0
+    }
+}
+
+/// Classifies the commits read by a backend, which lists the newest commit
+/// first, numbering them like [`parse_log`].
+#[cfg_attr(not(feature = "libgit2"), allow(dead_code))]
+fn classify_log(entries: Vec<LogEntry>, options: &ClassifyOptions) -> Vec<Commit> {
+    let total = entries.len();
+    entries
+        .into_par_iter()
+        .enumerate()
+        .map(|(i, entry)| {
+            Commit::new(
+                entry.hash,
+                entry.author,
+                entry.email,
+                entry.datetime,
+                &entry.message,
+                ordinal(total, i, options),
+                options,
+            )
+        })
+        .collect()
+}
+
+/// Parses a commit as [`split_commit`] reads it.
+fn parse_commit(record: &str, ordinal: usize, options: &ClassifyOptions) -> Option<Commit> {
+    let (hash, datetime, author, email, message) = split_commit(record)?;
+    Some(Commit::new(
+        hash.to_string(),
+        author,
+        email.to_string(),
+        datetime,
+        message,
+        ordinal,
+        options,
+    ))
+}
+
+/// Splits a commit into its hash, date, author, email and message. It's
+/// read as the [`FIELD_SEPARATOR`]-separated fields `git log` prints, or as
+/// a `<hash> <date> <author>` line like those piped to `--stdin`, which has
+/// no email or message.
+fn split_commit(record: &str) -> Option<(&str, DateTime<FixedOffset>, String, &str, &str)> {
+    if !record.contains(FIELD_SEPARATOR) {
+        let mut parts = record.split_whitespace();
+        let hash = parts.next()?;
+        let datetime = DateTime::parse_from_rfc3339(parts.next()?).ok()?;
+        let author = parts.collect::<Vec<&str>>().join(" ");
+        return Some((hash, datetime, author, "", ""));
+    }
+    let mut fields = record.splitn(5, FIELD_SEPARATOR);
+    let hash = fields.next()?;
+    let datetime = DateTime::parse_from_rfc3339(fields.next()?).ok()?;
+    let author = fields.next()?.to_string();
+    let email = fields.next().unwrap_or_default();
+    let message = fields.next().unwrap_or_default();
+    Some((hash, datetime, author, email, message))
+}
+
+/// Reads the signatures `git log --format=%H%x00%G?` prints, a line of a
+/// hash and a letter for each commit, by their hash.
+fn parse_signatures(output: &str) -> HashMap<String, char> {
+    output
+        .lines()
+        .filter_map(|line| {
+            let (hash, letter) = line.split_once(FIELD_SEPARATOR)?;
+            Some((hash.to_string(), letter.chars().next()?))
+        })
+        .collect()
+}
+
+/// A commit's signature in the Signature column, from the letter `git log`
+/// prints for it with `%G?`: good, from a key not known to be trusted,
+/// bad, expired, from an expired or revoked key, impossible to check, like
+/// without the key, or missing.
+fn display_signature(signature: &Option<char>) -> String {
+    let text = match signature {
+        None => "",
+        Some('G') => "signed",
+        Some('U') => "signed, untrusted",
+        Some('B') => "bad",
+        Some('X') => "expired",
+        Some('Y') => "expired key",
+        Some('R') => "revoked key",
+        Some('E') => "unverifiable",
+        Some('N') => "unsigned",
+        Some(_) => "unknown",
+    };
+    text.to_string()
+}
+
+/// Parses a line read with `--stdin`: a full hash, classified with the date
+/// and author after it when they're there as [`parse_commit`] reads them,
+/// and on its own otherwise, ignoring whatever follows it. Returns `None`
+/// when the line doesn't start with a hash.
+fn parse_hash_line(line: &str, options: &ClassifyOptions) -> Option<HashLine> {
+    let hash = line
+        .split(|c: char| c.is_whitespace() || c == FIELD_SEPARATOR)
+        .next()?;
+    if !is_full_hash(hash) {
+        return None;
+    }
+    let hash = hash.to_ascii_lowercase();
+    match split_commit(line) {
+        Some((_, datetime, author, email, message)) => Some(HashLine::Commit(Commit::new(
+            hash,
+            author,
+            email.to_string(),
+            datetime,
+            message,
+            0,
+            options,
+        ))),
+        None => Some(HashLine::Hash(HashRow {
+            rarity: Commit::get_hash_rarity(&hash, options),
+            hash,
+        })),
+    }
+}
+
+/// Classifies the hashes read with `--stdin`, skipping blank lines, and
+/// counts the lines that weren't hashes.
+fn read_hashes(input: impl BufRead, options: &ClassifyOptions) -> Result<(Vec<HashLine>, usize)> {
+    let lines = input
+        .lines()
+        .collect::<std::io::Result<Vec<String>>>()
+        .context("failed to read stdin")?;
+    let parsed = lines
+        .par_iter()
+        .map(|line| line.trim())
+        .filter(|line| !line.is_empty())
+        .map(|line| parse_hash_line(line, options))
+        .collect::<Vec<Option<HashLine>>>();
+    let invalid = parsed.iter().filter(|line| line.is_none()).count();
+    Ok((parsed.into_iter().flatten().collect(), invalid))
+}
+
+/// The commits read from one repository, named by its path when scanning
+/// several. Only the commits the view needs are kept, but all of them are
+/// counted.
+#[derive(Default)]
+struct Scan {
+    repo: String,
+    /// The path of the repository, or `None` for the current directory.
+    path: Option<PathBuf>,
+    commits: Vec<Commit>,
+    count: Count,
+    /// Whether the repository is a shallow clone, so some of its history
+    /// couldn't be scanned.
+    shallow: bool,
+    /// How many of the walked commits were classified and how many were read
+    /// from the cache, when it was used.
+    cached: Option<cache::Stats>,
+}
+
+impl Scan {
+    fn from_commits(commits: Vec<Commit>) -> Self {
+        Self {
+            repo: String::new(),
+            path: None,
+            count: Count::from_commits(&commits),
+            commits,
+            shallow: false,
+            cached: None,
+        }
+    }
+
+    /// Counts `commits`, keeping those that pass `keep`.
+    fn add(&mut self, commits: Vec<Commit>, keep: &Keep) {
+        self.count.add(&Count::from_commits(&commits));
+        self.commits.extend(commits.into_iter().filter(|c| keep(c)));
+    }
+
+    /// Classifies a batch of log entries, numbered by their position in
+    /// history, emptying it, and adds the time it takes to `timings`. Those
+    /// in `cache` are read from it instead, and all of them are written to
+    /// it. A cache that can't be written is dropped with a warning.
+    fn classify(
+        &mut self,
+        batch: &mut Vec<(usize, LogEntry)>,
+        options: &ClassifyOptions,
+        keep: &Keep,
+        cache: &mut Option<Cache>,
+        timings: &Timings,
+    ) {
+        let _classifying = timings.measure(Phase::Classification);
+        timings.count(batch.len());
+        let mut cached = match cache {
+            Some(cache) => batch
+                .iter()
+                .map(|(ordinal, entry)| {
+                    cache.lookup(&entry.hash, &entry.author, &entry.datetime, *ordinal)
+                })
+                .collect(),
+            None => vec![None; batch.len()],
+        };
+        let commits = batch
+            .par_drain(..)
+            .zip(cached.par_drain(..))
+            .map(|((ordinal, entry), rarity)| {
+                let commit = match rarity {
+                    Some(rarity) => {
+                        let LogEntry {
+                            hash,
+                            author,
+                            email,
+                            datetime,
+                            message,
+                        } = entry;
+                        Commit::with_rarity(hash, author, email, datetime, &message, rarity)
+                    }
+                    None => Commit::new(
+                        entry.hash,
+                        entry.author,
+                        entry.email,
+                        entry.datetime,
+                        &entry.message,
+                        ordinal,
+                        options,
+                    ),
+                };
+                (ordinal, commit)
+            })
+            .collect::<Vec<(usize, Commit)>>();
+        if let Some(writing) = cache {
+            let written = commits
+                .iter()
+                .try_for_each(|(ordinal, commit)| writing.record(commit, *ordinal));
+            if let Err(e) = written {
+                eprintln!("warning: not caching classifications: {e:#}");
+                *cache = None;
+            }
+        }
+        let commits = commits.into_iter().map(|(_, commit)| commit).collect();
+        self.add(commits, keep);
+    }
+
+    /// Classifies the numbered log entries read from `entries` in batches of
+    /// [`CLASSIFY_BATCH`] as they're read, like [`Scan::classify`], stopping
+    /// at the first that can't be read.
+    fn classify_all(
+        &mut self,
+        entries: impl Iterator<Item = Result<(usize, LogEntry)>>,
+        options: &ClassifyOptions,
+        keep: &Keep,
+        cache: &mut Option<Cache>,
+        timings: &Timings,
+    ) -> Result<()> {
+        let mut batch = Vec::with_capacity(CLASSIFY_BATCH);
+        for entry in entries {
+            batch.push(entry?);
+            if batch.len() == CLASSIFY_BATCH {
+                self.classify(&mut batch, options, keep, cache, timings);
+            }
+        }
+        self.classify(&mut batch, options, keep, cache, timings);
+        Ok(())
+    }
+}
+
+/// Which of the commits read to keep for the view, since a long history
+/// can't all be kept in memory.
+type Keep = dyn Fn(&Commit) -> bool + Sync;
+
+/// Number of commits or objects classified in parallel at a time as they're
+/// read.
+const CLASSIFY_BATCH: usize = 4096;
+
+/// What a scan reads besides the commits it walks.
+#[derive(Clone, Copy, Default)]
+struct Extras {
+    /// The annotated tags, with `--tags`.
+    tags: bool,
+    /// The commits of every stash, with `--stashes`.
+    stashes: bool,
+    /// Whether the signatures of the commits kept are checked, with
+    /// `--show-signatures`.
+    signatures: bool,
+}
+
+/// Reads and classifies the history of `repo`, or of the current directory,
+/// followed by the `extras`. Commits are classified in batches as they're
+/// read, keeping only those that pass `keep`. Tag objects are left out when
+/// `log` selects commits they can't match. The commits of a shallow clone
+/// aren't numbered, since their position in history is unknown, and neither
+/// are those of stashes, which aren't part of it. When the reflogs are
+/// walked, the commits only they record are marked unreachable. Signatures
+/// are checked last, for the commits kept alone. The time each phase takes
+/// is added to `timings`.
+#[allow(clippy::too_many_arguments)]
+fn read_commits(
+    backend: Backend,
+    repo: Option<&Path>,
+    revisions: &[String],
+    log: &LogOptions,
+    extras: Extras,
+    options: &ClassifyOptions,
+    keep: &Keep,
+    timings: &Arc<Timings>,
+) -> Result<Scan> {
+    let backend = backend.for_log(repo, log);
+    let shallow = match backend {
+        Backend::Git => backend::git_is_shallow(repo)?,
+        #[cfg(feature = "libgit2")]
+        Backend::Libgit2 => backend::libgit2_is_shallow(repo)?,
+    };
+    let commits = match backend {
+        Backend::Git => backend::git_log(repo, revisions, log, timings)?,
+        #[cfg(feature = "libgit2")]
+        Backend::Libgit2 => backend::libgit2_log(repo, revisions, log, timings)?,
+    };
+    let stashes = if extras.stashes {
+        match backend {
+            Backend::Git => backend::git_stashes(repo)?,
+            #[cfg(feature = "libgit2")]
+            Backend::Libgit2 => backend::libgit2_stashes(repo)?,
+        }
+    } else {
+        vec![]
+    };
+    // The walk can reach stash commits too, like through the reflogs, but
+    // they're only counted once, as part of their stash.
+    let stashed = stashes
+        .iter()
+        .map(|stash| stash.hash.as_str())
+        .collect::<HashSet<&str>>();
+    let total = commits.len.filter(|_| !shallow);
+    let mut scan = Scan {
+        path: repo.map(Path::to_path_buf),
+        shallow,
+        ..Scan::default()
+    };
+    let mut cache = match cache_key(options, revisions, log) {
+        Some(key) if options.cache => open_cache(backend, repo, key),
+        _ => None,
+    };
+    let entries = commits
+        .entries
+        .enumerate()
+        .filter_map(|(i, entry)| match entry {
+            Ok(entry) if stashed.contains(entry.hash.as_str()) => None,
+            entry => Some(entry.map(|entry| {
+                let ordinal = total.map_or(0, |total| ordinal(total, i, options));
+                (ordinal, entry)
+            })),
+        });
+    scan.classify_all(entries, options, keep, &mut cache, timings)?;
+    if let Some(cache) = cache {
+        scan.cached = Some(cache.stats());
+        if let Err(e) = cache.finish() {
+            eprintln!("warning: not caching classifications: {e:#}");
+        }
+    }
+    if !stashes.is_empty() {
+        let hashes = stashes
+            .iter()
+            .map(|stash| stash.hash.clone())
+            .collect::<Vec<String>>();
+        // The most recent commits are those of the walk, not the stashes'.
+        let stash_log = LogOptions {
+            max_count: None,
+            ..log.clone()
+        };
+        let entries = match backend {
+            Backend::Git => backend::git_commits(repo, &hashes, &stash_log, timings)?,
+            #[cfg(feature = "libgit2")]
+            Backend::Libgit2 => backend::libgit2_commits(repo, &hashes, &stash_log, timings)?,
+        };
+        let names = stashes
+            .iter()
+            .map(|stash| (stash.hash.as_str(), stash.name.as_str()))
+            .collect::<HashMap<&str, &str>>();
+        let mut commits = vec![];
+        for entry in entries.entries {
+            let entry = entry?;
+            let stash = names.get(entry.hash.as_str()).map(|name| name.to_string());
+            let commit = Commit::new(
+                entry.hash,
+                entry.author,
+                entry.email,
+                entry.datetime,
+                &entry.message,
+                0,
+                options,
+            );
+            commits.push(Commit { stash, ..commit });
+        }
+        scan.add(commits, keep);
+    }
+    if revisions.iter().any(|revision| revision == "--reflog") {
+        let unreachable = match backend {
+            Backend::Git => backend::git_unreachable(repo)?,
+            #[cfg(feature = "libgit2")]
+            Backend::Libgit2 => backend::libgit2_unreachable(repo)?,
+        };
+        for commit in &mut scan.commits {
+            commit.unreachable = unreachable.contains(&commit.hash);
+        }
+    }
+    if extras.tags && log.allows_tag_objects() {
+        let tag_objects = match backend {
+            Backend::Git => parse_log(&backend::git_tags(repo)?, options),
+            #[cfg(feature = "libgit2")]
+            Backend::Libgit2 => classify_log(backend::libgit2_tags(repo)?, options),
+        };
+        let tag_objects = tag_objects
+            .into_iter()
+            .filter(|tag| log.dates.contains(&tag.datetime))
+            .map(|tag| Commit {
+                object: ObjectKind::Tag,
+                ..tag
+            })
+            .collect();
+        scan.add(tag_objects, keep);
+    }
+    if extras.signatures {
+        let hashes = scan
+            .commits
+            .iter()
+            .filter(|commit| commit.object == ObjectKind::Commit)
+            .map(|commit| commit.hash.clone())
+            .collect::<Vec<String>>();
+        let signatures = backend::git_signatures(repo, &hashes)?;
+        for commit in &mut scan.commits {
+            commit.signature = signatures.get(&commit.hash).copied();
+        }
+    }
+    Ok(scan)
+}
+
+/// Identifies the cached classifications of a walk of `revisions` with `log`
+/// by everything that decides which commits it reads and how they're
+/// classified, including git-rare's version. Plugins can't be told apart by
+/// their path, so there's no key with them.
+fn cache_key(options: &ClassifyOptions, revisions: &[String], log: &LogOptions) -> Option<u64> {
+    #[cfg(feature = "plugins")]
+    if !options.plugins.is_empty() {
+        return None;
+    }
+    let mut hasher = DefaultHasher::new();
+    env!("CARGO_PKG_VERSION").hash(&mut hasher);
+    for rule in &options.registry {
+        rule.name().hash(&mut hasher);
+    }
+    options.min_run.hash(&mut hasher);
+    format!("{:?}", options.rules).hash(&mut hasher);
+    options.lucky_numbers.hash(&mut hasher);
+    let mut overrides = options
+        .overrides
+        .iter()
+        .map(|(name, rule_override)| format!("{name} {rule_override:?}"))
+        .collect::<Vec<String>>();
+    overrides.sort();
+    overrides.hash(&mut hasher);
+    options.patterns.hash(&mut hasher);
+    options.memes.hash(&mut hasher);
+    options.leet.hash(&mut hasher);
+    options.short_hash.hash(&mut hasher);
+    options.whole_history.hash(&mut hasher);
+    revisions.hash(&mut hasher);
+    format!("{log:?}").hash(&mut hasher);
+    Some(hasher.finish())
+}
+
+/// The git directory that `repo`, or the current directory, shares with its
+/// worktrees.
+fn common_dir(backend: Backend, repo: Option<&Path>) -> Result<PathBuf> {
+    match backend {
+        Backend::Git => backend::git_common_dir(repo),
+        #[cfg(feature = "libgit2")]
+        Backend::Libgit2 => backend::libgit2_common_dir(repo),
+    }
+}
+
+/// The cache of the walk with `key` in `repo`, or the current directory, or
+/// none with a warning when it can't be written, like in a repository that
+/// isn't ours.
+fn open_cache(backend: Backend, repo: Option<&Path>, key: u64) -> Option<Cache> {
+    let cache = common_dir(backend, repo).and_then(|dir| Cache::open(&cache::dir(&dir), key));
+    cache
+        .inspect_err(|e| eprintln!("warning: not caching classifications: {e:#}"))
+        .ok()
+}
+
+/// Installs, removes or describes the post-commit hook of `repo`, or the
+/// current directory.
+fn run_hook(repo: Option<&Path>, action: &HookAction) -> Result<()> {
+    match action {
+        HookAction::Install {
+            force,
+            amend_trailer,
+        } => {
+            let path = hook::install(repo, *force, *amend_trailer)?;
+            println!("Installed a post-commit hook at {}", path.display());
+        }
+        HookAction::Uninstall => match hook::uninstall(repo)? {
+            (path, true) => println!("Removed the post-commit hook at {}", path.display()),
+            (path, false) => println!("No post-commit hook to remove at {}", path.display()),
+        },
+        HookAction::Status => match hook::status(repo)? {
+            (path, hook::Status::Installed) => {
+                println!(
+                    "git-rare's post-commit hook is installed at {}",
+                    path.display()
+                )
+            }
+            (path, hook::Status::Other) => {
+                println!(
+                    "A post-commit hook git-rare didn't install is at {}",
+                    path.display()
+                )
+            }
+            (path, hook::Status::Missing) => {
+                println!("No post-commit hook is installed at {}", path.display())
+            }
+        },
+    }
+    Ok(())
+}
+
+/// Classifies the one commit `revision` names in `repo`, or the current
+/// directory, read as `log` reads it but without its history, so it isn't
+/// numbered.
+fn check(
+    backend: Backend,
+    repo: Option<&Path>,
+    revision: &str,
+    log: &LogOptions,
+    options: &ClassifyOptions,
+    timings: &Arc<Timings>,
+) -> Result<Commit> {
+    let log = LogOptions {
+        max_count: Some(1),
+        mailmap: log.mailmap,
+        committer: log.committer,
+        ..LogOptions::default()
+    };
+    let backend = backend.for_log(repo, &log);
+    let revisions = [revision.to_string()];
+    let mut commits = match backend {
+        Backend::Git => backend::git_log(repo, &revisions, &log, timings)?,
+        #[cfg(feature = "libgit2")]
+        Backend::Libgit2 => backend::libgit2_log(repo, &revisions, &log, timings)?,
+    };
+    let entry = commits
+        .entries
+        .next()
+        .transpose()?
+        .with_context(|| format!("'{revision}' names no commit"))?;
+    Ok(Commit::new(
+        entry.hash,
+        entry.author,
+        entry.email,
+        entry.datetime,
+        &entry.message,
+        0,
+        options,
+    ))
+}
+
+/// Announces HEAD in `repo`, or the current directory, unless it's Common,
+/// and with `amend_trailer` amends it to add a `Rarity:` trailer to its
+/// message, announcing the commit that makes too. Nothing is done for the
+/// amend's own post-commit hook, and merges and commits made during a
+/// rebase aren't amended, since that would lose the merge's resolution or
+/// the rebase's place.
+fn post_commit(
+    backend: Backend,
+    repo: Option<&Path>,
+    amend_trailer: bool,
+    log: &LogOptions,
+    options: &ClassifyOptions,
+    timings: &Arc<Timings>,
+) -> Result<()> {
+    if std::env::var_os(hook::AMENDING).is_some() {
+        return Ok(());
+    }
+    let commit = check(backend, repo, "HEAD", log, options, timings)?;
+    if commit.rarity.tier == RarityTier::Common {
+        return Ok(());
+    }
+    println!("{}", announcement(&commit));
+    if !amend_trailer {
+        return Ok(());
+    }
+    if backend::git_head_is_merge(repo)? {
+        eprintln!("note: not adding a Rarity trailer to a merge commit");
+        return Ok(());
+    }
+    if backend::git_is_rebasing(repo)? {
+        eprintln!("note: not adding a Rarity trailer during a rebase");
+        return Ok(());
+    }
+    let trailer = format!("Rarity: {}", rarity_summary(&commit.rarity));
+    if !backend::git_amend_trailer(repo, &trailer, (hook::AMENDING, "1"))? {
+        return Ok(());
+    }
+    let amended = check(backend, repo, "HEAD", log, options, timings)?;
+    println!("Amended the commit to add `{trailer}`, which changed its hash:");
+    println!("{}", announcement(&amended));
+    Ok(())
+}
+
+/// A commit's tier with what its rarest match is, like `Rare (ends with 9
+/// letters)`.
+fn rarity_summary(rarity: &Rarity) -> String {
+    match rarity.matches.first() {
+        Some(rarest) => format!("{} ({})", rarity.tier, lowercase_first(&rarest.explanation)),
+        None => rarity.tier.to_string(),
+    }
+}
+
+/// `text` with its first letter lowercased, to follow on from another
+/// sentence.
+fn lowercase_first(text: &str) -> String {
+    let mut chars = text.chars();
+    chars
+        .next()
+        .map(|first| first.to_lowercase().chain(chars).collect())
+        .unwrap_or_default()
+}
+
+/// Characters of a hash shown when announcing a commit, unless its rarest
+/// match ends further in.
+const ANNOUNCED_LENGTH: usize = 11;
+
+/// The line `git-rare check` prints for `commit`: its tier, its hash up to
+/// the end of its rarest match, and what that match is.
+fn announcement(commit: &Commit) -> String {
+    let rarest = commit.rarity.matches.first();
+    let end = rarest
+        .and_then(|m| m.span.as_ref())
+        .map_or(0, |span| span.range().end)
+        .max(ANNOUNCED_LENGTH);
+    let hash = match commit.hash.get(..end) {
+        Some(prefix) if end < commit.hash.len() => format!("{prefix}…"),
+        _ => commit.hash.clone(),
+    };
+    let tier = &commit.rarity.tier;
+    match rarest {
+        Some(m) if *tier != RarityTier::Common => {
+            format!(
+                "✨ {tier} commit! {hash} {}",
+                lowercase_first(&m.explanation)
+            )
+        }
+        _ => format!("{tier} commit {hash}"),
+    }
+}
+
+/// Deletes the cached classifications of each of `repos`, or of the current
+/// directory.
+fn clear_cache(backend: Backend, repos: &[PathBuf]) -> Result<()> {
+    let repos = match repos {
+        [] => vec![None],
+        repos => repos.iter().map(|repo| Some(repo.as_path())).collect(),
+    };
+    for repo in repos {
+        let common_dir = common_dir(backend, repo)?;
+        let dir = cache::dir(&common_dir);
+        if cache::clear(&common_dir)? {
+            println!("Deleted the cached classifications in {}", dir.display());
+        } else {
+            println!("No cached classifications in {}", dir.display());
+        }
+    }
+    Ok(())
+}
+
+/// Reads and classifies the commits of `repo`, or the current directory,
+/// that no ref or HEAD can reach anymore, keeping those that pass `keep`.
+/// They're found among every object, but only those that are commits are
+/// held in memory. Having no place in history, they're listed newest first
+/// and aren't numbered.
+fn scan_lost(
+    backend: Backend,
+    repo: Option<&Path>,
+    log: &LogOptions,
+    options: &ClassifyOptions,
+    keep: &Keep,
+    timings: &Arc<Timings>,
+) -> Result<Scan> {
+    let backend = backend.for_log(repo, log);
+    let hashes = match backend {
+        Backend::Git => backend::git_lost(repo)?,
+        #[cfg(feature = "libgit2")]
+        Backend::Libgit2 => backend::libgit2_lost(repo)?,
+    };
+    // They're only sorted once they're read, so the most recent can't be
+    // read first.
+    let unlimited = LogOptions {
+        max_count: None,
+        ..log.clone()
+    };
+    let commits = match backend {
+        Backend::Git => backend::git_commits(repo, &hashes, &unlimited, timings)?,
+        #[cfg(feature = "libgit2")]
+        Backend::Libgit2 => backend::libgit2_commits(repo, &hashes, &unlimited, timings)?,
+    };
+    let mut entries = commits.entries.collect::<Result<Vec<LogEntry>>>()?;
+    entries.sort_by_key(|entry| std::cmp::Reverse(entry.datetime));
+    if let Some(max_count) = log.max_count {
+        entries.truncate(max_count);
+    }
+    let mut batch = entries.into_iter().map(|entry| (0, entry)).collect();
+    let mut scan = Scan {
+        path: repo.map(Path::to_path_buf),
+        ..Scan::default()
+    };
+    scan.classify(&mut batch, options, keep, &mut None, timings);
+    Ok(scan)
+}
+
+/// Reads and classifies the commits of a repository on GitHub, keeping those
+/// that pass `keep`, in batches as its pages are read. Like those of a
+/// shallow clone, they aren't numbered, since the history's length isn't
+/// known.
+#[cfg(feature = "github")]
+fn scan_github(
+    repo: &str,
+    log: &LogOptions,
+    options: &ClassifyOptions,
+    keep: &Keep,
+    timings: &Timings,
+) -> Result<Scan> {
+    let commits = github::log(repo, log)?;
+    let mut scan = Scan::default();
+    let mut entries = commits.entries;
+    // Waiting for each page of commits is the time spent on git's part.
+    let entries = std::iter::from_fn(|| timings.time(Phase::Git, || entries.next()));
+    let entries = entries.map(|entry| entry.map(|entry| (0, entry)));
+    scan.classify_all(entries, options, keep, &mut None, timings)?;
+    Ok(scan)
+}
+
+/// Protects the lost commits found by `scan` that aren't Common from
+/// `git gc` with refs of their own, reporting how many there were.
+fn rescue(backend: Backend, repo: Option<&Path>, scan: &Scan) -> Result<()> {
+    let hashes = scan
+        .commits
+        .iter()
+        .filter(|commit| commit.rarity.tier != RarityTier::Common)
+        .map(|commit| commit.hash.clone())
+        .collect::<Vec<String>>();
+    if hashes.is_empty() {
+        return Ok(());
+    }
+    match backend.for_repo(repo) {
+        Backend::Git => backend::git_rescue(repo, &hashes)?,
+        #[cfg(feature = "libgit2")]
+        Backend::Libgit2 => backend::libgit2_rescue(repo, &hashes)?,
+    }
+    eprintln!("Rescued {} lost commits under refs/rare/", hashes.len());
+    Ok(())
+}
+
+/// The notes ref `git-rare annotate` writes under.
+const NOTES_REF: &str = "refs/notes/rare";
+
+/// The note `git-rare annotate` writes on a commit.
+fn note(rarity: &Rarity) -> String {
+    let mut note = format!(
+        "Tier: {}\nExplanation: {}\n",
+        rarity.tier,
+        display_explanations(&rarity.matches)
+    );
+    if let Some(percentage) = rarity.percentage {
+        note.push_str(&format!("Probability: {percentage}\n"));
+    }
+    note
+}
+
+/// The `rare/<hash>` tag of each of `hashes`, with the hash it names: the
+/// hash abbreviated to `abbrev` characters, or as many as tell it apart from
+/// the others, so no two of them get the same name.
+fn rare_tags(hashes: &[String], abbrev: usize) -> Vec<(String, String)> {
+    let abbreviations = abbreviations(hashes.iter().map(String::as_str), abbrev);
+    hashes
+        .iter()
+        .map(|hash| {
+            let short = &hash[..abbreviations[hash.as_str()]];
+            (format!("rare/{short}"), hash.clone())
+        })
+        .collect()
+}
+
+/// Writes a note under [`NOTES_REF`] on each of `commits` in `repo`, or the
+/// current directory, and with `tags` a [`rare_tags`] tag of at least
+/// `abbrev` characters for each. Notes that are already there as they'd be
+/// written are left alone, so annotating again only writes what changed.
+/// With `dry_run`, what would be written is listed instead.
+fn annotate(
+    backend: Backend,
+    repo: Option<&Path>,
+    commits: &[Commit],
+    tags: bool,
+    abbrev: usize,
+    dry_run: bool,
+) -> Result<()> {
+    if commits.is_empty() {
+        println!("No uncommon or rare commits found.");
+        return Ok(());
+    }
+    let backend = backend.for_repo(repo);
+    let hashes = commits
+        .iter()
+        .map(|commit| commit.hash.clone())
+        .collect::<Vec<String>>();
+    let existing = match backend {
+        Backend::Git => backend::git_notes(repo, NOTES_REF, &hashes)?,
+        #[cfg(feature = "libgit2")]
+        Backend::Libgit2 => backend::libgit2_notes(repo, NOTES_REF, &hashes)?,
+    };
+    let notes = commits
+        .iter()
+        .zip(existing)
+        .map(|(commit, existing)| (commit.hash.clone(), note(&commit.rarity), existing))
+        .filter(|(_, note, existing)| {
+            existing.as_deref().map(str::trim_end) != Some(note.trim_end())
+        })
+        .map(|(hash, note, _)| (hash, note))
+        .collect::<Vec<(String, String)>>();
+    let tags = if tags {
+        rare_tags(&hashes, abbrev)
+    } else {
+        vec![]
+    };
+    let unchanged = commits.len() - notes.len();
+    if dry_run {
+        for (hash, note) in &notes {
+            println!("Would write a note on {hash} under {NOTES_REF}:");
+            for line in note.lines() {
+                println!("    {line}");
+            }
+        }
+        for (name, hash) in &tags {
+            println!("Would create the tag {name} for {hash}");
+        }
+        if unchanged > 0 {
+            println!("{unchanged} notes are already up to date");
+        }
+        return Ok(());
+    }
+    match backend {
+        Backend::Git => {
+            backend::git_write_notes(repo, NOTES_REF, &notes)?;
+            backend::git_create_tags(repo, &tags)?;
+        }
+        #[cfg(feature = "libgit2")]
+        Backend::Libgit2 => {
+            backend::libgit2_write_notes(repo, NOTES_REF, &notes)?;
+            backend::libgit2_create_tags(repo, &tags)?;
+        }
+    }
+    println!(
+        "Wrote {} notes under {NOTES_REF}, and {unchanged} were already up to date",
+        notes.len()
+    );
+    if !tags.is_empty() {
+        println!("Created {} tags under refs/tags/rare/", tags.len());
+    }
+    Ok(())
+}
+
+/// Classifies the hash of every object in `repo`, or the current directory,
+/// keeping those whose rarity passes `keep`, along with where the trees and
+/// blobs among them are referenced. Objects are classified in batches as
+/// they're listed, so only the matches are held in memory.
+fn scan_objects(
+    backend: Backend,
+    repo: Option<&Path>,
+    max_count: Option<usize>,
+    options: &ClassifyOptions,
+    keep: impl Fn(&Rarity) -> bool + Sync,
+) -> Result<Vec<ObjectMatch>> {
+    let backend = backend.for_repo(repo);
+    let mut found = vec![];
+    let mut batch = Vec::with_capacity(CLASSIFY_BATCH);
+    let mut classify = |batch: &mut Vec<(String, ObjectKind)>| {
+        found.par_extend(batch.par_drain(..).filter_map(|(hash, object)| {
+            let rarity = Commit::get_hash_rarity(&hash, options);
+            keep(&rarity).then(|| ObjectMatch {
+                object,
+                hash,
+                path: String::new(),
+                rarity,
+            })
+        }));
+    };
+    let mut listed = 0;
+    let mut visit = |hash, object| {
+        batch.push((hash, object));
+        listed += 1;
+        if batch.len() == CLASSIFY_BATCH {
+            classify(&mut batch);
+        }
+        max_count.is_none_or(|max| listed < max)
+    };
+    match backend {
+        Backend::Git => backend::git_objects(repo, &mut visit)?,
+        #[cfg(feature = "libgit2")]
+        Backend::Libgit2 => backend::libgit2_objects(repo, &mut visit)?,
+    }
+    classify(&mut batch);
+
+    let referenced = found
+        .iter()
+        .filter(|m| matches!(m.object, ObjectKind::Tree | ObjectKind::Blob))
+        .map(|m| m.hash.clone())
+        .collect::<HashSet<String>>();
+    let paths = match backend {
+        Backend::Git => backend::git_object_paths(repo, &referenced)?,
+        #[cfg(feature = "libgit2")]
+        Backend::Libgit2 => backend::libgit2_object_paths(repo, &referenced)?,
+    };
+    for m in &mut found {
+        if let Some(path) = paths.get(&m.hash) {
+            m.path = path.clone();
+        }
+    }
+    Ok(found)
+}
+
+/// Scans the repositories in parallel, each from the tag `since_tag` finds
+/// in it with `--since-tag`. One that can't be read is reported and skipped,
+/// unless none of them can.
+#[allow(clippy::too_many_arguments)]
+fn scan_repos(
+    backend: Backend,
+    repos: &[PathBuf],
+    revisions: &[String],
+    since_tag: Option<Option<&str>>,
+    log: &LogOptions,
+    extras: Extras,
+    options: &ClassifyOptions,
+    keep: &Keep,
+    timings: &Arc<Timings>,
+) -> Result<Vec<Scan>> {
+    let scans = repos
+        .par_iter()
+        .filter_map(|path| {
+            let scanned = since_tag
+                .map(|tag| since_tag_range(backend, Some(path), tag))
+                .transpose()
+                .and_then(|range| {
+                    let revisions = [revisions, range.as_slice()].concat();
+                    read_commits(
+                        backend,
+                        Some(path),
+                        &revisions,
+                        log,
+                        extras,
+                        options,
+                        keep,
+                        timings,
+                    )
+                });
+            match scanned {
+                Ok(scan) => Some(Scan {
+                    repo: path.display().to_string(),
+                    ..scan
+                }),
+                Err(e) => {
+                    eprintln!("warning: skipping {}: {e:#}", path.display());
+                    None
+                }
+            }
+        })
+        .collect::<Vec<Scan>>();
+    if scans.is_empty() {
+        bail!("none of the {} repositories could be scanned", repos.len());
+    }
+    Ok(scans)
+}
+
+/// The repositories, or the current directory when none are given, each
+/// followed by its submodules and theirs as paths under it. Submodules that
+/// aren't checked out are reported and skipped.
+fn with_submodules(backend: Backend, repos: &[PathBuf]) -> Result<Vec<PathBuf>> {
+    let supers = if repos.is_empty() {
+        vec![PathBuf::from(".")]
+    } else {
+        repos.to_vec()
+    };
+    let mut all = vec![];
+    for repo in supers {
+        let submodules = match backend.for_repo(Some(&repo)) {
+            Backend::Git => backend::git_submodules(Some(&repo)),
+            #[cfg(feature = "libgit2")]
+            Backend::Libgit2 => backend::libgit2_submodules(Some(&repo)),
+        }
+        .with_context(|| format!("failed to list the submodules of {}", repo.display()))?;
+        all.push(repo.clone());
+        for submodule in submodules {
+            let path = if repo == Path::new(".") {
+                submodule.path
+            } else {
+                repo.join(submodule.path)
+            };
+            if submodule.initialized {
+                all.push(path);
+            } else {
+                eprintln!(
+                    "warning: skipping {}: submodule isn't initialized",
+                    path.display()
+                );
+            }
+        }
+    }
+    Ok(all)
+}
+
+/// Reads a `--repos-file`, which lists a repository per line. Blank lines and
+/// lines starting with `#` are skipped, and relative paths are resolved
+/// against the file's directory.
+fn read_repos_file(path: &Path) -> Result<Vec<PathBuf>> {
+    let contents = std::fs::read_to_string(path)
+        .with_context(|| format!("failed to read {}", path.display()))?;
+    let base = path.parent().unwrap_or(Path::new(""));
+    Ok(contents
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(|line| base.join(line))
+        .collect())
+}
+
+/// Prints commits like [`print_rows`], with the type of their object in a
+/// first column when `objects` is set.
+fn print_commits(
+    rows: Vec<(String, Commit)>,
+    tagged: bool,
+    objects: bool,
+    labels: Labels,
+    print: PrintOptions,
+) -> Result<()> {
+    if objects {
+        let rows = rows
+            .into_iter()
+            .map(|(repo, commit)| {
+                let row = ObjectRow {
+                    object: commit.object,
+                    commit: commit.clone(),
+                };
+                (repo, commit, row)
+            })
+            .collect();
+        print_labelled(rows, tagged, labels, print)
+    } else {
+        let rows = rows
+            .into_iter()
+            .map(|(repo, commit)| (repo, commit.clone(), commit))
+            .collect();
+        print_labelled(rows, tagged, labels, print)
+    }
+}
+
+/// Prints `rows` with `print_rows`, or with `--group-by-tier` the rows of
+/// each tier after a line naming it and how many of `noun` it has. The rows of
+/// a tier must be next to each other, as [`CliArgs::sort`] puts them.
+fn print_sections<'a, T: Sortable>(
+    rows: Vec<T>,
+    noun: &str,
+    print: PrintOptions<'a>,
+    print_rows: impl Fn(Vec<T>, PrintOptions<'a>) -> Result<()>,
+) -> Result<()> {
+    if !print.footer {
+        return print_tables(rows, noun, print, print_rows);
+    }
+    let footer = Shown::new(&rows).footer(noun);
+    print_tables(
+        rows,
+        noun,
+        PrintOptions {
+            timed: false,
+            ..print
+        },
+        print_rows,
+    )?;
+    // A line right after a Markdown table would be taken as one of its rows.
+    if print.format == Format::Markdown {
+        print.output.println("");
+    }
+    print.output.println(footer);
+    if print.timed {
+        print_duration(&print);
+    }
+    Ok(())
+}
+
+/// Prints `rows` like [`print_sections`], without the footer.
+fn print_tables<'a, T: Sortable>(
+    rows: Vec<T>,
+    noun: &str,
+    print: PrintOptions<'a>,
+    print_rows: impl Fn(Vec<T>, PrintOptions<'a>) -> Result<()>,
+) -> Result<()> {
+    if !print.grouped {
+        return print_rows(rows, print);
+    }
+    let mut sections: Vec<Vec<T>> = vec![];
+    for row in rows {
+        match sections.last_mut() {
+            Some(section) if section[0].tier() == row.tier() => section.push(row),
+            _ => sections.push(vec![row]),
+        }
+    }
+    let last = sections.len().saturating_sub(1);
+    for (i, section) in sections.into_iter().enumerate() {
+        if i > 0 {
+            print.output.println("");
+        }
+        let plural = if section.len() == 1 { "" } else { "s" };
+        print.output.println(format!(
+            "{} ({} {noun}{plural})\n",
+            section[0].tier(),
+            section.len()
+        ));
+        let timed = print.timed && i == last;
+        print_rows(section, PrintOptions { timed, ..print })?;
+    }
+    Ok(())
+}
+
+/// The columns after a commit's rarity, each shown when its flag is given,
+/// and whether its subject is.
+#[derive(Clone, Copy)]
+struct Labels {
+    /// Whether the commit is unreachable, with `--reflog`.
+    reflog: bool,
+    /// Which stash the commit is part of, with `--stashes`.
+    stashes: bool,
+    /// The first line of the commit's message, with `--show-subject`.
+    subject: bool,
+}
+
+/// The borders `--style` draws the table with, from those of tabled.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, ValueEnum)]
+enum TableStyle {
+    Rounded,
+    Modern,
+    /// Only ASCII characters, for logs and terminals that can't show the
+    /// box drawing characters.
+    Ascii,
+    Psql,
+    Markdown,
+    /// No borders, with the columns separated by spaces.
+    #[value(alias = "compact")]
+    Blank,
+}
+
+impl TableStyle {
+    /// The style of `args` or else of `config`, or the default: rounded in
+    /// a terminal, but ASCII when printing to a file or a pipe, whose reader
+    /// may not expect UTF-8. The Windows console, for one, is written to in
+    /// UTF-16, which shows the box drawing characters, but output redirected
+    /// from it is often read in the console's code page.
+    fn new(args: &CliArgs, config: Option<Self>, output: &Output) -> Self {
+        args.style.or(config).unwrap_or_else(|| {
+            if !output.is_file() && std::io::stdout().is_terminal() {
+                Self::Rounded
+            } else {
+                Self::Ascii
+            }
+        })
+    }
+
+    fn apply(self, table: &mut Table) {
+        match self {
+            Self::Rounded => table.with(Style::rounded()),
+            Self::Modern => table.with(Style::modern()),
+            Self::Ascii => table.with(Style::ascii()),
+            Self::Psql => table.with(Style::psql()),
+            Self::Markdown => table.with(Style::markdown()),
+            Self::Blank => table.with(Style::blank()),
+        };
+    }
+}
+
+/// How the Datetime column shows dates, as chrono displays them.
+const DATETIME_FORMAT: &str = "%Y-%m-%d %H:%M:%S %:z";
+
+/// The time zone `--utc` or `--local` shows dates in.
+#[derive(Clone, Copy)]
+enum Zone {
+    Utc,
+    Local,
+}
+
+/// How tables are printed.
+#[derive(Clone, Copy)]
+struct PrintOptions<'a> {
+    /// [`Format::Table`] or [`Format::Markdown`].
+    format: Format,
+    /// How long each phase took, and when git-rare started, to note how
+    /// long it took once the table is printed.
+    timings: &'a Timings,
+    /// Where the table is printed.
+    output: &'a Output,
+    /// Whether tiers and the matches in hashes are colored, which only
+    /// [`Format::Table`] is.
+    color: bool,
+    /// The style of [`Format::Table`].
+    style: TableStyle,
+    /// The symbols of the `[emoji]` of the config, with `--emoji`.
+    emoji: Option<&'a HashMap<RarityTier, String>>,
+    /// Whether the symbols are limited to ASCII, when [`Format::Table`] is
+    /// drawn in the ASCII style `--style` or the config asks for. The style
+    /// a pipe defaults to doesn't limit them, nor does Markdown.
+    ascii: bool,
+    /// The length of hashes abbreviated with `--short`.
+    short: Option<usize>,
+    /// The columns `--columns` chooses, in its order.
+    columns: Option<&'a [Column]>,
+    /// When the scan started, which `--relative-dates` shows dates relative
+    /// to.
+    now: Option<DateTime<Utc>>,
+    /// The format of `--date-format`.
+    date_format: Option<&'a str>,
+    /// The zone `--utc` or `--local` shows dates in.
+    zone: Option<Zone>,
+    /// The width subjects are cut to when they're shown.
+    subject: Option<usize>,
+    /// Whether the Email column is shown.
+    email: bool,
+    /// Whether the Signature column is shown.
+    signatures: bool,
+    /// Whether each tier gets a table of its own, with `--group-by-tier`
+    /// unless `--only` leaves a single one.
+    grouped: bool,
+    /// Whether a line counting what's shown follows the table, unless
+    /// `--no-footer`.
+    footer: bool,
+    /// The width [`Format::Table`] is fit in, from `--max-width` or the
+    /// terminal.
+    width: Option<usize>,
+    /// Whether the time the scan took is printed after the table, which
+    /// is only after the last of the tables of `--group-by-tier`.
+    timed: bool,
+}
+
+impl<'a> PrintOptions<'a> {
+    fn new(
+        args: &'a CliArgs,
+        timings: &'a Timings,
+        output: &'a Output,
+        emoji: &'a HashMap<RarityTier, String>,
+        style: Option<TableStyle>,
+    ) -> Self {
+        Self {
+            format: args.format,
+            timings,
+            output,
+            color: args.format == Format::Table && args.color.enabled(output.is_file()),
+            style: TableStyle::new(args, style, output),
+            emoji: args.emoji.then_some(emoji),
+            ascii: args.format == Format::Table && args.style.or(style) == Some(TableStyle::Ascii),
+            short: args.short_abbrev(),
+            columns: args.columns.as_deref(),
+            now: args.relative_dates.then(Utc::now),
+            date_format: args.date_format.as_deref(),
+            subject: args
+                .shows_subject()
+                .then(|| args.subject_width.unwrap_or(DEFAULT_SUBJECT_WIDTH)),
+            email: args.shows_email(),
+            signatures: args.show_signatures,
+            zone: match (args.utc, args.local) {
+                (true, _) => Some(Zone::Utc),
+                (_, true) => Some(Zone::Local),
+                _ => None,
+            },
+            grouped: args.group_by_tier && args.only.is_none(),
+            footer: !args.no_footer,
+            width: width::limit(args.max_width, output.is_file()),
+            timed: true,
+        }
+    }
+
+    /// The lengths [`Self::hash`] abbreviates each of `hashes` to with
+    /// `--short`, or none to show them in full.
+    fn abbreviations<'h>(
+        &self,
+        hashes: impl IntoIterator<Item = &'h str>,
+    ) -> HashMap<&'h str, usize> {
+        match self.short {
+            Some(abbrev) => abbreviations(hashes, abbrev),
+            None => HashMap::new(),
+        }
+    }
+
+    /// The name of a tier in the Tier column, in its color when the table is
+    /// colored and after its symbol with `--emoji`, or `text` as it is when
+    /// it isn't one, like the column's header.
+    fn tier(&self, text: &str) -> String {
+        let Ok(tier) = RarityTier::from_str(text, true) else {
+            return text.to_string();
+        };
+        let name = if self.color {
+            color::paint(text, &tier)
+        } else {
+            text.to_string()
+        };
+        match self.emoji {
+            Some(symbols) => format!("{} {name}", emoji::symbol(&tier, symbols, self.ascii)),
+            None => name,
+        }
+    }
+
+    /// Whether the Datetime column is shown otherwise than as it is.
+    fn dates_changed(&self) -> bool {
+        self.now.is_some() || self.date_format.is_some() || self.zone.is_some()
+    }
+
+    /// A datetime in the Datetime column, as how long before the scan it was
+    /// with `--relative-dates`, or in the zone and format asked for, or
+    /// `text` as it is when it isn't one.
+    fn datetime(&self, text: &str) -> String {
+        let Ok(datetime) = DateTime::parse_from_str(text, DATETIME_FORMAT) else {
+            return text.to_string();
+        };
+        if let Some(now) = self.now {
+            return output::display_relative(&datetime, &now);
+        }
+        let datetime = match self.zone {
+            Some(Zone::Utc) => datetime.with_timezone(&Utc).fixed_offset(),
+            Some(Zone::Local) => datetime.with_timezone(&Local).fixed_offset(),
+            None => datetime,
+        };
+        datetime
+            .format(self.date_format.unwrap_or(DATETIME_FORMAT))
+            .to_string()
+    }
+
+    /// `hash`, abbreviated to the length `abbreviations` has for it, with
+    /// the characters the matches of `rarity` depend on colored when the
+    /// table is.
+    fn hash(&self, hash: &str, rarity: &Rarity, abbreviations: &HashMap<&str, usize>) -> String {
+        let hash = &hash[..abbreviations.get(hash).copied().unwrap_or(hash.len())];
+        if self.color {
+            color::highlight(hash, &rarity.spans(), &rarity.tier)
+        } else {
+            hash.to_string()
+        }
+    }
+}
+
+/// Prints the rows of commits like [`print_rows`], followed by the columns
+/// `labels` asks for.
+fn print_labelled<T: Tabled>(
+    rows: Vec<(String, Commit, T)>,
+    tagged: bool,
+    labels: Labels,
+    print: PrintOptions,
+) -> Result<()> {
+    match (labels.reflog, labels.stashes) {
+        (false, false) => {
+            let rows = rows.into_iter().map(|(repo, _, row)| (repo, row)).collect();
+            print_rows(rows, tagged, print)
+        }
+        (true, false) => {
+            let rows = rows
+                .into_iter()
+                .map(|(repo, commit, row)| (repo, ReflogRow::new(row, &commit)))
+                .collect();
+            print_rows(rows, tagged, print)
+        }
+        (false, true) => {
+            let rows = rows
+                .into_iter()
+                .map(|(repo, commit, row)| (repo, StashRow::new(row, &commit)))
+                .collect();
+            print_rows(rows, tagged, print)
+        }
+        (true, true) => {
+            let rows = rows
+                .into_iter()
+                .map(|(repo, commit, row)| {
+                    let row = StashRow::new(ReflogRow::new(row, &commit), &commit);
+                    (repo, row)
+                })
+                .collect();
+            print_rows(rows, tagged, print)
+        }
+    }
+}
+
+/// Prints `rows`, with their repository in a first column when `tagged`.
+fn print_rows<T: Tabled>(rows: Vec<(String, T)>, tagged: bool, print: PrintOptions) -> Result<()> {
+    if tagged {
+        let rows = rows
+            .into_iter()
+            .map(|(repo, row)| RepoRow { repo, row })
+            .collect();
+        print_table(&rows, print)
+    } else {
+        let rows = rows.into_iter().map(|(_, row)| row).collect();
+        print_table(&rows, print)
+    }
+}
+
+fn print_table<T>(commits: &Vec<T>, print: PrintOptions) -> Result<()>
+where
+    T: Tabled,
+{
+    let header = T::headers();
+    let names = header
+        .iter()
+        .map(|name| name.as_ref())
+        .collect::<Vec<&str>>();
+    let order = match print.columns {
+        Some(columns) => output::column_order(&names, columns),
+        // The odds are shown instead of the percentage they're made from.
+        None => (0..names.len())
+            .filter(|&i| names[i] != "Percentage")
+            .filter(|&i| names[i] != "Subject" || print.subject.is_some())
+            .filter(|&i| names[i] != "Email" || print.email)
+            .collect(),
+    };
+    // Signatures are only read when they're shown.
+    let order = order
+        .into_iter()
+        .filter(|&i| names[i] != "Signature" || print.signatures)
+        .collect::<Vec<usize>>();
+    let mut builder = Builder::default();
+    builder.push_record(order.iter().map(|&i| header[i].to_string()));
+    for commit in commits {
+        let fields = commit.fields();
+        builder.push_record(order.iter().map(|&i| fields[i].to_string()));
+    }
+    let mut table = builder.build();
+    if print.color || print.emoji.is_some() {
+        table.modify(
+            ByColumnName::new("Tier"),
+            tabled::settings::Format::content(move |tier| print.tier(tier)),
+        );
+    }
+    if let Some(width) = print.subject {
+        table.modify(
+            ByColumnName::new("Subject"),
+            tabled::settings::Format::content(move |subject| truncate(subject, width)),
+        );
+    }
+    if print.dates_changed() {
+        table.modify(
+            ByColumnName::new("Datetime"),
+            tabled::settings::Format::content(move |datetime| print.datetime(datetime)),
+        );
+    }
+    if print.format == Format::Markdown {
+        table.with(Style::markdown()).modify(
+            Segment::all(),
+            tabled::settings::Format::content(output::escape_markdown),
+        );
+        print.output.println(table);
+        return Ok(());
+    }
+    print.style.apply(&mut table);
+    if let Some(max_width) = print.width {
+        let names = order.iter().map(|&i| names[i]).collect::<Vec<&str>>();
+        width::fit(&mut table, &names, max_width);
+    }
+    print.output.println(table);
+    if print.timed {
+        print_duration(&print);
+    }
+    Ok(())
+}
+
+/// Notes how long git-rare took, after the last table of [`Format::Table`],
+/// for [`run`] to print once the table is shown.
+fn print_duration(print: &PrintOptions) {
+    // It's meant to be pasted somewhere, so the time isn't printed.
+    if print.format == Format::Markdown {
+        return;
+    }
+    print.timings.stop();
+}
+
+fn main() -> Result<()> {
+    let start_time = Instant::now();
+    let mut args = CliArgs::parse();
+    if args.plain {
+        args.format = Format::Plain;
+    }
+    let verbose = args.verbose;
+    run(args, Arc::new(Timings::new(start_time))).inspect_err(|e| {
+        if let Some(not_a_repository) = e.chain().find_map(|e| e.downcast_ref::<NotARepository>()) {
+            eprintln!("Error: {not_a_repository}");
+            if verbose {
+                eprintln!("{}", not_a_repository.detail);
+            }
+            std::process::exit(2);
+        }
+    })
+}
+
+fn run(args: CliArgs, timings: Arc<Timings>) -> Result<()> {
+    if args.header && args.format != Format::Plain {
+        bail!("--header is for --plain, whose lines have no header otherwise");
+    }
+    if args.output.is_some() && args.command.is_some() {
+        bail!("--output writes what a scan finds, which subcommands don't print");
+    }
+    // Like git, only what's meant to be read is paged, and only in a
+    // terminal.
+    let pager = if args.format.is_table()
+        && args.output.is_none()
+        && !args.no_pager
+        && std::io::stdout().is_terminal()
+    {
+        pager::command()
+    } else {
+        None
+    };
+    let output = Arc::new(Output::new(args.output.as_deref(), pager)?);
+    let (quiet, report) = (args.quiet, args.timings);
+    scan(args, &timings, &output)?;
+    // Measured before the pager, which takes as long as it's read.
+    let report = report.then(|| timings.report());
+    output.finish()?;
+    // On stderr after the table, so it's in neither what's piped nor a
+    // pager.
+    if let Some(took) = timings.took().filter(|_| !quiet) {
+        eprintln!("This operation took {took:?}");
+    }
+    if let Some(report) = report {
+        eprint!("{report}");
+    }
+    Ok(())
+}
+
+/// Runs the subcommand or scan `args` asks for, printing what's found to
+/// `output`.
+fn scan(args: CliArgs, timings: &Arc<Timings>, output: &Arc<Output>) -> Result<()> {
+    if let Some(git_dir) = &args.git_dir {
+        // Both git and libgit2 read it, and nothing else runs yet.
+        std::env::set_var("GIT_DIR", resolve_git_dir(git_dir)?);
+    }
+    match &args.command {
+        Some(Command::Cache {
+            action: CacheAction::Clear,
+        }) => {
+            let mut repos = args.repo.clone();
+            if let Some(path) = &args.repos_file {
+                repos.extend(read_repos_file(path)?);
+            }
+            return clear_cache(args.backend, &repos);
+        }
+        Some(Command::Hook { action }) => {
+            return run_hook(args.repo.first().map(PathBuf::as_path), action);
+        }
+        _ => {}
+    }
+    let config = config::Config::load(args.config.as_deref())?;
+    let print = PrintOptions::new(&args, timings, output, &config.emoji, config.style);
+    let mut revisions = args.walk();
+    let log = args.log_options()?;
+    let options = ClassifyOptions {
+        registry: rule_registry(&args.disabled_rules),
+        min_run: args.min_run.or(config.min_run).unwrap_or(DEFAULT_MIN_RUN),
+        rules: config.rules.into_iter().chain(args.rules.clone()).collect(),
+        lucky_numbers: config.lucky_numbers,
+        overrides: config.overrides,
+        patterns: args
+            .patterns_file
+            .as_deref()
+            .map(PatternSet::load)
+            .transpose()?,
+        #[cfg(feature = "plugins")]
+        plugins: args
+            .plugins
+            .iter()
+            .map(|path| plugin::Plugin::load(path))
+            .collect::<Result<_>>()?,
+        memes: args.memes,
+        leet: args.leet,
+        short_hash: args
+            .short_hash_rules
+            .then(|| args.abbrev.unwrap_or(DEFAULT_ABBREV)),
+        whole_history: revisions.is_empty()
+            && args.since_tag.is_none()
+            && log.is_unfiltered()
+            && !args.stdin,
+        cache: !args.no_cache,
+    };
+    if let Some(Command::Check {
+        revision,
+        quiet_common,
+    }) = &args.command
+    {
+        let repo = args.repo.first().map(PathBuf::as_path);
+        let commit = check(args.backend, repo, revision, &log, &options, timings)?;
+        if commit.rarity.tier != RarityTier::Common || !quiet_common {
+            println!("{}", announcement(&commit));
+        }
+        return Ok(());
+    }
+    if let Some(Command::PostCommit { amend_trailer }) = &args.command {
+        let repo = args.repo.first().map(PathBuf::as_path);
+        return post_commit(args.backend, repo, *amend_trailer, &log, &options, timings);
+    }
+    let mut repos = args.repo.clone();
+    if let Some(path) = &args.repos_file {
+        repos.extend(read_repos_file(path)?);
+    }
+    if args.recurse_submodules {
+        repos = with_submodules(args.backend, &repos)?;
+    }
+    // The tag of each of several repositories is found as it's scanned.
+    let since_tag = args.since_tag.as_ref().map(Option::as_deref);
+    if let Some(tag) = since_tag.filter(|_| repos.len() <= 1) {
+        let repo = repos.first().map(PathBuf::as_path);
+        revisions.push(since_tag_range(args.backend, repo, tag)?);
+    }
+    if let Some(Command::Annotate { tags, dry_run }) = &args.command {
+        #[cfg(feature = "github")]
+        let github = args.github.is_some();
+        #[cfg(not(feature = "github"))]
+        let github = false;
+        if args.objects || args.stdin || args.lost || github {
+            bail!("annotate writes to the repository it scans the history of");
+        }
+        if repos.len() > 1 {
+            bail!("annotate scans one repository at a time");
+        }
+        if args.only == Some(RarityTier::Common) {
+            bail!("annotate only writes to the commits that aren't Common");
+        }
+        let repo = repos.first().map(PathBuf::as_path);
+        let only = args.only.clone();
+        let keep = move |commit: &Commit| {
+            commit.object == ObjectKind::Commit
+                && match &only {
+                    Some(only) => commit.rarity.tier == *only,
+                    None => commit.rarity.tier != RarityTier::Common,
+                }
+        };
+        let extras = Extras {
+            tags: false,
+            stashes: args.stashes,
+            signatures: args.show_signatures,
+        };
+        let scan = read_commits(
+            args.backend,
+            repo,
+            &revisions,
+            &log,
+            extras,
+            &options,
+            &keep,
+            timings,
+        )?;
+        let abbrev = args.abbrev.unwrap_or(DEFAULT_ABBREV);
+        return annotate(args.backend, repo, &scan.commits, *tags, abbrev, *dry_run);
+    }
+    if args.objects {
+        if repos.len() > 1 {
+            bail!("--objects scans one repository at a time");
+        }
+        let repo = repos.first().map(PathBuf::as_path);
+        let keep = |rarity: &Rarity| match &args.only {
+            Some(only) => rarity.tier == *only,
+            None => rarity.tier != RarityTier::Common,
+        };
+        let mut found = scan_objects(args.backend, repo, args.max_count, &options, keep)?;
+        let _rendering = timings.measure(Phase::Rendering);
+        args.sort(&mut found);
+        if found.is_empty() && args.format.is_table() {
+            println!("No matching objects found.");
+            return Ok(());
+        }
+        if !args.format.is_table() {
+            return output::print_objects(&args, &found, &options, output);
+        }
+        let hashes = found
+            .iter()
+            .map(|o| o.hash.clone())
+            .collect::<Vec<String>>();
+        let abbreviations = print.abbreviations(hashes.iter().map(String::as_str));
+        for object in &mut found {
+            object.hash = print.hash(&object.hash, &object.rarity, &abbreviations);
+        }
+        return print_sections(found, "object", print, |found, print| {
+            print_table(&found, print)
+        });
+    }
+    if args.stdin {
+        let (mut lines, invalid) = timings.time(Phase::Classification, || {
+            read_hashes(std::io::stdin().lock(), &options)
+        })?;
+        timings.count(lines.len());
+        if !log.authors.is_empty() {
+            // Only the author's name is read with a hash.
+            lines.retain(|line| {
+                matches!(line, HashLine::Commit(commit) if log.matches_author(&commit.author))
+            });
+        }
+        if let Some(max_count) = log.max_count {
+            lines.truncate(max_count);
+        }
+        if lines.iter().all(|line| matches!(line, HashLine::Commit(_))) {
+            let commits = lines
+                .into_iter()
+                .filter_map(|line| match line {
+                    HashLine::Commit(commit) => Some(commit),
+                    HashLine::Hash(_) => None,
+                })
+                .collect();
+            let scan = Scan::from_commits(commits);
+            print_scans(&args, vec![scan], &options, print)?;
+        } else {
+            let hashes = lines
+                .into_iter()
+                .map(|line| match line {
+                    HashLine::Commit(commit) => commit.into(),
+                    HashLine::Hash(row) => row,
+                })
+                .collect();
+            print_hashes(&args, hashes, &options, print)?;
+        }
+        if invalid > 0 {
+            eprintln!("warning: skipped {invalid} lines that didn't start with a full hash");
+        }
+        return Ok(());
+    }
+    if args.verbose {
+        if let Some(window) = describe_dates(&log.dates) {
+            let dated = if log.author_dates {
+                "authored"
+            } else {
+                "committed"
+            };
+            eprintln!("Scanning commits {dated} {window}");
+        }
+        if !log.authors.is_empty() {
+            let authors = log.authors.iter().map(|regex| regex.as_str());
+            eprintln!(
+                "Scanning commits by authors matching {}",
+                authors.collect::<Vec<&str>>().join(" or ")
+            );
+        }
+        if !log.greps.is_empty() {
+            let greps = log.greps.iter().map(|regex| regex.as_str());
+            let greps =
+                greps
+                    .collect::<Vec<&str>>()
+                    .join(if log.all_match { " and " } else { " or " });
+            let matching = if log.invert_grep {
+                "don't match"
+            } else {
+                "match"
+            };
+            eprintln!("Scanning commits whose messages {matching} {greps}");
+        }
+        if !log.paths.is_empty() {
+            eprintln!("Scanning commits that modify {}", log.paths.join(" or "));
+        }
+    }
+    let keep = args.keep();
+    // With `--format ndjson`, each commit kept is printed as soon as it's
+    // classified, in the order it was read, instead of being kept.
+    let streams = output::streams(&args, repos.len());
+    let labels = args.labels();
+    let objects = args.tags;
+    let stream = Arc::clone(output);
+    let keep = move |commit: &Commit| {
+        let kept = keep(commit);
+        if kept && streams {
+            output::stream(&stream, commit, objects, labels);
+            return false;
+        }
+        kept
+    };
+    #[cfg(feature = "github")]
+    if let Some(repo) = &args.github {
+        let scan = scan_github(repo, &log, &options, &keep, timings)?;
+        return print_scans(&args, vec![scan], &options, print);
+    }
+    if args.lost {
+        if repos.len() > 1 {
+            bail!("--lost scans one repository at a time");
+        }
+        let repo = repos.first().map(PathBuf::as_path);
+        let scan = scan_lost(args.backend, repo, &log, &options, &keep, timings)?;
+        if scan.count.total == 0 && args.format.is_table() {
+            println!("No lost commits found.");
+            return Ok(());
+        }
+        if args.rescue {
+            rescue(args.backend, repo, &scan)?;
+        }
+        let shown = !scan.commits.is_empty();
+        print_scans(&args, vec![scan], &options, print)?;
+        if shown && !args.rescue {
+            eprintln!(
+                "note: these commits are unreachable, so `git gc` will delete them once they \
+                 expire; pass --rescue to keep them"
+            );
+        }
+        return Ok(());
+    }
+    let extras = Extras {
+        tags: args.tags,
+        stashes: args.stashes,
+        signatures: args.show_signatures,
+    };
+    let scans = if repos.len() > 1 {
+        scan_repos(
+            args.backend,
+            &repos,
+            &revisions,
+            since_tag,
+            &log,
+            extras,
+            &options,
+            &keep,
+            timings,
+        )?
+    } else {
+        let repo = repos.first().map(PathBuf::as_path);
+        vec![read_commits(
+            args.backend,
+            repo,
+            &revisions,
+            &log,
+            extras,
+            &options,
+            &keep,
+            timings,
+        )?]
+    };
+    check_history(&scans, args.require_full_history)?;
+    if args.verbose {
+        for scan in &scans {
+            let Some(cached) = scan.cached else {
+                continue;
+            };
+            let repo = match scan.repo.as_str() {
+                "" => String::new(),
+                repo => format!("{repo}: "),
+            };
+            let commits = if cached.classified == 1 {
+                "commit"
+            } else {
+                "commits"
+            };
+            eprintln!(
+                "{repo}Classified {} {commits} and reused {} cached classifications",
+                cached.classified, cached.reused
+            );
+        }
+    }
+    print_scans(&args, scans, &options, print)
+}
+
+/// The directory `--git-dir` names: `path` itself, or the one a `.git` file
+/// there points to, as a linked worktree or submodule has in place of a
+/// `.git` directory.
+fn resolve_git_dir(path: &Path) -> Result<PathBuf> {
+    if path.is_dir() {
+        return Ok(path.to_path_buf());
+    }
+    let missing = || anyhow::anyhow!("'{}' does not exist or is not a directory", path.display());
+    if !path.is_file() {
+        return Err(missing());
+    }
+    let contents = std::fs::read_to_string(path)
+        .with_context(|| format!("failed to read {}", path.display()))?;
+    let target = contents
+        .trim_end()
+        .strip_prefix("gitdir: ")
+        .ok_or_else(missing)?;
+    // A relative path is relative to the file's directory.
+    let git_dir = path.parent().unwrap_or(Path::new("")).join(target);
+    if !git_dir.is_dir() {
+        bail!(
+            "'{}' points to '{}', which does not exist",
+            path.display(),
+            git_dir.display()
+        );
+    }
+    Ok(git_dir)
+}
+
+/// The range of the commits since the tag `--since-tag` names in `repo`, or
+/// the current directory, noting which tag that is. The range is of the tag's
+/// ref, so a branch of the same name isn't taken for it.
+fn since_tag_range(backend: Backend, repo: Option<&Path>, tag: Option<&str>) -> Result<String> {
+    let tag = since_tag(backend.for_repo(repo), repo, tag)?;
+    match repo {
+        Some(repo) => eprintln!("Scanning commits since {tag} in {}", repo.display()),
+        None => eprintln!("Scanning commits since {tag}"),
+    }
+    Ok(format!("refs/tags/{tag}..HEAD"))
+}
+
+/// The tag `--since-tag` scans the commits since: `tag`, checked to exist,
+/// or the most recent one reachable from HEAD.
+fn since_tag(backend: Backend, repo: Option<&Path>, tag: Option<&str>) -> Result<String> {
+    let Some(tag) = tag else {
+        let latest = match backend {
+            Backend::Git => backend::git_latest_tag(repo)?,
+            #[cfg(feature = "libgit2")]
+            Backend::Libgit2 => backend::libgit2_latest_tag(repo)?,
+        };
+        return latest.context(
+            "--since-tag found no tag reachable from HEAD; pass one with --since-tag TAG, \
+             or a range like `main~20..HEAD`",
+        );
+    };
+    let exists = match backend {
+        Backend::Git => backend::git_has_tag(repo, tag)?,
+        #[cfg(feature = "libgit2")]
+        Backend::Libgit2 => backend::libgit2_has_tag(repo, tag)?,
+    };
+    if !exists {
+        bail!("tag '{tag}' doesn't exist; `git tag --list` shows the tags there are");
+    }
+    Ok(tag.to_string())
+}
+
+/// Warns about the scans of shallow clones, whose numbers only cover some of
+/// their history, or fails for them with `require_full_history`.
+fn check_history(scans: &[Scan], require_full_history: bool) -> Result<()> {
+    for scan in scans.iter().filter(|scan| scan.shallow) {
+        let clone = match scan.repo.as_str() {
+            "" => "shallow clone".to_string(),
+            repo => format!("{repo} is a shallow clone"),
+        };
+        if require_full_history {
+            bail!("{clone}, and --require-full-history was given; run `git fetch --unshallow`");
+        }
+        eprintln!(
+            "warning: {clone} — only {} of unknown total commits scanned; \
+             run `git fetch --unshallow` for full results",
+            scan.count.total
+        );
+    }
+    Ok(())
+}
+
+/// Prints the commits read from the repositories as the flags ask: all of
+/// them, one tier, the near misses, the counts, or those that aren't Common.
+fn print_scans(
+    args: &CliArgs,
+    scans: Vec<Scan>,
+    options: &ClassifyOptions,
+    print: PrintOptions,
+) -> Result<()> {
+    let _rendering = print.timings.measure(Phase::Rendering);
+    if !args.format.is_table() {
+        return output::print_scans(args, &scans, options, print.output);
+    }
+    if scans.iter().all(|scan| scan.count.total == 0) {
+        println!("No commits found.");
+        return Ok(());
+    }
+    let tagged = scans.len() > 1;
+    let labels = args.labels();
+    // Markdown links each hash to its commit on the forge the repository's
+    // origin is on, when it's one git-rare knows.
+    let commit_urls = scans
+        .iter()
+        .map(|scan| match print.format {
+            Format::Markdown => output::commit_url(args.backend, scan.path.as_deref()),
+            _ => None,
+        })
+        .collect::<Vec<Option<String>>>();
+    let link = |repo: &str, hash: &str, abbreviations: &HashMap<&str, usize>| {
+        let url = scans
+            .iter()
+            .zip(&commit_urls)
+            .find_map(|(scan, url)| (scan.repo == repo).then_some(url.as_deref()))
+            .flatten();
+        output::link_hash(hash, abbreviations.get(hash).copied(), url)
+    };
+    let print_groups = |rows, print| print_commits(rows, tagged, args.tags, labels, print);
+    let commits = |keep: &dyn Fn(&Commit) -> bool| {
+        let kept = scans
+            .iter()
+            .flat_map(|scan| scan.commits.iter().filter(|c| keep(c)));
+        let abbreviations = print.abbreviations(kept.map(|c| c.hash.as_str()));
+        let mut commits = scans
+            .iter()
+            .flat_map(|scan| {
+                scan.commits.iter().filter(|c| keep(c)).map(|c| {
+                    let hash = match print.format {
+                        Format::Markdown => link(&scan.repo, &c.hash, &abbreviations),
+                        _ => print.hash(&c.hash, &c.rarity, &abbreviations),
+                    };
+                    (scan.repo.clone(), Commit { hash, ..c.clone() })
+                })
+            })
+            .collect::<Vec<(String, Commit)>>();
+        args.sort(&mut commits);
+        commits
+    };
+
+    if args.all && args.only.is_none() {
+        print_sections(commits(&|_| true), "commit", print, print_groups)
+    } else if let Some(only) = &args.only {
+        let only_commits = commits(&|c| c.rarity.tier == *only);
+        if only_commits.is_empty() {
+            println!("No {} commits found.", only);
+            return Ok(());
+        }
+        print_sections(only_commits, "commit", print, print_groups)
+    } else if args.near_misses {
+        let found = near_misses(&scans, options.min_run, args.limit);
+        let hashes = found
+            .iter()
+            .map(|(_, near_miss)| near_miss.hash.clone())
+            .collect::<Vec<String>>();
+        let abbreviations = print.abbreviations(hashes.iter().map(String::as_str));
+        let near_misses = found
+            .into_iter()
+            .map(|(repo, near_miss)| {
+                let hash = link(&repo, &near_miss.hash, &abbreviations);
+                (repo, NearMiss { hash, ..near_miss })
+            })
+            .collect::<Vec<(String, NearMiss)>>();
+        if near_misses.is_empty() {
+            println!("No near misses found.");
+            return Ok(());
+        }
+        print_rows(near_misses, tagged, print)
+    } else if args.count {
+        let mut counts = scans
+            .iter()
+            .map(|scan| (scan.repo.clone(), scan.count.clone()))
+            .collect::<Vec<(String, Count)>>();
+        if tagged {
+            let per_repo = counts
+                .iter()
+                .map(|(_, count)| count.clone())
+                .collect::<Vec<Count>>();
+            counts.push(("All repositories".to_string(), Count::total(&per_repo)));
+        }
+        print_rows(counts, tagged, print)
+    } else {
+        let not_common_commits = commits(&|c| c.rarity.tier != RarityTier::Common);
+        if not_common_commits.is_empty() {
+            println!("No uncommon or rare commits found.");
+            return Ok(());
+        }
+        print_sections(not_common_commits, "commit", print, print_groups)
+    }
+}
+
+/// The `limit` common commits of `scans` that came closest to a rule, closest
+/// first, with the repository each is from.
+fn near_misses(scans: &[Scan], min_run: usize, limit: usize) -> Vec<(String, NearMiss)> {
+    let mut near_misses = scans
+        .iter()
+        .flat_map(|scan| {
+            NearMiss::from_commits(&scan.commits, min_run, limit)
+                .into_iter()
+                .map(|near_miss| (scan.repo.clone(), near_miss))
+        })
+        .collect::<Vec<(String, NearMiss)>>();
+    near_misses.sort_by(|(_, a), (_, b)| b.closeness.total_cmp(&a.closeness));
+    near_misses.truncate(limit);
+    near_misses
+}
+
+/// Prints hashes read with `--stdin` like [`print_scans`], for input without
+/// the author and date of each commit.
+fn print_hashes(
+    args: &CliArgs,
+    hashes: Vec<HashRow>,
+    options: &ClassifyOptions,
+    print: PrintOptions,
+) -> Result<()> {
+    let _rendering = print.timings.measure(Phase::Rendering);
+    if hashes.is_empty() && args.format.is_table() {
+        println!("No commits found.");
+        return Ok(());
+    }
+    if args.near_misses {
+        bail!("--near-misses needs the date and author of each commit after its hash");
+    }
+    if !args.format.is_table() {
+        return output::print_hashes(args, &hashes, options, print.output);
+    }
+    if args.count {
+        let rarities = hashes.iter().map(|h| &h.rarity).collect::<Vec<&Rarity>>();
+        return print_table(&vec![Count::from_rarities(&rarities)], print);
+    }
+    let mut hashes = match &args.only {
+        Some(only) => hashes
+            .into_iter()
+            .filter(|h| h.rarity.tier == *only)
+            .collect(),
+        None if args.all => hashes,
+        None => hashes
+            .into_iter()
+            .filter(|h| h.rarity.tier != RarityTier::Common)
+            .collect::<Vec<HashRow>>(),
+    };
+    if hashes.is_empty() {
+        match &args.only {
+            Some(only) => println!("No {} commits found.", only),
+            None => println!("No uncommon or rare commits found."),
+        }
+        return Ok(());
+    }
+    args.sort(&mut hashes);
+    let full = hashes
+        .iter()
+        .map(|h| h.hash.clone())
+        .collect::<Vec<String>>();
+    let abbreviations = print.abbreviations(full.iter().map(String::as_str));
+    let hashes = hashes
+        .into_iter()
+        .map(|h| HashRow {
+            hash: print.hash(&h.hash, &h.rarity, &abbreviations),
+            ..h
+        })
+        .collect::<Vec<HashRow>>();
+    print_sections(hashes, "commit", print, |hashes, print| {
+        print_table(&hashes, print)
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use crate::*;
+
+    #[test]
+    fn signatures_are_read_by_hash() {
+        let output = ["G", "U", "B", "X", "Y", "R", "E", "N", "?"]
+            .iter()
+            .enumerate()
+            .map(|(i, letter)| format!("{i:040x}\0{letter}\n"))
+            .collect::<String>();
+        let signatures = parse_signatures(&output);
+        let shown = (0..9)
+            .map(|i| display_signature(&signatures.get(&format!("{i:040x}")).copied()))
+            .collect::<Vec<String>>();
+        assert_eq!(
+            shown,
+            [
+                "signed",
+                "signed, untrusted",
+                "bad",
+                "expired",
+                "expired key",
+                "revoked key",
+                "unverifiable",
+                "unsigned",
+                "unknown"
+            ]
+        );
+        assert_eq!(display_signature(&None), "");
+        // Lines without a letter, like of a commit git couldn't read, are
+        // left out.
+        assert!(parse_signatures("abc\0\n\n").is_empty());
+    }
+
+    #[test]
+    fn subjects_are_truncated_with_an_ellipsis() {
+        assert_eq!(truncate("Fix it", 6), "Fix it");
+        assert_eq!(truncate("Fix it now", 6), "Fix i…");
+        assert_eq!(truncate("Fix it now", 5), "Fix…");
+        assert_eq!(truncate("Größere Änderung", 7), "Größer…");
+        assert_eq!(truncate("", 2), "");
+    }
+
+    #[test]
+    fn probabilities_are_shown_without_a_products_error() {
+        assert_eq!(display_probability(0.000244140625), "0.000244140625");
+        assert_eq!(display_probability(0.3 * 0.1), "0.03");
+        assert_eq!(
+            display_probability(0.00000005931899999999999),
+            "0.000000059319"
+        );
+        assert_eq!(display_probability(1.0), "1");
+        assert_eq!(display_probability(0.0), "0");
+    }
+
+    /// A hash with no notable patterns, used to pad synthetic hashes.
+    const FILLER: &str = "7e3a91c5d48b2f371a9e73cb5d284e6f10a9c3b7";
+
+    /// Builds a full-length hash starting with `prefix`.
+    fn with_prefix(prefix: &str) -> String {
+        format!("{prefix}{}", &FILLER[prefix.len()..])
+    }
+
+    /// Classifies `hash` as if it were committed on 2001-02-03.
+    fn classify(hash: &str) -> Rarity {
+        classify_on(hash, "2001-02-03T04:05:06+00:00")
+    }
+
+    fn classify_on(hash: &str, datetime: &str) -> Rarity {
+        Commit::get_rarity(
+            &CommitContext {
+                hash,
+                datetime: Some(&DateTime::parse_from_rfc3339(datetime).unwrap()),
+                author: "Jo",
+                message: "",
+                ordinal: 1,
+            },
+            &ClassifyOptions::default(),
+        )
+    }
+
+    fn has_run(class: CharClass, position: RunPosition, hash: &str) -> bool {
+        RunRule::new(class, position, DEFAULT_MIN_RUN)
+            .rule_match(hash)
+            .is_some()
+    }
+
+    #[test]
+    fn verify_app() {
         use clap::CommandFactory;
         CliArgs::command().debug_assert()
     }
+
+    #[test]
+    fn nine_digit_run_at_start() {
+        let hash = "123456789abcdefabcdefabcdefabcdefabcdefa";
+        assert_eq!(hash.len(), HASH_LEN);
+        assert!(has_run(CharClass::Digit, RunPosition::Anywhere, hash));
+    }
+
+    #[test]
+    fn nine_digit_run_in_middle() {
+        let hash = "abcdefabcdefabc123456789bcdefabcdefabcde";
+        assert_eq!(hash.len(), HASH_LEN);
+        assert!(has_run(CharClass::Digit, RunPosition::Anywhere, hash));
+    }
+
+    #[test]
+    fn nine_digit_run_at_end() {
+        let hash = "abcdefabcdefabcdefabcdefabcdefa987654321";
+        assert_eq!(hash.len(), HASH_LEN);
+        assert!(has_run(CharClass::Digit, RunPosition::Anywhere, hash));
+    }
+
+    #[test]
+    fn eight_digit_run_does_not_match() {
+        let hash = "abcdef12345678abcdef12345678abcdef123456";
+        assert_eq!(hash.len(), HASH_LEN);
+        assert!(!has_run(CharClass::Digit, RunPosition::Anywhere, hash));
+    }
+
+    #[test]
+    fn long_digit_run_matches() {
+        let hash = "abcdef1234567890123456abcdefabcdefabcdef";
+        assert_eq!(hash.len(), HASH_LEN);
+        assert_eq!(longest_run_span(hash, |c| c.is_ascii_digit()).len(), 16);
+        assert!(has_run(CharClass::Digit, RunPosition::Anywhere, hash));
+    }
+
+    #[test]
+    fn eight_letter_run_does_not_match() {
+        let hash = "abcdefab12345678fedcbafe1234567812abcdef";
+        assert_eq!(hash.len(), HASH_LEN);
+        assert!(!has_run(CharClass::Letter, RunPosition::Anywhere, hash));
+    }
+
+    #[test]
+    fn nine_letter_run_at_start() {
+        let hash = "abcdefabc1234567890123456789012345678901";
+        assert_eq!(hash.len(), HASH_LEN);
+        assert!(has_run(CharClass::Letter, RunPosition::Anywhere, hash));
+    }
+
+    #[test]
+    fn nine_letter_run_in_middle() {
+        let hash = "1234567890123456fedcbafed123456789012345";
+        assert_eq!(hash.len(), HASH_LEN);
+        assert!(has_run(CharClass::Letter, RunPosition::Anywhere, hash));
+    }
+
+    #[test]
+    fn nine_letter_run_at_end() {
+        let hash = "1234567890123456789012345678901cafebabee";
+        assert_eq!(hash.len(), HASH_LEN);
+        assert!(has_run(CharClass::Letter, RunPosition::Anywhere, hash));
+    }
+
+    #[test]
+    fn fifteen_letter_run_matches() {
+        let hash = "1234567890abcdefabcdefabc123456789012345";
+        assert_eq!(hash.len(), HASH_LEN);
+        assert_eq!(
+            longest_run_span(hash, |c| c.is_ascii_alphabetic()).len(),
+            15
+        );
+        assert!(has_run(CharClass::Letter, RunPosition::Anywhere, hash));
+    }
+
+    #[test]
+    fn all_digit_hash_is_legendary() {
+        let hash = "1234567890123456789012345678901234567890";
+        let rarity = classify(hash);
+        assert!(rarity.tier == RarityTier::Legendary);
+        assert_eq!(
+            rarity.matches[0].explanation,
+            format!("{}: '{hash}' at offset 0", LegendaryExpl::AllDigits)
+        );
+    }
+
+    #[test]
+    fn all_letter_hash_is_legendary() {
+        let hash = "abcdefabcdefabcdefabcdefabcdefabcdefabcd";
+        let rarity = classify(hash);
+        assert!(rarity.tier == RarityTier::Legendary);
+        assert_eq!(
+            rarity.matches[0].explanation,
+            format!("{}: '{hash}' at offset 0", LegendaryExpl::AllLetters)
+        );
+    }
+
+    #[test]
+    fn legendary_wins_over_uncommon() {
+        let hash = "9999999990123456789012345678901234567890";
+        assert!(has_run(CharClass::Digit, RunPosition::Start, hash));
+        assert!(classify(hash).tier == RarityTier::Legendary);
+    }
+
+    #[test]
+    fn only_accepts_legendary() {
+        let args = CliArgs::try_parse_from(["git-rare", "--only", "legendary"]).unwrap();
+        assert!(args.only == Some(RarityTier::Legendary));
+    }
+
+    #[test]
+    fn leading_zeros_below_six_are_ignored() {
+        let hash = with_prefix("00000");
+        assert!(LeadingZerosExpl::from_hash(&hash).tier().is_none());
+        assert!(classify(&hash).tier == RarityTier::Common);
+    }
+
+    #[test]
+    fn leading_zeros_are_graded() {
+        let cases = [
+            (6, RarityTier::Uncommon),
+            (7, RarityTier::Uncommon),
+            (8, RarityTier::Rare),
+            (10, RarityTier::Legendary),
+        ];
+        for (zeros, tier) in cases {
+            let hash = with_prefix(&"0".repeat(zeros));
+            let rarity = classify(&hash);
+            assert!(rarity.tier == tier, "{hash}");
+            assert_eq!(
+                rarity.matches[0].explanation,
+                format!(
+                    "Starts with {zeros} zeros: '{}' at offset 0",
+                    "0".repeat(zeros)
+                )
+            );
+            assert_eq!(rarity.percentage, Some(probability::exact(zeros)));
+        }
+    }
+
+    #[test]
+    fn nine_leading_zeros_beat_nine_leading_digits() {
+        let hash = with_prefix("000000000");
+        assert!(has_run(CharClass::Digit, RunPosition::Start, &hash));
+        let rarity = classify(&hash);
+        assert!(rarity.tier == RarityTier::Rare);
+        assert_eq!(
+            rarity.matches[0].explanation,
+            "Starts with 9 zeros: '000000000' at offset 0"
+        );
+    }
+
+    #[test]
+    fn all_digit_hash_with_leading_zeros_is_legendary() {
+        let hash = "0000001234567890123456789012345678901234";
+        assert!(classify(hash).tier == RarityTier::Legendary);
+    }
+
+    #[test]
+    fn short_repeated_runs_are_ignored() {
+        let hash = with_prefix("3e333333");
+        let repeated = RepeatedCharExpl::from_hash(&hash);
+        assert_eq!((repeated.character, repeated.length), ('3', 6));
+        assert!(repeated.tier().is_none());
+    }
+
+    #[test]
+    fn repeated_run_spanning_prefix_boundary() {
+        // Neither the first nor the last nine characters are uniform, but
+        // the run straddles the ninth character.
+        let hash = with_prefix("7e31ffffffff");
+        let rarity = classify(&hash);
+        assert!(rarity.tier == RarityTier::Uncommon);
+        assert_eq!(
+            rarity.matches[0].explanation,
+            "Contains 8 consecutive 'f' characters: 'ffffffff' at offset 4"
+        );
+    }
+
+    #[test]
+    fn repeated_run_spanning_suffix_boundary() {
+        let hash = format!("{}333333333c5b", &FILLER[..28]);
+        let rarity = classify(&hash);
+        assert!(rarity.tier == RarityTier::Rare);
+        assert_eq!(
+            rarity.matches[0].explanation,
+            "Contains 9 consecutive '3' characters: '333333333' at offset 28"
+        );
+    }
+
+    #[test]
+    fn repeated_run_probability_depends_on_length() {
+        let seven = RepeatedCharExpl::from_hash(&with_prefix("7e3aaaaaaa")).rule_match(HASH_LEN);
+        let eight = RepeatedCharExpl::from_hash(&with_prefix("7e3aaaaaaaa")).rule_match(HASH_LEN);
+        let (seven, eight) = (seven.unwrap(), eight.unwrap());
+        assert!(eight.percentage < seven.percentage);
+        let expected = Some(probability::repeated(7, HASH_LEN));
+        assert_eq!(seven.percentage, expected);
+    }
+
+    #[test]
+    fn hexspeak_tier_scales_with_word_length() {
+        let rarity = classify(&with_prefix("7e3a91deadbeef"));
+        assert!(rarity.tier == RarityTier::Rare);
+        assert_eq!(
+            rarity.matches[0].explanation,
+            "Contains hexspeak 'deadbeef' at offset 6"
+        );
+        let rarity = classify(&with_prefix("7e3a91c0ffee"));
+        assert!(rarity.tier == RarityTier::Uncommon);
+        assert_eq!(
+            rarity.matches[0].explanation,
+            "Contains hexspeak 'c0ffee' at offset 6"
+        );
+    }
+
+    #[test]
+    fn odd_length_palindrome_prefix() {
+        let hash = with_prefix("7e3a9a3e7");
+        assert_eq!(PalindromeExpl::from_hash(&hash).length, 9);
+        let rarity = classify(&hash);
+        assert!(rarity.tier == RarityTier::Uncommon);
+        assert_eq!(
+            rarity.matches[0].explanation,
+            "First 9 characters form a palindrome: '7e3a9a3e7' at offset 0"
+        );
+        assert_eq!(rarity.percentage, Some(probability::palindrome(9)));
+    }
+
+    #[test]
+    fn even_length_palindrome_prefix() {
+        let hash = with_prefix("b7e3a91c5cc5c19a3e7b");
+        assert_eq!(PalindromeExpl::from_hash(&hash).length, 20);
+        let rarity = classify(&hash);
+        assert!(rarity.tier == RarityTier::Rare);
+        assert_eq!(
+            rarity.matches[0].explanation,
+            "First 20 characters form a palindrome: 'b7e3a91c5cc5c19a3e7b' at offset 0"
+        );
+    }
+
+    #[test]
+    fn full_palindrome_is_legendary() {
+        let half = &FILLER[..HASH_LEN / 2];
+        let hash = format!("{half}{}", half.chars().rev().collect::<String>());
+        let rarity = classify(&hash);
+        assert!(rarity.tier == RarityTier::Legendary);
+        assert_eq!(
+            rarity.matches[0].explanation,
+            "Is a palindrome: '7e3a91c5d48b2f371a9ee9a173f2b84d5c19a3e7' at offset 0"
+        );
+    }
+
+    #[test]
+    fn sha256_palindromes_span_the_whole_hash() {
+        let half = "7e3a91c5d48b2f371a9e73cb5d284e6f";
+        let hash = format!("{half}{}", half.chars().rev().collect::<String>());
+        assert_eq!(hash.len(), SHA256_HASH_LEN);
+        let rarity = classify(&hash);
+        assert!(rarity.tier == RarityTier::Legendary);
+        assert_eq!(rarity.matches[0].explanation, "Is a palindrome: '7e3a91c5d48b2f371a9e73cb5d284e6ff6e482d5bc37e9a173f2b84d5c19a3e7' at offset 0");
+
+        // A palindrome as long as a SHA-1 hash is only a prefix of this one.
+        let half = &FILLER[..HASH_LEN / 2];
+        let prefix = format!("{half}{}", half.chars().rev().collect::<String>());
+        let hash = format!("{prefix}{}", &FILLER[..SHA256_HASH_LEN - HASH_LEN]);
+        let expl = PalindromeExpl::from_hash(&hash);
+        assert_eq!(expl.length, HASH_LEN);
+        assert_eq!(expl.to_string(), "First 40 characters form a palindrome");
+        assert!(expl.tier() == Some(RarityTier::Rare));
+    }
+
+    #[test]
+    fn sha256_probabilities_use_the_longer_hash() {
+        let digits = "1234567890".repeat(7)[..SHA256_HASH_LEN].to_string();
+        let rarity = classify(&digits);
+        assert!(rarity.tier == RarityTier::Legendary);
+        let all_digits = rarity
+            .matches
+            .iter()
+            .find(|m| {
+                m.explanation
+                    .starts_with(&LegendaryExpl::AllDigits.to_string())
+            })
+            .unwrap();
+        assert_eq!(
+            all_digits.percentage,
+            Some(probability::all_of(probability::DIGIT, SHA256_HASH_LEN))
+        );
+
+        // The same word is likelier to turn up somewhere in a longer hash.
+        let sha1 = with_prefix("7e3adeadbeef");
+        let sha256 = format!("{sha1}{}", &FILLER[..SHA256_HASH_LEN - HASH_LEN]);
+        let hexspeak = |hash: &str| {
+            classify(hash)
+                .matches
+                .into_iter()
+                .find(|m| m.explanation == "Contains hexspeak 'deadbeef' at offset 4")
+                .and_then(|m| m.percentage)
+                .unwrap()
+        };
+        assert_eq!(
+            hexspeak(&sha256),
+            probability::contains("deadbeef", SHA256_HASH_LEN)
+        );
+        assert!(hexspeak(&sha256) > hexspeak(&sha1));
+    }
+
+    #[test]
+    fn full_hashes_are_sha1_or_sha256() {
+        assert!(is_full_hash(FILLER));
+        assert!(is_full_hash(&FILLER.repeat(2)[..SHA256_HASH_LEN]));
+        assert!(!is_full_hash(&FILLER.repeat(2)[..50]));
+        assert!(!is_full_hash(&FILLER[..7]));
+        assert!(!is_full_hash(&"g".repeat(HASH_LEN)));
+    }
+
+    #[test]
+    fn short_palindromes_are_ignored() {
+        assert_eq!(PalindromeExpl::from_hash(FILLER).length, 1);
+        let hash = with_prefix("77");
+        assert_eq!(PalindromeExpl::from_hash(&hash).length, 2);
+        assert!(PalindromeExpl::from_hash(&hash).tier().is_none());
+        assert!(classify(&hash).tier == RarityTier::Common);
+    }
+
+    #[test]
+    fn ascending_sequence() {
+        let hash = with_prefix("7e3a0123456789");
+        let rarity = classify(&hash);
+        assert!(rarity.tier == RarityTier::Rare);
+        assert_eq!(
+            rarity.matches[0].explanation,
+            "Contains ascending sequence '0123456789' at offset 4"
+        );
+    }
+
+    #[test]
+    fn descending_sequence() {
+        let hash = with_prefix("7e3a9fedcba9");
+        let rarity = classify(&hash);
+        assert!(rarity.tier == RarityTier::Uncommon);
+        assert_eq!(
+            rarity.matches[0].explanation,
+            "Contains descending sequence 'fedcba9' at offset 5"
+        );
+    }
+
+    #[test]
+    fn sequence_crosses_digit_letter_boundary() {
+        let sequence = SequenceExpl::from_hash(&with_prefix("7e3a56789abcd")).unwrap();
+        assert!(sequence.ascending);
+        assert_eq!(sequence.sequence, "56789abcd");
+        assert!(sequence.tier() == Some(RarityTier::Rare));
+    }
+
+    #[test]
+    fn six_long_sequences_are_ignored() {
+        let hash = with_prefix("7e3a789abc3fedcb0");
+        assert_eq!(SequenceExpl::from_hash(&hash).unwrap().sequence, "789abc");
+        assert!(classify(&hash).tier == RarityTier::Common);
+    }
+
+    #[test]
+    fn full_commit_date_is_legendary() {
+        let hash = with_prefix("7e3a20240315");
+        let rarity = classify_on(&hash, "2024-03-15T12:00:00+00:00");
+        assert!(rarity.tier == RarityTier::Legendary);
+        assert_eq!(
+            rarity.matches[0].explanation,
+            "Contains its commit date as YYYYMMDD '20240315' at offset 4"
+        );
+    }
+
+    #[test]
+    fn short_commit_dates_are_graded() {
+        let rarity = classify_on(&with_prefix("7e3a240315"), "2024-03-15T12:00:00+00:00");
+        assert!(rarity.tier == RarityTier::Rare);
+        assert_eq!(
+            rarity.matches[0].explanation,
+            "Contains its commit date as YYMMDD '240315' at offset 4"
+        );
+        let rarity = classify_on(&with_prefix("7e3a0315"), "2024-03-15T12:00:00+00:00");
+        assert!(rarity.tier == RarityTier::Uncommon);
+        assert_eq!(
+            rarity.matches[0].explanation,
+            "Contains its commit date as MMDD '0315' at offset 4"
+        );
+    }
+
+    #[test]
+    fn commit_date_uses_the_commit_offset() {
+        // 2024-03-15 23:30 at +05:00 is still the 15th where it was made.
+        let hash = with_prefix("7e3a0315");
+        let rarity = classify_on(&hash, "2024-03-15T23:30:00+05:00");
+        assert!(rarity.tier == RarityTier::Uncommon);
+        assert!(classify_on(&hash, "2024-03-16T01:00:00+00:00").tier == RarityTier::Common);
+    }
+
+    #[test]
+    fn full_unix_timestamp_is_rare() {
+        // 1711497600 is 2024-03-27T00:00:00Z.
+        let hash = with_prefix("7e3a1711497600");
+        let rarity = classify_on(&hash, "2024-03-27T00:00:00+00:00");
+        assert!(rarity.tier == RarityTier::Rare);
+        assert_eq!(
+            rarity.matches[0].explanation,
+            "Contains its Unix timestamp '1711497600' at offset 4"
+        );
+    }
+
+    #[test]
+    fn partial_unix_timestamp_is_uncommon() {
+        let hash = with_prefix("7e3a1497600");
+        let rarity = classify_on(&hash, "2024-03-27T00:00:00+00:00");
+        assert!(rarity.tier == RarityTier::Uncommon);
+        assert_eq!(
+            rarity.matches[0].explanation,
+            "Contains 7 digits of its Unix timestamp '1497600' at offset 4"
+        );
+        let hash = with_prefix("7e3a17114976");
+        let rarity = classify_on(&hash, "2024-03-27T00:00:00+00:00");
+        assert_eq!(
+            rarity.matches[0].explanation,
+            "Contains 8 digits of its Unix timestamp '17114976' at offset 4"
+        );
+    }
+
+    #[test]
+    fn off_by_one_unix_timestamp_does_not_match() {
+        let hash = with_prefix("7e3a1711497600");
+        let rarity = classify_on(&hash, "2024-03-27T00:00:01+00:00");
+        assert_ne!(
+            rarity.matches[0].explanation,
+            "Contains its Unix timestamp '1711497600'"
+        );
+        let hash = with_prefix("7e3a1497600");
+        let rarity = classify_on(&hash, "2024-03-27T00:00:01+00:00");
+        assert!(rarity.tier == RarityTier::Common);
+    }
+
+    #[test]
+    fn min_run_changes_classification() {
+        let hash = with_prefix("8305172");
+        let short = ClassifyOptions {
+            min_run: 7,
+            ..Default::default()
+        };
+        let commit = parse_commit(&format!("{hash} 2001-02-03T04:05:06+00:00 Jo"), 1, &short);
+        let rarity = commit.unwrap().rarity;
+        assert!(rarity.tier == RarityTier::Uncommon);
+        assert_eq!(
+            rarity.matches[0].explanation,
+            "Starts with 8 digits: '83051725' at offset 0"
+        );
+        assert_eq!(
+            rarity.percentage,
+            Some(probability::all_of(probability::DIGIT, 8))
+        );
+        assert!(classify(&hash).tier == RarityTier::Common);
+    }
+
+    #[test]
+    fn count_respects_min_run() {
+        let lines = [
+            format!("{} 2001-02-03T04:05:06+00:00 Jo", with_prefix("8305172")),
+            format!("{} 2001-02-03T04:05:06+00:00 Jo", with_prefix("830517264")),
+            format!("{} 2001-02-03T04:05:06+00:00 Jo", FILLER),
+        ];
+        let count = |min_run| {
+            let options = ClassifyOptions {
+                min_run,
+                ..Default::default()
+            };
+            let commits = lines
+                .iter()
+                .filter_map(|line| parse_commit(line, 1, &options))
+                .collect::<Vec<Commit>>();
+            Count::from_commits(&commits)
+        };
+        let default = count(DEFAULT_MIN_RUN);
+        assert_eq!((default.total, default.common, default.uncommon), (3, 2, 1));
+        let short = count(7);
+        assert_eq!((short.total, short.common, short.uncommon), (3, 1, 2));
+    }
+
+    #[test]
+    fn min_run_is_validated() {
+        assert_eq!(parse_min_run("4"), Ok(4));
+        assert_eq!(parse_min_run("40"), Ok(40));
+        assert!(parse_min_run("3").is_err());
+        assert!(parse_min_run("41").is_err());
+        assert!(parse_min_run("nine").is_err());
+        assert!(CliArgs::try_parse_from(["git-rare", "--min-run", "3"]).is_err());
+    }
+
+    #[test]
+    fn repeated_pair_pattern() {
+        let hash = with_prefix("7e35a5a5a5a5a5a");
+        let rarity = classify(&hash);
+        assert!(rarity.tier == RarityTier::Rare);
+        assert_eq!(
+            rarity.matches[0].explanation,
+            "Pattern '5a' repeated 6 times: '5a5a5a5a5a5a' at offset 3"
+        );
+        assert_eq!(
+            rarity.percentage,
+            Some(probability::periodic(2, 12, HASH_LEN))
+        );
+    }
+
+    #[test]
+    fn repeated_pair_pattern_at_boundaries() {
+        let pattern = PatternExpl::from_hash(&with_prefix("5a5a5a5a"), 2).unwrap();
+        assert_eq!((pattern.unit.as_str(), pattern.repetitions), ("5a", 4));
+        assert!(pattern.tier() == Some(RarityTier::Uncommon));
+        let hash = format!("{}c1c1c1c1c1", &FILLER[..30]);
+        let pattern = PatternExpl::from_hash(&hash, 2).unwrap();
+        assert_eq!((pattern.unit.as_str(), pattern.repetitions), ("c1", 5));
+    }
+
+    #[test]
+    fn interrupted_pair_pattern_is_ignored() {
+        let hash = with_prefix("7e35a5a5a5b5a5a");
+        let pattern = PatternExpl::from_hash(&hash, 2).unwrap();
+        assert_eq!(pattern.repetitions, 3);
+        assert!(pattern.tier().is_none());
+    }
+
+    #[test]
+    fn pair_pattern_ignores_single_repeated_character() {
+        let hash = with_prefix("7e3aaaaaaaaa");
+        let pattern = PatternExpl::from_hash(&hash, 2);
+        assert!(pattern.is_none_or(|p| p.tier().is_none()));
+    }
+
+    #[test]
+    fn longest_overlapping_pair_pattern_wins() {
+        // The stretch reads as both 'a5' and '5a' repeats, and is reported
+        // once from its first character rather than the shorter '82' run.
+        let hash = with_prefix("7e3a5a5a5a5a5a82828282");
+        let pattern = PatternExpl::from_hash(&hash, 2).unwrap();
+        assert_eq!((pattern.unit.as_str(), pattern.repetitions), ("a5", 5));
+    }
+
+    #[test]
+    fn repeated_triple_pattern() {
+        let hash = with_prefix("7e31f41f41f4");
+        let rarity = classify(&hash);
+        assert!(rarity.tier == RarityTier::Rare);
+        assert_eq!(
+            rarity.matches[0].explanation,
+            "Pattern '1f4' repeated 3 times: '1f41f41f4' at offset 3"
+        );
+    }
+
+    #[test]
+    fn partial_triple_repetitions_are_rounded_down() {
+        let pattern = PatternExpl::from_hash(&with_prefix("7e31f41f41f41f"), 3).unwrap();
+        assert_eq!((pattern.unit.as_str(), pattern.repetitions), ("1f4", 3));
+        assert!(pattern.tier() == Some(RarityTier::Rare));
+    }
+
+    #[test]
+    fn short_triple_pattern_is_ignored() {
+        let pattern = PatternExpl::from_hash(&with_prefix("7e31f41f41f"), 3).unwrap();
+        assert_eq!(pattern.repetitions, 2);
+        assert!(pattern.tier().is_none());
+        let pattern = PatternExpl::from_hash(FILLER, 3);
+        assert!(pattern.is_none_or(|p| p.tier().is_none()));
+    }
+
+    #[test]
+    fn triple_pattern_defers_to_shorter_periods() {
+        let rarity = classify(&with_prefix("7e3aaaaaaaaa"));
+        assert_eq!(
+            display_explanations(&rarity.matches),
+            "Contains 9 consecutive 'a' characters: 'aaaaaaaaa' at offset 3; \
+             Contains a run of 9 letters: 'aaaaaaaaa' at offset 3"
+        );
+        let rarity = classify(&with_prefix("7e35a5a5a5a5a5a"));
+        assert!(!display_explanations(&rarity.matches).contains("'5a5'"));
+    }
+
+    #[test]
+    fn famous_constant_prefix() {
+        let rarity = classify(&with_prefix("314159265"));
+        assert!(rarity.tier == RarityTier::Rare);
+        assert_eq!(
+            rarity.matches[0].explanation,
+            "Starts with the first 9 digits of π: '314159265' at offset 0"
+        );
+        assert_eq!(rarity.percentage, Some(probability::exact(9)));
+    }
+
+    #[test]
+    fn longer_famous_constants_are_legendary() {
+        let rarity = classify(&with_prefix("7e3a2718281828"));
+        assert!(rarity.tier == RarityTier::Legendary);
+        assert_eq!(
+            rarity.matches[0].explanation,
+            "Contains the first 10 digits of e: '2718281828' at offset 4"
+        );
+    }
+
+    #[test]
+    fn hex_words() {
+        let rarity = classify(&with_prefix("decade"));
+        assert!(rarity.tier == RarityTier::Uncommon);
+        assert_eq!(
+            rarity.matches[0].explanation,
+            "Starts with the word 'decade'"
+        );
+        let rarity = classify(&format!("{}effaced{}", &FILLER[..10], &FILLER[17..]));
+        assert_eq!(
+            rarity.matches[0].explanation,
+            "Contains the word 'effaced' at offset 10"
+        );
+    }
+
+    #[test]
+    fn leet_hex_words() {
+        let hash = with_prefix("5caff01d");
+        assert!(classify(&hash).tier == RarityTier::Common);
+        let rarity = Commit::get_rarity(
+            &CommitContext {
+                hash: &hash,
+                datetime: Some(&DateTime::parse_from_rfc3339("2001-02-03T04:05:06+00:00").unwrap()),
+                author: "Jo",
+                message: "",
+                ordinal: 1,
+            },
+            &ClassifyOptions {
+                leet: true,
+                ..Default::default()
+            },
+        );
+        assert!(rarity.tier == RarityTier::Rare);
+        assert_eq!(
+            rarity.matches[0].explanation,
+            "Starts with the word 'scaffold' as '5caff01d'"
+        );
+    }
+
+    #[test]
+    fn pandigital_prefix() {
+        let rarity = classify(&with_prefix("3a9f07c5e12d8b64"));
+        assert!(rarity.tier == RarityTier::Rare);
+        assert_eq!(
+            rarity.matches[0].explanation,
+            "First 16 characters are pandigital: '3a9f07c5e12d8b64' at offset 0"
+        );
+        assert_eq!(rarity.percentage, Some(probability::distinct(16, 16)));
+    }
+
+    #[test]
+    fn nearly_pandigital_prefix() {
+        // Every digit but 6, with 3 twice.
+        let rarity = classify(&with_prefix("3a9f07c5e12d8b43"));
+        assert!(rarity.tier == RarityTier::Uncommon);
+        assert_eq!(
+            rarity.matches[0].explanation,
+            "First 16 characters contain 15 distinct hex digits: '3a9f07c5e12d8b43' at offset 0"
+        );
+    }
+
+    fn classify_short(hash: &str, abbrev: usize) -> Rarity {
+        Commit::get_rarity(
+            &CommitContext {
+                hash,
+                datetime: Some(&DateTime::parse_from_rfc3339("2001-02-03T04:05:06+00:00").unwrap()),
+                author: "Jo",
+                message: "",
+                ordinal: 1,
+            },
+            &ClassifyOptions {
+                short_hash: Some(abbrev),
+                ..Default::default()
+            },
+        )
+    }
+
+    #[test]
+    fn short_hash_rules_only_apply_in_short_hash_mode() {
+        let hash = with_prefix("2840593");
+        assert!(classify(&hash).tier == RarityTier::Common);
+        let rarity = classify_short(&hash, DEFAULT_ABBREV);
+        assert!(rarity.tier == RarityTier::Uncommon);
+        assert_eq!(
+            display_explanations(&rarity.matches),
+            "Short hash is all digits: '2840593' at offset 0"
+        );
+        assert_eq!(
+            rarity.percentage,
+            Some(probability::all_of(probability::DIGIT, 7))
+        );
+    }
+
+    #[test]
+    fn short_hash_mode_ignores_the_full_hash_rules() {
+        let hash = format!("{}{}", &FILLER[..12], "0".repeat(28));
+        assert!(classify(&hash).tier == RarityTier::Legendary);
+        assert!(classify_short(&hash, DEFAULT_ABBREV).tier == RarityTier::Common);
+    }
+
+    #[test]
+    fn short_hash_sequences_and_repeats() {
+        let rarity = classify_short(&with_prefix("3456789"), DEFAULT_ABBREV);
+        assert!(rarity.tier == RarityTier::Rare);
+        assert_eq!(
+            display_explanations(&rarity.matches),
+            "Short hash is a sequence: '3456789' at offset 0; \
+             Short hash is all digits: '3456789' at offset 0"
+        );
+        assert_eq!(rarity.percentage, Some(probability::sequence(7, 7)));
+        let rarity = classify_short(&with_prefix("aaaaaaa"), DEFAULT_ABBREV);
+        assert_eq!(
+            display_explanations(&rarity.matches),
+            "Short hash is all the same character: 'aaaaaaa' at offset 0; \
+             Short hash is a palindrome: 'aaaaaaa' at offset 0; \
+             Short hash is all letters: 'aaaaaaa' at offset 0"
+        );
+    }
+
+    #[test]
+    fn short_hash_respects_abbrev() {
+        let hash = with_prefix("c3a9a3c");
+        assert!(classify_short(&hash, 5).tier == RarityTier::Common);
+        let rarity = classify_short(&hash, 7);
+        assert_eq!(
+            display_explanations(&rarity.matches),
+            "Short hash is a palindrome: 'c3a9a3c' at offset 0"
+        );
+        assert_eq!(rarity.percentage, Some(probability::palindrome(7)));
+    }
+
+    #[test]
+    fn abbreviations_are_extended_until_unique() {
+        let hashes = [
+            "7e3a91c5d48b2f371a9e73cb5d284e6f10a9c3b7",
+            "7e3a91c5d48b2f3eeeeeeeeeeeeeeeeeeeeeeeee",
+            "1234567890123456789012345678901234567890",
+            "7e3a91c5d48b2f371a9e73cb5d284e6f10a9c3b7",
+        ];
+        let abbreviations = abbreviations(hashes, 12);
+        assert_eq!(abbreviations.len(), 3);
+        assert_eq!(abbreviations[hashes[0]], 16);
+        assert_eq!(abbreviations[hashes[1]], 16);
+        assert_eq!(abbreviations[hashes[2]], 12);
+        // A hash that's a prefix of another is shown whole.
+        let abbreviations = super::abbreviations(["abcd", "abcdef"], 4);
+        assert_eq!(abbreviations["abcd"], 4);
+        assert_eq!(abbreviations["abcdef"], 5);
+    }
+
+    #[test]
+    fn rare_tags_are_named_apart() {
+        let hashes = [
+            "7e3a91c5d48b2f371a9e73cb5d284e6f10a9c3b7",
+            "1234567890123456789012345678901234567890",
+            "7e3a91cf00000000000000000000000000000000",
+        ]
+        .map(String::from);
+        let tags = rare_tags(&hashes, 7);
+        assert_eq!(
+            tags.iter()
+                .map(|(name, _)| name.as_str())
+                .collect::<Vec<&str>>(),
+            ["rare/7e3a91c5", "rare/1234567", "rare/7e3a91cf"]
+        );
+        assert_eq!(tags[1].1, hashes[1]);
+    }
+
+    #[test]
+    fn abbrev_is_validated() {
+        assert_eq!(parse_abbrev("12"), Ok(12));
+        assert!(parse_abbrev("3").is_err());
+        assert!(parse_abbrev("41").is_err());
+        assert!(CliArgs::try_parse_from(["git-rare", "--abbrev", "8"]).is_err());
+        assert!(
+            CliArgs::try_parse_from(["git-rare", "--short-hash-rules", "--abbrev", "8", "-c"])
+                .is_ok()
+        );
+    }
+
+    #[test]
+    fn alternating_run_at_start() {
+        let rarity = classify(&with_prefix("a1b2c3d4e5f6"));
+        assert!(rarity.tier == RarityTier::Uncommon);
+        assert_eq!(
+            rarity.matches[0].explanation,
+            "Alternates between digits and letters for 12 characters at offset 0"
+        );
+        assert_eq!(rarity.percentage, Some(probability::alternating(12, 40)));
+    }
+
+    #[test]
+    fn alternating_run_in_the_middle() {
+        // Starts and ends with a digit, next to digits on both sides.
+        let hash = format!("{}1b2c3d4e5f6a7b8{}", &FILLER[..11], &FILLER[26..]);
+        let rarity = classify(&hash);
+        assert!(rarity.tier == RarityTier::Rare);
+        assert_eq!(
+            rarity.matches[0].explanation,
+            "Alternates between digits and letters for 15 characters at offset 11"
+        );
+    }
+
+    #[test]
+    fn alternating_run_at_end() {
+        let hash = format!("{}f1e2d3c4b5", &FILLER[..30]);
+        assert_eq!(
+            classify(&hash).matches[0].explanation,
+            "Alternates between digits and letters for 10 characters at offset 30"
+        );
+    }
+
+    #[test]
+    fn short_alternating_runs_are_ignored() {
+        let hash = with_prefix("a1b2c3d4ef");
+        assert!(classify(&hash).tier == RarityTier::Common);
+    }
+
+    #[test]
+    fn prime_prefix_escalates_to_rare() {
+        let rarity = classify(&with_prefix("982451653"));
+        assert!(rarity.tier == RarityTier::Rare);
+        assert_eq!(
+            display_explanations(&rarity.matches),
+            "Starts with the 9-digit prime 982451653; \
+             Starts with 11 digits: '98245165348' at offset 0"
+        );
+        assert_eq!(rarity.percentage, probability::prime_prefix(9));
+    }
+
+    #[test]
+    fn longest_prime_prefix_wins() {
+        let rarity = classify(&with_prefix("830517264397"));
+        assert_eq!(
+            rarity.matches[0].explanation,
+            "Starts with the 12-digit prime 830517264397"
+        );
+    }
+
+    #[test]
+    fn composite_prefix_stays_uncommon() {
+        let rarity = classify(&with_prefix("982451655"));
+        assert!(rarity.tier == RarityTier::Uncommon);
+        assert_eq!(
+            display_explanations(&rarity.matches),
+            "Starts with 11 digits: '98245165548' at offset 0"
+        );
+    }
+
+    #[test]
+    fn near_miss_one_digit_short() {
+        let hash = with_prefix("83051726");
+        assert!(classify(&hash).tier == RarityTier::Common);
+        let strength = Commit::get_near_miss(&hash, DEFAULT_MIN_RUN).unwrap();
+        assert_eq!(strength.to_string(), "8/9 leading digits");
+        assert!(!strength.is_match());
+        assert_eq!(strength.closeness(), 8.0 / 9.0);
+    }
+
+    #[test]
+    fn near_miss_one_letter_short() {
+        let hash = format!("{}{}", &FILLER[..31], "1fedacbea");
+        assert!(classify(&hash).tier == RarityTier::Common);
+        let strength = Commit::get_near_miss(&hash, DEFAULT_MIN_RUN).unwrap();
+        assert_eq!(strength.to_string(), "8/9 trailing letters");
+    }
+
+    #[test]
+    fn near_miss_one_zero_short() {
+        let strength = Commit::get_near_miss(&with_prefix("00000"), DEFAULT_MIN_RUN).unwrap();
+        assert_eq!(strength.to_string(), "5/6 leading zeros");
+    }
+
+    #[test]
+    fn near_misses_are_sorted_and_limited() {
+        let lines = [
+            format!("{} 2001-02-03T04:05:06+00:00 Jo", with_prefix("8305")),
+            format!("{} 2001-02-03T04:05:06+00:00 Jo", with_prefix("83051726")),
+            format!("{} 2001-02-03T04:05:06+00:00 Jo", with_prefix("8305172a")),
+            format!("{} 2001-02-03T04:05:06+00:00 Jo", with_prefix("830517264")),
+        ];
+        let commits = lines
+            .iter()
+            .filter_map(|line| parse_commit(line, 1, &ClassifyOptions::default()))
+            .collect::<Vec<Commit>>();
+        let near_misses = NearMiss::from_commits(&commits, DEFAULT_MIN_RUN, 2);
+        // The nine digit prefix is not a miss at all.
+        let shown = near_misses
+            .iter()
+            .map(|m| m.strength.to_string())
+            .collect::<Vec<String>>();
+        assert_eq!(shown, ["8/9 leading digits", "7/9 leading digits"]);
+        assert_eq!(display_closeness(&near_misses[0].closeness), "89%");
+    }
+
+    fn classify_memes(hash: &str) -> Rarity {
+        Commit::get_rarity(
+            &CommitContext {
+                hash,
+                datetime: Some(&DateTime::parse_from_rfc3339("2001-02-03T04:05:06+00:00").unwrap()),
+                author: "Jo",
+                message: "",
+                ordinal: 1,
+            },
+            &ClassifyOptions {
+                memes: true,
+                ..Default::default()
+            },
+        )
+    }
+
+    #[test]
+    fn memes_are_opt_in() {
+        let hash = with_prefix("1337");
+        assert!(classify(&hash).tier == RarityTier::Common);
+        let rarity = classify_memes(&hash);
+        assert!(rarity.tier == RarityTier::Uncommon);
+        assert_eq!(
+            rarity.matches[0].explanation,
+            "Starts with 1337 (leet): '1337' at offset 0"
+        );
+    }
+
+    #[test]
+    fn short_memes_must_stand_out() {
+        // Somewhere in the middle, once: too likely to be worth reporting.
+        let once = format!("{}1337{}", &FILLER[..10], &FILLER[14..]);
+        assert!(classify_memes(&once).tier == RarityTier::Common);
+        let twice = format!(
+            "{}1337{}1337{}",
+            &FILLER[..10],
+            &FILLER[14..20],
+            &FILLER[28..]
+        );
+        let rarity = classify_memes(&twice);
+        assert!(rarity.tier == RarityTier::Uncommon);
+        assert_eq!(
+            rarity.matches[0].explanation,
+            "Contains 1337 (leet) 2 times"
+        );
+    }
+
+    #[test]
+    fn long_memes_are_reported_anywhere() {
+        let inside = format!("{}8675309{}", &FILLER[..10], &FILLER[17..]);
+        let rarity = classify_memes(&inside);
+        assert!(rarity.tier == RarityTier::Uncommon);
+        assert_eq!(
+            rarity.matches[0].explanation,
+            "Contains 8675309 (Jenny's number): '8675309' at offset 10"
+        );
+        let end = format!("{}8675309", &FILLER[..33]);
+        let rarity = classify_memes(&end);
+        assert!(rarity.tier == RarityTier::Rare);
+        assert_eq!(
+            rarity.matches[0].explanation,
+            "Ends with 8675309 (Jenny's number): '8675309' at offset 33"
+        );
+    }
+
+    /// Digits whose prefixes of nine or more digits are all even, so no
+    /// prime prefix rule fires alongside the run rules.
+    const RUN_DIGITS: &str = "8305172648620486";
+
+    /// `n` leading digits followed by a letter and the rest of [`FILLER`].
+    fn with_digit_run(n: usize) -> String {
+        format!("{}f{}", &RUN_DIGITS[..n], &FILLER[n + 1..])
+    }
+
+    #[test]
+    fn digit_runs_are_graded_by_length() {
+        let cases = [
+            (8, None),
+            (9, Some(RarityTier::Uncommon)),
+            (11, Some(RarityTier::Uncommon)),
+            (12, Some(RarityTier::Rare)),
+            (15, Some(RarityTier::Rare)),
+            (16, Some(RarityTier::Legendary)),
+        ];
+        let rule = RunRule::new(CharClass::Digit, RunPosition::Start, DEFAULT_MIN_RUN);
+        for (n, tier) in cases {
+            let hash = with_digit_run(n);
+            let found = rule.rule_match(&hash);
+            assert_eq!(found.as_ref().map(|m| m.tier.clone()), tier, "{hash}");
+            if let Some(found) = found {
+                assert_eq!(
+                    found.explanation,
+                    format!("Starts with {n} digits: '{}' at offset 0", &RUN_DIGITS[..n])
+                );
+                assert_eq!(found.span, Some(Span::new(0, &RUN_DIGITS[..n])));
+                assert_eq!(
+                    found.percentage,
+                    Some(probability::all_of(probability::DIGIT, n))
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn letter_runs_are_graded_by_length() {
+        let letters = "fedcbafedcbafedcba";
+        let cases = [
+            (8, None),
+            (9, Some(RarityTier::Rare)),
+            (11, Some(RarityTier::Rare)),
+            (12, Some(RarityTier::Legendary)),
+            (16, Some(RarityTier::Legendary)),
+        ];
+        let rule = RunRule::new(CharClass::Letter, RunPosition::Anywhere, DEFAULT_MIN_RUN);
+        for (n, tier) in cases {
+            let hash = format!("{}1{}1{}", &FILLER[..4], &letters[..n], &FILLER[n + 6..]);
+            let found = rule.rule_match(&hash);
+            assert_eq!(found.as_ref().map(|m| m.tier.clone()), tier, "{hash}");
+            if let Some(found) = found {
+                assert_eq!(
+                    found.explanation,
+                    format!(
+                        "Contains a run of {n} letters: '{}' at offset 5",
+                        &letters[..n]
+                    )
+                );
+                assert_eq!(found.span, Some(Span::new(5, &letters[..n])));
+            }
+        }
+    }
+
+    #[test]
+    fn raised_min_run_still_bumps_at_twelve() {
+        let rule = RunRule::new(CharClass::Digit, RunPosition::Start, 14);
+        assert!(rule.rule_match(&with_digit_run(13)).is_none());
+        let found = rule.rule_match(&with_digit_run(14)).unwrap();
+        assert!(found.tier == RarityTier::Rare);
+    }
+
+    #[test]
+    fn count_buckets_graded_runs() {
+        let commits = [8, 9, 11, 12, 16]
+            .into_iter()
+            .filter_map(|n| {
+                let line = format!("{} 2001-02-03T04:05:06+00:00 Jo", with_digit_run(n));
+                parse_commit(&line, 1, &ClassifyOptions::default())
+            })
+            .collect::<Vec<_>>();
+        let count = Count::from_commits(&commits);
+        assert_eq!(
+            (
+                count.total,
+                count.common,
+                count.uncommon,
+                count.rare,
+                count.legendary
+            ),
+            (5, 1, 2, 1, 1)
+        );
+    }
+
+    #[test]
+    fn patterns_are_reported_alongside_built_in_rules() {
+        let file = tempfile::NamedTempFile::new().unwrap();
+        std::fs::write(file.path(), "# Codenames\nrare:5eed\nc0de\n").unwrap();
+        let options = ClassifyOptions {
+            patterns: Some(PatternSet::load(file.path()).unwrap()),
+            ..Default::default()
+        };
+        let hash = format!("{}5eed{}", &FILLER[..10], &FILLER[14..]);
+        let line = format!("{hash} 2001-02-03T04:05:06+00:00 Jo");
+        let rarity = parse_commit(&line, 1, &options).unwrap().rarity;
+        assert!(rarity.tier == RarityTier::Rare);
+        assert_eq!(
+            display_explanations(&rarity.matches),
+            "Contains pattern '5eed' at offset 10"
+        );
+    }
+
+    /// Options with the lucky numbers from a config file containing `toml`.
+    fn lucky_options(toml: &str) -> ClassifyOptions {
+        let file = tempfile::NamedTempFile::new().unwrap();
+        std::fs::write(file.path(), toml).unwrap();
+        let config = config::Config::load(Some(file.path())).unwrap();
+        ClassifyOptions {
+            lucky_numbers: config.lucky_numbers,
+            ..Default::default()
+        }
+    }
+
+    fn classify_lucky(hash: &str, options: &ClassifyOptions) -> Rarity {
+        let line = format!("{hash} 2001-02-03T04:05:06+00:00 Jo");
+        parse_commit(&line, 1, options).unwrap().rarity
+    }
+
+    #[test]
+    fn lucky_numbers_are_graded_by_length() {
+        let options = lucky_options(r#"lucky_numbers = ["19900412", "070415"]"#);
+        let hash = format!("{}19900412{}", &FILLER[..14], &FILLER[22..]);
+        let rarity = classify_lucky(&hash, &options);
+        assert!(rarity.tier == RarityTier::Rare);
+        assert_eq!(
+            display_explanations(&rarity.matches),
+            "Contains your lucky number 19900412 at offset 14"
+        );
+        assert_eq!(rarity.matches[0].span, Some(Span::new(14, "19900412")));
+        assert_eq!(
+            rarity.percentage,
+            Some(probability::contains("19900412", HASH_LEN))
+        );
+
+        let hash = format!("{}070415{}", &FILLER[..24], &FILLER[30..]);
+        let rarity = classify_lucky(&hash, &options);
+        assert!(rarity.tier == RarityTier::Uncommon);
+        assert_eq!(
+            display_explanations(&rarity.matches),
+            "Contains your lucky number 070415 at offset 24"
+        );
+    }
+
+    #[test]
+    fn only_configured_lucky_numbers_match() {
+        let hash = format!("{}19900412{}", &FILLER[..14], &FILLER[22..]);
+        let options = lucky_options(r#"lucky_numbers = ["20240229"]"#);
+        assert!(classify_lucky(&hash, &options).tier == RarityTier::Common);
+        let options = lucky_options("min_run = 9");
+        assert!(options.lucky_numbers.is_empty());
+        assert!(classify_lucky(&hash, &options).tier == RarityTier::Common);
+    }
+
+    #[test]
+    fn spans_locate_start_end_and_interior_matches() {
+        let cases = [
+            (
+                with_digit_run(9),
+                Span::new(0, "830517264"),
+                "Starts with 9 digits: '830517264' at offset 0",
+            ),
+            (
+                format!("{}fedcbafed", &FILLER[..31]),
+                Span::new(31, "fedcbafed"),
+                "Ends with 9 letters: 'fedcbafed' at offset 31",
+            ),
+            (
+                format!("{}482910375{}", &FILLER[..14], &FILLER[23..]),
+                Span::new(14, "482910375"),
+                "Contains a run of 9 digits: '482910375' at offset 14",
+            ),
+        ];
+        for (hash, span, explanation) in cases {
+            let rarity = classify(&hash);
+            assert_eq!(rarity.matches[0].span, Some(span), "{hash}");
+            assert_eq!(display_explanations(&rarity.matches), explanation);
+        }
+    }
+
+    /// `run` inserted at offset 12 of [`FILLER`].
+    fn with_run_at_12(run: &str) -> String {
+        format!("{}{run}{}", &FILLER[..12], &FILLER[12 + run.len()..])
+    }
+
+    #[test]
+    fn strictly_increasing_runs() {
+        let rarity = classify(&with_run_at_12("0135789abd"));
+        assert!(rarity.tier == RarityTier::Rare);
+        assert_eq!(
+            display_explanations(&rarity.matches),
+            "Contains a strictly increasing run of 10 characters: '0135789abd' at offset 12"
+        );
+        assert_eq!(
+            rarity.percentage,
+            Some(probability::monotonic(10, HASH_LEN, true))
+        );
+        let rarity = classify(&with_run_at_12("0135689abdef"));
+        assert!(rarity.tier == RarityTier::Legendary);
+        assert_eq!(
+            rarity.matches[0].explanation,
+            "Contains a strictly increasing run of 12 characters: '0135689abdef' at offset 12"
+        );
+    }
+
+    #[test]
+    fn non_decreasing_runs() {
+        let rarity = classify(&with_run_at_12("0133478aacde"));
+        assert!(rarity.tier == RarityTier::Uncommon);
+        assert_eq!(
+            display_explanations(&rarity.matches),
+            "Contains a non-decreasing run of 12 characters: '0133478aacde' at offset 12"
+        );
+        assert_eq!(
+            rarity.percentage,
+            Some(probability::monotonic(12, HASH_LEN, false))
+        );
+        let rarity = classify(&with_run_at_12("00133478aacdef"));
+        assert!(rarity.tier == RarityTier::Rare);
+        // Eleven characters are not enough without being strict.
+        assert!(MonotonicExpl::from_hash(&with_run_at_12("0133478aacd")).is_none());
+    }
+
+    #[test]
+    fn rarer_monotonic_run_wins() {
+        // The whole run never decreases, and its last ten characters also
+        // strictly increase; the strict run is rarer.
+        let monotonic = MonotonicExpl::from_hash(&with_run_at_12("00135789abcd")).unwrap();
+        assert!(monotonic.strict);
+        assert_eq!((monotonic.offset, monotonic.length), (13, 11));
+    }
+
+    #[test]
+    fn descending_runs_are_not_monotonic() {
+        let hash = with_run_at_12("fedb9876321c");
+        assert!(MonotonicExpl::from_hash(&hash).is_none());
+        assert!(classify(&hash).tier == RarityTier::Common);
+    }
+
+    #[test]
+    fn count_includes_memes() {
+        let line = format!("{} 2001-02-03T04:05:06+00:00 Jo", with_prefix("42069"));
+        let options = ClassifyOptions {
+            memes: true,
+            ..Default::default()
+        };
+        let commits = parse_commit(&line, 1, &options)
+            .into_iter()
+            .collect::<Vec<_>>();
+        let count = Count::from_commits(&commits);
+        assert_eq!((count.total, count.common, count.uncommon), (1, 0, 1));
+    }
+
+    #[test]
+    fn rarest_match_wins_regardless_of_rule_order() {
+        // Nine digits at the start (Uncommon) and nine letters at the end
+        // (Rare); the digit rule is evaluated first.
+        let hash = format!("830517264{}abcdefabc", &FILLER[9..31]);
+        let rarity = classify(&hash);
+        assert!(rarity.tier == RarityTier::Rare);
+        assert_eq!(
+            rarity.matches[0].explanation,
+            "Ends with 9 letters: 'abcdefabc' at offset 31"
+        );
+        let reversed = Rarity::from_matches(rarity.matches.iter().rev().cloned().collect());
+        assert!(reversed == rarity);
+    }
+
+    #[test]
+    fn binary_prefix() {
+        let rarity = classify(&with_prefix("0110100110"));
+        assert!(rarity.tier == RarityTier::Uncommon);
+        assert_eq!(
+            rarity.matches[0].explanation,
+            "Starts with binary-looking '0110100110'"
+        );
+        assert_eq!(
+            rarity.percentage,
+            Some(probability::all_of(probability::BINARY, 10))
+        );
+    }
+
+    #[test]
+    fn binary_run_in_the_middle() {
+        let hash = format!("{}101101001011{}", &FILLER[..12], &FILLER[24..]);
+        let rarity = classify(&hash);
+        assert!(rarity.tier == RarityTier::Rare);
+        assert_eq!(
+            rarity.matches[0].explanation,
+            "Contains binary-looking '101101001011' at offset 12"
+        );
+        assert_eq!(
+            rarity.percentage,
+            Some(probability::run(probability::BINARY, 12, 40))
+        );
+    }
+
+    #[test]
+    fn short_binary_runs_are_ignored() {
+        assert!(classify(&with_prefix("01101001a")).tier == RarityTier::Common);
+    }
+
+    #[test]
+    fn leading_zeros_beat_binary() {
+        // Nine zeros are both a Rare zero prefix and an Uncommon binary one.
+        let rarity = classify(&with_prefix("000000000a"));
+        assert!(rarity.tier == RarityTier::Rare);
+        assert_eq!(
+            rarity.matches[0].explanation,
+            "Starts with 9 zeros: '000000000' at offset 0"
+        );
+        assert!(rarity
+            .matches
+            .iter()
+            .any(|m| m.explanation == "Starts with binary-looking '000000000'"));
+    }
+
+    fn sandwich(ends: &str) -> String {
+        format!("{ends}{}{ends}", &FILLER[ends.len()..40 - ends.len()])
+    }
+
+    #[test]
+    fn short_sandwiches_are_ignored() {
+        assert!(classify(&sandwich("4f9")).tier == RarityTier::Common);
+    }
+
+    #[test]
+    fn sandwich_of_four() {
+        let rarity = classify(&sandwich("4f9a"));
+        assert!(rarity.tier == RarityTier::Uncommon);
+        assert_eq!(rarity.matches[0].explanation, "Starts and ends with '4f9a'");
+        assert_eq!(rarity.percentage, Some(probability::exact(4)));
+    }
+
+    #[test]
+    fn sandwich_of_six() {
+        let rarity = classify(&sandwich("4f9ab1"));
+        assert!(rarity.tier == RarityTier::Rare);
+        assert_eq!(
+            rarity.matches[0].explanation,
+            "Starts and ends with '4f9ab1'"
+        );
+    }
+
+    #[test]
+    fn sandwich_halves_do_not_overlap() {
+        // The hash starts and ends with the same 36 characters, but only
+        // 20 of them fit in each half.
+        assert_eq!(
+            SandwichExpl::from_hash(&"4f9a".repeat(10)).0,
+            "4f9a".repeat(5)
+        );
+        assert_eq!(SandwichExpl::from_hash("4f9a4f9").0, "4f9");
+        assert_eq!(SandwichExpl::from_hash("abcab").0, "ab");
+    }
+
+    fn classify_by(hash: &str, author: &str, leet: bool) -> Rarity {
+        let line = format!("{hash} 2001-02-03T04:05:06+00:00 {author}");
+        let options = ClassifyOptions {
+            leet,
+            ..Default::default()
+        };
+        parse_commit(&line, 1, &options).unwrap().rarity
+    }
+
+    #[test]
+    fn author_initials() {
+        let hash = with_prefix("fab");
+        let rarity = classify_by(&hash, "Fae Alice Becker", false);
+        assert!(rarity.tier == RarityTier::Uncommon);
+        assert_eq!(
+            rarity.matches[0].explanation,
+            "Starts with author initials 'fab'"
+        );
+        assert_eq!(rarity.percentage, Some(probability::exact(3)));
+        assert!(classify_by(&hash, "Fae Becker", false).tier == RarityTier::Common);
+    }
+
+    #[test]
+    fn author_initials_skip_letters_outside_hex() {
+        // "Jane Fae Becker" can only be spelled as "fb".
+        let rarity = classify_by(&with_prefix("fb"), "Jane Fae Becker", false);
+        assert_eq!(
+            rarity.matches[0].explanation,
+            "Starts with author initials 'fb'"
+        );
+    }
+
+    #[test]
+    fn author_initials_need_two_letters() {
+        assert!(classify_by(&with_prefix("c"), "Chad", false).tier == RarityTier::Common);
+        assert!(classify_by(FILLER, "John Smith", false).tier == RarityTier::Common);
+    }
+
+    #[test]
+    fn author_initials_with_leet() {
+        let hash = with_prefix("50b");
+        assert!(classify_by(&hash, "Sam Oak Bee", false).tier == RarityTier::Common);
+        assert_eq!(
+            classify_by(&hash, "Sam Oak Bee", true).matches[0].explanation,
+            "Starts with author initials '50b'"
+        );
+    }
+
+    fn classify_with_message(hash: &str, message: &str) -> Rarity {
+        parse_commit(&record(hash, "Jo", message), 1, &ClassifyOptions::default())
+            .unwrap()
+            .rarity
+    }
+
+    #[test]
+    fn issue_number_in_hash() {
+        let hash = format!("{}4217{}", &FILLER[..12], &FILLER[16..]);
+        assert!(classify(&hash).tier == RarityTier::Common);
+        let rarity = classify_with_message(&hash, "Fix the parser\n\nFixes #4217");
+        assert!(rarity.tier == RarityTier::Uncommon);
+        assert_eq!(
+            rarity.matches[0].explanation,
+            "Contains the issue number it references as '#4217': '4217' at offset 12"
+        );
+        assert_eq!(rarity.percentage, Some(probability::contains("4217", 40)));
+    }
+
+    #[test]
+    fn longest_issue_number_wins() {
+        let hash = format!(
+            "{}4217{}285190{}",
+            &FILLER[..6],
+            &FILLER[10..20],
+            &FILLER[26..]
+        );
+        let rarity = classify_with_message(&hash, "Fixes #4217, see GH-285190 and !12");
+        assert!(rarity.tier == RarityTier::Rare);
+        assert_eq!(
+            rarity.matches[0].explanation,
+            "Contains the issue number it references as 'GH-285190': '285190' at offset 20"
+        );
+    }
+
+    #[test]
+    fn short_issue_numbers_are_ignored() {
+        // FILLER contains "e3a9", "7e3" and plenty of other short numbers.
+        let rarity = classify_with_message(FILLER, "Fixes #73, #9 and !284");
+        assert!(rarity.tier == RarityTier::Common);
+    }
+
+    fn classify_at(hash: &str, ordinal: usize) -> Rarity {
+        let line = format!("{hash} 2001-02-03T04:05:06+00:00 Jo");
+        parse_commit(&line, ordinal, &ClassifyOptions::default())
+            .unwrap()
+            .rarity
+    }
+
+    #[test]
+    fn ordinal_in_hash_prefix() {
+        let hash = format!("{}1234{}", &FILLER[..3], &FILLER[7..]);
+        assert!(classify_at(&hash, 1233).tier == RarityTier::Common);
+        let rarity = classify_at(&hash, 1234);
+        assert!(rarity.tier == RarityTier::Uncommon);
+        assert_eq!(
+            rarity.matches[0].explanation,
+            "Contains its position in history, 1234, at offset 3"
+        );
+        assert_eq!(rarity.percentage, Some(probability::contains("1234", 12)));
+    }
+
+    #[test]
+    fn long_ordinals_are_rarer() {
+        let hash = format!("{}52817{}", &FILLER[..6], &FILLER[11..]);
+        assert!(classify_at(&hash, 52817).tier == RarityTier::Rare);
+    }
+
+    #[test]
+    fn ordinal_must_be_near_the_start() {
+        let hash = format!("{}1234{}", &FILLER[..20], &FILLER[24..]);
+        assert!(classify_at(&hash, 1234).tier == RarityTier::Common);
+    }
+
+    #[test]
+    fn short_ordinals_are_ignored() {
+        // FILLER starts with "7e3a91".
+        assert!(classify_at(FILLER, 91).tier == RarityTier::Common);
+        assert!(classify_at(FILLER, 7).tier == RarityTier::Common);
+    }
+
+    #[test]
+    fn ordinals_count_from_the_root() {
+        // Newest first, so the 123rd commit from the root is 27 lines down.
+        let special = format!("123{}", &FILLER[3..]);
+        let output = (0..150)
+            .map(|i| {
+                let hash = if i == 27 { &special } else { FILLER };
+                format!("{}{RECORD_SEPARATOR}\n", record(hash, "Jo", ""))
+            })
+            .collect::<String>();
+        let commits = parse_log(&output, &ClassifyOptions::default());
+        assert_eq!(commits.len(), 150);
+        assert_eq!(
+            display_explanations(&commits[27].rarity.matches),
+            "Contains its position in history, 123, at offset 0"
+        );
+        assert!(commits
+            .iter()
+            .enumerate()
+            .all(|(i, c)| i == 27 || c.rarity.tier == RarityTier::Common));
+    }
+
+    #[test]
+    fn ordinals_are_unknown_when_scanning_revisions() {
+        let special = format!("123{}", &FILLER[3..]);
+        let output = (0..150)
+            .map(|i| {
+                let hash = if i == 27 { &special } else { FILLER };
+                format!("{}{RECORD_SEPARATOR}\n", record(hash, "Jo", ""))
+            })
+            .collect::<String>();
+        let options = ClassifyOptions {
+            whole_history: false,
+            ..Default::default()
+        };
+        let commits = parse_log(&output, &options);
+        assert!(commits.iter().all(|c| c.rarity.tier == RarityTier::Common));
+    }
+
+    /// A commit as `git log` prints it for [`parse_log`].
+    fn record(hash: &str, author: &str, message: &str) -> String {
+        [
+            hash,
+            "2001-02-03T04:05:06+00:00",
+            author,
+            "jo@example.com",
+            message,
+        ]
+        .join(&FIELD_SEPARATOR.to_string())
+    }
+
+    #[test]
+    fn parses_git_log_records() {
+        let output = format!(
+            "{}{RECORD_SEPARATOR}\n{}{RECORD_SEPARATOR}",
+            record(FILLER, "John Doe", "Subject\n\nBody"),
+            record(FILLER, "Jo", "Next"),
+        );
+        let commits = parse_log(&output, &ClassifyOptions::default());
+        assert_eq!(commits.len(), 2);
+        assert_eq!(commits[0].author, "John Doe");
+        assert_eq!(commits[1].author, "Jo");
+    }
+
+    #[test]
+    fn crlf_line_endings_are_ignored() {
+        let output = format!(
+            "{}{RECORD_SEPARATOR}\r\n{}{RECORD_SEPARATOR}\r\n",
+            record(FILLER, "John Doe", "Subject\r\n\r\nBody"),
+            record(FILLER, "Jo", "Next"),
+        );
+        let commits = parse_log(&output, &ClassifyOptions::default());
+        assert_eq!(commits.len(), 2);
+        assert_eq!(commits[1].author, "Jo");
+
+        let input = format!("{FILLER} 2001-02-03T04:05:06+00:00 Jo\r\n{FILLER}\r\n\r\n");
+        let (lines, invalid) = read_hashes(input.as_bytes(), &ClassifyOptions::default()).unwrap();
+        assert_eq!(invalid, 0);
+        assert!(matches!(&lines[0], HashLine::Commit(commit) if commit.author == "Jo"));
+        assert!(matches!(&lines[1], HashLine::Hash(row) if row.hash == FILLER));
+    }
+
+    #[test]
+    fn authors_are_read_verbatim() {
+        for author in [
+            "Mary  Ann   Smith",
+            "Tab\tSeparated",
+            "Zoë Ångström 李",
+            " ",
+        ] {
+            let record = record(FILLER, author, "Fix it\n\nFixes #1");
+            let (hash, _, parsed, email, message) = split_commit(&record).unwrap();
+            assert_eq!(hash, FILLER);
+            assert_eq!(parsed, author);
+            assert_eq!(email, "jo@example.com");
+            assert_eq!(message, "Fix it\n\nFixes #1");
+        }
+    }
+
+    #[test]
+    fn messages_may_look_like_fields() {
+        let message = format!("Subject{FIELD_SEPARATOR}not a field");
+        let record = record(FILLER, "Jo", &message);
+        let (_, _, author, _, parsed) = split_commit(&record).unwrap();
+        assert_eq!(author, "Jo");
+        assert_eq!(parsed, message);
+    }
+
+    #[test]
+    fn lines_without_fields_are_read_by_whitespace() {
+        let line = format!("{FILLER} 2001-02-03T04:05:06+00:00 Mary  Ann\tSmith");
+        let (hash, datetime, author, email, message) = split_commit(&line).unwrap();
+        assert_eq!(hash, FILLER);
+        assert_eq!(datetime.to_rfc3339(), "2001-02-03T04:05:06+00:00");
+        assert_eq!(author, "Mary Ann Smith");
+        assert_eq!((email, message), ("", ""));
+        assert!(split_commit(&format!("{FILLER} yesterday Jo")).is_none());
+        assert!(split_commit(&format!("{FILLER}{FIELD_SEPARATOR}yesterday")).is_none());
+    }
+
+    #[test]
+    fn stdin_lines_may_have_fields() {
+        let line = record(FILLER, "Mary  Ann", "");
+        let Some(HashLine::Commit(commit)) = parse_hash_line(&line, &ClassifyOptions::default())
+        else {
+            panic!("{line:?} wasn't read as a commit");
+        };
+        assert_eq!(commit.hash, FILLER);
+        assert_eq!(commit.author, "Mary  Ann");
+    }
+
+    #[test]
+    fn reports_every_matching_rule() {
+        let hash = format!("830517264{}fedcbafed", &FILLER[9..31]);
+        let rarity = classify(&hash);
+        assert_eq!(
+            display_explanations(&rarity.matches),
+            "Ends with 9 letters: 'fedcbafed' at offset 31; \
+             Starts with 11 digits: '83051726448' at offset 0"
+        );
+        assert!(rarity.tier == RarityTier::Rare);
+        // The two runs don't overlap, so their probabilities multiply.
+        assert_eq!(
+            rarity.percentage,
+            Some(
+                probability::all_of(probability::LETTER, 9)
+                    * probability::all_of(probability::DIGIT, 11)
+            )
+        );
+    }
+
+    #[test]
+    fn independent_matches_escalate() {
+        let hash = format!("830517264{}c0ffee{}", &FILLER[9..20], &FILLER[26..]);
+        let rarity = classify(&hash);
+        assert_eq!(
+            display_explanations(&rarity.matches),
+            "Contains hexspeak 'c0ffee' at offset 20; \
+             Starts with 11 digits: '83051726448' at offset 0"
+        );
+        assert!(rarity
+            .matches
+            .iter()
+            .all(|m| m.tier == RarityTier::Uncommon));
+        assert!(rarity.tier == RarityTier::Rare);
+        assert_eq!(
+            rarity.percentage,
+            Some(probability::contains("c0ffee", 40) * probability::all_of(probability::DIGIT, 11))
+        );
+    }
+
+    #[test]
+    fn overlapping_matches_do_not_escalate() {
+        // The same nine digits both start the hash and form a palindrome.
+        let rarity = classify(&with_prefix("835101538"));
+        assert_eq!(
+            display_explanations(&rarity.matches),
+            "First 9 characters form a palindrome: '835101538' at offset 0; \
+             Starts with 11 digits: '83510153848' at offset 0"
+        );
+        assert!(rarity.tier == RarityTier::Uncommon);
+        assert_eq!(rarity.percentage, Some(probability::palindrome(9)));
+    }
+
+    #[test]
+    fn common_hash_has_no_matches() {
+        let rarity = classify(FILLER);
+        assert!(rarity.matches.is_empty());
+        assert!(rarity.tier == RarityTier::Common);
+    }
+
+    #[test]
+    fn custom_rules_are_classified_with_built_ins() {
+        let args = CliArgs::try_parse_from([
+            "git-rare",
+            "--rule",
+            "rare:^7e3a",
+            "--rule",
+            "legendary:c3b7$",
+            "--rule",
+            "uncommon:^1337",
+        ])
+        .unwrap();
+        let options = ClassifyOptions {
+            rules: args.rules,
+            ..Default::default()
+        };
+        let commit = parse_commit(
+            &format!("{FILLER} 2001-02-03T04:05:06+00:00 Jo"),
+            1,
+            &options,
+        );
+        let rarity = commit.unwrap().rarity;
+        assert!(rarity.tier == RarityTier::Legendary);
+        assert_eq!(
+            display_explanations(&rarity.matches),
+            "c3b7$: 'c3b7' at offset 36; ^7e3a: '7e3a' at offset 0"
+        );
+        assert_eq!(rarity.percentage, None);
+    }
+
+    #[test]
+    fn overrides_remap_built_in_rules() {
+        let hash = with_digit_run(9);
+        assert!(classify(&hash).tier == RarityTier::Uncommon);
+        let options = |rule_override| ClassifyOptions {
+            overrides: HashMap::from([("starts_digits".to_string(), rule_override)]),
+            ..Default::default()
+        };
+        let context = CommitContext {
+            hash: &hash,
+            datetime: Some(&DateTime::parse_from_rfc3339("2001-02-03T04:05:06+00:00").unwrap()),
+            author: "Jo",
+            message: "",
+            ordinal: 1,
+        };
+        let rarity = Commit::get_rarity(
+            &context,
+            &options(RuleOverride {
+                tier: Some(RarityTier::Common),
+                probability: None,
+            }),
+        );
+        assert!(rarity.tier == RarityTier::Common);
+        let rarity = Commit::get_rarity(
+            &context,
+            &options(RuleOverride {
+                tier: Some(RarityTier::Legendary),
+                probability: Some(1e-9),
+            }),
+        );
+        assert!(rarity.tier == RarityTier::Legendary);
+        assert_eq!(rarity.percentage, Some(1e-9));
+    }
+
+    #[test]
+    fn disabled_rules_are_not_evaluated() {
+        let hash = with_digit_run(9);
+        assert!(classify(&hash).tier == RarityTier::Uncommon);
+        let args =
+            CliArgs::try_parse_from(["git-rare", "--disable-rule", "starts_digits"]).unwrap();
+        let options = ClassifyOptions {
+            registry: rule_registry(&args.disabled_rules),
+            ..Default::default()
+        };
+        let line = format!("{hash} 2001-02-03T04:05:06+00:00 Jo");
+        let rarity = parse_commit(&line, 1, &options).unwrap().rarity;
+        assert!(rarity.tier == RarityTier::Common);
+        assert!(rarity.matches.is_empty());
+    }
+
+    #[test]
+    fn unknown_disabled_rules_list_the_valid_names() {
+        let err = CliArgs::try_parse_from(["git-rare", "--disable-rule", "starts_nine_digits"])
+            .err()
+            .unwrap()
+            .to_string();
+        assert!(
+            err.contains("unknown rule 'starts_nine_digits', expected one of: leading_zeros,"),
+            "{err}"
+        );
+        assert!(err.contains("starts_digits"), "{err}");
+    }
+
+    #[test]
+    fn backend_defaults_to_the_library_when_built_with_it() {
+        let args = CliArgs::try_parse_from(["git-rare"]).unwrap();
+        assert_eq!(args.backend, Backend::default());
+        #[cfg(feature = "libgit2")]
+        assert_eq!(args.backend, Backend::Libgit2);
+        let args = CliArgs::try_parse_from(["git-rare", "--backend", "git"]).unwrap();
+        assert_eq!(args.backend, Backend::Git);
+    }
+
+    #[test]
+    fn repos_files_resolve_paths_against_their_directory() {
+        let dir = tempfile::tempdir().unwrap();
+        let list = dir.path().join("repos.txt");
+        std::fs::write(&list, "# Work\n  api  \n\n/src/web\n").unwrap();
+        assert_eq!(
+            read_repos_file(&list).unwrap(),
+            [dir.path().join("api"), PathBuf::from("/src/web")]
+        );
+        assert!(read_repos_file(&dir.path().join("missing.txt")).is_err());
+    }
+
+    #[test]
+    fn ref_set_flags_are_walked_before_revisions() {
+        let args =
+            CliArgs::try_parse_from(["git-rare", "--all-refs", "--branches", "^v1.0"]).unwrap();
+        assert_eq!(
+            args.walk(),
+            ["--branches", "--exclude=refs/stash", "--all", "^v1.0"]
+        );
+        assert!(CliArgs::try_parse_from(["git-rare"])
+            .unwrap()
+            .walk()
+            .is_empty());
+        let args = CliArgs::try_parse_from(["git-rare", "--tags"]).unwrap();
+        assert_eq!(args.walk(), ["--tags", "HEAD"]);
+        let args = CliArgs::try_parse_from(["git-rare", "--tags", "main"]).unwrap();
+        assert_eq!(args.walk(), ["--tags", "main"]);
+    }
+
+    #[test]
+    fn stdin_lines_are_classified_with_any_commit_fields() {
+        let options = ClassifyOptions::default();
+        let hash = "1234567890123456789012345678901234567890";
+        let lines = [
+            hash.to_string(),
+            format!("{} trailing text", hash.to_uppercase()),
+            format!("{hash} 2001-02-03T04:05:06+00:00 Jane Doe"),
+            "not a hash".to_string(),
+            "1234567".to_string(),
+            String::new(),
+        ];
+        let (parsed, invalid) = read_hashes(lines.join("\n").as_bytes(), &options).unwrap();
+        assert_eq!(invalid, 2);
+        assert_eq!(parsed.len(), 3);
+        for line in &parsed[..2] {
+            let HashLine::Hash(row) = line else {
+                panic!("expected a hash without commit fields");
+            };
+            assert_eq!(row.hash, hash);
+            assert!(row.rarity.tier == RarityTier::Legendary);
+        }
+        let HashLine::Commit(commit) = &parsed[2] else {
+            panic!("expected a commit");
+        };
+        assert_eq!(commit.author, "Jane Doe");
+        assert!(commit.rarity == parse_commit(&lines[2], 0, &options).unwrap().rarity);
+    }
+
+    #[test]
+    fn stdin_cannot_be_combined_with_a_repository() {
+        assert!(CliArgs::try_parse_from(["git-rare", "--stdin"]).is_ok());
+        assert!(CliArgs::try_parse_from(["git-rare", "--stdin", "-C", "repo"]).is_err());
+        assert!(CliArgs::try_parse_from(["git-rare", "--stdin", "HEAD~3.."]).is_err());
+    }
+
+    #[test]
+    fn dates_parse_absolute_and_relative_forms() {
+        let date = |s| DateTime::parse_from_rfc3339(s).unwrap();
+        assert_eq!(
+            parse_date("2024-03-04T05:06:07+02:00"),
+            Ok(date("2024-03-04T05:06:07+02:00"))
+        );
+        assert_eq!(parse_date("2024-03-04"), Ok(date("2024-03-04T00:00:00Z")));
+        assert_eq!(
+            parse_date(" 2024-03-04 05:06 "),
+            Ok(date("2024-03-04T05:06:00Z"))
+        );
+        let now = date("2024-03-31T12:00:00Z");
+        assert_eq!(
+            parse_relative_date("2 weeks ago", now),
+            Some(date("2024-03-17T12:00:00Z"))
+        );
+        assert_eq!(
+            parse_relative_date("1 month ago", now),
+            Some(date("2024-02-29T12:00:00Z"))
+        );
+        assert_eq!(
+            parse_relative_date("1 year ago", now),
+            Some(date("2023-03-31T12:00:00Z"))
+        );
+        assert_eq!(parse_relative_date("3 fortnights ago", now), None);
+        assert_eq!(parse_relative_date("2 weeks", now), None);
+        assert_eq!(parse_relative_date("4294967295 weeks ago", now), None);
+    }
+
+    #[test]
+    fn invalid_dates_show_the_accepted_forms() {
+        let error = parse_date("2024-13-01").unwrap_err();
+        assert!(error.starts_with("invalid date '2024-13-01'"), "{error}");
+        assert!(error.contains("2 weeks ago"), "{error}");
+        assert!(CliArgs::try_parse_from(["git-rare", "--since", "last tuesday"]).is_err());
+    }
+
+    #[test]
+    fn date_ranges_must_be_in_order() {
+        let args = |since, until| {
+            CliArgs::try_parse_from(["git-rare", "--since", since, "--until", until]).unwrap()
+        };
+        let dates = args("2024-01-01", "2024-02-01").dates().unwrap();
+        assert_eq!(
+            describe_dates(&dates).unwrap(),
+            "from 2024-01-01 00:00:00 +00:00 to 2024-02-01 00:00:00 +00:00"
+        );
+        assert!(dates.contains(&dates.since.unwrap()));
+        assert!(dates.contains(&dates.until.unwrap()));
+        let error = args("2024-02-01", "2024-01-01").dates().unwrap_err();
+        assert!(error.to_string().contains("is before --since"), "{error}");
+        assert!(describe_dates(&DateRange::default()).is_none());
+    }
+
+    #[test]
+    fn paths_are_given_with_path_or_after_a_separator() {
+        let args = CliArgs::try_parse_from([
+            "git-rare",
+            "--path",
+            "crates/core",
+            "v1.0..",
+            "--",
+            ":!vendor/",
+            "docs",
+        ])
+        .unwrap();
+        assert_eq!(args.revisions, ["v1.0.."]);
+        let log = args.log_options().unwrap();
+        assert_eq!(log.paths, ["crates/core", ":!vendor/", "docs"]);
+        assert!(!log.is_unfiltered());
+        assert!(CliArgs::try_parse_from(["git-rare", "--stdin", "--", "docs"]).is_err());
+    }
+
+    #[test]
+    fn n_is_short_for_the_near_miss_limit() {
+        let args = CliArgs::try_parse_from(["git-rare", "--near-misses", "-n", "3"]).unwrap();
+        assert_eq!((args.limit, args.max_count), (3, None));
+        let args = CliArgs::try_parse_from(["git-rare", "--max-count", "5"]).unwrap();
+        let log = args.log_options().unwrap();
+        assert_eq!(log.max_count, Some(5));
+        // Only some of the history is read, but commits are still numbered
+        // by their position in all of it.
+        assert!(log.is_unfiltered());
+    }
+
+    #[test]
+    fn merge_filters_select_by_parent_count() {
+        let log = |flag| {
+            let args = CliArgs::try_parse_from(["git-rare", "--tags", flag]).unwrap();
+            args.log_options().unwrap()
+        };
+        let (no_merges, merges_only) = (log("--no-merges"), log("--merges-only"));
+        assert_eq!(no_merges.merges, Merges::Exclude);
+        assert!(no_merges.merges.allows(1) && !no_merges.merges.allows(2));
+        assert!(!merges_only.merges.allows(0) && merges_only.merges.allows(3));
+        assert!(no_merges.allows_tag_objects());
+        assert!(!merges_only.allows_tag_objects());
+        assert!(!merges_only.is_unfiltered());
+    }
+
+    #[test]
+    fn invalid_custom_rules_fail_to_parse() {
+        assert!(CliArgs::try_parse_from(["git-rare", "--rule", "epic:^1337"]).is_err());
+        assert!(CliArgs::try_parse_from(["git-rare", "--rule", "rare:(1337"]).is_err());
+    }
+
+    #[test]
+    fn only_tier_names_are_painted_and_marked() {
+        let output = Output::new(None, None).unwrap();
+        let symbols = HashMap::from([(RarityTier::Rare, "R".to_string())]);
+        let timings = Timings::default();
+        let args = CliArgs::try_parse_from(["git-rare", "--color", "always"]).unwrap();
+        let print = PrintOptions::new(
+            &args,
+            &timings,
+            &output,
+            &symbols,
+            Some(TableStyle::Rounded),
+        );
+        assert_eq!(print.tier("Rare"), "\x1b[35mRare\x1b[0m");
+        assert_eq!(print.tier("Common"), "Common");
+        assert_eq!(print.tier("Tier"), "Tier");
+
+        let args = CliArgs::try_parse_from(["git-rare", "--color", "never", "--emoji"]).unwrap();
+        let print = PrintOptions::new(
+            &args,
+            &timings,
+            &output,
+            &symbols,
+            Some(TableStyle::Rounded),
+        );
+        assert_eq!(print.tier("Rare"), "R Rare");
+        assert_eq!(print.tier("Legendary"), "🌟 Legendary");
+        assert_eq!(print.tier("Tier"), "Tier");
+    }
 }
@@ -0,0 +1,92 @@
+//! An opt-in pack of meme numbers that can appear in a hash.
+
+/// Memes shorter than this turn up by chance in most repositories, so they
+/// are only reported when they stand out.
+pub const MIN_ANYWHERE_LEN: usize = 6;
+
+/// Meme numbers and what they are known as.
+pub const MEMES: &[(&str, &str)] = &[
+    ("1337", "leet"),
+    ("80085", "BOOBS"),
+    ("8008135", "BOOBIES"),
+    ("42069", "nice, blazed"),
+    ("69420", "nice, blazed"),
+    ("420420", "blazed twice"),
+    ("696969", "nice nice nice"),
+    ("666666", "the number of the beast, twice"),
+    ("8675309", "Jenny's number"),
+    ("31337", "eleet"),
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Position {
+    Start,
+    End,
+    Inside,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct MemeMatch {
+    pub digits: &'static str,
+    pub name: &'static str,
+    /// Where the first occurrence is, preferring the start or end of the hash.
+    pub position: Position,
+    /// Byte offset of the occurrence `position` describes.
+    pub offset: usize,
+    /// Number of non-overlapping occurrences.
+    pub count: usize,
+}
+
+/// Finds every meme number in `hash`.
+pub fn find(hash: &str) -> Vec<MemeMatch> {
+    MEMES
+        .iter()
+        .filter_map(|&(digits, name)| {
+            let count = hash.matches(digits).count();
+            let (position, offset) = if hash.starts_with(digits) {
+                (Position::Start, 0)
+            } else if hash.ends_with(digits) {
+                (Position::End, hash.len() - digits.len())
+            } else {
+                (Position::Inside, hash.find(digits)?)
+            };
+            Some(MemeMatch {
+                digits,
+                name,
+                position,
+                offset,
+                count,
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn finds_memes_and_positions() {
+        let found = find("1337a91c5d48b2f6e1a9e73cb5d284e6f8675309");
+        assert_eq!(found.len(), 2);
+        assert_eq!(found[0].digits, "1337");
+        assert_eq!(found[0].position, Position::Start);
+        assert_eq!(found[1].name, "Jenny's number");
+        assert_eq!(found[1].position, Position::End);
+        assert_eq!(found[1].offset, 33);
+    }
+
+    #[test]
+    fn counts_repeats() {
+        let found = find("7e3a91c5d4133748b2f6e1a9e1337cb5d284e6f1");
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].position, Position::Inside);
+        assert_eq!(found[0].offset, 10);
+        assert_eq!(found[0].count, 2);
+    }
+
+    #[test]
+    fn ignores_hashes_without_memes() {
+        assert!(find("7e3a91c5d48b2f6e1a9e73cb5d284e6f10a9c3b7").is_empty());
+    }
+}
@@ -0,0 +1,146 @@
+//! Probabilities as the odds the Odds column shows, like "1 in 4,096", which
+//! read more easily than a float with an exponent.
+
+/// Names of the powers of a thousand from a billion on, for odds too long
+/// to read in full.
+const SCALES: [(f64, &str); 9] = [
+    (1e9, "billion"),
+    (1e12, "trillion"),
+    (1e15, "quadrillion"),
+    (1e18, "quintillion"),
+    (1e21, "sextillion"),
+    (1e24, "septillion"),
+    (1e27, "octillion"),
+    (1e30, "nonillion"),
+    (1e33, "decillion"),
+];
+
+/// 10 to the power of `log10`, to two significant digits with an exponent,
+/// like "6.8e-86", which a float may be too large or too small to hold.
+pub fn scientific(log10: f64) -> String {
+    let mut exponent = log10.floor();
+    let mut mantissa = (10f64.powf(log10 - exponent) * 10.0).round() / 10.0;
+    if mantissa >= 10.0 {
+        mantissa = 1.0;
+        exponent += 1.0;
+    }
+    let mantissa = format!("{mantissa:.1}");
+    format!("{}e{exponent}", mantissa.trim_end_matches(".0"))
+}
+
+/// `n`, rounded to a whole number, with a comma between each group of
+/// three digits.
+fn separated(n: f64) -> String {
+    let digits = format!("{:.0}", n.round());
+    let mut separated = String::with_capacity(digits.len() * 4 / 3);
+    for (i, digit) in digits.chars().enumerate() {
+        if i > 0 && (digits.len() - i) % 3 == 0 {
+            separated.push(',');
+        }
+        separated.push(digit);
+    }
+    separated
+}
+
+/// The odds of something of `probability` as "1 in N": N to a tenth below a
+/// hundred, whole up to a billion, and in billions, trillions and so on
+/// beyond that, to two significant digits. Past a thousand decillion, which
+/// has no name as common, N has an exponent instead, like "1 in 1.5e48".
+/// Empty without a probability, or with one of zero, as for Common commits.
+pub fn display(probability: &Option<f64>) -> String {
+    let Some(probability) = probability.filter(|p| *p > 0.0) else {
+        return String::new();
+    };
+    let n = 1.0 / probability.min(1.0);
+    if n >= 1e36 {
+        return format!("1 in {}", scientific(n.log10()));
+    }
+    if n < 100.0 {
+        let tenths = format!("{:.1}", n);
+        return format!("1 in {}", tenths.trim_end_matches(".0"));
+    }
+    let Some((scale, name)) = SCALES.iter().rev().find(|(scale, _)| n >= *scale) else {
+        return format!("1 in {}", separated(n));
+    };
+    let scaled = n / scale;
+    let scaled = if scaled < 10.0 {
+        format!("{:.1}", scaled).trim_end_matches(".0").to_string()
+    } else {
+        // Two significant digits, but no fewer than the whole number has.
+        let magnitude = 10f64.powi(scaled.log10().floor() as i32 - 1);
+        separated((scaled / magnitude).round() * magnitude)
+    };
+    format!("1 in {scaled} {name}")
+}
+
+/// The odds of something whose probability is 10 to the power of `log10`,
+/// like [`display`] shows them, and also when the probability is too small
+/// for a float.
+pub fn display_log10(log10: Option<f64>) -> String {
+    match log10 {
+        Some(log10) if log10 <= -36.0 => format!("1 in {}", scientific(-log10)),
+        log10 => display(&log10.map(|log10| 10f64.powf(log10))),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn odds(probability: f64) -> String {
+        display(&Some(probability))
+    }
+
+    #[test]
+    fn likely_odds_have_a_tenth() {
+        assert_eq!(odds(1.0), "1 in 1");
+        assert_eq!(odds(0.9), "1 in 1.1");
+        assert_eq!(odds(0.5), "1 in 2");
+        assert_eq!(odds(0.3), "1 in 3.3");
+        assert_eq!(odds(1.0 / 16.0), "1 in 16");
+    }
+
+    #[test]
+    fn odds_below_a_billion_are_whole() {
+        assert_eq!(odds(0.001), "1 in 1,000");
+        assert_eq!(odds(1.0 / 4096.0), "1 in 4,096");
+        assert_eq!(odds(1.0 / 68_719_476.0), "1 in 68,719,476");
+        assert_eq!(odds(1.0 / 999_999_999.0), "1 in 999,999,999");
+    }
+
+    #[test]
+    fn tiny_probabilities_are_named() {
+        assert_eq!(odds(1.0 / 68_719_476_736.0), "1 in 69 billion");
+        assert_eq!(odds(1.5e-11), "1 in 67 billion");
+        assert_eq!(odds(1.0 / 1.5e9), "1 in 1.5 billion");
+        assert_eq!(odds(1e-12), "1 in 1 trillion");
+        assert_eq!(odds(1.0 / 123e15), "1 in 120 quadrillion");
+        assert_eq!(odds(1.0 / 999e33), "1 in 1,000 decillion");
+    }
+
+    #[test]
+    fn odds_past_the_names_have_an_exponent() {
+        assert_eq!(odds(1e-36), "1 in 1e36");
+        // The last of a full SHA-1 hash's 16^40 values.
+        assert_eq!(odds(16f64.powi(-40)), "1 in 1.5e48");
+        // Like the odds of hundreds of commits turning up together.
+        assert_eq!(odds(1.47e-86), "1 in 6.8e85");
+        assert_eq!(odds(f64::MIN_POSITIVE), "1 in 4.5e307");
+    }
+
+    #[test]
+    fn odds_too_small_for_a_float_have_an_exponent() {
+        assert_eq!(display_log10(Some(-1234.17)), "1 in 1.5e1234");
+        assert_eq!(display_log10(Some(-85.17)), "1 in 1.5e85");
+        assert_eq!(display_log10(Some(-2.0)), "1 in 100");
+        assert_eq!(display_log10(None), "");
+        assert_eq!(scientific(-85.17), "6.8e-86");
+        assert_eq!(scientific(2.99999), "1e3");
+    }
+
+    #[test]
+    fn commons_have_no_odds() {
+        assert_eq!(display(&None), "");
+        assert_eq!(odds(0.0), "");
+    }
+}
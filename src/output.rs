@@ -0,0 +1,1279 @@
+//! The machine-readable formats of `--format`, for piping a scan into other
+//! tools. A scan is described by its parameters, the commits it found and
+//! their counts, with each commit flattened into a [`CommitRecord`].
+
+mod html;
+
+use std::fs::{self, File};
+use std::io::{self, BufWriter, Write};
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, PoisonError};
+
+use anyhow::{bail, Context, Result};
+use chrono::{DateTime, FixedOffset, SecondsFormat, SubsecRound, Utc};
+use clap::ValueEnum;
+use serde::Serialize;
+
+use crate::backend::{self, Backend};
+use crate::pager;
+use crate::sort::Sortable;
+use crate::{
+    display_explanations, display_percentage, near_misses, ClassifyOptions, CliArgs, Commit, Count,
+    HashRow, Labels, NearMiss, ObjectKind, ObjectMatch, Rarity, RarityTier, Scan, Shown,
+    DEFAULT_ABBREV,
+};
+
+/// How what's found is printed.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default, ValueEnum)]
+pub enum Format {
+    /// A table for reading in a terminal.
+    #[default]
+    Table,
+    /// A single JSON document.
+    Json,
+    /// The document of `json` as YAML.
+    Yaml,
+    /// A JSON object per line for each commit, followed by one with the
+    /// counts.
+    Ndjson,
+    /// Comma-separated values with a header row, or separated by
+    /// `--delimiter`, and the footer of the table in a last row with
+    /// `--footer`.
+    Csv,
+    /// A GitHub-flavored Markdown table, with each hash linked to its commit
+    /// when the repository's origin is on GitHub or GitLab.
+    Markdown,
+    /// A self-contained HTML page with the counts and a sortable table of
+    /// the commits, their matches marked in each hash.
+    Html,
+    /// A line of tab-separated fields per commit, without a header unless
+    /// `--header` asks for one: its hash, tier, probability, datetime,
+    /// author and explanation, in that order, followed by the optional
+    /// fields of [`Fields`] and, last, the author's email. These are kept as
+    /// they are from one release to the next, with new fields only ever
+    /// added at the end.
+    Plain,
+}
+
+/// A column of the commits that `--columns` can choose, in the table, CSV
+/// and Markdown.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, ValueEnum)]
+pub enum Column {
+    Hash,
+    Author,
+    Datetime,
+    Tier,
+    Explanation,
+    /// The probability of the commit's rarity, which CSV names
+    /// `probability`. The table shows it only when asked to.
+    Percentage,
+    /// The probability as the odds of a hash so rare, like "1 in 4,096",
+    /// which the table shows by default and CSV only when asked to.
+    Odds,
+    /// The first line of the commit's message, otherwise shown with
+    /// `--show-subject`.
+    Subject,
+    /// The author's email, otherwise shown with `--show-email`.
+    Email,
+}
+
+impl Column {
+    /// Whether it's the column with `name` in a header, of the table or of
+    /// CSV.
+    fn names(self, name: &str) -> bool {
+        let own = self.to_possible_value().expect("no column is skipped");
+        own.get_name().eq_ignore_ascii_case(name)
+            || (self == Self::Percentage && name == "probability")
+    }
+}
+
+/// The order to print the columns of `header` in with `--columns`: those
+/// before the first a [`Column`] names as they are, like the repository,
+/// then those `columns` chooses in its order, then the rest no [`Column`]
+/// names, like the Object and Path columns of `--objects`.
+pub fn column_order(header: &[&str], columns: &[Column]) -> Vec<usize> {
+    let choosable = |name: &str| Column::value_variants().iter().any(|c| c.names(name));
+    let first = header
+        .iter()
+        .position(|name| choosable(name))
+        .unwrap_or(header.len());
+    let chosen = columns
+        .iter()
+        .filter_map(|column| header.iter().position(|name| column.names(name)));
+    (0..first)
+        .chain(chosen)
+        .chain((first..header.len()).filter(|&i| !choosable(header[i])))
+        .collect()
+}
+
+/// The cells of `row` in `order`, or as they are when there's none.
+fn reorder<T: AsRef<str>>(row: &[T], order: Option<&[usize]>) -> Vec<String> {
+    match order {
+        Some(order) => order.iter().map(|&i| row[i].as_ref().to_string()).collect(),
+        None => row.iter().map(|cell| cell.as_ref().to_string()).collect(),
+    }
+}
+
+impl Format {
+    /// Whether it's printed as a table by [`crate::print_table`], rather than
+    /// by this module.
+    pub fn is_table(self) -> bool {
+        matches!(self, Self::Table | Self::Markdown)
+    }
+}
+
+/// Where what's found is printed: stdout, or the file `--output` names.
+///
+/// A table printed to a terminal is held until the scan is done, then paged
+/// when it doesn't fit. The file is written under a temporary name next to
+/// it, and only renamed over it by [`Output::finish`], so a scan that fails
+/// or is interrupted leaves whatever the path held before rather than half
+/// a report.
+pub struct Output {
+    destination: Mutex<Destination>,
+}
+
+struct Destination {
+    writer: Box<dyn Write + Send>,
+    /// The first error writing failed with, returned by [`Output::finish`]
+    /// since printing is done where there's no error to return.
+    error: Option<io::Error>,
+    /// The temporary file being written and the `--output` path it's
+    /// renamed to.
+    file: Option<(PathBuf, PathBuf)>,
+    /// The pager to show what's printed with, and what's been printed.
+    pager: Option<(String, Vec<u8>)>,
+}
+
+impl Output {
+    /// Prints to the file at `path`, creating or truncating it when done, or
+    /// to stdout without one, through `pager` when it's too long for the
+    /// terminal.
+    pub fn new(path: Option<&Path>, pager: Option<String>) -> Result<Self> {
+        let destination = match path {
+            None => Destination {
+                writer: Box::new(io::stdout()),
+                error: None,
+                file: None,
+                pager: pager.map(|command| (command, vec![])),
+            },
+            Some(path) => {
+                let Some(name) = path.file_name() else {
+                    bail!("--output {} isn't the path of a file", path.display());
+                };
+                let mut temporary = std::ffi::OsString::from(".");
+                temporary.push(name);
+                temporary.push(format!(".{}.tmp", std::process::id()));
+                let temporary = path.with_file_name(temporary);
+                let file = File::create(&temporary)
+                    .with_context(|| format!("couldn't write {}", path.display()))?;
+                Destination {
+                    writer: Box::new(BufWriter::new(file)),
+                    error: None,
+                    file: Some((temporary, path.to_path_buf())),
+                    pager: None,
+                }
+            }
+        };
+        Ok(Self {
+            destination: Mutex::new(destination),
+        })
+    }
+
+    /// Whether it goes to a file rather than stdout.
+    pub fn is_file(&self) -> bool {
+        self.destination().file.is_some()
+    }
+
+    /// Prints `text`, unless printing already failed.
+    pub fn print(&self, text: &str) {
+        let mut destination = self.destination();
+        if let Some((_, held)) = &mut destination.pager {
+            held.extend_from_slice(text.as_bytes());
+        } else if destination.error.is_none() {
+            destination.error = destination.writer.write_all(text.as_bytes()).err();
+        }
+    }
+
+    /// Prints `text` followed by a newline, like `println!`.
+    pub fn println(&self, text: impl std::fmt::Display) {
+        self.print(&format!("{text}\n"));
+    }
+
+    /// Flushes what's been printed and moves the file into place, or pages
+    /// it, or returns the error printing failed with.
+    pub fn finish(&self) -> Result<()> {
+        let mut destination = self.destination();
+        if let Some((command, held)) = destination.pager.take() {
+            return pager::show(&command, &held).context("couldn't write to stdout");
+        }
+        let flushed = match destination.error.take() {
+            Some(e) => Err(e),
+            None => destination.writer.flush(),
+        };
+        let file = destination.file.take();
+        let Some((temporary, path)) = file else {
+            // Like the pager, a reader such as `head` can stop reading
+            // before the end without that being an error.
+            return match flushed {
+                Err(e) if e.kind() == io::ErrorKind::BrokenPipe => Ok(()),
+                flushed => flushed.context("couldn't write to stdout"),
+            };
+        };
+        // Nothing is left to write, and the file has to be closed before
+        // Windows lets it be renamed.
+        destination.writer = Box::new(io::sink());
+        flushed
+            .and_then(|()| fs::rename(&temporary, &path))
+            .inspect_err(|_| {
+                let _ = fs::remove_file(&temporary);
+            })
+            .with_context(|| format!("couldn't write {}", path.display()))
+    }
+
+    fn destination(&self) -> std::sync::MutexGuard<'_, Destination> {
+        self.destination
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+impl Drop for Output {
+    /// Removes the temporary file of a scan that failed before it was done.
+    fn drop(&mut self) {
+        let destination = self
+            .destination
+            .get_mut()
+            .unwrap_or_else(PoisonError::into_inner);
+        if let Some((temporary, _)) = destination.file.take() {
+            destination.writer = Box::new(io::sink());
+            let _ = fs::remove_file(temporary);
+        }
+    }
+}
+
+/// Parses a `--delimiter` value: one ASCII character, or `\t` for a tab.
+pub fn parse_delimiter(value: &str) -> Result<u8, String> {
+    match value.as_bytes() {
+        b"\\t" => Ok(b'\t'),
+        [delimiter] if delimiter.is_ascii() => Ok(*delimiter),
+        _ => Err(format!(
+            "expected one ASCII character or \\t for a tab, got '{value}'"
+        )),
+    }
+}
+
+/// The fields `--format plain` starts each line of commits with, in the
+/// order it promises to keep.
+const PLAIN_FIELDS: [&str; 6] = [
+    "hash",
+    "tier",
+    "probability",
+    "datetime",
+    "author",
+    "explanation",
+];
+
+/// The fields it starts each line of near misses with.
+const PLAIN_NEAR_MISS_FIELDS: [&str; 5] = ["hash", "near_miss", "closeness", "datetime", "author"];
+
+/// The fields it starts each line of counts with.
+const PLAIN_COUNT_FIELDS: [&str; 6] = [
+    "total",
+    "common",
+    "uncommon",
+    "rare",
+    "legendary",
+    "shallow",
+];
+
+/// Escapes a field of `--format plain`, so that a tab or newline in it can't
+/// be taken for the end of the field or line.
+fn escape_plain(field: &str) -> String {
+    let mut escaped = String::with_capacity(field.len());
+    for c in field.chars() {
+        match c {
+            '\\' => escaped.push_str("\\\\"),
+            '\t' => escaped.push_str("\\t"),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => escaped.push_str("\\r"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// Prints `rows` as the lines of `--format plain`, with the fields named in
+/// `leading` first, in that order, and the rest of those in `header` after
+/// them in theirs. The header itself is only printed with `--header`.
+fn print_plain(
+    args: &CliArgs,
+    output: &Output,
+    header: &[&str],
+    rows: &[Vec<String>],
+    leading: &[&str],
+) {
+    let order = leading
+        .iter()
+        .filter_map(|name| header.iter().position(|column| column == name))
+        .chain((0..header.len()).filter(|&i| !leading.contains(&header[i])))
+        .collect::<Vec<usize>>();
+    let header = header.iter().map(|name| name.to_string()).collect();
+    let lines = args.header.then_some(&header).into_iter().chain(rows);
+    for row in lines {
+        let fields = order
+            .iter()
+            .map(|&i| escape_plain(&row[i]))
+            .collect::<Vec<String>>();
+        output.println(fields.join("\t"));
+    }
+}
+
+/// Escapes the text of a Markdown table's cell: a pipe would end the cell
+/// early, and a newline the row.
+pub fn escape_markdown(text: &str) -> String {
+    text.replace('|', "\\|").replace('\n', "<br>")
+}
+
+/// The URL the commits of `repo`, or the current directory, can be seen at,
+/// followed by a hash, when its `origin` remote is on GitHub or GitLab.
+pub fn commit_url(backend: Backend, repo: Option<&Path>) -> Option<String> {
+    let remote = match backend.for_repo(repo) {
+        Backend::Git => backend::git_remote_url(repo, "origin"),
+        #[cfg(feature = "libgit2")]
+        Backend::Libgit2 => backend::libgit2_remote_url(repo, "origin"),
+    };
+    commit_url_prefix(&remote.ok()??)
+}
+
+/// The URL of the commits of the repository at `remote`, which may be an
+/// `https://` or `ssh://` URL or an scp-like `git@host:owner/name.git`,
+/// without the hash that ends it.
+fn commit_url_prefix(remote: &str) -> Option<String> {
+    let remote = remote.trim_end_matches('/');
+    let remote = remote.strip_suffix(".git").unwrap_or(remote);
+    let (authority, path) = match remote.split_once("://") {
+        Some((_, rest)) => rest.split_once('/')?,
+        None => remote.split_once(':')?,
+    };
+    let host = authority.rsplit('@').next()?;
+    let host = host.split(':').next()?;
+    let commit = match host {
+        "github.com" => "commit",
+        "gitlab.com" => "-/commit",
+        _ => return None,
+    };
+    if path.is_empty() {
+        return None;
+    }
+    Some(format!("https://{host}/{path}/{commit}/"))
+}
+
+/// `hash` for a Markdown table: abbreviated and linked to its commit at
+/// `commit_url`, or in full when there's nowhere to link it to. With
+/// `--short`, it's abbreviated to `abbrev` either way.
+pub fn link_hash(hash: &str, abbrev: Option<usize>, commit_url: Option<&str>) -> String {
+    match commit_url {
+        Some(url) => format!(
+            "[{}]({url}{hash})",
+            &hash[..abbrev.unwrap_or(DEFAULT_ABBREV)]
+        ),
+        None => hash[..abbrev.unwrap_or(hash.len())].to_string(),
+    }
+}
+
+/// A datetime as RFC 3339 text, like the JSON formats print it.
+fn display_datetime(datetime: &DateTime<FixedOffset>) -> String {
+    datetime.to_rfc3339_opts(SecondsFormat::AutoSi, true)
+}
+
+/// How long before `now` `datetime` was, like "3 weeks ago", rounded the way
+/// `git log --date=relative` rounds it: seconds up to 90 of them, then
+/// minutes, hours up to 36, days up to two weeks, weeks up to 10, months
+/// up to a year, years and months up to five years, and years after that.
+pub fn display_relative(datetime: &DateTime<FixedOffset>, now: &DateTime<Utc>) -> String {
+    let count = |n: i64, unit: &str| format!("{n} {unit}{}", if n == 1 { "" } else { "s" });
+    let ago = |n: i64, unit: &str| format!("{} ago", count(n, unit));
+    let seconds = now.signed_duration_since(datetime).num_seconds();
+    if seconds < 0 {
+        return "in the future".to_string();
+    }
+    if seconds < 90 {
+        return ago(seconds, "second");
+    }
+    let minutes = (seconds + 30) / 60;
+    if minutes < 90 {
+        return ago(minutes, "minute");
+    }
+    let hours = (minutes + 30) / 60;
+    if hours < 36 {
+        return ago(hours, "hour");
+    }
+    let days = (hours + 12) / 24;
+    if days < 14 {
+        ago(days, "day")
+    } else if days < 70 {
+        ago((days + 3) / 7, "week")
+    } else if days < 365 {
+        ago((days + 15) / 30, "month")
+    } else if days < 1825 {
+        let months = (days * 12 * 2 + 365) / (365 * 2);
+        match months % 12 {
+            0 => ago(months / 12, "year"),
+            rest => format!("{}, {}", count(months / 12, "year"), ago(rest, "month")),
+        }
+    } else {
+        ago((days + 183) / 365, "year")
+    }
+}
+
+/// A commit or object found, with its rarity flattened into plain fields.
+#[derive(Serialize)]
+pub struct CommitRecord<'a> {
+    /// The repository it was found in, when scanning several.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    repo: Option<&'a str>,
+    hash: &'a str,
+    /// The hash as `--short` shows it, abbreviated short of sharing a
+    /// prefix with another.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    short_hash: Option<&'a str>,
+    /// The author, or the tag's name for a tag object. Unknown for hashes
+    /// read with `--stdin` alone and for trees and blobs.
+    author: Option<&'a str>,
+    /// The author's email, unknown whenever the author is and for tags.
+    email: Option<&'a str>,
+    datetime: Option<&'a DateTime<FixedOffset>>,
+    /// The first line of the commit's message, with `--show-subject`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    subject: Option<&'a str>,
+    tier: &'a RarityTier,
+    explanation: String,
+    probability: Option<f64>,
+    /// The type of its object, with `--tags` or `--objects`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    object: Option<ObjectKind>,
+    /// Where a tree or blob is referenced from, with `--objects`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    path: Option<&'a str>,
+    /// Whether only a reflog still records the commit, with `--reflog`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    unreachable: Option<bool>,
+    /// The stash the commit is part of, with `--stashes`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    stash: Option<Option<&'a str>>,
+    /// The letter `git log` prints for its signature, with
+    /// `--show-signatures`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    signature: Option<char>,
+    /// The stretches of the hash its rarer matches depend on, for
+    /// `--format html` to mark.
+    #[serde(skip)]
+    spans: Vec<Range<usize>>,
+}
+
+/// Which of the optional fields of [`CommitRecord`] the records of a scan
+/// have, so that the formats with a header row know their columns before the
+/// first record, or without one.
+#[derive(Clone, Copy, Default)]
+struct Fields {
+    repo: bool,
+    short_hash: bool,
+    subject: bool,
+    /// With `--format csv --columns odds`.
+    odds: bool,
+    object: bool,
+    path: bool,
+    unreachable: bool,
+    stash: bool,
+    signature: bool,
+}
+
+impl Fields {
+    /// The names of the columns of a record with these fields.
+    fn header(self) -> Vec<&'static str> {
+        let optional = |set: bool, name| set.then_some(name);
+        optional(self.repo, "repo")
+            .into_iter()
+            .chain(["hash"])
+            .chain(optional(self.short_hash, "short_hash"))
+            .chain(["author", "datetime"])
+            .chain(optional(self.subject, "subject"))
+            .chain(["tier", "explanation", "probability"])
+            .chain(optional(self.odds, "odds"))
+            .chain(optional(self.object, "object"))
+            .chain(optional(self.path, "path"))
+            .chain(optional(self.unreachable, "unreachable"))
+            .chain(optional(self.stash, "stash"))
+            .chain(optional(self.signature, "signature"))
+            .chain(["email"])
+            .collect()
+    }
+}
+
+impl Sortable for CommitRecord<'_> {
+    fn tier(&self) -> &RarityTier {
+        self.tier
+    }
+
+    fn probability(&self) -> Option<f64> {
+        self.probability
+    }
+
+    fn author(&self) -> Option<&str> {
+        self.author
+    }
+
+    fn email(&self) -> Option<&str> {
+        self.email
+    }
+
+    fn datetime(&self) -> Option<&DateTime<FixedOffset>> {
+        self.datetime
+    }
+}
+
+impl<'a> CommitRecord<'a> {
+    /// The record as text, a cell per column of the header of `fields`.
+    fn cells(&self, fields: Fields) -> Vec<String> {
+        let text = |value: Option<&str>| value.unwrap_or_default().to_string();
+        let mut cells = vec![];
+        if fields.repo {
+            cells.push(text(self.repo));
+        }
+        cells.push(self.hash.to_string());
+        if fields.short_hash {
+            cells.push(text(self.short_hash));
+        }
+        cells.extend([
+            text(self.author),
+            self.datetime.map(display_datetime).unwrap_or_default(),
+        ]);
+        if fields.subject {
+            cells.push(text(self.subject));
+        }
+        cells.extend([
+            self.tier.to_string(),
+            self.explanation.clone(),
+            display_percentage(&self.probability),
+        ]);
+        if fields.odds {
+            cells.push(crate::odds::display(&self.probability));
+        }
+        if fields.object {
+            cells.push(
+                self.object
+                    .map(|o| o.to_string().to_lowercase())
+                    .unwrap_or_default(),
+            );
+        }
+        if fields.path {
+            cells.push(text(self.path));
+        }
+        if fields.unreachable {
+            cells.push(self.unreachable.unwrap_or_default().to_string());
+        }
+        if fields.stash {
+            cells.push(text(self.stash.flatten()));
+        }
+        if fields.signature {
+            cells.push(self.signature.map(String::from).unwrap_or_default());
+        }
+        cells.push(text(self.email));
+        cells
+    }
+
+    fn new(hash: &'a str, rarity: &'a Rarity) -> Self {
+        Self {
+            repo: None,
+            hash,
+            short_hash: None,
+            author: None,
+            email: None,
+            datetime: None,
+            subject: None,
+            tier: &rarity.tier,
+            explanation: display_explanations(&rarity.matches),
+            probability: rarity.percentage,
+            object: None,
+            path: None,
+            unreachable: None,
+            stash: None,
+            signature: None,
+            spans: rarity.spans(),
+        }
+    }
+
+    fn from_commit(repo: &'a str, commit: &'a Commit, objects: bool, labels: Labels) -> Self {
+        Self {
+            repo: (!repo.is_empty()).then_some(repo),
+            author: Some(&commit.author),
+            email: (!commit.email.is_empty()).then_some(commit.email.as_str()),
+            datetime: Some(&commit.datetime),
+            subject: labels.subject.then_some(commit.subject.as_str()),
+            object: objects.then_some(commit.object),
+            unreachable: labels.reflog.then_some(commit.unreachable),
+            stash: labels.stashes.then_some(commit.stash.as_deref()),
+            signature: commit.signature,
+            ..Self::new(&commit.hash, &commit.rarity)
+        }
+    }
+}
+
+/// A common commit that came close to a rule, with `--near-misses`.
+#[derive(Serialize)]
+struct NearMissRecord<'a> {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    repo: Option<&'a str>,
+    hash: &'a str,
+    author: &'a str,
+    datetime: &'a DateTime<FixedOffset>,
+    near_miss: String,
+    closeness: f64,
+}
+
+impl<'a> NearMissRecord<'a> {
+    /// The names of the columns of a near miss, after its repository's when
+    /// scanning several.
+    const HEADER: [&'static str; 5] = ["hash", "author", "datetime", "near_miss", "closeness"];
+
+    fn cells(&self) -> Vec<String> {
+        let cells = [
+            self.hash.to_string(),
+            self.author.to_string(),
+            display_datetime(self.datetime),
+            self.near_miss.clone(),
+            self.closeness.to_string(),
+        ];
+        self.repo
+            .map(str::to_string)
+            .into_iter()
+            .chain(cells)
+            .collect()
+    }
+
+    fn new(repo: &'a str, near_miss: &'a NearMiss) -> Self {
+        Self {
+            repo: (!repo.is_empty()).then_some(repo),
+            hash: &near_miss.hash,
+            author: &near_miss.author,
+            datetime: &near_miss.datetime,
+            near_miss: near_miss.strength.to_string(),
+            closeness: near_miss.closeness,
+        }
+    }
+}
+
+/// The counts of a scan, and whether any repository scanned is a shallow
+/// clone whose counts only cover some of its history.
+#[derive(Serialize)]
+struct Summary<'a> {
+    #[serde(flatten)]
+    count: Count,
+    shallow: bool,
+    /// The counts of each repository, when scanning several.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    repositories: Vec<RepoSummary<'a>>,
+}
+
+#[derive(Serialize)]
+struct RepoSummary<'a> {
+    repo: &'a str,
+    #[serde(flatten)]
+    count: &'a Count,
+    shallow: bool,
+}
+
+impl<'a> Summary<'a> {
+    /// The counts as rows under a header, a row per repository and one for
+    /// all of them when scanning several, like `--count` prints them.
+    fn rows(&self) -> Vec<Vec<String>> {
+        let cells = |count: &Count, shallow: bool| {
+            [
+                count.total,
+                count.common,
+                count.uncommon,
+                count.rare,
+                count.legendary,
+            ]
+            .iter()
+            .map(usize::to_string)
+            .chain([shallow.to_string()])
+            .collect::<Vec<String>>()
+        };
+        let header = [
+            "total",
+            "common",
+            "uncommon",
+            "rare",
+            "legendary",
+            "shallow",
+        ];
+        if self.repositories.is_empty() {
+            let header = header.iter().map(|name| name.to_string()).collect();
+            return vec![header, cells(&self.count, self.shallow)];
+        }
+        let header = ["repo"].iter().chain(&header).map(|name| name.to_string());
+        let repositories = self.repositories.iter().map(|repo| {
+            [repo.repo.to_string()]
+                .into_iter()
+                .chain(cells(repo.count, repo.shallow))
+                .collect()
+        });
+        let total = ["All repositories".to_string()]
+            .into_iter()
+            .chain(cells(&self.count, self.shallow))
+            .collect();
+        [header.collect()]
+            .into_iter()
+            .chain(repositories)
+            .chain([total])
+            .collect()
+    }
+
+    fn new(scans: &'a [Scan]) -> Self {
+        let counts = scans
+            .iter()
+            .map(|scan| scan.count.clone())
+            .collect::<Vec<Count>>();
+        let repositories = if scans.len() > 1 {
+            scans
+                .iter()
+                .map(|scan| RepoSummary {
+                    repo: &scan.repo,
+                    count: &scan.count,
+                    shallow: scan.shallow,
+                })
+                .collect()
+        } else {
+            vec![]
+        };
+        Self {
+            count: Count::total(&counts),
+            shallow: scans.iter().any(|scan| scan.shallow),
+            repositories,
+        }
+    }
+}
+
+/// What was scanned, as the flags that choose the commits and rules give it.
+#[derive(Serialize)]
+struct Parameters<'a> {
+    revisions: Vec<String>,
+    since: Option<DateTime<FixedOffset>>,
+    until: Option<DateTime<FixedOffset>>,
+    authors: Vec<&'a str>,
+    grep: Vec<&'a str>,
+    paths: Vec<&'a str>,
+    max_count: Option<usize>,
+    first_parent: bool,
+    no_merges: bool,
+    merges_only: bool,
+    mailmap: bool,
+    use_committer: bool,
+    only: Option<&'a RarityTier>,
+    all: bool,
+    min_run: usize,
+    rules: Vec<&'a str>,
+    disabled_rules: &'a [String],
+}
+
+impl<'a> Parameters<'a> {
+    fn new(args: &'a CliArgs, options: &'a ClassifyOptions) -> Self {
+        Self {
+            revisions: args.walk(),
+            since: args.since,
+            until: args.until,
+            authors: args.author.iter().map(regex::Regex::as_str).collect(),
+            grep: args.grep.iter().map(regex::Regex::as_str).collect(),
+            paths: args
+                .path
+                .iter()
+                .chain(&args.pathspecs)
+                .map(String::as_str)
+                .collect(),
+            max_count: args.max_count,
+            first_parent: args.first_parent,
+            no_merges: args.no_merges,
+            merges_only: args.merges_only,
+            mailmap: !args.no_mailmap,
+            use_committer: args.use_committer,
+            only: args.only.as_ref(),
+            all: args.all,
+            min_run: options.min_run,
+            rules: options
+                .rules
+                .iter()
+                .map(|rule| rule.regex.as_str())
+                .collect(),
+            disabled_rules: &args.disabled_rules,
+        }
+    }
+}
+
+/// The document `--format json` prints for a scan.
+#[derive(Serialize)]
+struct Document<'a> {
+    parameters: Parameters<'a>,
+    /// Left out with `--count` and `--near-misses`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    commits: Option<Vec<CommitRecord<'a>>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    near_misses: Option<Vec<NearMissRecord<'a>>>,
+    /// Left out for `--objects`, which only keeps the objects it finds.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    counts: Option<Summary<'a>>,
+    /// What's in `commits`, like the footer of the table counts it. Left out
+    /// with `--no-footer`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    shown: Option<Shown>,
+    #[serde(skip)]
+    fields: Fields,
+    /// Where the commits were read from, for `--format html` to name.
+    #[serde(skip)]
+    repositories: Vec<String>,
+}
+
+/// A line of `--format ndjson`, marked with what it describes by its `type`.
+#[derive(Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum Line<'a> {
+    Commit(CommitRecord<'a>),
+    NearMiss(NearMissRecord<'a>),
+    Summary(Summary<'a>),
+}
+
+/// Prints `line` on a line of its own.
+fn print_line(output: &Output, line: &Line) {
+    let json = serde_json::to_string(line).expect("records have no maps with keys but strings");
+    output.println(json);
+}
+
+/// Prints `value` as the YAML document serde_yaml writes for it.
+fn print_yaml(output: &Output, value: &impl Serialize) -> Result<()> {
+    let mut yaml = vec![];
+    serde_yaml::to_writer(&mut yaml, value)?;
+    output.print(&String::from_utf8_lossy(&yaml));
+    Ok(())
+}
+
+/// Prints `document` as `--format` asks: whole, or with `--format ndjson` as
+/// a line per commit or near miss followed by a line of counts. With
+/// `--count`, only the counts are printed.
+fn print_document(args: &CliArgs, mut document: Document, output: &Output) -> Result<()> {
+    if let (Some(abbrev), Some(commits)) = (args.short_abbrev(), &mut document.commits) {
+        let abbreviations = crate::abbreviations(commits.iter().map(|c| c.hash), abbrev);
+        for commit in commits {
+            commit.short_hash = Some(&commit.hash[..abbreviations[commit.hash]]);
+        }
+        document.fields.short_hash = true;
+    }
+    let odds = args.columns.iter().flatten().any(|c| *c == Column::Odds);
+    document.fields.odds = odds && args.format == Format::Csv;
+    match args.format {
+        Format::Json if args.count => {
+            output.println(serde_json::to_string_pretty(&document.counts)?)
+        }
+        Format::Json => output.println(serde_json::to_string_pretty(&document)?),
+        Format::Yaml if args.count => print_yaml(output, &document.counts)?,
+        Format::Yaml => print_yaml(output, &document)?,
+        Format::Ndjson => {
+            for commit in document.commits.into_iter().flatten() {
+                print_line(output, &Line::Commit(commit));
+            }
+            for near_miss in document.near_misses.into_iter().flatten() {
+                print_line(output, &Line::NearMiss(near_miss));
+            }
+            if let Some(counts) = document.counts {
+                print_line(output, &Line::Summary(counts));
+            }
+        }
+        Format::Csv => {
+            let mut writer = csv::WriterBuilder::new()
+                .delimiter(args.delimiter)
+                .from_writer(vec![]);
+            if let Some(commits) = &document.commits {
+                let header = document.fields.header();
+                let order = args.columns.as_deref().map(|c| column_order(&header, c));
+                writer.write_record(reorder(&header, order.as_deref()))?;
+                for commit in commits {
+                    let cells = commit.cells(document.fields);
+                    writer.write_record(reorder(&cells, order.as_deref()))?;
+                }
+                if let Some(shown) = document.shown.as_ref().filter(|_| args.footer) {
+                    let header = reorder(&header, order.as_deref());
+                    writer.write_record(shown_cells(&header, shown, document.fields.path))?;
+                }
+            } else if let Some(near_misses) = &document.near_misses {
+                let repo = document.fields.repo.then_some("repo");
+                let header = repo
+                    .into_iter()
+                    .chain(NearMissRecord::HEADER)
+                    .collect::<Vec<&str>>();
+                let order = args.columns.as_deref().map(|c| column_order(&header, c));
+                writer.write_record(reorder(&header, order.as_deref()))?;
+                for near_miss in near_misses {
+                    writer.write_record(reorder(&near_miss.cells(), order.as_deref()))?;
+                }
+            } else if let Some(counts) = &document.counts {
+                for row in counts.rows() {
+                    writer.write_record(row)?;
+                }
+            }
+            let csv = writer.into_inner().map_err(|e| e.into_error())?;
+            output.print(&String::from_utf8(csv).expect("records are strings"));
+        }
+        Format::Plain => {
+            if let Some(commits) = &document.commits {
+                let rows = commits
+                    .iter()
+                    .map(|commit| commit.cells(document.fields))
+                    .collect::<Vec<Vec<String>>>();
+                let header = document.fields.header();
+                print_plain(args, output, &header, &rows, &PLAIN_FIELDS);
+            } else if let Some(near_misses) = &document.near_misses {
+                let rows = near_misses
+                    .iter()
+                    .map(NearMissRecord::cells)
+                    .collect::<Vec<Vec<String>>>();
+                let repo = document.fields.repo.then_some("repo");
+                let header = repo
+                    .into_iter()
+                    .chain(NearMissRecord::HEADER)
+                    .collect::<Vec<&str>>();
+                print_plain(args, output, &header, &rows, &PLAIN_NEAR_MISS_FIELDS);
+            } else if let Some(counts) = &document.counts {
+                let rows = counts.rows();
+                let header = rows[0].iter().map(String::as_str).collect::<Vec<&str>>();
+                print_plain(args, output, &header, &rows[1..], &PLAIN_COUNT_FIELDS);
+            }
+        }
+        Format::Html => {
+            let generated = chrono::Local::now().fixed_offset().trunc_subsecs(0);
+            output.print(&html::report(&document, &generated))
+        }
+        Format::Table | Format::Markdown => unreachable!("tables are printed by print_table"),
+    }
+    Ok(())
+}
+
+/// What the footer of a table of `commits` would count, unless
+/// `--no-footer` leaves it out or there's nothing to count, when a table
+/// has no footer either.
+fn shown(args: &CliArgs, commits: &[CommitRecord]) -> Option<Shown> {
+    (!args.no_footer && !commits.is_empty()).then(|| Shown::new(commits))
+}
+
+/// The last record of `--format csv`, with the fields of `header`: no hash
+/// to tell it from those of the commits, or of the objects when `objects` is
+/// set, but the footer of the table as its explanation, and the probability
+/// of all of them. Without an explanation, the footer is its first field
+/// that's otherwise empty instead, so that it still says what it is.
+fn shown_cells(header: &[String], shown: &Shown, objects: bool) -> Vec<String> {
+    let noun = if objects { "object" } else { "commit" };
+    let mut cells = header
+        .iter()
+        .map(|name| match name.as_str() {
+            "explanation" => shown.footer(noun),
+            "probability" => shown.display_probability(),
+            "odds" => crate::odds::display_log10(shown.log10),
+            _ => String::new(),
+        })
+        .collect::<Vec<String>>();
+    if !header.iter().any(|name| name == "explanation") {
+        let empty = cells.iter().position(String::is_empty).unwrap_or(0);
+        cells[empty] = shown.footer(noun);
+    }
+    cells
+}
+
+/// Whether `--format ndjson` prints each commit as it's classified, through
+/// the `keep` of the scan, rather than once the scan is done. That's only
+/// when nothing after the walk can change what's printed, so never for a
+/// scan of several repositories, which are read in parallel, nor with
+/// `--count`, which prints the counts alone. The near misses are the closest
+/// of all commits, `--reflog` marks the unreachable commits after reading
+/// them, `--rescue` needs the commits it keeps, and `--short` abbreviates
+/// each hash short of sharing a prefix with the others. `--sort-by`,
+/// `--reverse` and `--group-by-tier` need every commit to order them, and
+/// `--show-signatures` checks the signatures of the commits found once the
+/// walk is done.
+pub fn streams(args: &CliArgs, repos: usize) -> bool {
+    args.format == Format::Ndjson
+        && repos <= 1
+        && !args.count
+        && !shows_near_misses(args)
+        && !args.reflog
+        && !args.rescue
+        && !args.short
+        && args.sort_by.is_none()
+        && !args.reverse
+        && !args.group_by_tier
+        && !args.show_signatures
+}
+
+/// Prints the line of `commit` as soon as it's classified, when [`streams`]
+/// says so, with the type of its object when `objects` is set and the
+/// fields `labels` asks for.
+pub fn stream(output: &Output, commit: &Commit, objects: bool, labels: Labels) {
+    print_line(
+        output,
+        &Line::Commit(CommitRecord::from_commit("", commit, objects, labels)),
+    );
+}
+
+/// Whether [`crate::print_scans`] shows the near misses rather than commits.
+fn shows_near_misses(args: &CliArgs) -> bool {
+    args.near_misses && args.only.is_none() && !args.all
+}
+
+/// The repository at `path`, or the current directory, as `--format html`
+/// names it.
+fn display_repo(path: Option<&Path>) -> String {
+    match path {
+        Some(path) => path.display().to_string(),
+        None => std::env::current_dir()
+            .map(|dir| dir.display().to_string())
+            .unwrap_or_else(|_| ".".to_string()),
+    }
+}
+
+/// The name of the repository `scan` read: its path, or its name on GitHub.
+#[cfg_attr(not(feature = "github"), allow(unused_variables))]
+fn repository(args: &CliArgs, scan: &Scan) -> String {
+    #[cfg(feature = "github")]
+    if let Some(repo) = &args.github {
+        return repo.clone();
+    }
+    if !scan.repo.is_empty() {
+        return scan.repo.clone();
+    }
+    display_repo(scan.path.as_deref())
+}
+
+/// Prints the commits read from the repositories like [`crate::print_scans`]
+/// chooses them, or only their counts with `--count`. With `--format
+/// ndjson` those [`stream`] printed aren't kept, so they aren't printed
+/// again.
+pub fn print_scans(
+    args: &CliArgs,
+    scans: &[Scan],
+    options: &ClassifyOptions,
+    output: &Output,
+) -> Result<()> {
+    let labels = args.labels();
+    let near_misses_shown = !args.count && shows_near_misses(args);
+    let found = if near_misses_shown {
+        near_misses(scans, options.min_run, args.limit)
+    } else {
+        vec![]
+    };
+    let mut document = Document {
+        parameters: Parameters::new(args, options),
+        commits: None,
+        near_misses: None,
+        counts: Some(Summary::new(scans)),
+        shown: None,
+        fields: Fields {
+            repo: scans.len() > 1,
+            object: args.tags,
+            unreachable: args.reflog,
+            stash: args.stashes,
+            subject: labels.subject,
+            signature: args.show_signatures,
+            ..Fields::default()
+        },
+        repositories: scans.iter().map(|scan| repository(args, scan)).collect(),
+    };
+    if near_misses_shown {
+        let records = found
+            .iter()
+            .map(|(repo, near_miss)| NearMissRecord::new(repo, near_miss))
+            .collect();
+        document.near_misses = Some(records);
+    } else if !args.count {
+        let keep = args.keep();
+        let mut commits = scans
+            .iter()
+            .flat_map(|scan| {
+                scan.commits
+                    .iter()
+                    .filter(|commit| keep(commit))
+                    .map(|commit| CommitRecord::from_commit(&scan.repo, commit, args.tags, labels))
+            })
+            .collect::<Vec<CommitRecord>>();
+        args.sort(&mut commits);
+        document.shown = shown(args, &commits);
+        document.commits = Some(commits);
+    }
+    print_document(args, document, output)
+}
+
+/// Prints hashes read with `--stdin` like [`print_scans`], for input without
+/// the author and date of each commit.
+pub fn print_hashes(
+    args: &CliArgs,
+    hashes: &[HashRow],
+    options: &ClassifyOptions,
+    output: &Output,
+) -> Result<()> {
+    let rarities = hashes.iter().map(|h| &h.rarity).collect::<Vec<&Rarity>>();
+    let mut commits = hashes
+        .iter()
+        .filter(|h| match &args.only {
+            Some(only) => h.rarity.tier == *only,
+            None => args.all || h.rarity.tier != RarityTier::Common,
+        })
+        .map(|h| CommitRecord::new(&h.hash, &h.rarity))
+        .collect::<Vec<CommitRecord>>();
+    args.sort(&mut commits);
+    let document = Document {
+        parameters: Parameters::new(args, options),
+        shown: shown(args, &commits).filter(|_| !args.count),
+        commits: (!args.count).then_some(commits),
+        near_misses: None,
+        counts: Some(Summary {
+            count: Count::from_rarities(&rarities),
+            shallow: false,
+            repositories: vec![],
+        }),
+        fields: Fields::default(),
+        repositories: vec![],
+    };
+    print_document(args, document, output)
+}
+
+/// Prints the objects `--objects` found.
+pub fn print_objects(
+    args: &CliArgs,
+    found: &[ObjectMatch],
+    options: &ClassifyOptions,
+    output: &Output,
+) -> Result<()> {
+    let commits = found
+        .iter()
+        .map(|o| CommitRecord {
+            object: Some(o.object),
+            path: Some(&o.path),
+            ..CommitRecord::new(&o.hash, &o.rarity)
+        })
+        .collect::<Vec<CommitRecord>>();
+    let document = Document {
+        parameters: Parameters::new(args, options),
+        shown: shown(args, &commits),
+        commits: Some(commits),
+        near_misses: None,
+        counts: None,
+        fields: Fields {
+            object: true,
+            path: true,
+            ..Fields::default()
+        },
+        repositories: vec![display_repo(args.repo.first().map(PathBuf::as_path))],
+    };
+    print_document(args, document, output)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn relative_dates_round_like_git() {
+        let now = DateTime::parse_from_rfc3339("2024-06-01T12:00:00Z")
+            .unwrap()
+            .with_timezone(&Utc);
+        let ago = |seconds: i64| {
+            let datetime = now - chrono::Duration::seconds(seconds);
+            display_relative(&datetime.fixed_offset(), &now)
+        };
+        let (minute, hour, day) = (60, 60 * 60, 24 * 60 * 60);
+        assert_eq!(ago(0), "0 seconds ago");
+        assert_eq!(ago(1), "1 second ago");
+        assert_eq!(ago(89), "89 seconds ago");
+        assert_eq!(ago(90), "2 minutes ago");
+        assert_eq!(ago(59 * minute), "59 minutes ago");
+        assert_eq!(ago(89 * minute), "89 minutes ago");
+        assert_eq!(ago(90 * minute), "2 hours ago");
+        assert_eq!(ago(35 * hour), "35 hours ago");
+        assert_eq!(ago(36 * hour), "2 days ago");
+        assert_eq!(ago(13 * day), "13 days ago");
+        assert_eq!(ago(14 * day), "2 weeks ago");
+        assert_eq!(ago(69 * day), "10 weeks ago");
+        assert_eq!(ago(70 * day), "2 months ago");
+        assert_eq!(ago(364 * day), "12 months ago");
+        assert_eq!(ago(365 * day), "1 year ago");
+        assert_eq!(ago(395 * day), "1 year, 1 month ago");
+        assert_eq!(ago(800 * day), "2 years, 2 months ago");
+        assert_eq!(ago(1825 * day), "5 years ago");
+        assert_eq!(ago(-1), "in the future");
+    }
+
+    #[test]
+    fn commit_urls_of_forges() {
+        for remote in [
+            "https://github.com/tywenk/git-rare.git",
+            "https://github.com/tywenk/git-rare",
+            "git@github.com:tywenk/git-rare.git",
+            "ssh://git@github.com/tywenk/git-rare.git",
+            "ssh://git@github.com:22/tywenk/git-rare/",
+        ] {
+            assert_eq!(
+                commit_url_prefix(remote).as_deref(),
+                Some("https://github.com/tywenk/git-rare/commit/"),
+                "{remote}"
+            );
+        }
+        assert_eq!(
+            commit_url_prefix("git@gitlab.com:group/sub/project.git").as_deref(),
+            Some("https://gitlab.com/group/sub/project/-/commit/")
+        );
+        for remote in [
+            "https://example.com/tywenk/git-rare.git",
+            "/srv/git/git-rare.git",
+            "C:\\repos\\git-rare",
+            "https://github.com/",
+        ] {
+            assert_eq!(commit_url_prefix(remote), None, "{remote}");
+        }
+    }
+
+    #[test]
+    fn chosen_columns_keep_the_others_around_them() {
+        let header = [
+            "Repository",
+            "Object",
+            "Hash",
+            "Path",
+            "Explanation",
+            "Tier",
+        ];
+        let order = column_order(&header, &[Column::Tier, Column::Hash]);
+        assert_eq!(order, [0, 1, 5, 2, 3]);
+        let header = ["repo", "hash", "author", "probability", "stash"];
+        let order = column_order(&header, &[Column::Percentage, Column::Datetime]);
+        assert_eq!(order, [0, 3, 4]);
+        assert_eq!(column_order(&["Total", "Common"], &[Column::Hash]), [0, 1]);
+    }
+
+    #[test]
+    fn markdown_cells_are_escaped() {
+        assert_eq!(escape_markdown("a | b\nc"), "a \\| b<br>c");
+    }
+
+    #[test]
+    fn linked_hashes_are_abbreviated() {
+        let hash = "0123456789abcdef0123456789abcdef01234567";
+        assert_eq!(
+            link_hash(hash, None, Some("https://github.com/o/r/commit/")),
+            format!("[0123456](https://github.com/o/r/commit/{hash})")
+        );
+        assert_eq!(link_hash(hash, None, None), hash);
+        assert_eq!(
+            link_hash(hash, Some(12), Some("https://github.com/o/r/commit/")),
+            format!("[0123456789ab](https://github.com/o/r/commit/{hash})")
+        );
+        assert_eq!(link_hash(hash, Some(12), None), "0123456789ab");
+    }
+}
@@ -0,0 +1,259 @@
+//! The report of `--format html`: a single page with nothing to fetch, for
+//! sharing a scan with someone away from a terminal.
+
+use std::ops::Range;
+
+use chrono::{DateTime, FixedOffset};
+use serde_json::Value;
+
+use super::{display_datetime, Document, NearMissRecord};
+
+/// Keeps the page readable without a stylesheet of its own, in light and
+/// dark mode alike.
+const STYLE: &str = "\
+body { font-family: system-ui, sans-serif; margin: 2rem; color-scheme: light dark; }
+table { border-collapse: collapse; margin-bottom: 2rem; }
+th, td { border: 1px solid #8888; padding: 0.25rem 0.5rem; text-align: left; vertical-align: top; }
+th { text-transform: capitalize; }
+table.sortable th { cursor: pointer; user-select: none; }
+th[data-order=ascending]::after { content: \" \\25B2\"; }
+th[data-order=descending]::after { content: \" \\25BC\"; }
+td.explanation { white-space: pre-wrap; }
+dt { font-weight: bold; text-transform: capitalize; }
+dd { margin: 0 0 0.5rem 1rem; }
+mark { font-weight: bold; }
+.uncommon { color: #2e8b57; }
+.rare { color: #1e6fd9; }
+.legendary { color: #c77c02; }
+";
+
+/// Sorts a table by the column whose header is clicked, numerically when
+/// both cells are numbers, and the other way on a second click.
+const SCRIPT: &str = "\
+document.querySelectorAll(\"table.sortable th\").forEach((th) => {
+  th.addEventListener(\"click\", () => {
+    const column = th.cellIndex;
+    const body = th.closest(\"table\").tBodies[0];
+    const ascending = th.dataset.order !== \"ascending\";
+    th.parentElement.querySelectorAll(\"th\").forEach((other) => delete other.dataset.order);
+    th.dataset.order = ascending ? \"ascending\" : \"descending\";
+    const key = (row) => row.cells[column].dataset.sort ?? row.cells[column].textContent;
+    const rows = Array.from(body.rows).sort((a, b) => {
+      const [x, y] = [key(a), key(b)];
+      const numbers = x !== \"\" && y !== \"\" && !isNaN(x) && !isNaN(y);
+      const order = numbers ? x - y : x.localeCompare(y);
+      return ascending ? order : -order;
+    });
+    body.append(...rows);
+  });
+});
+";
+
+/// `text` with the characters that mean something in HTML escaped, so it
+/// can go in an element or a quoted attribute.
+fn escape_html(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// `hash` with the characters in `spans` marked.
+fn highlight(hash: &str, spans: &[Range<usize>]) -> String {
+    let mut html = String::new();
+    let mut marked = false;
+    for (i, c) in hash.char_indices() {
+        let inside = spans.iter().any(|span| span.contains(&i));
+        if inside != marked {
+            html.push_str(if inside { "<mark>" } else { "</mark>" });
+            marked = inside;
+        }
+        html.push_str(&escape_html(c.encode_utf8(&mut [0; 4])));
+    }
+    if marked {
+        html.push_str("</mark>");
+    }
+    html
+}
+
+/// A parameter as text, or `None` when it's unset and isn't worth listing.
+fn display_parameter(value: &Value) -> Option<String> {
+    match value {
+        Value::Null | Value::Bool(false) => None,
+        Value::String(string) => Some(string.clone()),
+        Value::Array(values) if values.is_empty() => None,
+        Value::Array(values) => Some(
+            values
+                .iter()
+                .filter_map(display_parameter)
+                .collect::<Vec<String>>()
+                .join(", "),
+        ),
+        value => Some(value.to_string()),
+    }
+}
+
+/// Writes a table with `header` and `rows` of cells already escaped.
+fn write_table(html: &mut String, class: &str, header: &[String], rows: &[Vec<String>]) {
+    html.push_str(&format!("<table class=\"{class}\">\n<thead><tr>\n"));
+    for name in header {
+        html.push_str(&format!("<th>{}</th>\n", escape_html(name)));
+    }
+    html.push_str("</tr></thead>\n<tbody>\n");
+    for row in rows {
+        html.push_str("<tr>");
+        html.push_str(&row.concat());
+        html.push_str("</tr>\n");
+    }
+    html.push_str("</tbody>\n</table>\n");
+}
+
+/// The scan's repositories, when it was generated and the parameters that
+/// were set, as a list of their names and values.
+fn write_metadata(html: &mut String, document: &Document, generated: &DateTime<FixedOffset>) {
+    html.push_str("<section id=\"scan\">\n<h2>Scan</h2>\n<dl>\n");
+    let mut item = |name: &str, value: &str| {
+        html.push_str(&format!(
+            "<dt>{}</dt><dd>{}</dd>\n",
+            escape_html(name),
+            escape_html(value)
+        ));
+    };
+    if !document.repositories.is_empty() {
+        item("repository", &document.repositories.join(", "));
+    }
+    item("generated", &display_datetime(generated));
+    let parameters = serde_json::to_value(&document.parameters).unwrap_or_default();
+    for (name, value) in parameters.as_object().into_iter().flatten() {
+        let value = match (name.as_str(), display_parameter(value)) {
+            ("revisions", None) => Some("HEAD".to_string()),
+            (_, value) => value,
+        };
+        if let Some(value) = value {
+            item(&name.replace('_', " "), &value);
+        }
+    }
+    html.push_str("</dl>\n</section>\n");
+}
+
+/// The page `--format html` prints for `document`, generated at `generated`,
+/// with the counts of each tier, then a table of the commits or near misses
+/// that can be sorted by any of its columns.
+pub(super) fn report(document: &Document, generated: &DateTime<FixedOffset>) -> String {
+    let mut html = String::from(
+        "<!DOCTYPE html>\n<html lang=\"en\">\n<head>\n<meta charset=\"utf-8\">\n\
+         <meta name=\"viewport\" content=\"width=device-width, initial-scale=1\">\n",
+    );
+    let title = match document.repositories.as_slice() {
+        [repo] => format!("Rare commits of {repo}"),
+        _ => "Rare commits".to_string(),
+    };
+    html.push_str(&format!("<title>{}</title>\n", escape_html(&title)));
+    html.push_str(&format!("<style>\n{STYLE}</style>\n</head>\n<body>\n"));
+    html.push_str(&format!("<h1>{}</h1>\n", escape_html(&title)));
+    write_metadata(&mut html, document, generated);
+
+    if let Some(counts) = &document.counts {
+        html.push_str("<section id=\"summary\">\n<h2>Summary</h2>\n");
+        let rows = counts.rows();
+        let cells = rows[1..]
+            .iter()
+            .map(|row| {
+                row.iter()
+                    .map(|cell| format!("<td>{}</td>", escape_html(cell)))
+                    .collect()
+            })
+            .collect::<Vec<Vec<String>>>();
+        write_table(&mut html, "counts", &rows[0], &cells);
+        html.push_str("</section>\n");
+    }
+
+    if let Some(commits) = &document.commits {
+        html.push_str("<section id=\"commits\">\n<h2>Commits</h2>\n");
+        let header = document.fields.header();
+        let rows = commits
+            .iter()
+            .map(|commit| {
+                header
+                    .iter()
+                    .zip(commit.cells(document.fields))
+                    .map(|(&name, cell)| match name {
+                        "hash" => format!(
+                            "<td class=\"hash\"><code>{}</code></td>",
+                            highlight(&cell, &commit.spans)
+                        ),
+                        "tier" => format!(
+                            "<td class=\"{}\" data-sort=\"{}\">{}</td>",
+                            cell.to_lowercase(),
+                            commit.tier.clone() as u8,
+                            escape_html(&cell)
+                        ),
+                        name => format!("<td class=\"{name}\">{}</td>", escape_html(&cell)),
+                    })
+                    .collect()
+            })
+            .collect::<Vec<Vec<String>>>();
+        let header = header
+            .iter()
+            .map(|name| name.to_string())
+            .collect::<Vec<String>>();
+        write_table(&mut html, "sortable", &header, &rows);
+        html.push_str("</section>\n");
+    } else if let Some(near_misses) = &document.near_misses {
+        html.push_str("<section id=\"near-misses\">\n<h2>Near misses</h2>\n");
+        let header = document
+            .fields
+            .repo
+            .then_some("repo")
+            .into_iter()
+            .chain(NearMissRecord::HEADER)
+            .map(|name| name.replace('_', " "))
+            .collect::<Vec<String>>();
+        let rows = near_misses
+            .iter()
+            .map(|near_miss| {
+                near_miss
+                    .cells()
+                    .iter()
+                    .map(|cell| format!("<td>{}</td>", escape_html(cell)))
+                    .collect()
+            })
+            .collect::<Vec<Vec<String>>>();
+        write_table(&mut html, "sortable", &header, &rows);
+        html.push_str("</section>\n");
+    }
+
+    html.push_str(&format!("<script>\n{SCRIPT}</script>\n</body>\n</html>\n"));
+    html
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn text_is_escaped() {
+        assert_eq!(
+            escape_html("<script>alert(\"J&J's\")</script>"),
+            "&lt;script&gt;alert(&quot;J&amp;J&#39;s&quot;)&lt;/script&gt;"
+        );
+    }
+
+    #[test]
+    fn spans_are_marked() {
+        assert_eq!(highlight("abcdef", &[]), "abcdef");
+        assert_eq!(highlight("abcdef", &[0..2, 4..4]), "<mark>ab</mark>cdef");
+        assert_eq!(
+            highlight("abcdef", &[1..2, 2..3, 5..6]),
+            "a<mark>bc</mark>de<mark>f</mark>"
+        );
+    }
+}
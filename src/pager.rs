@@ -0,0 +1,146 @@
+//! Paging a table too long for the terminal, like git pages its output.
+
+use std::ffi::OsString;
+use std::io::{self, Write};
+use std::process::{Child, Command, Stdio};
+
+use crate::width;
+
+/// The pager git falls back on, which quits when what it's given fits on
+/// the screen, shows colors and leaves the screen as it is on quitting.
+const DEFAULT: &str = "less -FRX";
+
+/// The pager to run, from `GIT_PAGER` or else `PAGER` like git reads them,
+/// or `None` when it's set to nothing or `cat`, which is like none.
+pub fn command() -> Option<String> {
+    choose(std::env::var_os("GIT_PAGER"), std::env::var_os("PAGER"))
+}
+
+fn choose(git_pager: Option<OsString>, pager: Option<OsString>) -> Option<String> {
+    let command = git_pager.or(pager).map_or(DEFAULT.to_string(), |command| {
+        command.to_string_lossy().into_owned()
+    });
+    match command.trim() {
+        "" | "cat" => None,
+        _ => Some(command),
+    }
+}
+
+/// The status the shell exits with when it can't find the command it's
+/// asked to run.
+const NOT_FOUND: i32 = if cfg!(windows) { 9009 } else { 127 };
+
+/// Runs `command` through the shell, like git does, with its stdin piped.
+fn spawn(command: &str) -> io::Result<Child> {
+    let mut shell = if cfg!(windows) {
+        let mut shell = Command::new("cmd");
+        shell.arg("/C");
+        shell
+    } else {
+        let mut shell = Command::new("sh");
+        shell.arg("-c");
+        shell
+    };
+    shell.arg(command).stdin(Stdio::piped());
+    // The options git gives less and lv unless they've been set.
+    for (name, value) in [("LESS", "FRX"), ("LV", "-c")] {
+        if std::env::var_os(name).is_none() {
+            shell.env(name, value);
+        }
+    }
+    shell.spawn()
+}
+
+/// Pipes `text` through `command`, waiting until it's quit. Quitting before
+/// reading all of it isn't an error, but the shell not finding the command
+/// is one of [`io::ErrorKind::NotFound`].
+fn page(command: &str, text: &[u8]) -> io::Result<()> {
+    let mut child = spawn(command)?;
+    let written = child.stdin.take().expect("stdin is piped").write_all(text);
+    if child.wait()?.code() == Some(NOT_FOUND) {
+        return Err(io::ErrorKind::NotFound.into());
+    }
+    match written {
+        Err(e) if e.kind() == io::ErrorKind::BrokenPipe => Ok(()),
+        written => written,
+    }
+}
+
+/// How many rows of a terminal `columns` wide `text` takes, counting the
+/// lines too wide for it as many rows as they wrap onto.
+fn rows(text: &str, columns: usize) -> usize {
+    text.lines()
+        .map(|line| width::line(line).div_ceil(columns.max(1)).max(1))
+        .sum()
+}
+
+/// Shows `text` on stdout, through `command` when it doesn't fit in the
+/// terminal, or straight to stdout when it does or the pager can't be run.
+pub fn show(command: &str, text: &[u8]) -> io::Result<()> {
+    let fits = match terminal_size::terminal_size() {
+        Some((width, height)) => {
+            rows(&String::from_utf8_lossy(text), width.0.into()) < height.0.into()
+        }
+        None => true,
+    };
+    if fits {
+        return io::stdout().write_all(text);
+    }
+    match page(command, text) {
+        Err(e) if e.kind() == io::ErrorKind::NotFound => io::stdout().write_all(text),
+        paged => paged,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn git_pager_comes_before_pager() {
+        let var = |value: &str| Some(OsString::from(value));
+        assert_eq!(choose(None, None).as_deref(), Some("less -FRX"));
+        assert_eq!(choose(None, var("more")).as_deref(), Some("more"));
+        assert_eq!(
+            choose(var("less -S"), var("more")).as_deref(),
+            Some("less -S")
+        );
+        assert_eq!(choose(var(""), var("more")), None);
+        assert_eq!(choose(None, var("cat")), None);
+    }
+
+    #[test]
+    fn wrapped_lines_take_several_rows() {
+        assert_eq!(rows("abc\n\ndefgh\n", 4), 4);
+        assert_eq!(rows("\x1b[35mabcd\x1b[0m\n", 4), 1);
+        // An emoji takes two columns.
+        assert_eq!(rows("💎💎💎\n", 4), 2);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn the_pager_is_given_the_text() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let file = dir.path().join("paged");
+        page(&format!("cat > '{}'", file.display()), b"a\nb\n").unwrap();
+        assert_eq!(std::fs::read(file).unwrap(), b"a\nb\n");
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn quitting_the_pager_early_is_not_an_error() {
+        // More than a pipe holds, so that writing is still going on when
+        // the pager quits.
+        let text = "row\n".repeat(1 << 20);
+        page("head -n 1 > /dev/null", text.as_bytes()).unwrap();
+        page("true", text.as_bytes()).unwrap();
+        assert!(page("exit 3", b"").is_ok());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn a_pager_the_shell_cant_find_is_not_found() {
+        let error = page("git-rare-no-such-pager", b"a\n").unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::NotFound);
+    }
+}
@@ -0,0 +1,250 @@
+//! Literal patterns read from the file given with `--patterns-file`.
+//!
+//! Each line holds one pattern, optionally prefixed with its tier like
+//! `rare:c0de`; patterns without a tier are Uncommon. Blank lines and
+//! anything after a `#` are ignored. Unlike `--rule`, patterns are plain
+//! substrings, so thousands of them can be matched in a single pass.
+
+use std::collections::HashMap;
+use std::fs;
+use std::hash::{Hash, Hasher};
+use std::path::Path;
+
+use aho_corasick::{AhoCorasick, MatchKind};
+use anyhow::{anyhow, bail, Context, Result};
+
+use crate::custom_rule::parse_tier;
+use crate::{probability, RarityTier, RuleMatch, Span};
+
+#[derive(Debug, Clone, PartialEq, Hash)]
+struct Pattern {
+    text: String,
+    tier: RarityTier,
+}
+
+#[derive(Clone, Debug)]
+pub struct PatternSet {
+    patterns: Vec<Pattern>,
+    automaton: AhoCorasick,
+}
+
+impl Hash for PatternSet {
+    /// The automaton is built from the patterns alone.
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.patterns.hash(state);
+    }
+}
+
+impl PatternSet {
+    pub fn load(path: &Path) -> Result<Self> {
+        let contents = fs::read_to_string(path)
+            .with_context(|| format!("failed to read patterns file {}", path.display()))?;
+        Self::parse(&contents).with_context(|| format!("invalid patterns file {}", path.display()))
+    }
+
+    /// Parses a patterns file. A pattern listed more than once keeps its
+    /// rarest tier.
+    fn parse(contents: &str) -> Result<Self> {
+        let mut patterns: Vec<Pattern> = vec![];
+        let mut seen: HashMap<String, usize> = HashMap::new();
+        for (i, line) in contents.lines().enumerate() {
+            let Some(pattern) = parse_line(line).with_context(|| format!("line {}", i + 1))? else {
+                continue;
+            };
+            match seen.get(&pattern.text) {
+                Some(&index) => {
+                    let existing = &mut patterns[index];
+                    existing.tier = existing.tier.clone().max(pattern.tier);
+                }
+                None => {
+                    seen.insert(pattern.text.clone(), patterns.len());
+                    patterns.push(pattern);
+                }
+            }
+        }
+        let automaton = AhoCorasick::builder()
+            .match_kind(MatchKind::LeftmostLongest)
+            .build(patterns.iter().map(|p| &p.text))?;
+        Ok(Self {
+            patterns,
+            automaton,
+        })
+    }
+
+    /// The non-overlapping patterns found in `hash`, preferring the longest
+    /// pattern starting at each position.
+    pub fn matches(&self, hash: &str) -> Vec<RuleMatch> {
+        self.automaton
+            .find_iter(hash)
+            .map(|found| {
+                let pattern = &self.patterns[found.pattern().as_usize()];
+                RuleMatch {
+                    tier: pattern.tier.clone(),
+                    percentage: Some(probability::contains(&pattern.text, hash.len())),
+                    explanation: format!(
+                        "Contains pattern '{}' at offset {}",
+                        pattern.text,
+                        found.start()
+                    ),
+                    span: Some(Span::new(found.start(), &pattern.text)),
+                }
+            })
+            .collect()
+    }
+}
+
+/// Parses one line of a patterns file, returning `None` for blank and
+/// comment-only lines.
+fn parse_line(line: &str) -> Result<Option<Pattern>> {
+    let line = line.split_once('#').map_or(line, |(line, _)| line).trim();
+    if line.is_empty() {
+        return Ok(None);
+    }
+    let (tier, text) = match line.split_once(':') {
+        Some((tier, text)) => (
+            parse_tier(tier.trim()).map_err(|e| anyhow!(e))?,
+            text.trim(),
+        ),
+        None => (RarityTier::Uncommon, line),
+    };
+    if text.is_empty() {
+        bail!("missing pattern after tier");
+    }
+    if !text.chars().all(|c| c.is_ascii_hexdigit()) {
+        bail!("pattern '{text}' can never appear in a hash");
+    }
+    Ok(Some(Pattern {
+        text: text.to_ascii_lowercase(),
+        tier,
+    }))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::HASH_LEN;
+
+    const FILLER: &str = "7e3a91c5d48b2f371a9e73cb5d284e6f10a9c3b7";
+
+    #[test]
+    fn parses_tiers_and_comments() {
+        let set = PatternSet::parse(
+            "# Team in-jokes\n\
+             c0de\n\
+             \n\
+             rare: 5eed  # planted\n\
+             Legendary:CAFE\n",
+        )
+        .unwrap();
+        assert_eq!(
+            set.patterns,
+            [
+                Pattern {
+                    text: "c0de".into(),
+                    tier: RarityTier::Uncommon
+                },
+                Pattern {
+                    text: "5eed".into(),
+                    tier: RarityTier::Rare
+                },
+                Pattern {
+                    text: "cafe".into(),
+                    tier: RarityTier::Legendary
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn reports_pattern_and_offset() {
+        let set = PatternSet::parse("c0de\nrare:5eed\n").unwrap();
+        let hash = format!("{}5eed{}", &FILLER[..10], &FILLER[14..]);
+        let matches = set.matches(&hash);
+        assert_eq!(matches.len(), 1);
+        assert!(matches[0].tier == RarityTier::Rare);
+        assert_eq!(
+            matches[0].explanation,
+            "Contains pattern '5eed' at offset 10"
+        );
+        assert_eq!(matches[0].span, Some(Span::new(10, "5eed")));
+        assert_eq!(
+            matches[0].percentage,
+            Some(probability::contains("5eed", HASH_LEN))
+        );
+        assert!(set.matches(FILLER).is_empty());
+    }
+
+    #[test]
+    fn longest_pattern_wins_at_the_same_offset() {
+        let set = PatternSet::parse("c0de\nc0dec\n").unwrap();
+        let hash = format!("c0dec{}", &FILLER[5..]);
+        let matches = set.matches(&hash);
+        assert_eq!(matches.len(), 1);
+        assert_eq!(
+            matches[0].explanation,
+            "Contains pattern 'c0dec' at offset 0"
+        );
+    }
+
+    #[test]
+    fn matches_hundreds_of_patterns() {
+        let contents = (0..500u32)
+            .map(|i| {
+                format!(
+                    "{}:{:05x}\n",
+                    ["uncommon", "rare"][i as usize % 2],
+                    i * 2003
+                )
+            })
+            .collect::<String>();
+        let set = PatternSet::parse(&contents).unwrap();
+        assert_eq!(set.patterns.len(), 500);
+        // 499 * 2003 = 0xf4049, a rare pattern.
+        let hash = format!("{}f4049{}", &FILLER[..20], &FILLER[25..]);
+        let matches = set.matches(&hash);
+        assert_eq!(matches.len(), 1);
+        assert_eq!(
+            matches[0].explanation,
+            "Contains pattern 'f4049' at offset 20"
+        );
+        assert!(matches[0].tier == RarityTier::Rare);
+    }
+
+    #[test]
+    fn duplicate_patterns_keep_the_rarest_tier() {
+        let set = PatternSet::parse("rare:c0de\nc0de\nC0DE\n").unwrap();
+        assert_eq!(set.patterns.len(), 1);
+        assert!(set.patterns[0].tier == RarityTier::Rare);
+        let set = PatternSet::parse("c0de\nlegendary:c0de\n").unwrap();
+        assert!(set.patterns[0].tier == RarityTier::Legendary);
+    }
+
+    #[test]
+    fn rejects_invalid_tiers() {
+        let err = PatternSet::parse("c0de\nepic:5eed\n").unwrap_err();
+        assert_eq!(
+            format!("{err:#}"),
+            "line 2: invalid tier 'epic', expected one of: common, uncommon, rare, legendary"
+        );
+        let err = PatternSet::parse("rare:\n").unwrap_err();
+        assert_eq!(format!("{err:#}"), "line 1: missing pattern after tier");
+    }
+
+    #[test]
+    fn rejects_patterns_that_cannot_match() {
+        let err = PatternSet::parse("codename\n").unwrap_err();
+        assert_eq!(
+            format!("{err:#}"),
+            "line 1: pattern 'codename' can never appear in a hash"
+        );
+    }
+
+    #[test]
+    fn loads_from_a_file() {
+        let file = tempfile::NamedTempFile::new().unwrap();
+        fs::write(file.path(), "rare:5eed\n").unwrap();
+        assert_eq!(PatternSet::load(file.path()).unwrap().patterns.len(), 1);
+        let err = PatternSet::load(Path::new("/nonexistent/patterns.txt")).unwrap_err();
+        assert!(err.to_string().starts_with("failed to read patterns file"));
+    }
+}
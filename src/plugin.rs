@@ -0,0 +1,132 @@
+//! Rule plugins loaded from WebAssembly modules, given with `--plugin`.
+//!
+//! A plugin exports its `memory` and three functions:
+//!
+//! - `alloc(len: i32) -> i32` returns where the host may write `len` bytes.
+//! - `classify(ptr: i32, len: i32) -> i32` classifies the hash written at
+//!   `ptr`, returning 0 for no match or 1, 2 or 3 for Uncommon, Rare or
+//!   Legendary.
+//! - `explanation() -> i64` returns the explanation of the last match as
+//!   UTF-8 at `ptr << 32 | len`.
+//!
+//! Plugins are instantiated afresh for every hash, so they cannot keep state
+//! between commits.
+
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, OnceLock};
+use std::thread;
+use std::time::Duration;
+
+use anyhow::{bail, Context, Result};
+use wasmtime::{Config, Engine, InstancePre, Linker, Module, Store};
+
+use crate::{RarityTier, RuleMatch};
+
+/// How often the engine's epoch advances.
+const TICK: Duration = Duration::from_millis(10);
+
+/// Number of ticks a plugin may spend classifying one hash.
+const BUDGET_TICKS: u64 = 10;
+
+/// The engine shared by all plugins, whose epoch is advanced by a background
+/// thread to enforce the time budget.
+fn engine() -> &'static Engine {
+    static ENGINE: OnceLock<Engine> = OnceLock::new();
+    ENGINE.get_or_init(|| {
+        let mut config = Config::new();
+        config.epoch_interruption(true);
+        let engine = Engine::new(&config).expect("default engine config is valid");
+        let ticker = engine.clone();
+        thread::spawn(move || loop {
+            thread::sleep(TICK);
+            ticker.increment_epoch();
+        });
+        engine
+    })
+}
+
+#[derive(Clone)]
+pub struct Plugin {
+    /// File name of the module, used in warnings.
+    name: String,
+    pre: InstancePre<()>,
+    /// Set once the plugin has failed, after which it is skipped.
+    failed: Arc<AtomicBool>,
+}
+
+impl Plugin {
+    /// Compiles the module at `path`, which may be a binary or text module.
+    pub fn load(path: &Path) -> Result<Self> {
+        let module = Module::from_file(engine(), path)
+            .map_err(anyhow::Error::from)
+            .with_context(|| format!("failed to load plugin {}", path.display()))?;
+        let pre = Linker::new(engine())
+            .instantiate_pre(&module)
+            .map_err(anyhow::Error::from)
+            .with_context(|| format!("failed to link plugin {}", path.display()))?;
+        Ok(Self {
+            name: path.file_name().map_or_else(
+                || path.display().to_string(),
+                |n| n.to_string_lossy().into(),
+            ),
+            pre,
+            failed: Arc::new(AtomicBool::new(false)),
+        })
+    }
+
+    /// Classifies `hash`, warning about and then skipping plugins that trap,
+    /// exceed their time budget or break the interface.
+    pub fn rule_match(&self, hash: &str) -> Option<RuleMatch> {
+        if self.failed.load(Ordering::Relaxed) {
+            return None;
+        }
+        match self.classify(hash) {
+            Ok(found) => found.map(|(tier, explanation)| RuleMatch {
+                tier,
+                percentage: None,
+                explanation,
+                span: None,
+            }),
+            Err(e) => {
+                if !self.failed.swap(true, Ordering::Relaxed) {
+                    eprintln!("warning: skipping plugin {}: {e:#}", self.name);
+                }
+                None
+            }
+        }
+    }
+
+    fn classify(&self, hash: &str) -> Result<Option<(RarityTier, String)>> {
+        let mut store = Store::new(engine(), ());
+        store.set_epoch_deadline(BUDGET_TICKS);
+        let instance = self.pre.instantiate(&mut store)?;
+        let memory = instance
+            .get_memory(&mut store, "memory")
+            .context("plugin does not export its memory")?;
+        let alloc = instance.get_typed_func::<i32, i32>(&mut store, "alloc")?;
+        let classify = instance.get_typed_func::<(i32, i32), i32>(&mut store, "classify")?;
+        let explanation = instance.get_typed_func::<(), i64>(&mut store, "explanation")?;
+
+        let len = i32::try_from(hash.len())?;
+        let ptr = alloc.call(&mut store, len)?;
+        memory
+            .write(&mut store, u32::try_from(ptr)? as usize, hash.as_bytes())
+            .context("plugin allocated memory out of bounds")?;
+        let tier = match classify.call(&mut store, (ptr, len))? {
+            0 => return Ok(None),
+            1 => RarityTier::Uncommon,
+            2 => RarityTier::Rare,
+            3 => RarityTier::Legendary,
+            other => bail!("plugin returned unknown tier {other}"),
+        };
+        let packed = explanation.call(&mut store, ())? as u64;
+        let (ptr, len) = ((packed >> 32) as usize, (packed & 0xffff_ffff) as usize);
+        let bytes = memory
+            .data(&store)
+            .get(ptr..ptr + len)
+            .context("plugin explanation is out of bounds")?;
+        let explanation = std::str::from_utf8(bytes).context("plugin explanation is not UTF-8")?;
+        Ok(Some((tier, explanation.to_string())))
+    }
+}
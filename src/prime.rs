@@ -0,0 +1,74 @@
+//! A deterministic primality test for 64-bit numbers.
+
+/// Testing against these bases is enough to be exact for every `u64`.
+const BASES: [u64; 12] = [2, 3, 5, 7, 11, 13, 17, 19, 23, 29, 31, 37];
+
+/// Checks whether `n` is prime with the Miller–Rabin test.
+pub fn is_prime(n: u64) -> bool {
+    if n < 2 {
+        return false;
+    }
+    if let Some(&base) = BASES.iter().find(|&&base| n.is_multiple_of(base)) {
+        return n == base;
+    }
+    // Write n - 1 as d * 2^s with d odd.
+    let s = (n - 1).trailing_zeros();
+    let d = (n - 1) >> s;
+    BASES.iter().all(|&base| {
+        let mut x = pow_mod(base, d, n);
+        if x == 1 || x == n - 1 {
+            return true;
+        }
+        (1..s).any(|_| {
+            x = mul_mod(x, x, n);
+            x == n - 1
+        })
+    })
+}
+
+fn mul_mod(a: u64, b: u64, m: u64) -> u64 {
+    (a as u128 * b as u128 % m as u128) as u64
+}
+
+fn pow_mod(mut base: u64, mut exp: u64, m: u64) -> u64 {
+    let mut result = 1;
+    base %= m;
+    while exp > 0 {
+        if exp & 1 == 1 {
+            result = mul_mod(result, base, m);
+        }
+        base = mul_mod(base, base, m);
+        exp >>= 1;
+    }
+    result
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn small_numbers() {
+        let primes = (0..50).filter(|&n| is_prime(n)).collect::<Vec<u64>>();
+        assert_eq!(
+            primes,
+            [2, 3, 5, 7, 11, 13, 17, 19, 23, 29, 31, 37, 41, 43, 47]
+        );
+    }
+
+    #[test]
+    fn known_primes() {
+        assert!(is_prime(982451653));
+        assert!(is_prime(1000000007));
+        assert!(is_prime(999999999989));
+        assert!(is_prime(18446744073709551557));
+    }
+
+    #[test]
+    fn known_composites() {
+        assert!(!is_prime(982451655));
+        // A strong pseudoprime to bases 2, 3, 5 and 7.
+        assert!(!is_prime(3215031751));
+        assert!(!is_prime(1000000007 * 998244353));
+    }
+}
@@ -0,0 +1,421 @@
+//! Probabilities of the patterns the built-in rules look for, assuming every
+//! character of a hash is drawn uniformly from the 16 hex digits.
+
+use std::collections::HashMap;
+use std::sync::{Mutex, OnceLock};
+
+/// Number of distinct characters in a hex hash.
+const ALPHABET: usize = 16;
+
+/// Probability that a single character is a digit (0-9).
+pub const DIGIT: f64 = 10.0 / 16.0;
+
+/// Probability that a single character is a letter (a-f).
+pub const LETTER: f64 = 6.0 / 16.0;
+
+/// Probability that a single character is a binary digit (0 or 1).
+pub const BINARY: f64 = 2.0 / 16.0;
+
+/// Probability that `n` given positions all hold characters of a class that
+/// each character belongs to with probability `p`.
+pub fn all_of(p: f64, n: usize) -> f64 {
+    p.powi(n as i32)
+}
+
+/// Probability that `n` given positions hold one specific string.
+pub fn exact(n: usize) -> f64 {
+    all_of(1.0 / ALPHABET as f64, n)
+}
+
+/// Probability that a hash of `len` characters contains a run of at least
+/// `run` consecutive characters, each matching with probability `p`.
+pub fn run(p: f64, run: usize, len: usize) -> f64 {
+    if run == 0 {
+        return 1.0;
+    }
+    // states[i] is the probability of currently ending in a run of length i
+    // without ever having reached `run`.
+    let mut states = vec![0.0; run];
+    states[0] = 1.0;
+    let mut reached = 0.0;
+    for _ in 0..len {
+        let mut next = vec![0.0; run];
+        for (i, s) in states.iter().enumerate() {
+            next[0] += s * (1.0 - p);
+            if i + 1 == run {
+                reached += s * p;
+            } else {
+                next[i + 1] += s * p;
+            }
+        }
+        states = next;
+    }
+    reached
+}
+
+/// Probability that a hash of `len` characters contains a run of at least
+/// `n` copies of the same character.
+pub fn repeated(n: usize, len: usize) -> f64 {
+    // A run of n identical characters is n - 1 consecutive positions that
+    // each repeat the character before them.
+    match n {
+        0 | 1 => 1.0,
+        _ => run(1.0 / ALPHABET as f64, n - 1, len.saturating_sub(1)),
+    }
+}
+
+/// Probability that a hash of `len` characters contains a stretch of at
+/// least `n` characters that repeats with the given period.
+pub fn periodic(period: usize, n: usize, len: usize) -> f64 {
+    // Every character after the first unit must repeat the one a period
+    // before it.
+    match n.checked_sub(period) {
+        None | Some(0) => 1.0,
+        Some(n) => run(1.0 / ALPHABET as f64, n, len.saturating_sub(period)),
+    }
+}
+
+/// Probability that the first `n` characters of a hash form a palindrome.
+pub fn palindrome(n: usize) -> f64 {
+    // Only the first half is free; the rest mirrors it.
+    exact(n / 2)
+}
+
+/// Probability that a hash of `len` characters contains `word` anywhere.
+///
+/// This walks the KMP automaton of `word` so that overlapping
+/// self-similar words (like `0000`) are accounted for exactly.
+pub fn contains(word: &str, len: usize) -> f64 {
+    let word = word.as_bytes();
+    if word.is_empty() {
+        return 1.0;
+    }
+    if !word.iter().all(u8::is_ascii_hexdigit) {
+        return 0.0;
+    }
+    let alphabet = b"0123456789abcdef";
+    let transitions = (0..word.len())
+        .map(|matched| {
+            alphabet
+                .iter()
+                .map(|&c| longest_border(word, &word[..matched], c))
+                .collect::<Vec<usize>>()
+        })
+        .collect::<Vec<Vec<usize>>>();
+    let mut states = vec![0.0; word.len()];
+    states[0] = 1.0;
+    let mut reached = 0.0;
+    for _ in 0..len {
+        let mut next = vec![0.0; word.len()];
+        for (matched, s) in states.iter().enumerate() {
+            for &to in &transitions[matched] {
+                let p = s / ALPHABET as f64;
+                if to == word.len() {
+                    reached += p;
+                } else {
+                    next[to] += p;
+                }
+            }
+        }
+        states = next;
+    }
+    reached
+}
+
+/// Length of the longest prefix of `word` that is a suffix of `matched`
+/// followed by `c`.
+fn longest_border(word: &[u8], matched: &[u8], c: u8) -> usize {
+    let candidate = [matched, &[c]].concat();
+    (0..=candidate.len().min(word.len()))
+        .rev()
+        .find(|&n| candidate.ends_with(&word[..n]))
+        .unwrap_or(0)
+}
+
+/// Number of primes with exactly 9, 10, 11 and 12 digits.
+const PRIME_COUNTS: [(usize, f64); 4] = [
+    (9, 45086079.0),
+    (10, 404204977.0),
+    (11, 3663002302.0),
+    (12, 33489857205.0),
+];
+
+/// Probability that the first `n` characters of a hash form an `n`-digit
+/// prime, for the lengths in `PRIME_COUNTS`.
+pub fn prime_prefix(n: usize) -> Option<f64> {
+    PRIME_COUNTS
+        .iter()
+        .find(|(digits, _)| *digits == n)
+        .map(|(_, count)| count * exact(n))
+}
+
+/// Probability that a hash of `len` characters contains a run of at least
+/// `n` characters alternating between digits and letters.
+pub fn alternating(n: usize, len: usize) -> f64 {
+    if n <= 1 {
+        return if len > 0 { 1.0 } else { 0.0 };
+    }
+    // states[class][i] is the probability of the last character being a
+    // digit (0) or letter (1) and ending an alternating run of length i.
+    let mut states = vec![vec![0.0; n]; 2];
+    if len > 0 {
+        states[0][1] = DIGIT;
+        states[1][1] = LETTER;
+    }
+    let mut reached = 0.0;
+    for _ in 1..len {
+        let mut next = vec![vec![0.0; n]; 2];
+        for (class, runs) in states.iter().enumerate() {
+            let switch = if class == 0 { LETTER } else { DIGIT };
+            for (i, s) in runs.iter().enumerate() {
+                next[class][1] += s * (1.0 - switch);
+                if i + 1 == n {
+                    reached += s * switch;
+                } else {
+                    next[1 - class][i + 1] += s * switch;
+                }
+            }
+        }
+        states = next;
+    }
+    reached
+}
+
+/// Probability that a hash of `len` characters contains a run of at least
+/// `n` characters whose hex values never decrease, or always increase when
+/// `strict` is set.
+pub fn monotonic(n: usize, len: usize, strict: bool) -> f64 {
+    if n <= 1 {
+        return if len > 0 { 1.0 } else { 0.0 };
+    }
+    let p = 1.0 / ALPHABET as f64;
+    // states[value][i] is the probability of the last character having the
+    // given value and ending a monotonic run of length i.
+    let mut states = vec![vec![0.0; n]; ALPHABET];
+    if len > 0 {
+        for runs in &mut states {
+            runs[1] = p;
+        }
+    }
+    let mut reached = 0.0;
+    for _ in 1..len {
+        let mut next = vec![vec![0.0; n]; ALPHABET];
+        for (value, runs) in states.iter().enumerate() {
+            for (i, s) in runs.iter().enumerate() {
+                for (following, next_runs) in next.iter_mut().enumerate() {
+                    let continues = if strict {
+                        following > value
+                    } else {
+                        following >= value
+                    };
+                    if !continues {
+                        next_runs[1] += s * p;
+                    } else if i + 1 == n {
+                        reached += s * p;
+                    } else {
+                        next_runs[i + 1] += s * p;
+                    }
+                }
+            }
+        }
+        states = next;
+    }
+    reached
+}
+
+/// Probability that `n` characters contain at least `distinct` different
+/// hex digits.
+pub fn distinct(distinct: usize, n: usize) -> f64 {
+    // states[d] is the probability of having seen exactly d different digits.
+    let mut states = vec![0.0; ALPHABET + 1];
+    states[0] = 1.0;
+    for _ in 0..n {
+        let mut next = vec![0.0; ALPHABET + 1];
+        for (seen, s) in states.iter().enumerate() {
+            let new = (ALPHABET - seen) as f64 / ALPHABET as f64;
+            next[seen] += s * (1.0 - new);
+            if seen < ALPHABET {
+                next[seen + 1] += s * new;
+            }
+        }
+        states = next;
+    }
+    states.iter().skip(distinct).sum()
+}
+
+/// Probability that a hash of `len` characters contains a run of at least
+/// `n` characters whose hex values ascend (or descend) by exactly one.
+pub fn sequence(n: usize, len: usize) -> f64 {
+    static CACHE: OnceLock<Mutex<HashMap<(usize, usize), f64>>> = OnceLock::new();
+    // There are only 16 hex values, so no longer sequence can exist.
+    if n > ALPHABET {
+        return 0.0;
+    }
+    let mut cache = CACHE.get_or_init(Default::default).lock().unwrap();
+    *cache
+        .entry((n, len))
+        .or_insert_with(|| sequence_uncached(n, len))
+}
+
+fn sequence_uncached(n: usize, len: usize) -> f64 {
+    if n <= 1 {
+        return if len > 0 { 1.0 } else { 0.0 };
+    }
+    if len == 0 {
+        return 0.0;
+    }
+    // states[value][ascending][descending] is the probability of the last
+    // character being `value` while ending in runs of the given lengths.
+    let empty = vec![vec![vec![0.0; n]; n]; ALPHABET];
+    let mut states = empty.clone();
+    for value in states.iter_mut() {
+        value[1][1] = 1.0 / ALPHABET as f64;
+    }
+    let mut reached = 0.0;
+    for _ in 1..len {
+        let mut next = empty.clone();
+        for (prev, runs) in states.iter().enumerate() {
+            for (asc, descs) in runs.iter().enumerate() {
+                for (desc, s) in descs.iter().enumerate() {
+                    if *s == 0.0 {
+                        continue;
+                    }
+                    let p = s / ALPHABET as f64;
+                    for (value, next) in next.iter_mut().enumerate() {
+                        let asc = if value == prev + 1 { asc + 1 } else { 1 };
+                        let desc = if value + 1 == prev { desc + 1 } else { 1 };
+                        if asc >= n || desc >= n {
+                            reached += p;
+                        } else {
+                            next[asc][desc] += p;
+                        }
+                    }
+                }
+            }
+        }
+        states = next;
+    }
+    reached
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const HASH_LEN: usize = 40;
+
+    fn assert_close(actual: f64, expected: f64) {
+        let tolerance = expected.abs() * 1e-9;
+        assert!(
+            (actual - expected).abs() <= tolerance,
+            "expected {expected}, got {actual}"
+        );
+    }
+
+    #[test]
+    fn starts_or_ends_with_nine_digits() {
+        assert_close(all_of(DIGIT, 9), 0.014551915228366852);
+    }
+
+    #[test]
+    fn starts_or_ends_with_nine_letters() {
+        assert_close(all_of(LETTER, 9), 0.0001466497778892517);
+    }
+
+    #[test]
+    fn all_digits_or_letters() {
+        assert_close(all_of(DIGIT, HASH_LEN), 0.625f64.powi(40));
+        assert_close(all_of(LETTER, HASH_LEN), 0.375f64.powi(40));
+    }
+
+    #[test]
+    fn contains_nine_digit_run() {
+        assert_close(run(DIGIT, 9, HASH_LEN), 0.17517235515239918);
+        assert_eq!(run(DIGIT, 9, 8), 0.0);
+        assert_close(run(DIGIT, 9, 9), all_of(DIGIT, 9));
+    }
+
+    #[test]
+    fn contains_nine_letter_run() {
+        assert_close(run(LETTER, 9, HASH_LEN), 0.0029857532364730797);
+    }
+
+    #[test]
+    fn leading_zeros() {
+        assert_close(exact(8), 1.0 / 4294967296.0);
+    }
+
+    #[test]
+    fn repeated_characters() {
+        assert_close(repeated(7, HASH_LEN), 1.9036221565879527e-06);
+        assert_close(repeated(9, HASH_LEN), 6.999471211621213e-09);
+        assert_eq!(repeated(1, HASH_LEN), 1.0);
+    }
+
+    #[test]
+    fn periodic_patterns() {
+        assert_close(periodic(1, 9, HASH_LEN), repeated(9, HASH_LEN));
+        assert_close(periodic(2, 8, HASH_LEN), 1.8477428866267148e-06);
+        assert_eq!(periodic(2, 2, HASH_LEN), 1.0);
+    }
+
+    #[test]
+    fn palindromic_prefixes() {
+        assert_close(palindrome(9), exact(4));
+        assert_close(palindrome(10), exact(5));
+    }
+
+    #[test]
+    fn literal_words() {
+        assert_close(contains("deadbeef", HASH_LEN), 7.683411222959422e-09);
+        assert_close(contains("c0ffee", HASH_LEN), 2.0861610217087082e-06);
+        // Self-overlapping words are less likely than the naive estimate.
+        assert_close(contains("0000", HASH_LEN), 0.0005301344461636033);
+        assert!(contains("0000", HASH_LEN) < 37.0 * exact(4));
+        assert_eq!(contains("xyz", HASH_LEN), 0.0);
+    }
+
+    #[test]
+    fn prime_prefixes() {
+        assert_close(prime_prefix(9).unwrap(), 0.0006560887995874509);
+        assert_close(prime_prefix(12).unwrap(), 0.00011897987379327901);
+        assert_eq!(prime_prefix(13), None);
+    }
+
+    #[test]
+    fn monotonic_runs() {
+        assert_close(monotonic(12, HASH_LEN, false), 1.5582487574522333e-06);
+        assert_close(monotonic(10, HASH_LEN, true), 2.1833146761249924e-07);
+        // Strict runs are a subset of non-decreasing ones.
+        assert!(monotonic(10, HASH_LEN, true) < monotonic(10, HASH_LEN, false));
+        // Seventeen strictly increasing hex digits are impossible.
+        assert_eq!(monotonic(17, HASH_LEN, true), 0.0);
+        assert_eq!(monotonic(1, HASH_LEN, true), 1.0);
+    }
+
+    #[test]
+    fn alternating_runs() {
+        assert_close(alternating(10, HASH_LEN), 0.022505221624639117);
+        assert_close(alternating(14, HASH_LEN), 0.00108761291332933);
+        // Either class can start the run.
+        assert_close(alternating(10, 10), 2.0 * (DIGIT * LETTER).powi(5));
+        assert_eq!(alternating(10, 9), 0.0);
+    }
+
+    #[test]
+    fn distinct_digits() {
+        // 16! / 16^16 and 121 * 16! / 16^16.
+        assert_close(distinct(16, 16), 1.1342267125513672e-06);
+        assert_close(distinct(15, 16), 0.00013724143221871543);
+        assert_eq!(distinct(17, 16), 0.0);
+        assert_close(distinct(0, 16), 1.0);
+    }
+
+    #[test]
+    fn sequences() {
+        assert_close(sequence(7, HASH_LEN), 2.3948940066904913e-06);
+        assert_close(sequence(9, HASH_LEN), 7.0558598816862846e-09);
+        assert_eq!(sequence(17, HASH_LEN), 0.0);
+        assert_eq!(sequence(1, HASH_LEN), 1.0);
+    }
+}
@@ -0,0 +1,222 @@
+//! The orders `--sort-by` and `--reverse` print what's found in, rather than
+//! the order `git log` walked it in.
+
+use std::cmp::Ordering;
+
+use chrono::{DateTime, FixedOffset};
+use clap::ValueEnum;
+
+use crate::RarityTier;
+
+/// What `--sort-by` orders what's found by.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, ValueEnum)]
+pub enum SortKey {
+    /// Newest first.
+    Date,
+    /// Alphabetically by the author's name, ignoring case, and then by their
+    /// email.
+    Author,
+    /// Rarest first, and the least likely first within a tier.
+    Tier,
+    /// Least likely first.
+    Percentage,
+}
+
+/// What [`SortKey`] compares of a commit or object found.
+pub trait Sortable {
+    fn tier(&self) -> &RarityTier;
+    fn probability(&self) -> Option<f64>;
+    /// Unknown for hashes read with `--stdin` alone and for trees and blobs,
+    /// which are sorted after the others.
+    fn author(&self) -> Option<&str> {
+        None
+    }
+    /// Empty or unknown whenever the author is, and for tags.
+    fn email(&self) -> Option<&str> {
+        None
+    }
+    fn datetime(&self) -> Option<&DateTime<FixedOffset>> {
+        None
+    }
+}
+
+impl<T: Sortable> Sortable for (String, T) {
+    fn tier(&self) -> &RarityTier {
+        self.1.tier()
+    }
+
+    fn probability(&self) -> Option<f64> {
+        self.1.probability()
+    }
+
+    fn author(&self) -> Option<&str> {
+        self.1.author()
+    }
+
+    fn email(&self) -> Option<&str> {
+        self.1.email()
+    }
+
+    fn datetime(&self) -> Option<&DateTime<FixedOffset>> {
+        self.1.datetime()
+    }
+}
+
+/// Who the author of `found` is, ignoring case: their name, and then their
+/// email.
+fn identity(found: &impl Sortable) -> Option<(String, String)> {
+    let email = found.email().unwrap_or_default().to_lowercase();
+    found.author().map(|name| (name.to_lowercase(), email))
+}
+
+/// Orders known values by `order`, before unknown ones.
+fn known_first<T>(a: Option<T>, b: Option<T>, order: impl Fn(T, T) -> Ordering) -> Ordering {
+    match (a, b) {
+        (Some(a), Some(b)) => order(a, b),
+        (a, b) => b.is_some().cmp(&a.is_some()),
+    }
+}
+
+impl SortKey {
+    fn compare(self, a: &impl Sortable, b: &impl Sortable) -> Ordering {
+        let probability = || known_first(a.probability(), b.probability(), |a, b| a.total_cmp(&b));
+        match self {
+            Self::Date => known_first(a.datetime(), b.datetime(), |a, b| b.cmp(a)),
+            Self::Author => known_first(identity(a), identity(b), |a, b| a.cmp(&b)),
+            Self::Tier => b.tier().cmp(a.tier()).then_with(probability),
+            Self::Percentage => probability(),
+        }
+    }
+}
+
+/// Sorts `found` by `key`, the other way round with `reverse`, keeping those
+/// that compare equal in the order they were found in either way. With
+/// `reverse` alone, that order is reversed.
+pub fn sort<T: Sortable>(found: &mut [T], key: Option<SortKey>, reverse: bool) {
+    match key {
+        Some(key) if reverse => found.sort_by(|a, b| key.compare(b, a)),
+        Some(key) => found.sort_by(|a, b| key.compare(a, b)),
+        None if reverse => found.reverse(),
+        None => {}
+    }
+}
+
+/// Puts the rarest tier of `found` first, then the next, keeping the order
+/// of those of a tier.
+pub fn group<T: Sortable>(found: &mut [T]) {
+    found.sort_by(|a, b| b.tier().cmp(a.tier()));
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    struct Found {
+        name: &'static str,
+        tier: RarityTier,
+        probability: Option<f64>,
+        author: Option<&'static str>,
+        email: Option<&'static str>,
+        datetime: Option<DateTime<FixedOffset>>,
+    }
+
+    impl Sortable for Found {
+        fn tier(&self) -> &RarityTier {
+            &self.tier
+        }
+
+        fn probability(&self) -> Option<f64> {
+            self.probability
+        }
+
+        fn author(&self) -> Option<&str> {
+            self.author
+        }
+
+        fn email(&self) -> Option<&str> {
+            self.email
+        }
+
+        fn datetime(&self) -> Option<&DateTime<FixedOffset>> {
+            self.datetime.as_ref()
+        }
+    }
+
+    /// What's found, in the order it was walked in: `a` to `f`.
+    fn found() -> Vec<Found> {
+        let found = |name, tier, probability, author, minute: Option<u32>| Found {
+            name,
+            tier,
+            probability,
+            author,
+            email: None,
+            datetime: minute.map(|minute| {
+                DateTime::parse_from_rfc3339(&format!("2024-01-01T00:{minute:02}:00Z")).unwrap()
+            }),
+        };
+        vec![
+            found("a", RarityTier::Rare, Some(1e-5), Some("bob"), Some(5)),
+            found("b", RarityTier::Uncommon, None, Some("Alice"), Some(4)),
+            found("c", RarityTier::Legendary, None, Some("carol"), Some(5)),
+            found("d", RarityTier::Rare, Some(1e-7), Some("alice"), Some(1)),
+            found("e", RarityTier::Uncommon, Some(1e-3), None, None),
+            found("f", RarityTier::Rare, Some(1e-5), Some("Bob"), Some(9)),
+        ]
+    }
+
+    fn sorted(key: Option<SortKey>, reverse: bool) -> String {
+        let mut found = found();
+        sort(&mut found, key, reverse);
+        found.iter().map(|found| found.name).collect()
+    }
+
+    #[test]
+    fn each_key_sorts_stably() {
+        assert_eq!(sorted(None, false), "abcdef");
+        assert_eq!(sorted(Some(SortKey::Date), false), "facbde");
+        assert_eq!(sorted(Some(SortKey::Author), false), "bdafce");
+        assert_eq!(sorted(Some(SortKey::Tier), false), "cdafeb");
+        assert_eq!(sorted(Some(SortKey::Percentage), false), "dafebc");
+    }
+
+    #[test]
+    fn authors_are_sorted_by_their_name_then_email() {
+        let by = |name, author, email| Found {
+            name,
+            tier: RarityTier::Rare,
+            probability: None,
+            author: Some(author),
+            email,
+            datetime: None,
+        };
+        let mut found = vec![
+            by("a", "Jane Doe", Some("jane@example.com")),
+            by("b", "Adam", Some("zed@example.com")),
+            by("c", "jane doe", Some("JANE@example.com")),
+            by("d", "J. Doe", Some("jane@example.com")),
+            by("e", "jane", Some("")),
+            by("f", "jane doe", Some("doe@example.com")),
+        ];
+        sort(&mut found, Some(SortKey::Author), false);
+        let sorted = found.iter().map(|found| found.name).collect::<String>();
+        assert_eq!(sorted, "bdefac");
+    }
+
+    #[test]
+    fn groups_keep_the_order_of_their_tier() {
+        let mut found = found();
+        sort(&mut found, Some(SortKey::Date), false);
+        group(&mut found);
+        let grouped = found.iter().map(|found| found.name).collect::<String>();
+        assert_eq!(grouped, "cfadbe");
+    }
+
+    #[test]
+    fn reverse_keeps_ties_in_the_order_found() {
+        assert_eq!(sorted(None, true), "fedcba");
+        assert_eq!(sorted(Some(SortKey::Date), true), "edbacf");
+        assert_eq!(sorted(Some(SortKey::Author), true), "ecafbd");
+        assert_eq!(sorted(Some(SortKey::Tier), true), "beafdc");
+        assert_eq!(sorted(Some(SortKey::Percentage), true), "bceafd");
+    }
+}
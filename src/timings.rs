@@ -0,0 +1,184 @@
+//! How long each phase of a scan took, which `--timings` reports on stderr
+//! once everything is printed: waiting for git to print the log, parsing
+//! it, classifying the commits and rendering what's found.
+
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use std::sync::OnceLock;
+use std::time::{Duration, Instant};
+
+/// A phase of a scan, in the order they're reported.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Phase {
+    /// Waiting for `git log`, or libgit2, to give the next commit.
+    Git,
+    /// Reading the fields of each commit from what git printed.
+    Parsing,
+    /// Matching the rules against each hash.
+    Classification,
+    /// Printing the table or the other formats.
+    Rendering,
+}
+
+const PHASES: [(Phase, &str); 4] = [
+    (Phase::Git, "git"),
+    (Phase::Parsing, "parsing"),
+    (Phase::Classification, "classification"),
+    (Phase::Rendering, "rendering"),
+];
+
+/// The time spent in each [`Phase`] since git-rare started. The
+/// repositories of a scan of several are read in parallel, so each phase
+/// adds up the time of all of them and together they can take longer than
+/// the scan did.
+pub struct Timings {
+    start: Instant,
+    nanos: [AtomicU64; 4],
+    /// How many commits were classified.
+    commits: AtomicUsize,
+    /// How long git-rare had taken once the table was printed.
+    took: OnceLock<Duration>,
+}
+
+impl Default for Timings {
+    fn default() -> Self {
+        Self::new(Instant::now())
+    }
+}
+
+/// Adds the time from its creation to [`Phase`] once it's dropped, for
+/// timing what has more than one way to return.
+pub struct Measure<'a> {
+    timings: &'a Timings,
+    phase: Phase,
+    start: Instant,
+}
+
+impl Drop for Measure<'_> {
+    fn drop(&mut self) {
+        self.timings.add(self.phase, self.start.elapsed());
+    }
+}
+
+impl Timings {
+    pub fn new(start: Instant) -> Self {
+        Self {
+            start,
+            nanos: Default::default(),
+            commits: AtomicUsize::new(0),
+            took: OnceLock::new(),
+        }
+    }
+
+    /// How long it's been since git-rare started.
+    pub fn elapsed(&self) -> Duration {
+        self.start.elapsed()
+    }
+
+    /// Notes how long git-rare has taken, once the table is printed, for
+    /// the line after it.
+    pub fn stop(&self) {
+        let _ = self.took.set(self.elapsed());
+    }
+
+    /// How long git-rare took to print the table, when one was.
+    pub fn took(&self) -> Option<Duration> {
+        self.took.get().copied()
+    }
+
+    pub fn add(&self, phase: Phase, duration: Duration) {
+        let nanos = u64::try_from(duration.as_nanos()).unwrap_or(u64::MAX);
+        self.nanos[phase as usize].fetch_add(nanos, Ordering::Relaxed);
+    }
+
+    /// Runs `f`, adding the time it takes to `phase`.
+    pub fn time<T>(&self, phase: Phase, f: impl FnOnce() -> T) -> T {
+        let start = Instant::now();
+        let result = f();
+        self.add(phase, start.elapsed());
+        result
+    }
+
+    /// Times `phase` until what's returned is dropped.
+    pub fn measure(&self, phase: Phase) -> Measure<'_> {
+        Measure {
+            timings: self,
+            phase,
+            start: Instant::now(),
+        }
+    }
+
+    /// Counts `commits` more classified.
+    pub fn count(&self, commits: usize) {
+        self.commits.fetch_add(commits, Ordering::Relaxed);
+    }
+
+    fn duration(&self, phase: Phase) -> Duration {
+        Duration::from_nanos(self.nanos[phase as usize].load(Ordering::Relaxed))
+    }
+
+    /// A line for each phase with how long it took and how many commits a
+    /// second it got through, followed by the total.
+    pub fn report(&self) -> String {
+        let commits = self.commits.load(Ordering::Relaxed);
+        report(
+            PHASES.map(|(phase, name)| (name, self.duration(phase))),
+            commits,
+            self.elapsed(),
+        )
+    }
+}
+
+fn report(phases: [(&str, Duration); 4], commits: usize, total: Duration) -> String {
+    let rate = |duration: Duration| match duration.as_secs_f64() {
+        secs if secs > 0.0 && commits > 0 => {
+            format!(", {:.0} commits/s", commits as f64 / secs)
+        }
+        _ => String::new(),
+    };
+    let mut report = String::new();
+    for (name, duration) in phases {
+        report.push_str(&format!("{name:<15} {duration:>12.3?}{}\n", rate(duration)));
+    }
+    let plural = if commits == 1 { "" } else { "s" };
+    report.push_str(&format!(
+        "{:<15} {total:>12.3?}{} ({commits} commit{plural})\n",
+        "total",
+        rate(total)
+    ));
+    report
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn phases_are_reported_with_their_rate() {
+        let phases = [
+            ("git", Duration::from_millis(250)),
+            ("parsing", Duration::from_millis(10)),
+            ("classification", Duration::from_millis(500)),
+            ("rendering", Duration::ZERO),
+        ];
+        assert_eq!(
+            report(phases, 1000, Duration::from_secs(1)),
+            "\
+git                250.000ms, 4000 commits/s
+parsing             10.000ms, 100000 commits/s
+classification     500.000ms, 2000 commits/s
+rendering            0.000ns
+total                 1.000s, 1000 commits/s (1000 commits)
+"
+        );
+        assert_eq!(
+            report(
+                phases.map(|(name, _)| (name, Duration::ZERO)),
+                0,
+                Duration::ZERO
+            )
+            .lines()
+            .last(),
+            Some("total                0.000ns (0 commits)")
+        );
+    }
+}
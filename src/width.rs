@@ -0,0 +1,102 @@
+//! Fitting the table in the width of the terminal, or of `--max-width`: the
+//! columns that can lose their end to an ellipsis are cut until each row is
+//! a single line, while hashes, tiers and the others are left whole.
+
+use std::io::IsTerminal;
+
+use tabled::grid::util::string::{string_width, string_width_multiline};
+use tabled::settings::object::Columns;
+use tabled::settings::Width;
+use tabled::Table;
+
+/// The fewest characters a column is cut to, ellipsis included.
+const MIN_WIDTH: usize = 6;
+
+/// How readily a column is cut: those of the first level are cut before any
+/// of the second is, and the others never are.
+fn level(name: &str) -> Option<usize> {
+    match name {
+        "Explanation" | "Subject" | "Email" | "Path" => Some(0),
+        "Author" => Some(1),
+        _ => None,
+    }
+}
+
+/// The width the table is fit in: `max_width` when it's given, or else the
+/// terminal's when printing to one rather than to a file with `--output`.
+pub fn limit(max_width: Option<usize>, to_file: bool) -> Option<usize> {
+    if max_width.is_some() {
+        return max_width;
+    }
+    if to_file || !std::io::stdout().is_terminal() {
+        return None;
+    }
+    terminal_size::terminal_size().map(|(width, _)| width.0.into())
+}
+
+/// How many columns of a terminal `line` takes: two for a wide character
+/// like an emoji, and none for the escape sequences that color it.
+pub fn line(line: &str) -> usize {
+    string_width(line)
+}
+
+/// The widths of columns `widths` wide whose `levels` are those of [`level`]
+/// once `excess` characters are taken from them, from the widest column of
+/// a level first, and no more than leaves each [`MIN_WIDTH`] wide.
+fn shrink(widths: &[usize], levels: &[Option<usize>], excess: usize) -> Vec<usize> {
+    let mut widths = widths.to_vec();
+    let mut excess = excess;
+    for cut in [0, 1] {
+        while excess > 0 {
+            let widest = (0..widths.len())
+                .filter(|&i| levels[i] == Some(cut) && widths[i] > MIN_WIDTH)
+                .max_by_key(|&i| widths[i]);
+            let Some(widest) = widest else {
+                break;
+            };
+            widths[widest] -= 1;
+            excess -= 1;
+        }
+    }
+    widths
+}
+
+/// Cuts the columns of `table`, named `names`, so it's no wider than
+/// `max_width` where it can be.
+pub fn fit(table: &mut Table, names: &[&str], max_width: usize) {
+    let excess = table.total_width().saturating_sub(max_width);
+    if excess == 0 {
+        return;
+    }
+    let mut widths = vec![0; names.len()];
+    for row in table.get_records().iter() {
+        for (width, cell) in widths.iter_mut().zip(row) {
+            *width = (*width).max(string_width_multiline(cell.as_ref()));
+        }
+    }
+    let levels = names.iter().map(|name| level(name)).collect::<Vec<_>>();
+    let fitted = shrink(&widths, &levels, excess);
+    for (i, (&fitted, &width)) in fitted.iter().zip(&widths).enumerate() {
+        if fitted < width {
+            table.modify(Columns::single(i), Width::truncate(fitted).suffix("…"));
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn the_widest_columns_are_cut_first() {
+        let levels = [None, Some(1), Some(0), Some(0)];
+        assert_eq!(shrink(&[40, 20, 30, 10], &levels, 0), [40, 20, 30, 10]);
+        assert_eq!(shrink(&[40, 20, 30, 10], &levels, 15), [40, 20, 15, 10]);
+        assert_eq!(shrink(&[40, 20, 30, 10], &levels, 26), [40, 20, 7, 7]);
+        // The author is only cut once the others are as short as they go.
+        assert_eq!(shrink(&[40, 20, 30, 10], &levels, 30), [40, 18, 6, 6]);
+        assert_eq!(shrink(&[40, 20, 30, 10], &levels, 100), [40, 6, 6, 6]);
+        // Columns narrower than the least they're cut to are left alone.
+        assert_eq!(shrink(&[40, 4, 3], &levels[..3], 10), [40, 4, 3]);
+    }
+}
@@ -0,0 +1,120 @@
+//! An embedded list of English words that can be spelled in hex.
+
+use std::sync::OnceLock;
+
+use crate::hexspeak;
+
+/// Shorter words show up by chance in most repositories.
+pub const MIN_LEN: usize = 6;
+
+/// One word per line, spelled normally.
+const WORDLIST: &str = include_str!("../assets/hex_words.txt");
+
+/// Digits that stand in for letters outside a-f in leet spellings.
+const LEET: &[(char, char)] = &[('o', '0'), ('l', '1'), ('s', '5'), ('t', '7')];
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct WordMatch {
+    pub word: &'static str,
+    /// How the word is spelled in the hash.
+    pub spelling: &'static str,
+    pub offset: usize,
+}
+
+impl WordMatch {
+    /// Whether the word needed leet substitutions to be spelled in hex.
+    pub fn is_leet(&self) -> bool {
+        self.word != self.spelling
+    }
+}
+
+/// Every word in the list with its hex spelling, skipping words that
+/// can't be spelled even with leet substitutions.
+fn words() -> &'static [(&'static str, String)] {
+    static WORDS: OnceLock<Vec<(&'static str, String)>> = OnceLock::new();
+    WORDS.get_or_init(|| {
+        WORDLIST
+            .lines()
+            .map(str::trim)
+            .filter(|word| word.len() >= MIN_LEN)
+            .filter_map(|word| Some((word, spell(word)?)))
+            .collect()
+    })
+}
+
+fn spell(word: &str) -> Option<String> {
+    word.chars().map(|c| spell_char(c, true)).collect()
+}
+
+/// How a lowercase letter is spelled in hex, if it can be, optionally
+/// allowing leet substitutions.
+pub fn spell_char(c: char, leet: bool) -> Option<char> {
+    match LEET.iter().find(|(letter, _)| *letter == c) {
+        Some((_, digit)) => leet.then_some(*digit),
+        None => matches!(c, 'a'..='f').then_some(c),
+    }
+}
+
+/// Finds the longest word in `hash`, preferring the earliest one when
+/// several words of the same length match. Leet spellings are only
+/// considered when `leet` is set.
+pub fn find(hash: &str, leet: bool) -> Option<WordMatch> {
+    words()
+        .iter()
+        .filter(|(word, spelling)| leet || word == spelling)
+        // These are already reported as hexspeak.
+        .filter(|(_, spelling)| !hexspeak::WORDS.contains(&spelling.as_str()))
+        .filter_map(|(word, spelling)| {
+            hash.find(spelling.as_str()).map(|offset| WordMatch {
+                word,
+                spelling,
+                offset,
+            })
+        })
+        .min_by_key(|m| (std::cmp::Reverse(m.word.len()), m.offset))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn finds_strict_hex_words() {
+        let found = find("7e3a91c5d48bdecade1a9e73cb5d284e6f10a9c3", false).unwrap();
+        assert_eq!(found.word, "decade");
+        assert_eq!(found.offset, 12);
+        assert!(!found.is_leet());
+    }
+
+    #[test]
+    fn finds_leet_words_only_when_enabled() {
+        let hash = "5caff01d91c5d48b2f6e1a9e73cb5d284e6f10a9";
+        assert_eq!(find(hash, false), None);
+        let found = find(hash, true).unwrap();
+        assert_eq!(found.word, "scaffold");
+        assert_eq!(found.spelling, "5caff01d");
+        assert!(found.is_leet());
+    }
+
+    #[test]
+    fn ignores_short_words() {
+        // "bead", "faced" and "decaf" are words, but too short to report.
+        assert_eq!(find("7e3a91bead8b2f6faced9e73decaf284e6f10a9c", true), None);
+    }
+
+    #[test]
+    fn leaves_hexspeak_to_the_hexspeak_rule() {
+        assert_eq!(
+            find("7e3a91c5d48bfacade1a9e73cb5d284e6f10a9c3", false),
+            None
+        );
+    }
+
+    #[test]
+    fn every_listed_word_can_be_spelled() {
+        for word in WORDLIST.lines() {
+            assert!(word.len() >= MIN_LEN, "'{word}' is too short");
+            assert!(spell(word).is_some(), "'{word}' can't be spelled in hex");
+        }
+    }
+}
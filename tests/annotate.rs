@@ -0,0 +1,203 @@
+mod common;
+
+use common::{backends, fixture, git_command, TestRepo};
+
+/// A repository with twenty commits and an identity for the notes written
+/// to it, with their hashes.
+fn annotated_repo() -> (TestRepo, Vec<String>) {
+    let repo = TestRepo::new();
+    repo.git(&["config", "user.name", "John Doe"]);
+    repo.git(&["config", "user.email", "john@example.com"]);
+    let hashes = (0..20)
+        .map(|i| repo.commit(&format!("commit {i}")))
+        .collect();
+    (repo, hashes)
+}
+
+/// Runs `git-rare annotate` with the rules of the `rules.toml` fixture,
+/// scanning with `args` and annotating with `annotate_args`.
+fn annotate(repo: &TestRepo, args: &[&str], annotate_args: &[&str]) -> String {
+    let config = fixture("rules.toml");
+    let args = [
+        &["--config", config.to_str().unwrap()],
+        args,
+        &["annotate"],
+        annotate_args,
+    ]
+    .concat();
+    repo.git_rare_stdout(&args)
+}
+
+/// The hash and tier of each commit in `repo` that isn't Common, as
+/// `git-rare` lists them with the rules of the `rules.toml` fixture and `args`.
+fn tiers(repo: &TestRepo, args: &[&str]) -> Vec<(String, String)> {
+    let config = fixture("rules.toml");
+    let args = [&["--config", config.to_str().unwrap()], args].concat();
+    repo.git_rare_stdout(&args)
+        .lines()
+        .filter_map(|line| {
+            let cells = line.split('│').map(str::trim).collect::<Vec<&str>>();
+            let hash = cells.iter().find(|cell| cell.len() == 40)?;
+            let tier = cells.iter().rev().find(|cell| !cell.is_empty())?;
+            Some((hash.to_string(), tier.to_string()))
+        })
+        .collect()
+}
+
+/// The note on `hash` under refs/notes/rare, as git reads it back.
+fn note(repo: &TestRepo, hash: &str) -> Option<String> {
+    let output = git_command(repo.path())
+        .args(["notes", "--ref=rare", "show", hash])
+        .output()
+        .unwrap();
+    output
+        .status
+        .success()
+        .then(|| String::from_utf8(output.stdout).unwrap())
+}
+
+#[test]
+fn notes_are_written_on_the_commits_found() {
+    for backend in backends() {
+        let (repo, hashes) = annotated_repo();
+        let found = tiers(&repo, &["--backend", backend]);
+        let stdout = annotate(&repo, &["--backend", backend], &[]);
+        for hash in &hashes {
+            let note = note(&repo, hash);
+            match found.iter().find(|(found, _)| found == hash) {
+                Some((_, tier)) => {
+                    let note = note.unwrap_or_else(|| panic!("{backend}: no note on {hash}"));
+                    assert!(
+                        note.starts_with(&format!("Tier: {tier}\nExplanation: ")),
+                        "{note}"
+                    );
+                }
+                None => assert_eq!(note, None, "{backend} {hash}"),
+            }
+        }
+        assert!(!found.is_empty());
+        assert!(
+            stdout.contains(&format!(
+                "Wrote {} notes under refs/notes/rare",
+                found.len()
+            )),
+            "{backend}: {stdout}"
+        );
+    }
+}
+
+#[test]
+fn a_note_holds_the_explanation_and_probability() {
+    let (repo, hashes) = annotated_repo();
+    let rule = format!("--rule=legendary:^{}", &hashes[3][..5]);
+    annotate(&repo, &["--disable-rule", "starts_digits", &rule], &[]);
+    let legendary = note(&repo, &hashes[3]).unwrap();
+    assert!(
+        legendary.starts_with("Tier: Legendary\nExplanation: "),
+        "{legendary}"
+    );
+    assert!(
+        legendary.contains(&format!("'{}' at offset 0", &hashes[3][..5])),
+        "{legendary}"
+    );
+    // Another commit has no match but the fixture's first rule.
+    let (hash, note) = hashes
+        .iter()
+        .find_map(|hash| {
+            let note = note(&repo, hash)?;
+            let digit = note.starts_with("Tier: Uncommon\nExplanation: Starts with a digit: ");
+            (digit && !note.contains(';')).then_some((hash, note))
+        })
+        .expect("a commit that only starts with a digit");
+    assert_eq!(
+        note,
+        format!(
+            "Tier: Uncommon\nExplanation: Starts with a digit: '{}' at offset 0\n\
+             Probability: 0.625\n",
+            &hash[..1]
+        )
+    );
+}
+
+#[test]
+fn annotating_again_only_writes_what_changed() {
+    for backend in backends() {
+        let (repo, hashes) = annotated_repo();
+        annotate(&repo, &["--backend", backend], &[]);
+        let notes_commits = || repo.git(&["rev-list", "--count", "refs/notes/rare"]);
+        let written = notes_commits();
+        let stdout = annotate(&repo, &["--backend", backend], &[]);
+        assert!(stdout.starts_with("Wrote 0 notes"), "{backend}: {stdout}");
+        assert_eq!(notes_commits(), written);
+
+        // A commit a new rule finds gets a note, and the others keep theirs.
+        let unannotated = hashes.iter().find(|hash| note(&repo, hash).is_none());
+        let unannotated = unannotated.expect("a common commit");
+        let rule = format!("legendary:^{unannotated}$");
+        let stdout = annotate(&repo, &["--backend", backend, "--rule", &rule], &[]);
+        assert!(stdout.starts_with("Wrote 1 notes"), "{backend}: {stdout}");
+        assert!(note(&repo, unannotated)
+            .unwrap()
+            .starts_with("Tier: Legendary"));
+        let written = written.parse::<usize>().unwrap() + 1;
+        assert_eq!(notes_commits(), written.to_string());
+    }
+}
+
+#[test]
+fn tags_are_created_with_tags() {
+    for backend in backends() {
+        let (repo, _) = annotated_repo();
+        let stdout = annotate(
+            &repo,
+            &["--backend", backend, "--only", "rare"],
+            &["--tags"],
+        );
+        let rare = tiers(&repo, &["--backend", backend])
+            .into_iter()
+            .filter(|(_, tier)| tier == "Rare")
+            .map(|(hash, _)| hash)
+            .collect::<Vec<String>>();
+        assert!(
+            stdout.contains(&format!("Created {} tags", rare.len())),
+            "{stdout}"
+        );
+        for hash in rare {
+            let tag = format!("rare/{}", &hash[..7]);
+            assert_eq!(repo.git(&["rev-parse", &tag]), hash, "{backend}");
+        }
+    }
+}
+
+#[test]
+fn dry_run_writes_nothing() {
+    let (repo, _) = annotated_repo();
+    let stdout = annotate(&repo, &[], &["--tags", "--dry-run"]);
+    assert!(stdout.contains("Would write a note on "), "{stdout}");
+    assert!(stdout.contains("    Tier: "), "{stdout}");
+    assert!(stdout.contains("Would create the tag rare/"), "{stdout}");
+    let notes = git_command(repo.path())
+        .args(["rev-parse", "--verify", "--quiet", "refs/notes/rare"])
+        .output()
+        .unwrap();
+    assert!(!notes.status.success());
+    assert_eq!(repo.git(&["tag", "--list"]), "");
+}
+
+#[test]
+fn common_commits_arent_annotated() {
+    let (repo, _) = annotated_repo();
+    let output = repo.git_rare(&["--only", "common", "annotate"]);
+    assert!(!output.status.success(), "{output:?}");
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(stderr.contains("aren't Common"), "{stderr}");
+    assert_eq!(repo.git(&["notes", "--ref", "rare", "list"]), "");
+}
+
+#[test]
+fn the_git_backend_writes_every_note_in_one_commit() {
+    let (repo, _) = annotated_repo();
+    let stdout = annotate(&repo, &["--backend", "git"], &[]);
+    assert!(!stdout.starts_with("Wrote 0 notes"), "{stdout}");
+    assert_eq!(repo.git(&["rev-list", "--count", "refs/notes/rare"]), "1");
+}
@@ -0,0 +1,130 @@
+mod common;
+
+use common::{backends, git_command, git_rare_with_stdin, scanned, TestRepo};
+
+/// A repository with five commits by John Doe <john@example.com>, and three
+/// by Jane Roe <jane@corp.test> between them.
+fn two_author_repo() -> TestRepo {
+    let repo = TestRepo::new();
+    for i in 0..8 {
+        if i % 3 == 1 {
+            let output = git_command(repo.path())
+                .args(["commit", "-q", "--allow-empty", "-m", &format!("jane {i}")])
+                .env("GIT_AUTHOR_NAME", "Jane Roe")
+                .env("GIT_AUTHOR_EMAIL", "jane@corp.test")
+                .env("GIT_AUTHOR_DATE", format!("2024-01-01T00:0{i}:30Z"))
+                .env("GIT_COMMITTER_DATE", format!("2024-01-01T00:0{i}:30Z"))
+                .output()
+                .unwrap();
+            assert!(output.status.success(), "{output:?}");
+        } else {
+            repo.commit(&format!("john {i}"));
+        }
+    }
+    repo
+}
+
+/// The commits of `author`, newest first, as git lists them.
+fn commits_by(repo: &TestRepo, author: &str) -> Vec<String> {
+    let commits = repo.git(&["rev-list", "--fixed-strings", "--author", author, "HEAD"]);
+    commits.lines().map(str::to_string).collect()
+}
+
+#[test]
+fn authors_are_matched_by_name_or_email() {
+    let repo = two_author_repo();
+    let (jane, john) = (commits_by(&repo, "Jane Roe"), commits_by(&repo, "John Doe"));
+    assert_eq!((jane.len(), john.len()), (3, 5));
+    for backend in backends() {
+        assert_eq!(scanned(&repo, backend, &[]).len(), 8, "{backend}");
+        assert_eq!(
+            scanned(&repo, backend, &["--author", "Jane"]),
+            jane,
+            "{backend}"
+        );
+        assert_eq!(
+            scanned(&repo, backend, &["--author", r"@corp\.test"]),
+            jane,
+            "{backend}"
+        );
+        assert_eq!(
+            scanned(&repo, backend, &["--author", "^John"]),
+            john,
+            "{backend}"
+        );
+        assert!(scanned(&repo, backend, &["--author", "nobody"]).is_empty());
+    }
+}
+
+#[test]
+fn authors_are_matched_as_rust_regexes_by_every_backend() {
+    let repo = two_author_repo();
+    let jane = commits_by(&repo, "Jane Roe");
+    // Neither is a POSIX extended regular expression, which git reads.
+    for pattern in ["(?i)^JANE ROE", r"^\D+ Roe <"] {
+        for backend in backends() {
+            assert_eq!(
+                scanned(&repo, backend, &["--author", pattern]),
+                jane,
+                "{backend}: {pattern}"
+            );
+            let limited = ["--author", pattern, "--max-count", "2"];
+            assert_eq!(
+                scanned(&repo, backend, &limited),
+                jane[..2],
+                "{backend}: {pattern}"
+            );
+        }
+    }
+}
+
+#[test]
+fn repeated_authors_match_any_of_them() {
+    let repo = two_author_repo();
+    for backend in backends() {
+        let both = scanned(&repo, backend, &["--author", "jane@", "--author", "Doe"]);
+        assert_eq!(both.len(), 8, "{backend}");
+        assert_eq!(scanned(&repo, backend, &["--author", "Jane|John"]).len(), 8);
+    }
+}
+
+#[test]
+fn counts_only_include_the_authors_commits() {
+    let repo = two_author_repo();
+    let expected = repo.git(&["rev-list", "--count", "--author=Jane", "HEAD"]);
+    assert_eq!(expected, "3");
+    for backend in backends() {
+        let stdout = repo.git_rare_stdout(&["--backend", backend, "--author", "Jane", "--count"]);
+        assert!(stdout.contains("│ 3 "), "{backend}:\n{stdout}");
+        let stdout = repo.git_rare_stdout(&[
+            "--backend",
+            backend,
+            "--author",
+            "Jane",
+            "--since",
+            "2024-01-01T00:05:00Z",
+            "--count",
+        ]);
+        assert!(stdout.contains("│ 1 "), "{backend}:\n{stdout}");
+    }
+}
+
+#[test]
+fn stdin_lines_are_filtered_by_author_name() {
+    let repo = two_author_repo();
+    let log = repo.git(&["log", "--pretty=%H %aI %an"]);
+    let output = git_rare_with_stdin(repo.path(), &["--stdin", "--all", "--author", "Roe$"], &log);
+    assert!(output.status.success(), "{output:?}");
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert_eq!(stdout.matches("Jane Roe").count(), 3, "{stdout}");
+    assert!(!stdout.contains("John Doe"), "{stdout}");
+}
+
+#[test]
+fn invalid_author_patterns_are_rejected() {
+    let repo = two_author_repo();
+    let output = repo.git_rare(&["--author", "(Jane"]);
+    assert!(!output.status.success());
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(stderr.contains("invalid regex '(Jane'"), "{stderr}");
+}
@@ -0,0 +1,116 @@
+#![cfg(feature = "libgit2")]
+
+mod common;
+
+use std::process::Command;
+
+use common::TestRepo;
+
+/// A repository with a merge and messages that exercise the message rules.
+fn fixture_repo() -> TestRepo {
+    let repo = TestRepo::new();
+    for i in 0..20 {
+        repo.commit(&format!("commit {i}\n\nFixes #{i}"));
+    }
+    repo.git(&["checkout", "-q", "-b", "topic", "HEAD~5"]);
+    for i in 0..5 {
+        repo.commit(&format!("topic {i}"));
+    }
+    repo.git(&["checkout", "-q", "-"]);
+    repo.commit("before merge");
+    repo.git(&["merge", "-q", "--no-ff", "-m", "Merge topic", "topic"]);
+    repo.commit("after merge");
+    repo
+}
+
+/// What git-rare prints with `backend` and `args`.
+fn rows(repo: &TestRepo, backend: &str, args: &[&str]) -> String {
+    let args = [&["--backend", backend][..], args].concat();
+    repo.git_rare_stdout(&args)
+}
+
+fn assert_same_output(repo: &TestRepo, args: &[&str]) {
+    let git = rows(repo, "git", args);
+    let libgit2 = rows(repo, "libgit2", args);
+    assert_eq!(git, libgit2);
+}
+
+#[test]
+fn backends_produce_identical_results() {
+    let repo = fixture_repo();
+    assert_same_output(&repo, &["--all"]);
+    assert_same_output(&repo, &["--count"]);
+    assert!(rows(&repo, "libgit2", &["--count"]).contains("│ 28 "));
+}
+
+#[test]
+fn backends_agree_on_a_detached_head() {
+    let repo = fixture_repo();
+    let newest = repo.git(&["rev-parse", "HEAD"]);
+    repo.git(&["checkout", "-q", "--detach", "HEAD~3"]);
+    assert_same_output(&repo, &["--all"]);
+    assert!(!rows(&repo, "libgit2", &["--all"]).contains(&newest));
+}
+
+#[test]
+fn backends_agree_on_a_checked_out_annotated_tag() {
+    let repo = fixture_repo();
+    repo.git(&["tag", "-a", "v1.0", "-m", "Release 1.0", "HEAD~10"]);
+    repo.git(&["checkout", "-q", "v1.0"]);
+    assert_same_output(&repo, &["--all"]);
+}
+
+#[test]
+fn libgit2_backend_does_not_need_git_installed() {
+    let repo = fixture_repo();
+    let head = repo.git(&["rev-parse", "HEAD"]);
+    let output = Command::new(env!("CARGO_BIN_EXE_git-rare"))
+        .current_dir(repo.path())
+        .args(["--backend", "libgit2", "--all"])
+        .env("PATH", repo.path().join("no-bin"))
+        .env("XDG_CONFIG_HOME", repo.path().join(".no-config"))
+        .env("GIT_CONFIG_GLOBAL", common::NULL_DEVICE)
+        .env("GIT_CONFIG_NOSYSTEM", "1")
+        .output()
+        .unwrap();
+    assert!(output.status.success(), "{output:?}");
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(stdout.contains(&head), "{stdout}");
+
+    let output = Command::new(env!("CARGO_BIN_EXE_git-rare"))
+        .current_dir(repo.path())
+        .args(["--backend", "git"])
+        .env("PATH", repo.path().join("no-bin"))
+        .output()
+        .unwrap();
+    assert!(!output.status.success());
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    let name = format!("git{}", std::env::consts::EXE_SUFFIX);
+    assert!(
+        stderr.contains(&format!("{name} wasn't found on PATH")),
+        "{stderr}"
+    );
+    assert!(stderr.contains("--backend libgit2"), "{stderr}");
+}
+
+#[test]
+fn libgit2_backend_reports_an_empty_repository() {
+    let repo = TestRepo::new();
+    let stdout = repo.git_rare_stdout(&["--backend", "libgit2"]);
+    assert_eq!(stdout.trim(), "No commits found.");
+}
+
+#[cfg(unix)]
+#[test]
+fn a_chatty_git_doesnt_stall_the_log() {
+    let repo = fixture_repo();
+    let head = repo.git(&["rev-parse", "HEAD"]);
+    let output = common::git_rare_with_chatty_git(&repo, &["--backend", "git", "--all"]);
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(stdout.contains(&head), "{stdout}");
+}
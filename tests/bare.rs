@@ -0,0 +1,76 @@
+mod common;
+
+use std::fs;
+use std::path::Path;
+
+use common::{backends, git_command, git_rare, TestRepo};
+
+/// A repository with a `.mailmap`, a branch besides main and a tag, cloned
+/// next to it both normally and with `--bare`. Returns it with the paths of
+/// the two clones.
+fn clones() -> (TestRepo, String, String) {
+    let repo = TestRepo::new();
+    fs::write(repo.path().join(".mailmap"), "Johnny <john@example.com>\n").unwrap();
+    repo.git(&["add", ".mailmap"]);
+    for i in 0..6 {
+        repo.commit(&format!("main {i}"));
+    }
+    repo.git(&["tag", "v1.0", "HEAD~2"]);
+    repo.git(&["checkout", "-q", "-b", "topic", "HEAD~3"]);
+    for i in 0..3 {
+        repo.commit(&format!("topic {i}"));
+    }
+    repo.git(&["checkout", "-q", "main"]);
+    let source = repo.path().to_str().unwrap().to_string();
+    let clone = repo.path().join("clones/work");
+    let bare = repo.path().join("clones/project.git");
+    for (path, flags) in [(&clone, &[][..]), (&bare, &["--bare"])] {
+        let output = git_command(repo.path())
+            .args(["clone", "-q"])
+            .args(flags)
+            .args([&source, path.to_str().unwrap()])
+            .output()
+            .unwrap();
+        assert!(output.status.success(), "{output:?}");
+    }
+    let path = |path: &Path| path.to_str().unwrap().to_string();
+    (repo, path(&clone), path(&bare))
+}
+
+/// The output of git-rare run in `dir` with `args`.
+fn scan(dir: &Path, args: &[&str]) -> String {
+    let output = git_rare(dir, args);
+    assert!(output.status.success(), "{args:?}: {output:?}");
+    String::from_utf8(output.stdout).unwrap()
+}
+
+#[test]
+fn bare_repositories_scan_like_their_work_tree_clones() {
+    let (_repo, clone, bare) = clones();
+    let scans: [&[&str]; 5] = [
+        &["--all"],
+        &["--count"],
+        &["--all", "--all-refs"],
+        &["--all", "--tags"],
+        &["--count", "--all-refs", "--recurse-submodules"],
+    ];
+    for backend in backends() {
+        for flags in scans {
+            let args = [&["--backend", backend, "--rule", "legendary:."][..], flags].concat();
+            let expected = scan(Path::new(&clone), &args);
+            assert_eq!(scan(Path::new(&bare), &args), expected, "{args:?}");
+        }
+    }
+}
+
+#[test]
+fn bare_repositories_can_be_scanned_from_elsewhere() {
+    let (repo, clone, bare) = clones();
+    for backend in backends() {
+        let args = ["--backend", backend, "--all", "--rule", "legendary:."];
+        let expected = scan(Path::new(&clone), &args);
+        assert!(expected.contains("Johnny"), "{expected}");
+        let args = [&args[..], &["-C", &bare]].concat();
+        assert_eq!(scan(repo.path(), &args), expected, "{backend}");
+    }
+}
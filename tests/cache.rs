@@ -0,0 +1,145 @@
+mod common;
+
+use std::path::Path;
+
+use common::{backends, git_command, git_rare, repo_with_commits};
+
+/// The output of git-rare run in `dir` with `args`, and the cache counters
+/// it printed to stderr.
+fn scan(dir: &Path, args: &[&str]) -> (String, String) {
+    let output = git_rare(dir, args);
+    assert!(output.status.success(), "{args:?}: {output:?}");
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    let counters = String::from_utf8(output.stderr)
+        .unwrap()
+        .lines()
+        .filter(|line| line.starts_with("Classified"))
+        .collect::<Vec<&str>>()
+        .join("\n");
+    (stdout, counters)
+}
+
+#[test]
+fn only_new_commits_are_classified_again() {
+    for backend in backends() {
+        let (repo, _) = repo_with_commits(5);
+        let args = ["--backend", backend, "--all", "--verbose"];
+        let (_, counters) = scan(repo.path(), &args);
+        assert_eq!(
+            counters, "Classified 5 commits and reused 0 cached classifications",
+            "{backend}"
+        );
+        repo.commit("one more");
+        let (stdout, counters) = scan(repo.path(), &args);
+        assert_eq!(
+            counters, "Classified 1 commit and reused 5 cached classifications",
+            "{backend}"
+        );
+        let (uncached, counters) = scan(repo.path(), &[&args[..], &["--no-cache"]].concat());
+        assert_eq!(uncached, stdout, "{backend}");
+        assert!(counters.is_empty(), "{backend}: {counters}");
+        // Timing the scan doesn't change what it's cached by.
+        let (_, counters) = scan(repo.path(), &[&args[..], &["--timings"]].concat());
+        assert_eq!(
+            counters, "Classified 0 commits and reused 6 cached classifications",
+            "{backend}"
+        );
+    }
+}
+
+#[test]
+fn rewritten_history_is_classified_again() {
+    for backend in backends() {
+        let (repo, _) = repo_with_commits(4);
+        let args = ["--backend", backend, "--all", "--verbose"];
+        scan(repo.path(), &args);
+        let output = git_command(repo.path())
+            .args(["commit", "-q", "--amend", "--allow-empty", "-m", "amended"])
+            .output()
+            .unwrap();
+        assert!(output.status.success(), "{output:?}");
+        let (stdout, counters) = scan(repo.path(), &args);
+        assert_eq!(
+            counters, "Classified 1 commit and reused 3 cached classifications",
+            "{backend}"
+        );
+        let (uncached, _) = scan(repo.path(), &[&args[..], &["--no-cache"]].concat());
+        assert_eq!(uncached, stdout, "{backend}");
+
+        repo.git(&["reset", "-q", "--hard", "HEAD~2"]);
+        let (_, counters) = scan(repo.path(), &args);
+        assert_eq!(
+            counters, "Classified 0 commits and reused 2 cached classifications",
+            "{backend}"
+        );
+    }
+}
+
+#[test]
+fn changing_the_rules_invalidates_the_cache() {
+    let (repo, _) = repo_with_commits(3);
+    let args = ["--all", "--verbose"];
+    scan(repo.path(), &args);
+    let (stdout, counters) = scan(repo.path(), &[&args[..], &["--rule", "rare:^"]].concat());
+    assert_eq!(
+        counters,
+        "Classified 3 commits and reused 0 cached classifications"
+    );
+    assert_eq!(stdout.matches("│ Rare").count(), 3, "{stdout}");
+    let (_, counters) = scan(repo.path(), &[&args[..], &["--min-run", "5"]].concat());
+    assert_eq!(
+        counters,
+        "Classified 3 commits and reused 0 cached classifications"
+    );
+    // Each walk and rule set keeps a cache of its own.
+    let (_, counters) = scan(repo.path(), &args);
+    assert_eq!(
+        counters,
+        "Classified 0 commits and reused 3 cached classifications"
+    );
+}
+
+#[test]
+fn the_cache_can_be_cleared() {
+    let (repo, _) = repo_with_commits(2);
+    scan(repo.path(), &["--count"]);
+    let cache = repo.path().join(".git/git-rare/cache");
+    assert!(cache.is_dir());
+    let (stdout, _) = scan(repo.path(), &["cache", "clear"]);
+    assert!(
+        stdout.starts_with("Deleted the cached classifications"),
+        "{stdout}"
+    );
+    assert!(!cache.exists());
+    let (stdout, _) = scan(repo.path(), &["cache", "clear"]);
+    assert!(stdout.starts_with("No cached classifications"), "{stdout}");
+    let (_, counters) = scan(repo.path(), &["--count", "--verbose"]);
+    assert_eq!(
+        counters,
+        "Classified 2 commits and reused 0 cached classifications"
+    );
+}
+
+#[test]
+fn worktrees_share_the_cache() {
+    let (repo, _) = repo_with_commits(3);
+    let worktree = repo.path().join("worktree");
+    repo.git(&[
+        "worktree",
+        "add",
+        "-q",
+        "--detach",
+        worktree.to_str().unwrap(),
+    ]);
+    let args = ["--count", "--verbose"];
+    scan(repo.path(), &args);
+    let (_, counters) = scan(&worktree, &args);
+    assert_eq!(
+        counters,
+        "Classified 0 commits and reused 3 cached classifications"
+    );
+    assert!(!repo
+        .path()
+        .join(".git/worktrees/worktree/git-rare")
+        .exists());
+}
@@ -0,0 +1,96 @@
+mod common;
+
+use common::{backends, git_rare_command, repo_with_commits};
+
+/// `text` without the escape sequences that color it.
+fn strip_colors(text: &str) -> String {
+    let mut stripped = String::new();
+    let mut chars = text.chars();
+    while let Some(c) = chars.next() {
+        if c == '\x1b' {
+            chars.by_ref().find(|&c| c == 'm');
+        } else {
+            stripped.push(c);
+        }
+    }
+    stripped
+}
+
+/// The lines of a table, which the time it took doesn't follow on stdout.
+fn table(stdout: &str) -> Vec<&str> {
+    let lines = stdout.lines().collect::<Vec<&str>>();
+    assert!(!stdout.contains("This operation took"), "{stdout}");
+    lines
+}
+
+#[test]
+fn always_colors_the_matches_and_tiers() {
+    let (repo, hashes) = repo_with_commits(3);
+    for backend in backends() {
+        let args = ["--backend", backend, "--rule", "rare:^[0-9a-f]{2}"];
+        let colored = repo.git_rare_stdout(&[&args[..], &["--color", "always"]].concat());
+        for hash in &hashes {
+            let highlighted = format!("\x1b[35m{}\x1b[0m{}", &hash[..2], &hash[2..]);
+            assert!(
+                colored.contains(&highlighted),
+                "{backend}: {hash} in {colored}"
+            );
+        }
+        assert!(
+            colored.contains("\x1b[35mRare\x1b[0m"),
+            "{backend}: {colored}"
+        );
+        // The escape sequences take no room, so the columns line up as they
+        // do without them.
+        let plain = repo.git_rare_stdout(&args);
+        let stripped = strip_colors(&colored);
+        assert_eq!(table(&stripped), table(&plain), "{backend}");
+    }
+}
+
+#[test]
+fn tiers_have_colors_of_their_own() {
+    let (repo, _) = repo_with_commits(3);
+    let args = ["--rule", "uncommon:^", "--color", "always"];
+    let stdout = repo.git_rare_stdout(&args);
+    assert!(stdout.contains("\x1b[33mUncommon\x1b[0m"), "{stdout}");
+    // The header of the column isn't a tier, and stays as it is.
+    assert!(stdout.contains(" Tier "), "{stdout}");
+}
+
+#[test]
+fn piped_output_has_no_colors() {
+    let (repo, _) = repo_with_commits(3);
+    for args in [
+        &["--all"][..],
+        &["--all", "--color", "auto"],
+        &["--all", "--color", "never"],
+    ] {
+        let stdout = repo.git_rare_stdout(args);
+        assert!(!stdout.contains('\x1b'), "{args:?}: {stdout}");
+    }
+    // Nor with a format for other tools to read, or when writing to a file.
+    let stdout = repo.git_rare_stdout(&["--all", "--plain", "--color", "always"]);
+    assert!(!stdout.contains('\x1b'), "{stdout}");
+    let stdout = repo.git_rare_stdout(&["--all", "--format", "markdown", "--color", "always"]);
+    assert!(!stdout.contains('\x1b'), "{stdout}");
+}
+
+#[test]
+fn no_color_is_honored() {
+    let (repo, _) = repo_with_commits(3);
+    let output = git_rare_command(repo.path())
+        .args(["--all"])
+        .env("NO_COLOR", "1")
+        .output()
+        .unwrap();
+    assert!(output.status.success(), "{output:?}");
+    assert!(!output.stdout.contains(&b'\x1b'), "{output:?}");
+    // Asking for colors outweighs it.
+    let output = git_rare_command(repo.path())
+        .args(["--rule", "rare:^", "--color", "always"])
+        .env("NO_COLOR", "1")
+        .output()
+        .unwrap();
+    assert!(output.stdout.contains(&b'\x1b'), "{output:?}");
+}
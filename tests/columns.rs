@@ -0,0 +1,144 @@
+mod common;
+
+use std::fs;
+
+use common::{backends, repo_with_commits};
+
+/// The cells of each row of a table, without its borders or the footer.
+fn cells(stdout: &str) -> Vec<Vec<String>> {
+    stdout
+        .lines()
+        .filter(|line| line.starts_with('│'))
+        .map(|line| {
+            line.trim_matches('│')
+                .split('│')
+                .map(|cell| cell.trim().to_string())
+                .collect()
+        })
+        .collect()
+}
+
+#[test]
+fn columns_are_chosen_and_ordered() {
+    let (repo, mut hashes) = repo_with_commits(3);
+    // Newest first, as they're printed.
+    hashes.reverse();
+    for backend in backends() {
+        let stdout =
+            repo.git_rare_stdout(&["--backend", backend, "--all", "--columns", "tier,Hash"]);
+        let rows = cells(&stdout);
+        assert_eq!(rows[0], ["Tier", "Hash"], "{backend}: {stdout}");
+        let found = rows[1..]
+            .iter()
+            .map(|row| row[1].clone())
+            .collect::<Vec<String>>();
+        assert_eq!(found, hashes, "{backend}");
+        assert!(rows[1..].iter().all(|row| row[0] == "Common"), "{stdout}");
+    }
+
+    let stdout = repo.git_rare_stdout(&["--all", "--columns", "HASH,explanation"]);
+    assert_eq!(cells(&stdout)[0], ["Hash", "Explanation"]);
+    let stdout = repo.git_rare_stdout(&[
+        "--all",
+        "--columns",
+        "percentage,datetime,author",
+        "-C",
+        ".",
+        "-C",
+        ".",
+    ]);
+    assert_eq!(
+        cells(&stdout)[0],
+        ["Repository", "Percentage", "Datetime", "Author"]
+    );
+}
+
+#[test]
+fn csv_and_markdown_have_the_columns() {
+    let (repo, mut hashes) = repo_with_commits(3);
+    // Newest first, as they're printed.
+    hashes.reverse();
+    let csv = repo.git_rare_stdout(&[
+        "--all",
+        "--format",
+        "csv",
+        "--columns",
+        "tier,hash",
+        "--footer",
+    ]);
+    let expected = ["tier,hash".to_string()]
+        .into_iter()
+        .chain(hashes.iter().map(|hash| format!("Common,{hash}")))
+        // The footer's record has neither, so the footer takes the place of
+        // its explanation.
+        .chain(["3 commits shown: 3 Common,".to_string()])
+        .collect::<Vec<String>>();
+    assert_eq!(csv.lines().collect::<Vec<&str>>(), expected);
+
+    let csv = repo.git_rare_stdout(&["--all", "--format", "csv", "--columns", "percentage"]);
+    assert_eq!(csv.lines().next(), Some("probability"));
+
+    let markdown =
+        repo.git_rare_stdout(&["--all", "--format", "markdown", "--columns", "hash,tier"]);
+    assert_eq!(
+        markdown.lines().next(),
+        Some("| Hash                                     | Tier   |")
+    );
+}
+
+#[test]
+fn odds_are_shown_in_place_of_the_percentage() {
+    let (repo, _) = repo_with_commits(2);
+    let config = repo.path().join(".no-config/git-rare");
+    fs::create_dir_all(&config).unwrap();
+    fs::write(
+        config.join("config.toml"),
+        "[[rules]]\nname = \"all\"\npattern = \"^\"\ntier = \"rare\"\n\
+         explanation = \"Anything\"\nprobability = 0.000244140625\n",
+    )
+    .unwrap();
+    for backend in backends() {
+        let rows = cells(&repo.git_rare_stdout(&["--backend", backend]));
+        assert_eq!(
+            rows[0],
+            ["Author", "Datetime", "Hash", "Explanation", "Odds", "Tier"],
+            "{backend}"
+        );
+        assert!(
+            rows[1..].iter().all(|row| row[4] == "1 in 4,096"),
+            "{rows:?}"
+        );
+    }
+
+    let rows = cells(&repo.git_rare_stdout(&["--columns", "odds,percentage"]));
+    assert_eq!(rows[0], ["Odds", "Percentage"]);
+    assert_eq!(rows[1], ["1 in 4,096", "0.000244140625"]);
+
+    let csv = repo.git_rare_stdout(&["--format", "csv", "--columns", "tier,odds", "--footer"]);
+    let lines = csv.lines().collect::<Vec<&str>>();
+    assert_eq!(
+        lines,
+        [
+            "tier,odds",
+            "Rare,\"1 in 4,096\"",
+            "Rare,\"1 in 4,096\"",
+            "\"2 commits shown: 2 Rare (1 in 16,777,216 together)\",\"1 in 16,777,216\""
+        ]
+    );
+    // Without asking for them, CSV has the probability alone.
+    let csv = repo.git_rare_stdout(&["--format", "csv"]);
+    assert!(csv.starts_with("hash,author,datetime,tier,explanation,probability,email\n"));
+}
+
+#[test]
+fn unknown_columns_list_the_valid_ones() {
+    let (repo, _) = repo_with_commits(1);
+    let output = repo.git_rare(&["--columns", "hash,message"]);
+    assert!(!output.status.success());
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(stderr.contains("invalid value 'message'"), "{stderr}");
+    assert!(
+        stderr.contains("hash, author, datetime, tier, explanation, percentage"),
+        "{stderr}"
+    );
+}
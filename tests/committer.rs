@@ -0,0 +1,86 @@
+mod common;
+
+use common::{backends, git_command, TestRepo};
+
+/// A repository with a commit authored by John Doe in 2024 and amended by
+/// Carol Roe in 2025, after an older commit by both of them.
+fn amended_repo() -> (TestRepo, String) {
+    let repo = TestRepo::new();
+    repo.commit("older");
+    repo.commit("amended");
+    let output = git_command(repo.path())
+        .args(["commit", "-q", "--amend", "--allow-empty", "--no-edit"])
+        .env("GIT_COMMITTER_NAME", "Carol Roe")
+        .env("GIT_COMMITTER_EMAIL", "carol@example.com")
+        .env("GIT_COMMITTER_DATE", "2025-06-01T12:00:00+02:00")
+        .output()
+        .unwrap();
+    assert!(output.status.success(), "{output:?}");
+    let hash = repo.git(&["rev-parse", "HEAD"]);
+    (repo, hash)
+}
+
+/// The author and date cells of the row for `hash` in `git-rare --all`.
+fn row(repo: &TestRepo, backend: &str, hash: &str, args: &[&str]) -> Option<[String; 2]> {
+    let args = [&["--backend", backend, "--all"][..], args].concat();
+    let stdout = repo.git_rare_stdout(&args);
+    let line = stdout.lines().find(|line| line.contains(hash))?;
+    let cells = line.split('│').map(str::trim).collect::<Vec<&str>>();
+    Some([cells[1].to_string(), cells[2].to_string()])
+}
+
+#[test]
+fn author_or_committer_is_shown() {
+    let (repo, hash) = amended_repo();
+    for backend in backends() {
+        assert_eq!(
+            row(&repo, backend, &hash, &[]).unwrap(),
+            ["John Doe", "2024-01-01 00:01:00 +00:00"],
+            "{backend}"
+        );
+        assert_eq!(
+            row(&repo, backend, &hash, &["--use-committer"]).unwrap(),
+            ["Carol Roe", "2025-06-01 12:00:00 +02:00"],
+            "{backend}"
+        );
+    }
+}
+
+#[test]
+fn dates_are_filtered_by_the_commit_date() {
+    let (repo, hash) = amended_repo();
+    for backend in backends() {
+        for shown in [&[][..], &["--use-committer"]] {
+            let since = [&["--since", "2025-01-01"][..], shown].concat();
+            assert!(row(&repo, backend, &hash, &since).is_some(), "{backend}");
+            let until = [&["--until", "2024-12-31"][..], shown].concat();
+            assert!(row(&repo, backend, &hash, &until).is_none(), "{backend}");
+        }
+
+        let since = ["--author-date", "--since", "2025-01-01"];
+        assert!(row(&repo, backend, &hash, &since).is_none(), "{backend}");
+        let until = ["--author-date", "--until", "2024-12-31"];
+        assert!(row(&repo, backend, &hash, &until).is_some(), "{backend}");
+    }
+}
+
+#[test]
+fn author_dates_are_filtered_before_counting() {
+    let (repo, _) = amended_repo();
+    for backend in backends() {
+        let stdout = repo.git_rare_stdout(&[
+            "--backend",
+            backend,
+            "--author-date",
+            "--until",
+            "2024-12-31",
+            "--max-count",
+            "1",
+            "--count",
+        ]);
+        assert!(stdout.contains("│ 1 "), "{backend}:\n{stdout}");
+        let stdout =
+            repo.git_rare_stdout(&["--backend", backend, "--until", "2024-12-31", "--count"]);
+        assert!(stdout.contains("│ 1 "), "{backend}:\n{stdout}");
+    }
+}
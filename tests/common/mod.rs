@@ -0,0 +1,240 @@
+//! Helpers for running git-rare against throwaway repositories.
+
+#![allow(dead_code)]
+
+use std::cell::Cell;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Output, Stdio};
+
+use tempfile::TempDir;
+
+/// A git repository in a temporary directory whose commits have fixed
+/// identities and dates, so their hashes are reproducible.
+pub struct TestRepo {
+    dir: TempDir,
+    commits: Cell<u32>,
+}
+
+impl TestRepo {
+    pub fn new() -> Self {
+        Self::init(&[])
+    }
+
+    /// A repository using the SHA-256 object format, whose hashes are 64
+    /// characters long.
+    pub fn sha256() -> Self {
+        Self::init(&["--object-format=sha256"])
+    }
+
+    fn init(args: &[&str]) -> Self {
+        let repo = Self {
+            dir: TempDir::new().unwrap(),
+            commits: Cell::new(0),
+        };
+        repo.git(&[&["init", "-q", "-b", "main"][..], args].concat());
+        repo
+    }
+
+    pub fn path(&self) -> &Path {
+        self.dir.path()
+    }
+
+    /// Runs git in the repository, panicking if it fails, and returns its
+    /// trimmed stdout.
+    pub fn git(&self, args: &[&str]) -> String {
+        let output = git_command(self.path()).args(args).output().unwrap();
+        assert!(
+            output.status.success(),
+            "git {args:?} failed: {}",
+            String::from_utf8_lossy(&output.stderr)
+        );
+        String::from_utf8(output.stdout).unwrap().trim().to_string()
+    }
+
+    /// Creates an empty commit one minute after the previous one and
+    /// returns its hash.
+    pub fn commit(&self, message: &str) -> String {
+        let n = self.commits.get();
+        self.commits.set(n + 1);
+        let date = format!("2024-01-01T{:02}:{:02}:00Z", n / 60, n % 60);
+        let output = git_command(self.path())
+            .args(["commit", "-q", "--allow-empty", "-m", message])
+            .env("GIT_AUTHOR_DATE", &date)
+            .env("GIT_COMMITTER_DATE", &date)
+            .output()
+            .unwrap();
+        assert!(output.status.success(), "{output:?}");
+        self.git(&["rev-parse", "HEAD"])
+    }
+
+    /// Runs git-rare in the repository.
+    pub fn git_rare(&self, args: &[&str]) -> Output {
+        git_rare(self.path(), args)
+    }
+
+    /// Runs git-rare in the repository, asserting success, and returns its
+    /// stdout.
+    pub fn git_rare_stdout(&self, args: &[&str]) -> String {
+        let output = self.git_rare(args);
+        assert!(
+            output.status.success(),
+            "git-rare {args:?} failed: {}",
+            String::from_utf8_lossy(&output.stderr)
+        );
+        String::from_utf8(output.stdout).unwrap()
+    }
+}
+
+/// Where git reads an empty config from, in place of the user's.
+pub const NULL_DEVICE: &str = if cfg!(windows) { "NUL" } else { "/dev/null" };
+
+/// A git command with a fixed identity that ignores the user's config.
+pub fn git_command(dir: &Path) -> Command {
+    let mut command = Command::new("git");
+    command
+        .current_dir(dir)
+        .env("GIT_CONFIG_GLOBAL", NULL_DEVICE)
+        .env("GIT_CONFIG_NOSYSTEM", "1")
+        .env("GIT_AUTHOR_NAME", "John Doe")
+        .env("GIT_AUTHOR_EMAIL", "john@example.com")
+        .env("GIT_COMMITTER_NAME", "John Doe")
+        .env("GIT_COMMITTER_EMAIL", "john@example.com");
+    command
+}
+
+/// A git-rare command in `dir` that doesn't pick up the user's config file.
+pub fn git_rare_unstyled(dir: &Path) -> Command {
+    let mut command = Command::new(env!("CARGO_BIN_EXE_git-rare"));
+    command
+        .current_dir(dir)
+        .env("XDG_CONFIG_HOME", dir.join(".no-config"))
+        .env("GIT_CONFIG_GLOBAL", NULL_DEVICE)
+        .env("GIT_CONFIG_NOSYSTEM", "1");
+    command
+}
+
+/// A git-rare command like [`git_rare_unstyled`] that draws tables as it
+/// does in a terminal, though its stdout is piped to the test.
+pub fn git_rare_command(dir: &Path) -> Command {
+    let mut command = git_rare_unstyled(dir);
+    command.args(["--style", "rounded"]);
+    command
+}
+
+/// Runs git-rare in `dir` without picking up the user's config file.
+pub fn git_rare(dir: &Path, args: &[&str]) -> Output {
+    git_rare_command(dir).args(args).output().unwrap()
+}
+
+/// Runs git-rare in `dir` like [`git_rare`], writing `input` to its stdin.
+pub fn git_rare_with_stdin(dir: &Path, args: &[&str], input: &str) -> Output {
+    let mut child = git_rare_command(dir)
+        .args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .unwrap();
+    child
+        .stdin
+        .take()
+        .unwrap()
+        .write_all(input.as_bytes())
+        .unwrap();
+    child.wait_with_output().unwrap()
+}
+
+/// Runs git-rare in `repo` with a `git` first on `PATH` that prints a
+/// megabyte on stderr before running the real one, much more than a pipe
+/// holds. Fails the test if the scan doesn't finish within a minute, as it
+/// wouldn't if git-rare left stderr to fill up while reading stdout.
+#[cfg(unix)]
+pub fn git_rare_with_chatty_git(repo: &TestRepo, args: &[&str]) -> Output {
+    use std::os::unix::fs::PermissionsExt;
+    use std::sync::mpsc;
+    use std::time::Duration;
+
+    let real = Command::new("sh")
+        .args(["-c", "command -v git"])
+        .output()
+        .unwrap();
+    let real = String::from_utf8(real.stdout).unwrap();
+    let bin = TempDir::new().unwrap();
+    let git = bin.path().join("git");
+    std::fs::write(
+        &git,
+        format!(
+            "#!/bin/sh\nhead -c 1048576 /dev/zero | tr '\\0' . >&2\nexec '{}' \"$@\"\n",
+            real.trim()
+        ),
+    )
+    .unwrap();
+    std::fs::set_permissions(&git, std::fs::Permissions::from_mode(0o755)).unwrap();
+    let path = format!(
+        "{}:{}",
+        bin.path().display(),
+        std::env::var("PATH").unwrap_or_default()
+    );
+    let child = git_rare_command(repo.path())
+        .args(args)
+        .env("PATH", path)
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .unwrap();
+    let (sender, receiver) = mpsc::channel();
+    std::thread::spawn(move || sender.send(child.wait_with_output().unwrap()));
+    receiver
+        .recv_timeout(Duration::from_secs(60))
+        .expect("git-rare stalled on git's stderr")
+}
+
+/// Path to a file in `tests/fixtures`.
+pub fn fixture(name: &str) -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("tests")
+        .join("fixtures")
+        .join(name)
+}
+
+/// The backends a test runs against: git, and libgit2 when it's built.
+pub fn backends() -> Vec<&'static str> {
+    let mut backends = vec!["git"];
+    if cfg!(feature = "libgit2") {
+        backends.push("libgit2");
+    }
+    backends
+}
+
+/// Makes every hash Legendary, so `--only legendary` lists every commit
+/// scanned.
+pub const LEGENDARY_RULE: [&str; 2] = ["--rule", "legendary:."];
+
+/// A repository with `n` commits, and their hashes, oldest first.
+pub fn repo_with_commits(n: usize) -> (TestRepo, Vec<String>) {
+    let repo = TestRepo::new();
+    let hashes = (0..n)
+        .map(|i| repo.commit(&format!("commit {i}")))
+        .collect();
+    (repo, hashes)
+}
+
+/// The full hashes in the rows of a table, in order.
+pub fn hashes(stdout: &str) -> Vec<String> {
+    stdout
+        .lines()
+        .filter_map(|line| {
+            line.split('│')
+                .map(str::trim)
+                .find(|cell| cell.len() == 40 && cell.chars().all(|c| c.is_ascii_hexdigit()))
+                .map(str::to_string)
+        })
+        .collect()
+}
+
+/// Hashes listed by `git-rare --all` with `backend` and `args`, in order.
+pub fn scanned(repo: &TestRepo, backend: &str, args: &[&str]) -> Vec<String> {
+    let args = [&["--backend", backend, "--all"][..], args].concat();
+    hashes(&repo.git_rare_stdout(&args))
+}
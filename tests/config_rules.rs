@@ -0,0 +1,149 @@
+mod common;
+
+use common::{fixture, TestRepo};
+
+#[test]
+fn config_rules_are_reported_with_their_explanation() {
+    let repo = TestRepo::new();
+    let hashes = (0..20)
+        .map(|i| repo.commit(&format!("commit {i}")))
+        .collect::<Vec<String>>();
+    let config = fixture("rules.toml");
+    let stdout = repo.git_rare_stdout(&["--all", "--config", config.to_str().unwrap()]);
+
+    for hash in &hashes {
+        let row = stdout
+            .lines()
+            .find(|line| line.contains(hash.as_str()))
+            .unwrap_or_else(|| panic!("{hash} missing from output:\n{stdout}"));
+        let starts_with_digit = hash.starts_with(|c: char| c.is_ascii_digit());
+        let ends_with_letter = hash.ends_with(|c: char| c.is_ascii_alphabetic());
+        assert_eq!(
+            row.contains("Starts with a digit"),
+            starts_with_digit,
+            "{row}"
+        );
+        assert_eq!(
+            row.contains("Ends with a letter"),
+            ends_with_letter,
+            "{row}"
+        );
+        assert!(!row.contains("Contains a g"), "{row}");
+    }
+}
+
+#[test]
+fn config_rules_flow_into_only() {
+    let repo = TestRepo::new();
+    let hashes = (0..20)
+        .map(|i| repo.commit(&format!("commit {i}")))
+        .collect::<Vec<String>>();
+    let config = fixture("rules.toml");
+    let stdout = repo.git_rare_stdout(&["--only", "rare", "--config", config.to_str().unwrap()]);
+
+    for hash in hashes
+        .iter()
+        .filter(|h| h.ends_with(|c: char| c.is_ascii_alphabetic()))
+    {
+        assert!(stdout.contains(hash.as_str()), "{hash} missing:\n{stdout}");
+    }
+}
+
+#[test]
+fn invalid_config_rules_are_reported_by_name() {
+    let repo = TestRepo::new();
+    repo.commit("initial");
+    let config = repo.path().join("bad.toml");
+    std::fs::write(
+        &config,
+        "[[rules]]\nname = \"oops\"\npattern = \"1\"\ntier = \"epic\"\nexplanation = \"x\"\n",
+    )
+    .unwrap();
+    let output = repo.git_rare(&["--config", config.to_str().unwrap()]);
+    assert!(!output.status.success());
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(
+        stderr.contains("rule 'oops': invalid tier 'epic'"),
+        "{stderr}"
+    );
+}
+
+/// The tier column of a table row.
+fn tier(row: &str) -> &str {
+    row.trim_end_matches('│').rsplit('│').next().unwrap().trim()
+}
+
+/// Rows of `--all` output for a repository with `overrides` applied, along
+/// with the repository and config path for further runs.
+fn override_rows(overrides: &str) -> (TestRepo, String, Vec<String>) {
+    let repo = TestRepo::new();
+    for i in 0..40 {
+        repo.commit(&format!("commit {i}"));
+    }
+    let config = repo.path().join("overrides.toml");
+    std::fs::write(
+        &config,
+        format!("min_run = 4\n\n[overrides]\n{overrides}\n"),
+    )
+    .unwrap();
+    let config = config.to_str().unwrap().to_string();
+    let stdout = repo.git_rare_stdout(&["--all", "--config", &config]);
+    let rows = stdout
+        .lines()
+        .filter(|line| line.contains("2024-01-01"))
+        .map(str::to_string)
+        .collect();
+    (repo, config, rows)
+}
+
+fn count_row(repo: &TestRepo, config: &str) -> Vec<String> {
+    let stdout = repo.git_rare_stdout(&["--count", "--config", config]);
+    let row = stdout.lines().nth(3).unwrap();
+    row.split('│')
+        .map(|cell| cell.trim().to_string())
+        .filter(|cell| !cell.is_empty())
+        .collect()
+}
+
+#[test]
+fn overrides_remap_tiers_into_only_and_count() {
+    let (repo, config, rows) = override_rows("starts_digits = \"legendary\"");
+    let starts_digits = rows
+        .iter()
+        .filter(|row| {
+            row.split("Starts with ").skip(1).any(|rest| {
+                rest.split(' ')
+                    .nth(1)
+                    .is_some_and(|w| w.starts_with("digits"))
+            })
+        })
+        .collect::<Vec<&String>>();
+    assert!(!starts_digits.is_empty(), "{rows:#?}");
+    let legendary = repo.git_rare_stdout(&["--only", "legendary", "--config", &config]);
+    for row in &starts_digits {
+        assert_eq!(tier(row), "Legendary", "{row}");
+        let hash = row.split('│').nth(3).unwrap().trim();
+        assert!(legendary.contains(hash), "{hash} missing:\n{legendary}");
+    }
+    let legendary_rows = rows.iter().filter(|row| tier(row) == "Legendary").count();
+    assert_eq!(count_row(&repo, &config)[4], legendary_rows.to_string());
+}
+
+#[test]
+fn overrides_to_common_drop_commits_from_the_default_view() {
+    let (repo, config, rows) = override_rows("starts_digits = \"common\"");
+    let common = rows
+        .iter()
+        .filter(|row| tier(row) == "Common")
+        .collect::<Vec<&String>>();
+    assert!(
+        common.iter().any(|row| row.contains("Starts with")),
+        "{rows:#?}"
+    );
+    let stdout = repo.git_rare_stdout(&["--config", &config]);
+    for row in &common {
+        let hash = row.split('│').nth(3).unwrap().trim();
+        assert!(!stdout.contains(hash), "{hash} shown:\n{stdout}");
+    }
+    assert_eq!(count_row(&repo, &config)[1], common.len().to_string());
+}
@@ -0,0 +1,152 @@
+mod common;
+
+use std::fs;
+
+use common::{git_command, TestRepo};
+
+/// A repository with a commit by an author whose name has a comma and
+/// quotes in it, and a config with a rule whose explanation has a newline.
+fn quoted_repo() -> (TestRepo, String) {
+    let repo = TestRepo::new();
+    let output = git_command(repo.path())
+        .args(["commit", "-q", "--allow-empty", "-m", "quoted"])
+        .env("GIT_AUTHOR_NAME", "Doe, John \"JD\" Jr")
+        .env("GIT_AUTHOR_DATE", "2024-01-01T00:00:00Z")
+        .env("GIT_COMMITTER_DATE", "2024-01-01T00:00:00Z")
+        .output()
+        .unwrap();
+    assert!(output.status.success(), "{output:?}");
+    let hash = repo.git(&["rev-parse", "HEAD"]);
+    let config = repo.path().join(".no-config/git-rare");
+    fs::create_dir_all(&config).unwrap();
+    fs::write(
+        config.join("config.toml"),
+        "[[rules]]\nname = \"all\"\npattern = \"^\"\ntier = \"rare\"\n\
+         explanation = \"Matches \\\"everything\\\",\\nreally\"\nprobability = 1.52587890625e-5\n",
+    )
+    .unwrap();
+    (repo, hash)
+}
+
+/// Parses `csv` separated by `delimiter` into its rows, header first.
+fn parse(csv: &str, delimiter: u8) -> Vec<Vec<String>> {
+    csv::ReaderBuilder::new()
+        .delimiter(delimiter)
+        .has_headers(false)
+        .from_reader(csv.as_bytes())
+        .records()
+        .map(|record| record.unwrap().iter().map(str::to_string).collect())
+        .collect()
+}
+
+#[test]
+fn fields_are_quoted_and_escaped() {
+    let (repo, hash) = quoted_repo();
+    let stdout = repo.git_rare_stdout(&["--format", "csv"]);
+    assert!(stdout.contains("\"Doe, John \"\"JD\"\" Jr\""), "{stdout}");
+    let rows = parse(&stdout, b',');
+    assert_eq!(
+        rows[0],
+        [
+            "hash",
+            "author",
+            "datetime",
+            "tier",
+            "explanation",
+            "probability",
+            "email"
+        ]
+    );
+    assert_eq!(rows.len(), 2);
+    let row = &rows[1];
+    assert_eq!(row[0], hash);
+    assert_eq!(row[1], "Doe, John \"JD\" Jr");
+    assert_eq!(row[2], "2024-01-01T00:00:00Z");
+    assert_eq!(row[3], "Rare");
+    assert_eq!(
+        row[4].split("; ").next(),
+        Some("Matches \"everything\",\nreally"),
+        "{}",
+        row[4]
+    );
+    // Probabilities are plain decimals, without an exponent, even one as
+    // small as the rule's.
+    assert!(row[5].starts_with("0.00000"), "{}", row[5]);
+    assert!(!row[5].contains('e'), "{}", row[5]);
+    assert!(row[5].parse::<f64>().unwrap() <= 1.52587890625e-5);
+    assert_eq!(row[6], "john@example.com");
+    let stdout = repo.git_rare_stdout(&["--format", "csv", "--footer"]);
+    let rows = parse(&stdout, b',');
+    assert_eq!(rows.len(), 3);
+    // The footer of the table comes last, in a record without a hash.
+    assert_eq!(rows[2][..4], ["", "", "", ""]);
+    assert!(
+        rows[2][4].starts_with("1 commit shown: 1 Rare (1 in "),
+        "{stdout}"
+    );
+    assert_eq!(rows[2][5], row[5]);
+    assert_eq!(rows[2][6], "");
+}
+
+#[test]
+fn a_delimiter_gives_tab_separated_values() {
+    let (repo, hash) = quoted_repo();
+    let stdout = repo.git_rare_stdout(&["--format", "csv", "--delimiter", "\\t"]);
+    let header = stdout.lines().next().unwrap();
+    assert_eq!(
+        header,
+        "hash\tauthor\tdatetime\ttier\texplanation\tprobability\temail"
+    );
+    let rows = parse(&stdout, b'\t');
+    assert_eq!(rows[1][0], hash);
+    assert_eq!(rows[1][1], "Doe, John \"JD\" Jr");
+
+    let semicolons = repo.git_rare_stdout(&["--format", "csv", "--delimiter", ";"]);
+    assert_eq!(parse(&semicolons, b';'), parse(&stdout, b'\t'));
+
+    let invalid = repo.git_rare(&["--format", "csv", "--delimiter", "ab"]);
+    assert!(!invalid.status.success());
+}
+
+#[test]
+fn count_is_a_row_of_counts() {
+    let repo = TestRepo::new();
+    for i in 0..3 {
+        repo.commit(&format!("commit {i}"));
+    }
+    let rows = parse(&repo.git_rare_stdout(&["--format", "csv", "--count"]), b',');
+    assert_eq!(
+        rows[0],
+        [
+            "total",
+            "common",
+            "uncommon",
+            "rare",
+            "legendary",
+            "shallow"
+        ]
+    );
+    assert_eq!(rows.len(), 2);
+    assert_eq!(rows[1][0], "3");
+    assert_eq!(rows[1][5], "false");
+
+    let args = ["--format", "csv", "--count", "-C", ".", "-C", "."];
+    let rows = parse(&repo.git_rare_stdout(&args), b',');
+    assert_eq!(rows[0][0], "repo");
+    let totals = rows[1..]
+        .iter()
+        .map(|row| (row[0].as_str(), row[1].as_str()))
+        .collect::<Vec<(&str, &str)>>();
+    assert_eq!(totals, [(".", "3"), (".", "3"), ("All repositories", "6")]);
+}
+
+#[test]
+fn nothing_found_is_only_a_header() {
+    let repo = TestRepo::new();
+    repo.commit("initial");
+    let stdout = repo.git_rare_stdout(&["--format", "csv", "--only", "legendary"]);
+    assert_eq!(
+        stdout,
+        "hash,author,datetime,tier,explanation,probability,email\n"
+    );
+}
@@ -0,0 +1,93 @@
+mod common;
+
+use common::{backends, git_command, git_rare_command, TestRepo};
+
+/// A repository with a commit made at noon on New Year's Day in +13:00,
+/// which was still the last day of 2023 in UTC.
+fn new_year_repo() -> TestRepo {
+    let repo = TestRepo::new();
+    let date = "2024-01-01T12:00:00+13:00";
+    let output = git_command(repo.path())
+        .args(["commit", "-q", "--allow-empty", "-m", "new year"])
+        .env("GIT_AUTHOR_DATE", date)
+        .env("GIT_COMMITTER_DATE", date)
+        .output()
+        .unwrap();
+    assert!(output.status.success(), "{output:?}");
+    repo
+}
+
+/// The dates of the Datetime column of a table, with `TZ` set to `tz`.
+fn dates(repo: &TestRepo, tz: &str, args: &[&str]) -> Vec<String> {
+    let output = git_rare_command(repo.path())
+        .args(["--all"])
+        .args(args)
+        .env("TZ", tz)
+        .output()
+        .unwrap();
+    assert!(output.status.success(), "{output:?}");
+    String::from_utf8(output.stdout)
+        .unwrap()
+        .lines()
+        .filter(|line| line.starts_with('│'))
+        .skip(1)
+        .map(|line| line.split('│').nth(2).unwrap().trim().to_string())
+        .collect()
+}
+
+#[test]
+fn dates_are_shown_in_a_format_and_zone() {
+    let repo = new_year_repo();
+    for backend in backends() {
+        let args = ["--backend", backend];
+        assert_eq!(
+            dates(&repo, "UTC", &args),
+            ["2024-01-01 12:00:00 +13:00"],
+            "{backend}"
+        );
+        let args = ["--backend", backend, "--date-format", "%d %b %Y, %H:%M"];
+        assert_eq!(dates(&repo, "UTC", &args), ["01 Jan 2024, 12:00"]);
+        let args = ["--backend", backend, "--utc"];
+        assert_eq!(dates(&repo, "JST-9", &args), ["2023-12-31 23:00:00 +00:00"]);
+        let args = ["--backend", backend, "--utc", "--date-format", "%Y-%m-%d"];
+        assert_eq!(dates(&repo, "UTC", &args), ["2023-12-31"]);
+    }
+    // JST-9 is nine hours ahead of UTC, without needing the zone database.
+    assert_eq!(
+        dates(&repo, "JST-9", &["--local"]),
+        ["2024-01-01 08:00:00 +09:00"]
+    );
+}
+
+#[test]
+fn machine_formats_keep_the_original_offset() {
+    let repo = new_year_repo();
+    let json = repo.git_rare_stdout(&["--all", "--utc", "--format", "json"]);
+    assert!(json.contains("\"2024-01-01T12:00:00+13:00\""), "{json}");
+    // The instant is the same, so filtering by it is too.
+    let args = ["--all", "--utc", "--since", "2023-12-31T22:59:00Z"];
+    assert!(repo.git_rare_stdout(&args).contains("2023-12-31 23:00:00"));
+    let args = ["--all", "--utc", "--until", "2023-12-31T22:59:00Z"];
+    assert!(!repo.git_rare_stdout(&args).contains("2023-12-31 23:00:00"));
+}
+
+#[test]
+fn invalid_formats_and_conflicts_are_rejected() {
+    let repo = new_year_repo();
+    let output = repo.git_rare(&["--date-format", "%Y-%Q"]);
+    assert!(!output.status.success());
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(
+        stderr.contains("'%Y-%Q' is not a valid strftime format"),
+        "{stderr}"
+    );
+    for args in [
+        ["--relative-dates", "--date-format", "%Y"].as_slice(),
+        ["--utc", "--local"].as_slice(),
+    ] {
+        let output = repo.git_rare(args);
+        assert!(!output.status.success(), "{args:?}");
+        let stderr = String::from_utf8(output.stderr).unwrap();
+        assert!(stderr.contains("cannot be used with"), "{stderr}");
+    }
+}
@@ -0,0 +1,102 @@
+mod common;
+
+use common::{backends, git_command, scanned, TestRepo};
+
+/// Commits on the first of each month of 2024, with their author and
+/// committer dates set to it.
+fn monthly_repo() -> TestRepo {
+    let repo = TestRepo::new();
+    for month in 1..=12 {
+        let date = format!("2024-{month:02}-01T12:00:00Z");
+        let output = git_command(repo.path())
+            .args([
+                "commit",
+                "-q",
+                "--allow-empty",
+                "-m",
+                &format!("month {month}"),
+            ])
+            .env("GIT_AUTHOR_DATE", &date)
+            .env("GIT_COMMITTER_DATE", &date)
+            .output()
+            .unwrap();
+        assert!(output.status.success(), "{output:?}");
+    }
+    repo
+}
+
+#[test]
+fn since_and_until_restrict_the_scan() {
+    let repo = monthly_repo();
+    for backend in backends() {
+        assert_eq!(scanned(&repo, backend, &[]).len(), 12, "{backend}");
+        assert_eq!(scanned(&repo, backend, &["--since", "2024-10-01"]).len(), 3);
+        assert_eq!(
+            scanned(&repo, backend, &["--until", "2024-03-01 12:00"]).len(),
+            3
+        );
+        assert_eq!(
+            scanned(
+                &repo,
+                backend,
+                &[
+                    "--since",
+                    "2024-04-15",
+                    "--until",
+                    "2024-06-30T23:59:59+00:00"
+                ]
+            )
+            .len(),
+            2,
+            "{backend}"
+        );
+        let expected = repo.git(&["rev-list", "--since=2024-05-01T00:00:00Z", "HEAD"]);
+        assert_eq!(
+            scanned(&repo, backend, &["--since", "2024-05-01"]),
+            expected.lines().collect::<Vec<&str>>(),
+            "{backend}"
+        );
+    }
+}
+
+#[test]
+fn dates_compose_with_count_and_ranges() {
+    let repo = monthly_repo();
+    for backend in backends() {
+        let stdout =
+            repo.git_rare_stdout(&["--backend", backend, "--since", "2024-07-01", "--count"]);
+        assert!(stdout.contains("│ 6 "), "{backend}:\n{stdout}");
+        // The last four commits are from September to December.
+        assert_eq!(
+            scanned(&repo, backend, &["--since", "2024-09-01", "HEAD~4.."]).len(),
+            4
+        );
+        assert_eq!(
+            scanned(&repo, backend, &["--until", "2024-09-01 12:00", "HEAD~4.."]).len(),
+            1
+        );
+    }
+}
+
+#[test]
+fn verbose_output_echoes_the_dates() {
+    let repo = monthly_repo();
+    let output = repo.git_rare(&["--verbose", "--since", "2024-10-01", "--count"]);
+    assert!(output.status.success(), "{output:?}");
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(
+        stderr.contains("Scanning commits committed since 2024-10-01 00:00:00 +00:00"),
+        "{stderr}"
+    );
+}
+
+#[test]
+fn invalid_dates_are_rejected() {
+    let repo = monthly_repo();
+    let output = repo.git_rare(&["--since", "2024-02-30"]);
+    assert!(!output.status.success());
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(stderr.contains("invalid date '2024-02-30'"), "{stderr}");
+    let output = repo.git_rare(&["--since", "2024-06-01", "--until", "2024-05-01"]);
+    assert!(!output.status.success());
+}
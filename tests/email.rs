@@ -0,0 +1,108 @@
+mod common;
+
+use common::{backends, git_command, TestRepo};
+
+/// A repository with commits by Jane Doe under three spellings of her name,
+/// the last with her email in capitals, and one by Kim between them.
+fn spellings_repo() -> TestRepo {
+    let repo = TestRepo::new();
+    let authors = [
+        ("Jane Doe", "jane@example.com"),
+        ("Kim", "kim@example.com"),
+        ("jane doe", "jane@example.com"),
+        ("Plain Jane", "JANE@example.com"),
+    ];
+    for (i, (name, email)) in authors.into_iter().enumerate() {
+        let date = format!("2024-01-01T00:0{i}:00Z");
+        let output = git_command(repo.path())
+            .args(["commit", "-q", "--allow-empty", "-m", name])
+            .env("GIT_AUTHOR_NAME", name)
+            .env("GIT_AUTHOR_EMAIL", email)
+            .env("GIT_AUTHOR_DATE", &date)
+            .env("GIT_COMMITTER_DATE", &date)
+            .output()
+            .unwrap();
+        assert!(output.status.success(), "{output:?}");
+    }
+    repo
+}
+
+/// The cells of each row of a table, header first.
+fn cells(stdout: &str) -> Vec<Vec<String>> {
+    stdout
+        .lines()
+        .filter(|line| line.starts_with('│'))
+        .map(|line| {
+            line.trim_matches('│')
+                .split('│')
+                .map(|cell| cell.trim().to_string())
+                .collect()
+        })
+        .collect()
+}
+
+#[test]
+fn emails_are_a_column_when_asked_for() {
+    let repo = spellings_repo();
+    for backend in backends() {
+        let rows = cells(&repo.git_rare_stdout(&["--backend", backend, "--all", "--show-email"]));
+        assert_eq!(rows[0][..3], ["Author", "Email", "Datetime"], "{backend}");
+        let emails = rows[1..]
+            .iter()
+            .map(|row| row[1].as_str())
+            .collect::<Vec<&str>>();
+        assert_eq!(
+            emails,
+            [
+                "JANE@example.com",
+                "jane@example.com",
+                "kim@example.com",
+                "jane@example.com"
+            ],
+            "{backend}"
+        );
+    }
+    let rows = cells(&repo.git_rare_stdout(&["--all"]));
+    assert!(!rows[0].contains(&"Email".to_string()), "{rows:?}");
+    let rows = cells(&repo.git_rare_stdout(&["--all", "--columns", "email,author"]));
+    assert_eq!(rows[1], ["JANE@example.com", "Plain Jane"]);
+    assert!(!repo
+        .git_rare(&["--show-email", "--columns", "hash"])
+        .status
+        .success());
+}
+
+#[test]
+fn machine_formats_always_have_the_email() {
+    let repo = spellings_repo();
+    let json = repo.git_rare_stdout(&["--all", "--format", "json"]);
+    let json: serde_json::Value = serde_json::from_str(&json).unwrap();
+    assert_eq!(json["commits"][2]["email"], "kim@example.com");
+    let csv = repo.git_rare_stdout(&["--all", "--format", "csv"]);
+    // It ends each record, after the columns CSV had before it.
+    assert!(
+        csv.starts_with("hash,author,datetime,tier,explanation,probability,email\n"),
+        "{csv}"
+    );
+    assert!(csv.contains(",kim@example.com\n"), "{csv}");
+}
+
+#[test]
+fn authors_are_sorted_by_their_name() {
+    let repo = spellings_repo();
+    for backend in backends() {
+        let args = ["--backend", backend, "--all", "--sort-by", "author"];
+        let rows = cells(&repo.git_rare_stdout(&args));
+        let authors = rows[1..]
+            .iter()
+            .map(|row| row[0].as_str())
+            .collect::<Vec<&str>>();
+        // Names that differ only in case are ordered by their email, and
+        // otherwise kept in the order they were found.
+        assert_eq!(
+            authors,
+            ["jane doe", "Jane Doe", "Kim", "Plain Jane"],
+            "{backend}"
+        );
+    }
+}
@@ -0,0 +1,107 @@
+mod common;
+
+use std::fs;
+
+use common::{backends, git_rare_unstyled, repo_with_commits, TestRepo};
+
+/// Rules that make the commits of [`repo_with_commits`] rare and legendary.
+const RULES: [&str; 6] = [
+    "--rule",
+    "rare:^[0-7]",
+    "--rule",
+    "legendary:^[8-9a-f]",
+    "--color",
+    "never",
+];
+
+/// The table printed for `args`, without the footer that follows it.
+fn table(repo: &TestRepo, args: &[&str]) -> String {
+    let stdout = repo.git_rare_stdout(args);
+    let Some((table, footer)) = stdout.trim_end().rsplit_once('\n') else {
+        panic!("{stdout}");
+    };
+    assert!(footer.contains(" commits shown: "), "{stdout}");
+    format!("{table}\n")
+}
+
+#[test]
+fn tiers_follow_their_symbol() {
+    let (repo, _) = repo_with_commits(3);
+    for backend in backends() {
+        let args = [&["--backend", backend][..], &RULES].concat();
+        assert_eq!(
+            table(&repo, &args),
+            "\
+╭──────────┬────────────────────────────┬──────────────────────────────────────────┬────────────────────────────┬──────┬───────────╮
+│ Author   │ Datetime                   │ Hash                                     │ Explanation                │ Odds │ Tier      │
+├──────────┼────────────────────────────┼──────────────────────────────────────────┼────────────────────────────┼──────┼───────────┤
+│ John Doe │ 2024-01-01 00:02:00 +00:00 │ 5357b4bf593a79fd8268af95710efcec7a757262 │ ^[0-7]: '5' at offset 0    │      │ Rare      │
+│ John Doe │ 2024-01-01 00:01:00 +00:00 │ b9e7d8a992f3c552a72ad3f72e022bbfbdc73f2b │ ^[8-9a-f]: 'b' at offset 0 │      │ Legendary │
+│ John Doe │ 2024-01-01 00:00:00 +00:00 │ 9fa901b44f74464eb37eace36fe0ce2e6df9a20b │ ^[8-9a-f]: '9' at offset 0 │      │ Legendary │
+╰──────────┴────────────────────────────┴──────────────────────────────────────────┴────────────────────────────┴──────┴───────────╯
+",
+            "{backend}"
+        );
+        // The emoji take two columns each, which the borders make room for.
+        assert_eq!(
+            table(&repo, &[&args[..], &["--emoji"]].concat()),
+            "\
+╭──────────┬────────────────────────────┬──────────────────────────────────────────┬────────────────────────────┬──────┬──────────────╮
+│ Author   │ Datetime                   │ Hash                                     │ Explanation                │ Odds │ Tier         │
+├──────────┼────────────────────────────┼──────────────────────────────────────────┼────────────────────────────┼──────┼──────────────┤
+│ John Doe │ 2024-01-01 00:02:00 +00:00 │ 5357b4bf593a79fd8268af95710efcec7a757262 │ ^[0-7]: '5' at offset 0    │      │ 💎 Rare      │
+│ John Doe │ 2024-01-01 00:01:00 +00:00 │ b9e7d8a992f3c552a72ad3f72e022bbfbdc73f2b │ ^[8-9a-f]: 'b' at offset 0 │      │ 🌟 Legendary │
+│ John Doe │ 2024-01-01 00:00:00 +00:00 │ 9fa901b44f74464eb37eace36fe0ce2e6df9a20b │ ^[8-9a-f]: '9' at offset 0 │      │ 🌟 Legendary │
+╰──────────┴────────────────────────────┴──────────────────────────────────────────┴────────────────────────────┴──────┴──────────────╯
+",
+            "{backend}"
+        );
+    }
+}
+
+#[test]
+fn the_config_sets_the_symbols() {
+    let (repo, _) = repo_with_commits(3);
+    let config = repo.path().join(".no-config/git-rare");
+    fs::create_dir_all(&config).unwrap();
+    fs::write(config.join("config.toml"), "[emoji]\nlegendary = \"(L)\"\n").unwrap();
+    let table = table(&repo, &[&RULES[..], &["--emoji"]].concat());
+    assert!(table.contains("│ (L) Legendary │"), "{table}");
+    assert!(table.contains("│ 💎 Rare       │"), "{table}");
+}
+
+/// What git-rare prints for `args` in the style it picks for a pipe, unless
+/// they choose one.
+fn unstyled(repo: &TestRepo, args: &[&str]) -> String {
+    let args = [&RULES[..], &["--emoji"], args].concat();
+    let output = git_rare_unstyled(repo.path()).args(&args).output().unwrap();
+    assert!(output.status.success(), "{output:?}");
+    String::from_utf8(output.stdout).unwrap()
+}
+
+#[test]
+fn ascii_tables_fall_back_to_characters() {
+    let (repo, _) = repo_with_commits(3);
+    let table = unstyled(&repo, &["--style", "ascii"]);
+    assert!(table.contains("| * Rare      |"), "{table}");
+    assert!(table.contains("| ! Legendary |"), "{table}");
+}
+
+#[test]
+fn markdown_keeps_the_symbols_when_piped() {
+    let (repo, _) = repo_with_commits(3);
+    let markdown = unstyled(&repo, &["--format", "markdown"]);
+    assert!(markdown.contains("| 💎 Rare "), "{markdown}");
+    assert!(markdown.contains("| 🌟 Legendary "), "{markdown}");
+}
+
+#[test]
+fn machine_formats_ignore_it() {
+    let (repo, _) = repo_with_commits(3);
+    for format in ["json", "csv", "yaml", "plain"] {
+        let args = [&RULES[..], &["--format", format]].concat();
+        let without = repo.git_rare_stdout(&args);
+        let with = repo.git_rare_stdout(&[&args[..], &["--emoji"]].concat());
+        assert_eq!(with, without, "{format}");
+    }
+}
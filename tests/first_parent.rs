@@ -0,0 +1,108 @@
+mod common;
+
+use std::fs;
+
+use common::{backends, git_command, scanned, TestRepo};
+
+/// Hash prefix the side branch's commit is engineered to have.
+const PREFIX: &str = "00";
+
+/// A repository whose mainline merged a side branch made of a single commit
+/// with a hash starting with [`PREFIX`], returned along with it. Candidate
+/// commits are hashed in a single `git hash-object` call, since hundreds of
+/// them are needed.
+fn merged_repo() -> (TestRepo, String) {
+    let repo = TestRepo::new();
+    for i in 0..3 {
+        repo.commit(&format!("main {i}"));
+    }
+    let tree = repo.git(&["rev-parse", "HEAD^{tree}"]);
+    let parent = repo.git(&["rev-parse", "HEAD"]);
+    let candidates = repo.path().join(".candidates");
+    fs::create_dir(&candidates).unwrap();
+    let paths = (0..3000)
+        .map(|nonce| {
+            let path = candidates.join(nonce.to_string());
+            let signature = "John Doe <john@example.com> 1704067200 +0000";
+            let commit = format!(
+                "tree {tree}\nparent {parent}\nauthor {signature}\ncommitter {signature}\n\n\
+                 side {nonce}\n"
+            );
+            fs::write(&path, commit).unwrap();
+            path.display().to_string()
+        })
+        .collect::<Vec<String>>();
+    let hashes = repo.git(
+        &[
+            &["hash-object", "-t", "commit"][..],
+            &paths.iter().map(String::as_str).collect::<Vec<&str>>(),
+        ]
+        .concat(),
+    );
+    let (path, side) = paths
+        .iter()
+        .zip(hashes.lines())
+        .find(|(_, hash)| hash.starts_with(PREFIX))
+        .expect("no candidate hashed to the prefix");
+    assert_eq!(repo.git(&["hash-object", "-t", "commit", "-w", path]), side);
+    let side = side.to_string();
+    fs::remove_dir_all(&candidates).unwrap();
+    repo.git(&["branch", "side", &side]);
+    for i in 0..2 {
+        repo.commit(&format!("main {}", i + 3));
+    }
+    let output = git_command(repo.path())
+        .args(["merge", "-q", "--no-ff", "-m", "merge side", "side"])
+        .env("GIT_AUTHOR_DATE", "2024-01-02T00:00:00Z")
+        .env("GIT_COMMITTER_DATE", "2024-01-02T00:00:00Z")
+        .output()
+        .unwrap();
+    assert!(output.status.success(), "{output:?}");
+    repo.commit("main 5");
+    (repo, side)
+}
+
+#[test]
+fn first_parent_leaves_out_merged_branches() {
+    let (repo, side) = merged_repo();
+    let rule = format!("legendary:^{PREFIX}");
+    for backend in backends() {
+        let args = ["--backend", backend, "--rule", &rule, "--only", "legendary"];
+        let stdout = repo.git_rare_stdout(&args);
+        assert!(stdout.contains(&side), "{backend}:\n{stdout}");
+        let stdout = repo.git_rare_stdout(&[&args[..], &["--first-parent"]].concat());
+        assert!(!stdout.contains(&side), "{backend}:\n{stdout}");
+    }
+}
+
+#[test]
+fn first_parent_scans_like_rev_list() {
+    let (repo, _) = merged_repo();
+    let mainline = repo.git(&["rev-list", "--first-parent", "HEAD"]);
+    assert_eq!(mainline.lines().count(), 7);
+    let range = repo.git(&["rev-list", "--first-parent", "HEAD~3..HEAD"]);
+    for backend in backends() {
+        assert_eq!(scanned(&repo, backend, &[]).len(), 8, "{backend}");
+        assert_eq!(
+            scanned(&repo, backend, &["--first-parent"]).join("\n"),
+            mainline,
+            "{backend}"
+        );
+        assert_eq!(
+            scanned(&repo, backend, &["--first-parent", "HEAD~3..HEAD"]).join("\n"),
+            range,
+            "{backend}"
+        );
+    }
+}
+
+#[test]
+fn first_parent_counts_only_the_mainline() {
+    let (repo, _) = merged_repo();
+    for backend in backends() {
+        let stdout = repo.git_rare_stdout(&["--backend", backend, "--count"]);
+        assert!(stdout.contains("│ 8 "), "{backend}:\n{stdout}");
+        let stdout = repo.git_rare_stdout(&["--backend", backend, "--first-parent", "--count"]);
+        assert!(stdout.contains("│ 7 "), "{backend}:\n{stdout}");
+    }
+}
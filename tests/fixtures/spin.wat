@@ -0,0 +1,11 @@
+;; A plugin that never finishes classifying a hash.
+(module
+  (memory (export "memory") 1)
+  (func (export "alloc") (param $len i32) (result i32)
+    (i32.const 0))
+  (func (export "classify") (param $ptr i32) (param $len i32) (result i32)
+    (loop $forever
+      (br $forever))
+    (i32.const 0))
+  (func (export "explanation") (result i64)
+    (i64.const 0)))
@@ -0,0 +1,9 @@
+;; A plugin that traps on every hash.
+(module
+  (memory (export "memory") 1)
+  (func (export "alloc") (param $len i32) (result i32)
+    (i32.const 0))
+  (func (export "classify") (param $ptr i32) (param $len i32) (result i32)
+    (unreachable))
+  (func (export "explanation") (result i64)
+    (i64.const 0)))
@@ -0,0 +1,208 @@
+mod common;
+
+use std::fs;
+
+use common::{backends, TestRepo};
+
+/// A repository of five commits that [`RULES`] makes one Legendary, two
+/// Rare, one Uncommon and one Common.
+fn repo() -> TestRepo {
+    let repo = TestRepo::new();
+    for i in 0..5 {
+        repo.commit(&format!("commit {i}"));
+    }
+    repo
+}
+
+const RULES: [&str; 6] = [
+    "--rule",
+    "rare:^[0-6]",
+    "--rule",
+    "legendary:^[a-c]",
+    "--columns",
+    "hash,tier",
+];
+
+/// The rows of a table and the footer after it.
+fn rows_and_footer(stdout: &str) -> (usize, &str) {
+    let rows = stdout.lines().filter(|line| line.starts_with('│')).count();
+    let footer = stdout
+        .lines()
+        .find(|line| line.contains(" shown: "))
+        .unwrap_or_else(|| panic!("{stdout}"));
+    // Less the header.
+    (rows - 1, footer)
+}
+
+#[test]
+fn the_footer_counts_the_rows_shown() {
+    let repo = repo();
+    for backend in backends() {
+        let args = [&["--backend", backend, "--all"][..], &RULES].concat();
+        let stdout = repo.git_rare_stdout(&args);
+        assert_eq!(
+            rows_and_footer(&stdout),
+            (
+                5,
+                "5 commits shown: 1 Common, 1 Uncommon, 2 Rare, 1 Legendary"
+            ),
+            "{backend}"
+        );
+        // Not those of the whole repository.
+        let args = [&["--backend", backend, "--only", "rare"][..], &RULES].concat();
+        let stdout = repo.git_rare_stdout(&args);
+        assert_eq!(
+            rows_and_footer(&stdout),
+            (2, "2 commits shown: 2 Rare"),
+            "{backend}"
+        );
+        let args = [&["--backend", backend, "--only", "legendary"][..], &RULES].concat();
+        let stdout = repo.git_rare_stdout(&args);
+        assert_eq!(
+            rows_and_footer(&stdout),
+            (1, "1 commit shown: 1 Legendary"),
+            "{backend}"
+        );
+    }
+
+    let args = [&["--only", "rare", "--no-footer"][..], &RULES].concat();
+    let output = repo.git_rare(&args);
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(!stdout.contains(" shown: "), "{stdout}");
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(stderr.starts_with("This operation took"), "{stderr}");
+}
+
+#[test]
+fn machine_formats_have_what_the_footer_counts() {
+    let repo = repo();
+    let args = [&["--only", "rare", "--format", "json"][..], &RULES].concat();
+    let json: serde_json::Value = serde_json::from_str(&repo.git_rare_stdout(&args)).unwrap();
+    assert_eq!(json["commits"].as_array().unwrap().len(), 2);
+    assert_eq!(
+        json["shown"],
+        serde_json::json!({
+            "total": 2,
+            "common": 0,
+            "uncommon": 0,
+            "rare": 2,
+            "legendary": 0,
+            "probability": null,
+        })
+    );
+    // The counts are still those of the whole scan.
+    assert_eq!(json["counts"]["total"], 5);
+
+    let args = [&["--only", "rare", "--format", "csv"][..], &RULES].concat();
+    // CSV only has it when asked for, so every record is of a commit.
+    assert_eq!(repo.git_rare_stdout(&args).lines().count(), 3);
+    let args = [&args[..], &["--footer"]].concat();
+    let csv = repo.git_rare_stdout(&args);
+    let lines = csv.lines().collect::<Vec<&str>>();
+    assert_eq!(lines.len(), 4, "{csv}");
+    // Without the explanation, the footer is the first field instead.
+    assert_eq!(lines[3], "2 commits shown: 2 Rare,", "{csv}");
+    let args = [
+        &["--only", "rare", "--format", "csv", "--footer"][..],
+        &RULES[..4],
+    ]
+    .concat();
+    let csv = repo.git_rare_stdout(&args);
+    assert!(csv.ends_with(",,,,2 commits shown: 2 Rare,,\n"), "{csv}");
+
+    let args = [
+        &["--only", "rare", "--no-footer", "--format", "json"][..],
+        &RULES,
+    ]
+    .concat();
+    let json = repo.git_rare_stdout(&args);
+    assert!(!json.contains("\"shown\""), "{json}");
+    let args = [
+        &["--only", "rare", "--no-footer", "--format", "csv"][..],
+        &RULES,
+    ]
+    .concat();
+    assert_eq!(repo.git_rare_stdout(&args).lines().count(), 3);
+}
+
+#[test]
+fn the_footer_has_the_odds_of_them_all() {
+    let repo = repo();
+    let config = repo.path().join(".no-config/git-rare");
+    fs::create_dir_all(&config).unwrap();
+    fs::write(
+        config.join("config.toml"),
+        "[[rules]]\nname = \"all\"\npattern = \"^\"\ntier = \"rare\"\n\
+         explanation = \"Anything\"\nprobability = 0.0625\n",
+    )
+    .unwrap();
+    let stdout = repo.git_rare_stdout(&["--max-count", "3"]);
+    let (rows, footer) = rows_and_footer(&stdout);
+    assert_eq!(rows, 3);
+    assert!(
+        footer.starts_with("3 commits shown: 3 Rare (1 in "),
+        "{stdout}"
+    );
+    assert!(footer.ends_with(" together)"), "{stdout}");
+    let json = repo.git_rare_stdout(&["--max-count", "3", "--format", "json"]);
+    let json: serde_json::Value = serde_json::from_str(&json).unwrap();
+    let product = json["commits"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|commit| commit["probability"].as_f64().unwrap())
+        .product::<f64>();
+    let probability = json["shown"]["probability"].as_f64().unwrap();
+    assert!((probability - product).abs() <= product * 1e-9, "{json}");
+    assert!(probability <= 0.0625f64.powi(3));
+}
+
+#[test]
+fn odds_too_small_for_a_float_are_kept() {
+    let repo = repo();
+    let config = repo.path().join(".no-config/git-rare");
+    fs::create_dir_all(&config).unwrap();
+    fs::write(
+        config.join("config.toml"),
+        "[[rules]]\nname = \"all\"\npattern = \"^\"\ntier = \"rare\"\n\
+         explanation = \"Anything\"\nprobability = 1e-200\n",
+    )
+    .unwrap();
+    // Five of them together are under 1e-1000.
+    let stdout = repo.git_rare_stdout(&[]);
+    let (_, footer) = rows_and_footer(&stdout);
+    let odds = footer
+        .strip_prefix("5 commits shown: 5 Rare (1 in ")
+        .and_then(|odds| odds.strip_suffix(" together)"))
+        .unwrap_or_else(|| panic!("{stdout}"));
+    let (_, exponent) = odds.split_once('e').unwrap_or_else(|| panic!("{odds}"));
+    assert!(exponent.parse::<u32>().unwrap() >= 1000, "{odds}");
+
+    let csv = repo.git_rare_stdout(&[
+        "--format",
+        "csv",
+        "--columns",
+        "hash,percentage",
+        "--footer",
+    ]);
+    let last = csv.lines().last().unwrap();
+    let probability = last.rsplit(',').next().unwrap();
+    let (_, exponent) = probability
+        .split_once("e-")
+        .unwrap_or_else(|| panic!("{csv}"));
+    assert!(exponent.parse::<u32>().unwrap() >= 1000, "{csv}");
+}
+
+#[test]
+fn the_csv_footer_says_what_it_is_whatever_the_columns() {
+    let repo = repo();
+    let args = [&["--all", "--format", "csv", "--footer"][..], &RULES].concat();
+    let csv = repo.git_rare_stdout(&args);
+    let footer = "\"5 commits shown: 1 Common, 1 Uncommon, 2 Rare, 1 Legendary\"";
+    assert_eq!(csv.lines().next(), Some("hash,tier"));
+    assert_eq!(csv.lines().last(), Some(format!("{footer},").as_str()));
+    let args = [&RULES[..4], &["--all", "--format", "csv", "--footer"]].concat();
+    let args = [&args[..], &["--columns", "tier,explanation"]].concat();
+    let csv = repo.git_rare_stdout(&args);
+    assert_eq!(csv.lines().last(), Some(format!(",{footer}").as_str()));
+}
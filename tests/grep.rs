@@ -0,0 +1,91 @@
+mod common;
+
+use common::{backends, scanned, TestRepo};
+
+/// A repository with two release commits among others, one of them
+/// mentioning a fix below its subject.
+fn release_repo() -> TestRepo {
+    let repo = TestRepo::new();
+    for message in [
+        "initial import",
+        "release: 1.0",
+        "fix the parser",
+        "docs: explain release: steps",
+        "release: 1.1\n\nfix the build",
+    ] {
+        repo.commit(message);
+    }
+    repo
+}
+
+/// Hashes listed by `git rev-list` with `args`, in order.
+fn rev_list(repo: &TestRepo, args: &[&str]) -> Vec<String> {
+    repo.git(&[&["rev-list", "--extended-regexp"][..], args, &["HEAD"]].concat())
+        .lines()
+        .map(str::to_string)
+        .collect()
+}
+
+#[test]
+fn commits_are_matched_by_message_like_git_log() {
+    let repo = release_repo();
+    let greps: [&[&str]; 5] = [
+        &["--grep", "^release:"],
+        &["--grep", "^fix"],
+        &["--grep", "^release:", "--grep", "parser"],
+        &["--grep", "^release:", "--grep", "^fix", "--all-match"],
+        &["--grep", "^release:", "--invert-grep"],
+    ];
+    let counts = greps.map(|args| rev_list(&repo, args).len());
+    assert_eq!(counts, [2, 2, 3, 1, 3]);
+    for backend in backends() {
+        for args in greps {
+            let expected = rev_list(&repo, args);
+            assert_eq!(
+                scanned(&repo, backend, args),
+                expected,
+                "{backend} {args:?}"
+            );
+        }
+        assert!(scanned(&repo, backend, &["--grep", "nothing like it"]).is_empty());
+    }
+}
+
+#[test]
+fn grep_composes_with_count_and_only() {
+    let repo = release_repo();
+    for backend in backends() {
+        let stdout =
+            repo.git_rare_stdout(&["--backend", backend, "--grep", "^release:", "--count"]);
+        assert!(stdout.contains("│ 2 "), "{backend}:\n{stdout}");
+
+        let stdout = repo.git_rare_stdout(&[
+            "--backend",
+            backend,
+            "--grep",
+            "^release:",
+            "--rule",
+            "legendary:.",
+            "--only",
+            "legendary",
+        ]);
+        assert_eq!(
+            stdout.matches("│ Legendary").count(),
+            2,
+            "{backend}:\n{stdout}"
+        );
+    }
+}
+
+#[test]
+fn grep_companions_need_a_pattern() {
+    let repo = release_repo();
+    for flag in ["--all-match", "--invert-grep"] {
+        let output = repo.git_rare(&[flag]);
+        assert!(!output.status.success(), "{flag}");
+        let stderr = String::from_utf8(output.stderr).unwrap();
+        assert!(stderr.contains("--grep <PATTERN>"), "{stderr}");
+    }
+    let output = repo.git_rare(&["--grep", "x", "--stdin"]);
+    assert!(!output.status.success());
+}
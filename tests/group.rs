@@ -0,0 +1,119 @@
+mod common;
+
+use common::{backends, TestRepo};
+
+/// A repository of five commits that [`RULES`] makes one Legendary, two
+/// Rare, one Uncommon and one Common.
+fn repo() -> TestRepo {
+    let repo = TestRepo::new();
+    for i in 0..5 {
+        repo.commit(&format!("commit {i}"));
+    }
+    repo
+}
+
+const RULES: [&str; 6] = [
+    "--rule",
+    "rare:^[0-6]",
+    "--rule",
+    "legendary:^[a-c]",
+    "--columns",
+    "hash,tier",
+];
+
+/// The lines of a table's sections that aren't part of a table.
+fn headers(stdout: &str) -> Vec<&str> {
+    stdout
+        .lines()
+        .filter(|line| !line.is_empty() && !line.starts_with(['│', '╭', '├', '╰']))
+        .collect()
+}
+
+#[test]
+fn each_tier_has_a_table_rarest_first() {
+    let repo = repo();
+    for backend in backends() {
+        let args = [
+            &["--backend", backend, "--group-by-tier", "--all"][..],
+            &RULES,
+        ]
+        .concat();
+        let stdout = repo.git_rare_stdout(&args);
+        let headers = headers(&stdout);
+        assert_eq!(
+            headers[..4],
+            [
+                "Legendary (1 commit)",
+                "Rare (2 commits)",
+                "Uncommon (1 commit)",
+                "Common (1 commit)",
+            ],
+            "{backend}: {stdout}"
+        );
+        // The footer counts every table, and is only printed once, after the
+        // last of them.
+        assert_eq!(headers.len(), 5, "{stdout}");
+        assert_eq!(
+            headers[4],
+            "5 commits shown: 1 Common, 1 Uncommon, 2 Rare, 1 Legendary"
+        );
+        let sections = stdout.split("\n\n").collect::<Vec<&str>>();
+        assert_eq!(sections.len(), 8, "{stdout}");
+        assert_eq!(sections[3].matches("│ Rare │").count(), 2, "{stdout}");
+    }
+
+    // Common commits aren't shown by default, so neither is their tier.
+    let args = [&["--group-by-tier"][..], &RULES].concat();
+    let stdout = repo.git_rare_stdout(&args);
+    assert_eq!(
+        headers(&stdout)[..3],
+        [
+            "Legendary (1 commit)",
+            "Rare (2 commits)",
+            "Uncommon (1 commit)"
+        ]
+    );
+    assert!(!stdout.contains("Common ("), "{stdout}");
+}
+
+#[test]
+fn a_single_tier_has_no_header() {
+    let repo = repo();
+    let args = [&["--group-by-tier", "--only", "rare"][..], &RULES].concat();
+    let grouped = repo.git_rare_stdout(&args);
+    assert!(!grouped.contains("Rare ("), "{grouped}");
+    assert_eq!(grouped.matches("│ Rare │").count(), 2, "{grouped}");
+}
+
+#[test]
+fn documents_list_each_tier_together() {
+    let repo = repo();
+    let args = [
+        &["--group-by-tier", "--all", "--format", "json"][..],
+        &RULES,
+    ]
+    .concat();
+    let json = serde_json::from_str::<serde_json::Value>(&repo.git_rare_stdout(&args)).unwrap();
+    let tiers = json["commits"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|commit| commit["tier"].as_str().unwrap())
+        .collect::<Vec<&str>>();
+    assert_eq!(tiers, ["Legendary", "Rare", "Rare", "Uncommon", "Common"]);
+
+    let args = [
+        &["--group-by-tier", "--all", "--format", "ndjson"][..],
+        &RULES,
+    ]
+    .concat();
+    let ndjson = repo.git_rare_stdout(&args);
+    let tiers = ndjson
+        .lines()
+        .filter_map(|line| {
+            let line = serde_json::from_str::<serde_json::Value>(line).unwrap();
+            line["tier"].as_str().map(str::to_string)
+        })
+        .collect::<Vec<String>>();
+    assert_eq!(tiers, ["Legendary", "Rare", "Rare", "Uncommon", "Common"]);
+}
@@ -0,0 +1,214 @@
+mod common;
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Output;
+
+use common::{backends, git_command, TestRepo};
+
+/// A repository with one commit whose hooks are read from `hooks` in its
+/// work tree, as `core.hooksPath` says.
+fn hooked_repo() -> TestRepo {
+    let repo = TestRepo::new();
+    repo.commit("initial");
+    repo.git(&["config", "core.hooksPath", "hooks"]);
+    repo
+}
+
+/// Where the tests' configs are read from, like [`common::git_rare`] sets.
+fn config_home(repo: &TestRepo) -> PathBuf {
+    repo.path().join(".no-config")
+}
+
+/// Commits in `repo` at `date` with git-rare on `PATH`, so the hook can run
+/// it, and returns everything git printed, which includes what the hook did.
+fn commit_with_hook(repo: &TestRepo, message: &str, date: &str) -> String {
+    let bin = Path::new(env!("CARGO_BIN_EXE_git-rare")).parent().unwrap();
+    let path = std::env::var_os("PATH").unwrap_or_default();
+    let path = std::env::join_paths(
+        [bin.to_path_buf()]
+            .into_iter()
+            .chain(std::env::split_paths(&path)),
+    )
+    .unwrap();
+    let output = git_command(repo.path())
+        .args(["commit", "-q", "--allow-empty", "-m", message])
+        .env("PATH", path)
+        .env("XDG_CONFIG_HOME", config_home(repo))
+        .env("GIT_AUTHOR_DATE", date)
+        .env("GIT_COMMITTER_DATE", date)
+        .output()
+        .unwrap();
+    assert!(output.status.success(), "{output:?}");
+    let mut printed = String::from_utf8(output.stdout).unwrap();
+    printed.push_str(&String::from_utf8(output.stderr).unwrap());
+    printed
+}
+
+fn stdout(output: Output) -> String {
+    assert!(output.status.success(), "{output:?}");
+    String::from_utf8(output.stdout).unwrap()
+}
+
+#[test]
+fn the_hook_announces_each_new_commit() {
+    let repo = hooked_repo();
+    let installed = stdout(repo.git_rare(&["hook", "install"]));
+    assert!(
+        installed.starts_with("Installed a post-commit hook"),
+        "{installed}"
+    );
+    assert!(repo.path().join("hooks/post-commit").is_file());
+
+    // Common commits are left unannounced.
+    assert_eq!(
+        commit_with_hook(&repo, "common", "2024-03-01T00:00:00Z"),
+        ""
+    );
+
+    match_everything(&repo);
+    let printed = commit_with_hook(&repo, "uncommon", "2024-03-01T00:01:00Z");
+    let hash = repo.git(&["rev-parse", "HEAD"]);
+    assert_eq!(
+        printed.trim(),
+        format!("✨ Uncommon commit! {}… matches everything", &hash[..11])
+    );
+}
+
+#[test]
+fn check_classifies_one_commit() {
+    let repo = hooked_repo();
+    let hash = repo.git(&["rev-parse", "HEAD"]);
+    for backend in backends() {
+        let checked = stdout(repo.git_rare(&["--backend", backend, "check"]));
+        assert_eq!(checked.trim(), format!("Common commit {}…", &hash[..11]));
+        let quiet = repo.git_rare(&["--backend", backend, "check", "HEAD", "--quiet-common"]);
+        assert_eq!(stdout(quiet), "");
+        let ruled = repo.git_rare(&["--backend", backend, "--rule", "rare:^", "check", &hash]);
+        assert!(
+            stdout(ruled).starts_with(&format!("✨ Rare commit! {}…", &hash[..11])),
+            "{backend}"
+        );
+        let missing = repo.git_rare(&["--backend", backend, "check", "no-such-branch"]);
+        assert!(!missing.status.success(), "{backend}");
+    }
+}
+
+#[test]
+fn other_hooks_are_left_alone() {
+    let repo = hooked_repo();
+    let hook = repo.path().join("hooks/post-commit");
+    fs::create_dir_all(hook.parent().unwrap()).unwrap();
+    fs::write(&hook, "#!/bin/sh\necho mine\n").unwrap();
+
+    let status = stdout(repo.git_rare(&["hook", "status"]));
+    assert!(
+        status.starts_with("A post-commit hook git-rare didn't install"),
+        "{status}"
+    );
+    let refused = repo.git_rare(&["hook", "install"]);
+    assert!(!refused.status.success());
+    let stderr = String::from_utf8(refused.stderr).unwrap();
+    assert!(stderr.contains("pass --force to replace it"), "{stderr}");
+    let refused = repo.git_rare(&["hook", "uninstall"]);
+    assert!(!refused.status.success());
+    assert_eq!(fs::read_to_string(&hook).unwrap(), "#!/bin/sh\necho mine\n");
+
+    stdout(repo.git_rare(&["hook", "install", "--force"]));
+    let status = stdout(repo.git_rare(&["hook", "status"]));
+    assert!(
+        status.starts_with("git-rare's post-commit hook is installed"),
+        "{status}"
+    );
+    let removed = stdout(repo.git_rare(&["hook", "uninstall"]));
+    assert!(
+        removed.starts_with("Removed the post-commit hook"),
+        "{removed}"
+    );
+    assert!(!hook.exists());
+    let status = stdout(repo.git_rare(&["hook", "status"]));
+    assert!(
+        status.starts_with("No post-commit hook is installed"),
+        "{status}"
+    );
+}
+
+/// Makes every commit Uncommon for git-rare run in `repo`, through the config
+/// the tests read.
+fn match_everything(repo: &TestRepo) {
+    let config = config_home(repo).join("git-rare");
+    fs::create_dir_all(&config).unwrap();
+    fs::write(
+        config.join("config.toml"),
+        "[[rules]]\nname = \"all\"\npattern = \"^\"\ntier = \"uncommon\"\n\
+         explanation = \"Matches everything\"\n",
+    )
+    .unwrap();
+}
+
+#[test]
+fn the_trailer_hook_amends_each_commit_once() {
+    let repo = hooked_repo();
+    match_everything(&repo);
+    stdout(repo.git_rare(&["hook", "install", "--amend-trailer"]));
+    let parent = repo.git(&["rev-parse", "HEAD"]);
+    let printed = commit_with_hook(&repo, "rare one", "2024-03-01T00:00:00Z");
+    assert_eq!(repo.git(&["rev-parse", "HEAD~1"]), parent);
+    assert_eq!(
+        repo.git(&["log", "-1", "--format=%B"]),
+        "rare one\n\nRarity: Uncommon (matches everything)"
+    );
+    // The amend runs the hook again, which leaves the new commit alone.
+    assert_eq!(
+        printed.matches("✨ Uncommon commit!").count(),
+        2,
+        "{printed}"
+    );
+    let hash = repo.git(&["rev-parse", "HEAD"]);
+    assert!(
+        printed.contains(&format!(
+            "Amended the commit to add `Rarity: Uncommon (matches everything)`, which changed \
+             its hash:\n✨ Uncommon commit! {}…",
+            &hash[..11]
+        )),
+        "{printed}"
+    );
+
+    // A commit that already has the trailer isn't amended again.
+    stdout(repo.git_rare(&["post-commit", "--amend-trailer"]));
+    assert_eq!(repo.git(&["rev-parse", "HEAD"]), hash);
+}
+
+#[test]
+fn merges_and_rebases_are_not_amended() {
+    let repo = hooked_repo();
+    match_everything(&repo);
+    repo.git(&["checkout", "-q", "-b", "topic"]);
+    repo.commit("topic");
+    repo.git(&["checkout", "-q", "main"]);
+    repo.commit("main");
+    repo.git(&["merge", "-q", "--no-ff", "-m", "merge topic", "topic"]);
+    let merge = repo.git(&["rev-parse", "HEAD"]);
+    let output = repo.git_rare(&["post-commit", "--amend-trailer"]);
+    let stderr = String::from_utf8(output.stderr.clone()).unwrap();
+    // The merge is made now, so it may be rarer than Uncommon.
+    assert!(stdout(output).starts_with("✨ "));
+    assert!(
+        stderr.contains("not adding a Rarity trailer to a merge commit"),
+        "{stderr}"
+    );
+    assert_eq!(repo.git(&["rev-parse", "HEAD"]), merge);
+
+    repo.commit("after the merge");
+    let head = repo.git(&["rev-parse", "HEAD"]);
+    let rebasing = repo.path().join(".git/rebase-merge");
+    fs::create_dir(&rebasing).unwrap();
+    let output = repo.git_rare(&["post-commit", "--amend-trailer"]);
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(
+        stderr.contains("not adding a Rarity trailer during a rebase"),
+        "{stderr}"
+    );
+    assert_eq!(repo.git(&["rev-parse", "HEAD"]), head);
+    fs::remove_dir(&rebasing).unwrap();
+}
@@ -0,0 +1,104 @@
+mod common;
+
+use std::fs;
+
+use serde_json::Value;
+
+use common::{backends, git_command, TestRepo};
+
+// Git drops angle brackets from names, so the markup of a script is in the
+// explanation of a rule instead.
+const AUTHOR: &str = "O'Doe \"JD\" & Co";
+
+/// A repository with three commits by an author whose name has characters to
+/// escape in HTML, and a config with a rule whose explanation is markup.
+fn scripted_repo() -> TestRepo {
+    let repo = TestRepo::new();
+    for i in 0..3 {
+        let date = format!("2024-01-01T00:0{i}:00Z");
+        let output = git_command(repo.path())
+            .args([
+                "commit",
+                "-q",
+                "--allow-empty",
+                "-m",
+                &format!("commit {i}"),
+            ])
+            .env("GIT_AUTHOR_NAME", AUTHOR)
+            .env("GIT_AUTHOR_DATE", &date)
+            .env("GIT_COMMITTER_DATE", &date)
+            .output()
+            .unwrap();
+        assert!(output.status.success(), "{output:?}");
+    }
+    let config = repo.path().join(".no-config/git-rare");
+    fs::create_dir_all(&config).unwrap();
+    fs::write(
+        config.join("config.toml"),
+        "[[rules]]\nname = \"script\"\npattern = \"^[0-9a-f]{3}\"\ntier = \"rare\"\n\
+         explanation = \"<script>alert(1)</script>\"\n",
+    )
+    .unwrap();
+    repo
+}
+
+#[test]
+fn commits_are_a_table_with_their_matches_marked() {
+    let repo = scripted_repo();
+    let document: Value =
+        serde_json::from_str(&repo.git_rare_stdout(&["--format", "json"])).unwrap();
+    let commits = document["commits"].as_array().unwrap();
+    assert_eq!(commits.len(), 3);
+    for backend in backends() {
+        let html = repo.git_rare_stdout(&["--backend", backend, "--format", "html"]);
+        assert!(html.starts_with("<!DOCTYPE html>\n"), "{backend}: {html}");
+        assert!(html.ends_with("</html>\n"), "{backend}: {html}");
+        let text = html.replace("<mark>", "").replace("</mark>", "");
+        for commit in commits {
+            let hash = commit["hash"].as_str().unwrap();
+            let marked = format!("<td class=\"hash\"><code><mark>{}</mark>", &hash[..3]);
+            assert!(html.contains(&marked), "{backend}: {hash} in {html}");
+            let whole = format!("<td class=\"hash\"><code>{hash}</code></td>");
+            assert!(text.contains(&whole), "{backend}: {hash} in {html}");
+        }
+        let counts = "<tr><td>3</td><td>0</td><td>0</td><td>3</td><td>0</td><td>false</td></tr>";
+        assert!(html.contains(counts), "{backend}: {html}");
+        let rules = "<dt>rules</dt><dd>^[0-9a-f]{3}</dd>";
+        assert!(html.contains(rules), "{backend}: {html}");
+        let revisions = "<dt>revisions</dt><dd>HEAD</dd>";
+        assert!(html.contains(revisions), "{backend}: {html}");
+    }
+}
+
+#[test]
+fn text_is_escaped() {
+    let repo = scripted_repo();
+    let html = repo.git_rare_stdout(&["--format", "html"]);
+    let author = "<td class=\"author\">O&#39;Doe &quot;JD&quot; &amp; Co</td>";
+    assert!(html.contains(author), "{html}");
+    let explanation = "<td class=\"explanation\">&lt;script&gt;alert(1)&lt;/script&gt;: ";
+    assert!(html.contains(explanation), "{html}");
+    assert!(!html.contains("<script>alert"), "{html}");
+    // The only script is the one sorting the table.
+    assert_eq!(html.matches("<script>").count(), 1, "{html}");
+}
+
+#[test]
+fn nothing_is_fetched() {
+    let repo = scripted_repo();
+    let html = repo.git_rare_stdout(&["--format", "html"]);
+    for external in ["http://", "https://", "src=", "<link", "@import"] {
+        assert!(!html.contains(external), "{external} in {html}");
+    }
+}
+
+#[test]
+fn count_prints_only_the_summary() {
+    let repo = scripted_repo();
+    let output = repo.git_rare(&["--format", "html", "--count"]);
+    assert!(output.status.success(), "{output:?}");
+    let html = String::from_utf8(output.stdout).unwrap();
+    assert!(html.contains("<section id=\"summary\">"), "{html}");
+    assert!(!html.contains("<section id=\"commits\">"), "{html}");
+    assert!(!html.contains("This operation took"), "{html}");
+}
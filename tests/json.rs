@@ -0,0 +1,151 @@
+mod common;
+
+use serde_json::Value;
+
+use common::{
+    backends, fixture, git_command, git_rare, git_rare_with_stdin, repo_with_commits, TestRepo,
+};
+
+/// Runs git-rare with `--format json` and `args`, parsing all of its stdout
+/// as one JSON document.
+fn json(repo: &TestRepo, args: &[&str]) -> Value {
+    let stdout = repo.git_rare_stdout(&[&["--format", "json"], args].concat());
+    serde_json::from_str(&stdout).unwrap_or_else(|e| panic!("{e}: {stdout}"))
+}
+
+#[test]
+fn commits_are_listed_with_their_rarity() {
+    let (repo, hashes) = repo_with_commits(3);
+    for backend in backends() {
+        let document = json(&repo, &["--backend", backend, "--all", "--rule", "rare:^"]);
+        let commits = document["commits"].as_array().unwrap();
+        let listed = commits
+            .iter()
+            .map(|commit| commit["hash"].as_str().unwrap())
+            .collect::<Vec<&str>>();
+        let newest_first = hashes
+            .iter()
+            .rev()
+            .map(String::as_str)
+            .collect::<Vec<&str>>();
+        assert_eq!(listed, newest_first, "{backend}");
+        let first = &commits[2];
+        assert_eq!(first["author"], "John Doe", "{backend}");
+        assert_eq!(first["datetime"], "2024-01-01T00:00:00Z", "{backend}");
+        assert_eq!(first["tier"], "Rare", "{backend}");
+        assert_eq!(first["explanation"], "^", "{backend}");
+        assert!(first["probability"].is_null(), "{backend}");
+        assert_eq!(document["counts"]["total"], 3, "{backend}");
+        assert_eq!(document["counts"]["shallow"], false, "{backend}");
+        assert_eq!(document["parameters"]["all"], true, "{backend}");
+        assert_eq!(document["parameters"]["rules"][0], "^", "{backend}");
+    }
+}
+
+#[test]
+fn the_probability_of_a_rule_is_a_number() {
+    let (repo, _) = repo_with_commits(10);
+    let config = fixture("rules.toml");
+    let document = json(
+        &repo,
+        &["--config", config.to_str().unwrap(), "--only", "uncommon"],
+    );
+    let commits = document["commits"].as_array().unwrap();
+    assert!(!commits.is_empty(), "{document}");
+    for commit in commits {
+        assert_eq!(commit["tier"], "Uncommon");
+        assert!(commit["probability"].is_f64(), "{commit}");
+    }
+    assert_eq!(document["parameters"]["only"], "Uncommon");
+    let uncommon = document["counts"]["uncommon"].as_u64().unwrap();
+    assert_eq!(uncommon, commits.len() as u64);
+}
+
+#[test]
+fn count_prints_only_the_summary() {
+    let (repo, _) = repo_with_commits(4);
+    for backend in backends() {
+        let summary = json(&repo, &["--backend", backend, "--count"]);
+        let counted = ["common", "uncommon", "rare", "legendary"]
+            .iter()
+            .map(|tier| summary[tier].as_u64().unwrap())
+            .sum::<u64>();
+        assert_eq!(summary["total"], 4, "{backend}");
+        assert_eq!(counted, 4, "{backend}");
+        assert_eq!(summary["shallow"], false, "{backend}");
+        assert!(summary.get("commits").is_none(), "{backend}");
+    }
+}
+
+#[test]
+fn several_repositories_are_counted_apart() {
+    let (repo, _) = repo_with_commits(2);
+    let (other, _) = repo_with_commits(3);
+    let other_path = other.path().to_str().unwrap();
+    let document = json(&repo, &["-C", ".", "-C", other_path, "--all"]);
+    let repositories = document["counts"]["repositories"].as_array().unwrap();
+    let totals = repositories
+        .iter()
+        .map(|repo| {
+            (
+                repo["repo"].as_str().unwrap(),
+                repo["total"].as_u64().unwrap(),
+            )
+        })
+        .collect::<Vec<(&str, u64)>>();
+    assert_eq!(totals, [(".", 2), (other_path, 3)]);
+    assert_eq!(document["counts"]["total"], 5);
+    let commits = document["commits"].as_array().unwrap();
+    assert_eq!(commits.len(), 5);
+    assert!(commits.iter().all(|commit| commit["repo"].is_string()));
+}
+
+#[test]
+fn shallow_clones_are_marked() {
+    let (repo, _) = repo_with_commits(5);
+    let clone = tempfile::TempDir::new().unwrap();
+    let url = format!("file://{}", repo.path().display());
+    let output = git_command(repo.path())
+        .args(["clone", "-q", "--depth", "2", &url])
+        .arg(clone.path())
+        .output()
+        .unwrap();
+    assert!(output.status.success(), "{output:?}");
+    let output = git_rare(clone.path(), &["--format", "json", "--count"]);
+    assert!(output.status.success(), "{output:?}");
+    let summary: Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(summary["shallow"], true);
+    assert_eq!(summary["total"], 2);
+}
+
+#[test]
+fn stdout_is_only_json() {
+    let (repo, _) = repo_with_commits(2);
+    for args in [&["--format", "json"][..], &["--format", "json", "--count"]] {
+        let output = repo.git_rare(args);
+        let stdout = String::from_utf8(output.stdout).unwrap();
+        assert!(!stdout.contains("This operation took"), "{stdout}");
+        serde_json::from_str::<Value>(&stdout).unwrap();
+    }
+    // Finding nothing is still a document, with no commits in it.
+    let document = json(&repo, &["--only", "legendary"]);
+    assert_eq!(document["commits"], Value::Array(vec![]));
+}
+
+#[test]
+fn hashes_read_from_stdin_have_no_author() {
+    let (repo, hashes) = repo_with_commits(2);
+    let input = format!("{}\n{}\n", hashes[0], hashes[1]);
+    let output = git_rare_with_stdin(
+        repo.path(),
+        &["--format", "json", "--stdin", "--all"],
+        &input,
+    );
+    assert!(output.status.success(), "{output:?}");
+    let document: Value = serde_json::from_slice(&output.stdout).unwrap();
+    let commits = document["commits"].as_array().unwrap();
+    assert_eq!(commits.len(), 2);
+    assert_eq!(commits[0]["hash"], hashes[0].as_str());
+    assert!(commits[0]["author"].is_null());
+    assert!(commits[0]["datetime"].is_null());
+}
@@ -0,0 +1,114 @@
+mod common;
+
+use common::{backends, git_command, TestRepo};
+
+/// A repository with three commits on main and two on a branch that was
+/// deleted, so no ref reaches them anymore. Returns it with the hashes of
+/// the orphaned commits, newest first.
+fn orphan_repo() -> (TestRepo, Vec<String>) {
+    let repo = TestRepo::new();
+    for i in 0..3 {
+        repo.commit(&format!("main {i}"));
+    }
+    repo.git(&["checkout", "-q", "-b", "doomed"]);
+    let first = repo.commit("doomed 0");
+    let second = repo.commit("doomed 1");
+    repo.git(&["checkout", "-q", "main"]);
+    repo.git(&["branch", "-q", "-D", "doomed"]);
+    (repo, vec![second, first])
+}
+
+/// The hashes in the table in `stdout`, from top to bottom.
+fn hashes(stdout: &str) -> Vec<String> {
+    stdout
+        .lines()
+        .filter(|line| line.contains("2024-"))
+        .map(|line| line.split('│').nth(3).unwrap().trim().to_string())
+        .collect()
+}
+
+#[test]
+fn lost_finds_the_commits_of_deleted_branches() {
+    let (repo, orphans) = orphan_repo();
+    let rule = format!("legendary:^{}", &orphans[1][..12]);
+    for backend in backends() {
+        let args = ["--backend", backend, "--rule", &rule, "--only", "legendary"];
+        let stdout = repo.git_rare_stdout(&args);
+        assert!(
+            stdout.contains("No Legendary commits found."),
+            "{backend}:\n{stdout}"
+        );
+
+        let output = repo.git_rare(&[&args[..], &["--lost"]].concat());
+        assert!(output.status.success(), "{output:?}");
+        let stdout = String::from_utf8(output.stdout).unwrap();
+        assert_eq!(hashes(&stdout), [orphans[1].clone()], "{backend}");
+        let stderr = String::from_utf8(output.stderr).unwrap();
+        assert!(stderr.contains("`git gc` will delete them"), "{stderr}");
+    }
+}
+
+#[test]
+fn lost_lists_only_unreachable_commits_newest_first() {
+    let (repo, orphans) = orphan_repo();
+    for backend in backends() {
+        let stdout = repo.git_rare_stdout(&["--backend", backend, "--all", "--lost"]);
+        assert_eq!(hashes(&stdout), orphans, "{backend}");
+
+        let stdout = repo.git_rare_stdout(&["--backend", backend, "--count", "--lost"]);
+        assert!(stdout.contains("│ 2 "), "{backend}:\n{stdout}");
+    }
+}
+
+#[test]
+fn lost_without_orphans_says_so() {
+    let repo = TestRepo::new();
+    repo.commit("only");
+    for backend in backends() {
+        let stdout = repo.git_rare_stdout(&["--backend", backend, "--lost"]);
+        assert!(stdout.contains("No lost commits found."), "{stdout}");
+    }
+}
+
+#[test]
+fn rescue_keeps_lost_commits_from_gc() {
+    for backend in backends() {
+        let (repo, orphans) = orphan_repo();
+        let rule = format!("legendary:^{}", &orphans[0][..12]);
+        let output = repo.git_rare(&["--backend", backend, "--rule", &rule, "--lost", "--rescue"]);
+        assert!(output.status.success(), "{output:?}");
+        let stderr = String::from_utf8(output.stderr).unwrap();
+        assert!(stderr.contains("Rescued 1 lost commits"), "{stderr}");
+
+        let rescued = format!("refs/rare/{}", orphans[0]);
+        assert_eq!(repo.git(&["rev-parse", &rescued]), orphans[0]);
+        repo.git(&["reflog", "expire", "--expire=now", "--all"]);
+        repo.git(&["gc", "-q", "--prune=now"]);
+        for orphan in &orphans {
+            // Both survive, since the rescued commit's parent is the other.
+            let output = git_command(repo.path())
+                .args(["cat-file", "-e", orphan])
+                .output()
+                .unwrap();
+            assert!(output.status.success(), "{backend}: {orphan} was pruned");
+        }
+        let stdout = repo.git_rare_stdout(&["--backend", backend, "--lost"]);
+        assert!(stdout.contains("No lost commits found."), "{stdout}");
+    }
+}
+
+#[cfg(unix)]
+#[test]
+fn a_chatty_git_doesnt_stall_the_hunt() {
+    let (repo, lost) = orphan_repo();
+    let output = common::git_rare_with_chatty_git(
+        &repo,
+        &["--backend", "git", "--lost", "--rule", "rare:."],
+    );
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    assert_eq!(hashes(&String::from_utf8(output.stdout).unwrap()), lost);
+}
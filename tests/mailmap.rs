@@ -0,0 +1,109 @@
+mod common;
+
+use std::fs;
+
+use common::{backends, git_command, TestRepo};
+
+/// A repository whose commits are by "John Doe <john@example.com>", "jdoe"
+/// with an old address and "Johnny" with the current one. Its `.mailmap`
+/// merges them all into John Doe.
+fn mailmap_repo() -> TestRepo {
+    let repo = TestRepo::new();
+    fs::write(
+        repo.path().join(".mailmap"),
+        "John Doe <john@example.com>\nJohn Doe <john@example.com> <jdoe@old.example>\n",
+    )
+    .unwrap();
+    repo.git(&["add", ".mailmap"]);
+    repo.commit("add a mailmap");
+    for (i, (name, email)) in [("jdoe", "jdoe@old.example"), ("Johnny", "john@example.com")]
+        .into_iter()
+        .enumerate()
+    {
+        let date = format!("2024-01-02T00:0{i}:00Z");
+        let output = git_command(repo.path())
+            .args(["commit", "-q", "--allow-empty", "-m", name])
+            .env("GIT_AUTHOR_NAME", name)
+            .env("GIT_AUTHOR_EMAIL", email)
+            .env("GIT_AUTHOR_DATE", &date)
+            .env("GIT_COMMITTER_DATE", &date)
+            .output()
+            .unwrap();
+        assert!(output.status.success(), "{output:?}");
+    }
+    repo
+}
+
+/// The authors of the commits listed by `git-rare --all`, newest first.
+fn authors(repo: &TestRepo, backend: &str, args: &[&str]) -> Vec<String> {
+    let args = [&["--backend", backend, "--all"][..], args].concat();
+    repo.git_rare_stdout(&args)
+        .lines()
+        .filter(|line| line.contains("2024-"))
+        .map(|line| line.split('│').nth(1).unwrap().trim().to_string())
+        .collect()
+}
+
+#[test]
+fn authors_are_shown_by_their_canonical_name() {
+    let repo = mailmap_repo();
+    for backend in backends() {
+        assert_eq!(authors(&repo, backend, &[]), ["John Doe"; 3], "{backend}");
+        assert_eq!(
+            authors(&repo, backend, &["--no-mailmap"]),
+            ["Johnny", "jdoe", "John Doe"],
+            "{backend}"
+        );
+    }
+}
+
+#[test]
+fn emails_are_shown_as_the_mailmap_has_them() {
+    let repo = mailmap_repo();
+    for backend in backends() {
+        let args = [
+            "--backend",
+            backend,
+            "--all",
+            "--format",
+            "csv",
+            "--no-footer",
+        ];
+        let csv = repo.git_rare_stdout(&args);
+        let emails = csv
+            .lines()
+            .skip(1)
+            .map(|line| line.rsplit(',').next().unwrap())
+            .collect::<Vec<&str>>();
+        assert_eq!(emails, ["john@example.com"; 3], "{backend}");
+        let args = [
+            "--backend",
+            backend,
+            "--all",
+            "--no-mailmap",
+            "--format",
+            "csv",
+        ];
+        let csv = repo.git_rare_stdout(&args);
+        assert!(csv.contains(",jdoe,"), "{backend}: {csv}");
+        assert!(csv.contains(",jdoe@old.example\n"), "{backend}: {csv}");
+    }
+}
+
+#[test]
+fn authors_are_matched_by_their_canonical_identity() {
+    let repo = mailmap_repo();
+    for backend in backends() {
+        assert_eq!(
+            authors(&repo, backend, &["--author", "John Doe <john@"]).len(),
+            3,
+            "{backend}"
+        );
+        assert_eq!(
+            authors(&repo, backend, &["--no-mailmap", "--author", "old.example"]),
+            ["jdoe"],
+            "{backend}"
+        );
+        assert!(authors(&repo, backend, &["--author", "old.example"]).is_empty());
+    }
+}
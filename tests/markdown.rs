@@ -0,0 +1,104 @@
+mod common;
+
+use common::{backends, git_command, TestRepo};
+
+/// A repository with three commits by an author with a pipe in their name.
+fn piped_repo() -> TestRepo {
+    let repo = TestRepo::new();
+    for i in 0..3 {
+        let date = format!("2024-01-01T00:0{i}:00Z");
+        let output = git_command(repo.path())
+            .args([
+                "commit",
+                "-q",
+                "--allow-empty",
+                "-m",
+                &format!("commit {i}"),
+            ])
+            .env("GIT_AUTHOR_NAME", "Jane | Doe")
+            .env("GIT_AUTHOR_DATE", &date)
+            .env("GIT_COMMITTER_DATE", &date)
+            .output()
+            .unwrap();
+        assert!(output.status.success(), "{output:?}");
+    }
+    repo
+}
+
+#[test]
+fn commits_are_a_markdown_table() {
+    let repo = piped_repo();
+    for backend in backends() {
+        let stdout = repo.git_rare_stdout(&[
+            "--backend",
+            backend,
+            "--format",
+            "markdown",
+            "--rule",
+            "rare:^(a|8)",
+        ]);
+        assert_eq!(
+            stdout,
+            r"| Author      | Datetime                   | Hash                                     | Explanation                                                                      | Odds | Tier |
+|-------------|----------------------------|------------------------------------------|----------------------------------------------------------------------------------|------|------|
+| Jane \| Doe | 2024-01-01 00:02:00 +00:00 | ad5eab5bb84e163f71219322a2550b04a70676ff | ^(a\|8): 'a' at offset 0                                                         |      | Rare |
+| Jane \| Doe | 2024-01-01 00:01:00 +00:00 | 89240bde14e04722afefad9caade780c88976895 | ^(a\|8): '8' at offset 0                                                         |      | Rare |
+| Jane \| Doe | 2024-01-01 00:00:00 +00:00 | 83a58a10086d4400766166e4a0f00dff6be2615a | ^(a\|8): '8' at offset 0; Contains a run of 10 digits: '4400766166' at offset 12 |      | Rare |
+
+3 commits shown: 3 Rare
+",
+            "{backend}"
+        );
+    }
+}
+
+#[test]
+fn count_is_a_markdown_table() {
+    let repo = piped_repo();
+    let stdout = repo.git_rare_stdout(&["--format", "markdown", "--count"]);
+    assert_eq!(
+        stdout,
+        "\
+| Total | Common | Uncommon | Rare | Legendary |
+|-------|--------|----------|------|-----------|
+| 3     | 2      | 1        | 0    | 0         |
+"
+    );
+}
+
+#[test]
+fn hashes_link_to_the_forge_of_origin() {
+    let repo = piped_repo();
+    let remotes = [
+        (
+            "git@github.com:jane/project.git",
+            "https://github.com/jane/project/commit/",
+        ),
+        (
+            "https://gitlab.com/jane/project.git",
+            "https://gitlab.com/jane/project/-/commit/",
+        ),
+    ];
+    for (remote, commits) in remotes {
+        repo.git(&["remote", "add", "origin", remote]);
+        for backend in backends() {
+            let args = ["--backend", backend, "--format", "markdown"];
+            let stdout = repo.git_rare_stdout(&args);
+            let hash = "83a58a10086d4400766166e4a0f00dff6be2615a";
+            assert!(
+                stdout.contains(&format!("| [83a58a1]({commits}{hash}) |")),
+                "{backend}: {stdout}"
+            );
+        }
+        repo.git(&["remote", "remove", "origin"]);
+    }
+    // Other hosts aren't linked to.
+    repo.git(&[
+        "remote",
+        "add",
+        "origin",
+        "https://example.com/jane/project",
+    ]);
+    let stdout = repo.git_rare_stdout(&["--format", "markdown"]);
+    assert!(!stdout.contains("]("), "{stdout}");
+}
@@ -0,0 +1,78 @@
+mod common;
+
+use common::{backends, git_rare_with_stdin, scanned, TestRepo, LEGENDARY_RULE};
+
+fn fixture_repo() -> TestRepo {
+    let repo = TestRepo::new();
+    for i in 0..50 {
+        repo.commit(&format!("commit {i}"));
+    }
+    repo
+}
+
+#[test]
+fn max_count_scans_the_most_recent_commits() {
+    let repo = fixture_repo();
+    let newest = repo.git(&["rev-list", "--max-count", "10", "HEAD"]);
+    for backend in backends() {
+        assert_eq!(
+            scanned(&repo, backend, &["--max-count", "10"]).join("\n"),
+            newest
+        );
+        assert_eq!(scanned(&repo, backend, &["--max-count", "100"]).len(), 50);
+        let stdout = repo.git_rare_stdout(&["--backend", backend, "--max-count", "10", "--count"]);
+        assert!(stdout.contains("│ 10 "), "{backend}:\n{stdout}");
+    }
+}
+
+#[test]
+fn max_count_applies_after_filters() {
+    let repo = fixture_repo();
+    let expected = repo.git(&["rev-list", "--max-count", "3", "HEAD~20..HEAD~5"]);
+    for backend in backends() {
+        let args = [
+            &["--max-count", "3", "--only", "legendary"][..],
+            &LEGENDARY_RULE,
+        ]
+        .concat();
+        let hashes = scanned(&repo, backend, &[&args[..], &["HEAD~20..HEAD~5"]].concat());
+        assert_eq!(hashes.join("\n"), expected, "{backend}");
+        // The commits are a minute apart, starting at midnight.
+        let stdout = repo.git_rare_stdout(&[
+            "--backend",
+            backend,
+            "--max-count",
+            "4",
+            "--until",
+            "2024-01-01T00:02:00Z",
+            "--count",
+        ]);
+        assert!(stdout.contains("│ 3 "), "{backend}:\n{stdout}");
+    }
+}
+
+#[test]
+fn max_count_limits_the_hashes_read_from_stdin() {
+    let repo = fixture_repo();
+    let log = repo.git(&["log", "--pretty=%H %aI %an"]);
+    let output = git_rare_with_stdin(
+        repo.path(),
+        &["--stdin", "--count", "--max-count", "7"],
+        &log,
+    );
+    assert!(output.status.success(), "{output:?}");
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(stdout.contains("│ 7 "), "{stdout}");
+}
+
+#[test]
+fn n_limits_the_near_misses_rather_than_the_scan() {
+    let repo = fixture_repo();
+    let near_misses = |args: &[&str]| {
+        let stdout = repo.git_rare_stdout(&[&["--near-misses"][..], args].concat());
+        stdout.lines().filter(|line| line.contains("2024-")).count()
+    };
+    assert_eq!(near_misses(&["-n", "3"]), 3);
+    assert_eq!(near_misses(&["--limit", "3"]), 3);
+    assert!(!repo.git_rare(&["-n", "3"]).status.success());
+}
@@ -0,0 +1,138 @@
+//! Checks that long histories are streamed. Scanning one of 200k commits is
+//! slow, so that test is ignored by default; run it with
+//! `cargo test --release --test memory -- --ignored`. A shorter history of
+//! long messages checks the same in every run.
+#![cfg(target_os = "linux")]
+
+mod common;
+
+use std::io::Write;
+use std::process::{Command, Stdio};
+use std::thread;
+use std::time::Duration;
+
+use common::{git_command, TestRepo};
+
+/// A repository with a linear history of `commits` empty commits, written
+/// with `git fast-import` since committing them one at a time would take
+/// hours.
+fn long_history(commits: usize) -> TestRepo {
+    history(commits, 0)
+}
+
+/// A repository like [`long_history`] whose messages have a body of
+/// `body` bytes.
+fn history(commits: usize, body: usize) -> TestRepo {
+    let repo = TestRepo::new();
+    let mut stream = Vec::new();
+    for n in 1..=commits {
+        let message = match body {
+            0 => format!("commit {n}\n"),
+            body => format!("commit {n}\n\n{}\n", "x".repeat(body)),
+        };
+        write!(
+            stream,
+            "commit refs/heads/main\nmark :{n}\n\
+             committer John Doe <john@example.com> {} +0000\n\
+             data {}\n{message}",
+            1_700_000_000 + n,
+            message.len(),
+        )
+        .unwrap();
+        if n > 1 {
+            writeln!(stream, "from :{}", n - 1).unwrap();
+        }
+        writeln!(stream).unwrap();
+    }
+    let mut child = git_command(repo.path())
+        .args(["fast-import", "--quiet"])
+        .stdin(Stdio::piped())
+        .spawn()
+        .unwrap();
+    child.stdin.take().unwrap().write_all(&stream).unwrap();
+    assert!(child.wait().unwrap().success());
+    assert_eq!(
+        repo.git(&["rev-list", "--count", "HEAD"]),
+        commits.to_string()
+    );
+    repo
+}
+
+/// Runs git-rare in `repo` and returns the peak resident memory it reached,
+/// in kilobytes, by polling its `VmHWM` until it exits.
+fn peak_memory(repo: &TestRepo, args: &[&str]) -> u64 {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_git-rare"))
+        .current_dir(repo.path())
+        .args(args)
+        .env("XDG_CONFIG_HOME", repo.path().join(".no-config"))
+        .stdout(Stdio::null())
+        .spawn()
+        .unwrap();
+    let status = format!("/proc/{}/status", child.id());
+    let mut peak = 0;
+    while child.try_wait().unwrap().is_none() {
+        let vm_hwm = std::fs::read_to_string(&status)
+            .unwrap_or_default()
+            .lines()
+            .find_map(|line| line.strip_prefix("VmHWM:"))
+            .and_then(|kb| kb.trim().trim_end_matches("kB").trim().parse().ok());
+        peak = peak.max(vm_hwm.unwrap_or(0));
+        thread::sleep(Duration::from_millis(10));
+    }
+    assert!(child.wait().unwrap().success());
+    peak
+}
+
+#[test]
+fn histories_spanning_several_batches_are_read_in_order() {
+    let repo = long_history(6_000);
+    let expected = repo.git(&["rev-list", "HEAD"]);
+    for backend in ["git", "libgit2"] {
+        if backend == "libgit2" && !cfg!(feature = "libgit2") {
+            continue;
+        }
+        let stdout = repo.git_rare_stdout(&["--backend", backend, "--all"]);
+        let hashes = stdout
+            .lines()
+            .filter_map(|line| line.split('│').map(str::trim).find(|cell| cell.len() == 40))
+            .collect::<Vec<&str>>();
+        assert_eq!(hashes.join("\n"), expected, "{backend}");
+        let stdout = repo.git_rare_stdout(&["--backend", backend, "--count"]);
+        assert!(stdout.contains("│ 6000 "), "{backend}:\n{stdout}");
+    }
+}
+
+#[test]
+#[ignore]
+fn long_histories_are_scanned_in_bounded_memory() {
+    let repo = long_history(200_000);
+    // Buffering the log alone would take around 20MB, and
+    // keeping every commit several times that.
+    let git = peak_memory(&repo, &["--backend", "git", "--count"]);
+    assert!(git < 32 * 1024, "git backend peaked at {git}kB");
+    // Only the rows shown are kept.
+    let only = peak_memory(&repo, &["--backend", "git", "--only", "legendary"]);
+    assert!(only < 32 * 1024, "--only peaked at {only}kB");
+    if cfg!(feature = "libgit2") {
+        // The revwalk keeps a node per commit, so this grows with the
+        // history, but the commits themselves are not held.
+        let libgit2 = peak_memory(&repo, &["--backend", "libgit2", "--count"]);
+        assert!(
+            libgit2 < 128 * 1024,
+            "libgit2 backend peaked at {libgit2}kB"
+        );
+    }
+}
+
+#[test]
+fn long_messages_are_scanned_in_flat_memory() {
+    // Commits are classified a batch at a time, so both histories fill
+    // whole batches, but the longer one's log is 40MB more, which buffering
+    // it, or keeping its commits, would show.
+    let short = peak_memory(&history(5_000, 2_000), &["--backend", "git", "--count"]);
+    let long = peak_memory(&history(25_000, 2_000), &["--backend", "git", "--count"]);
+    assert!(
+        long < short + 4 * 1024,
+        "peaked at {long}kB, against {short}kB for a fifth of the history"
+    );
+}
@@ -0,0 +1,99 @@
+mod common;
+
+use std::collections::HashSet;
+
+use common::{backends, git_command, scanned, TestRepo};
+
+/// A repository with two topic branches merged into main, one of them with
+/// two commits.
+fn merged_repo() -> TestRepo {
+    let repo = TestRepo::new();
+    repo.commit("base");
+    for (topic, commits) in [("one", 1), ("two", 2)] {
+        repo.git(&["checkout", "-q", "-b", topic, "main"]);
+        for i in 0..commits {
+            repo.commit(&format!("{topic} {i}"));
+        }
+        repo.git(&["checkout", "-q", "main"]);
+        repo.commit(&format!("before {topic}"));
+        let output = git_command(repo.path())
+            .args([
+                "merge",
+                "-q",
+                "--no-ff",
+                "-m",
+                &format!("merge {topic}"),
+                topic,
+            ])
+            .env("GIT_AUTHOR_DATE", format!("2024-01-02T00:0{commits}:00Z"))
+            .env(
+                "GIT_COMMITTER_DATE",
+                format!("2024-01-02T00:0{commits}:00Z"),
+            )
+            .output()
+            .unwrap();
+        assert!(output.status.success(), "{output:?}");
+    }
+    repo
+}
+
+#[test]
+fn merges_can_be_left_out_or_scanned_alone() {
+    let repo = merged_repo();
+    let rev_list = |args: &[&str]| {
+        repo.git(&[&["rev-list"][..], args, &["HEAD"]].concat())
+            .lines()
+            .map(str::to_string)
+            .collect::<Vec<String>>()
+    };
+    let (all, no_merges, merges) = (
+        rev_list(&[]),
+        rev_list(&["--no-merges"]),
+        rev_list(&["--merges"]),
+    );
+    assert_eq!((all.len(), no_merges.len(), merges.len()), (8, 6, 2));
+    for backend in backends() {
+        assert_eq!(scanned(&repo, backend, &[]), all, "{backend}");
+        assert_eq!(
+            scanned(&repo, backend, &["--no-merges"]),
+            no_merges,
+            "{backend}"
+        );
+        assert_eq!(
+            scanned(&repo, backend, &["--merges-only"]),
+            merges,
+            "{backend}"
+        );
+    }
+    let split = no_merges
+        .iter()
+        .chain(&merges)
+        .collect::<HashSet<&String>>();
+    assert_eq!(split, all.iter().collect());
+}
+
+#[test]
+fn merge_filters_are_counted() {
+    let repo = merged_repo();
+    for backend in backends() {
+        let stdout = repo.git_rare_stdout(&["--backend", backend, "--merges-only", "--count"]);
+        assert!(stdout.contains("│ 2 "), "{backend}:\n{stdout}");
+        let stdout = repo.git_rare_stdout(&[
+            "--backend",
+            backend,
+            "--no-merges",
+            "--first-parent",
+            "--count",
+        ]);
+        assert!(stdout.contains("│ 3 "), "{backend}:\n{stdout}");
+    }
+}
+
+#[test]
+fn merge_filters_are_mutually_exclusive() {
+    let repo = merged_repo();
+    let output = repo.git_rare(&["--no-merges", "--merges-only"]);
+    assert!(!output.status.success());
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(stderr.contains("cannot be used with"), "{stderr}");
+}
@@ -0,0 +1,133 @@
+mod common;
+
+use tempfile::TempDir;
+
+use common::{git_rare, TestRepo};
+
+/// Makes every hash starting with a zero Legendary, so the number of
+/// Legendary commits in a repository is exactly the number engineered.
+const RULE: [&str; 2] = ["--rule", "legendary:^0"];
+
+/// A repository of `total` commits, of which the first `zeros` have hashes
+/// starting with a zero and the rest don't.
+fn engineered_repo(total: usize, zeros: usize) -> TestRepo {
+    let repo = TestRepo::new();
+    for i in 0..total {
+        let want_zero = i < zeros;
+        for nonce in 0.. {
+            let hash = repo.commit(&format!("commit {i} attempt {nonce}"));
+            if hash.starts_with('0') == want_zero {
+                break;
+            }
+            if i == 0 {
+                repo.git(&["update-ref", "-d", "HEAD"]);
+            } else {
+                repo.git(&["reset", "-q", "--soft", "HEAD~1"]);
+            }
+        }
+    }
+    repo
+}
+
+fn run(args: &[&str]) -> (String, String) {
+    let cwd = TempDir::new().unwrap();
+    let output = git_rare(cwd.path(), &[&RULE[..], args].concat());
+    assert!(
+        output.status.success(),
+        "git-rare {args:?} failed: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    (
+        String::from_utf8(output.stdout).unwrap(),
+        String::from_utf8(output.stderr).unwrap(),
+    )
+}
+
+/// The cells of the `--count` row for `repo`.
+fn count_row(stdout: &str, repo: &str) -> Vec<String> {
+    let row = stdout
+        .lines()
+        .find(|line| {
+            line.split('│')
+                .nth(1)
+                .is_some_and(|cell| cell.trim() == repo)
+        })
+        .unwrap_or_else(|| panic!("{repo} missing from output:\n{stdout}"));
+    row.split('│')
+        .map(|cell| cell.trim().to_string())
+        .filter(|cell| !cell.is_empty())
+        .collect()
+}
+
+/// Asserts the total and Legendary counts in a `--count` row.
+fn assert_counts(stdout: &str, repo: &str, total: usize, legendary: usize) {
+    let row = count_row(stdout, repo);
+    assert_eq!(row[1], total.to_string(), "{row:?}");
+    assert_eq!(row[5], legendary.to_string(), "{row:?}");
+}
+
+#[test]
+fn counts_are_aggregated_and_broken_down_per_repo() {
+    let a = engineered_repo(8, 3);
+    let b = engineered_repo(5, 1);
+    let (a_path, b_path) = (a.path().to_str().unwrap(), b.path().to_str().unwrap());
+    let (stdout, _) = run(&["--repo", a_path, "--repo", b_path, "--count"]);
+    assert!(stdout.contains("│ Repository "), "{stdout}");
+    assert_counts(&stdout, a_path, 8, 3);
+    assert_counts(&stdout, b_path, 5, 1);
+    assert_counts(&stdout, "All repositories", 13, 4);
+}
+
+#[test]
+fn rows_are_tagged_with_their_repository() {
+    let a = engineered_repo(4, 2);
+    let b = engineered_repo(3, 1);
+    let (a_path, b_path) = (a.path().to_str().unwrap(), b.path().to_str().unwrap());
+    let (stdout, _) = run(&["-C", a_path, "-C", b_path, "--only", "legendary"]);
+    let rows = stdout
+        .lines()
+        .filter(|line| line.starts_with('│') && line.contains("Legendary"))
+        .collect::<Vec<&str>>();
+    assert_eq!(rows.len(), 3, "{stdout}");
+    assert_eq!(rows.iter().filter(|row| row.contains(a_path)).count(), 2);
+    assert_eq!(rows.iter().filter(|row| row.contains(b_path)).count(), 1);
+}
+
+#[test]
+fn unreadable_repos_are_reported_without_aborting_the_others() {
+    let a = engineered_repo(3, 1);
+    let b = engineered_repo(2, 1);
+    let missing = TempDir::new().unwrap();
+    let missing = missing.path().join("gone");
+    let (a_path, b_path) = (a.path().to_str().unwrap(), b.path().to_str().unwrap());
+    let (stdout, stderr) = run(&[
+        "-C",
+        a_path,
+        "-C",
+        missing.to_str().unwrap(),
+        "-C",
+        b_path,
+        "--count",
+    ]);
+    assert!(stderr.contains("warning: skipping"), "{stderr}");
+    assert!(stderr.contains("gone"), "{stderr}");
+    assert_counts(&stdout, a_path, 3, 1);
+    assert_counts(&stdout, b_path, 2, 1);
+    assert_counts(&stdout, "All repositories", 5, 2);
+}
+
+#[test]
+fn repos_can_be_listed_in_a_file() {
+    let a = engineered_repo(3, 2);
+    let b = engineered_repo(2, 0);
+    let list = a.path().join("repos.txt");
+    std::fs::write(
+        &list,
+        format!("# Relative to this file.\n.\n\n{}\n", b.path().display()),
+    )
+    .unwrap();
+    let (stdout, _) = run(&["--repos-file", list.to_str().unwrap(), "--count"]);
+    assert_counts(&stdout, &a.path().join(".").display().to_string(), 3, 2);
+    assert_counts(&stdout, b.path().to_str().unwrap(), 2, 0);
+    assert_counts(&stdout, "All repositories", 5, 2);
+}
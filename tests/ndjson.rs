@@ -0,0 +1,98 @@
+mod common;
+
+use serde_json::{Map, Value};
+
+use common::{backends, repo_with_commits, TestRepo};
+
+/// Runs git-rare with `--format ndjson` and `args`, parsing each line of its
+/// stdout on its own.
+fn lines(repo: &TestRepo, args: &[&str]) -> Vec<Map<String, Value>> {
+    let stdout = repo.git_rare_stdout(&[&["--format", "ndjson"], args].concat());
+    stdout
+        .lines()
+        .map(|line| match serde_json::from_str(line) {
+            Ok(Value::Object(object)) => object,
+            parsed => panic!("{line}: {parsed:?}"),
+        })
+        .collect()
+}
+
+#[test]
+fn each_commit_is_a_line_followed_by_the_summary() {
+    let (repo, _) = repo_with_commits(12);
+    for backend in backends() {
+        let lines = lines(&repo, &["--backend", backend, "--all"]);
+        assert_eq!(lines.len(), 13, "{backend}");
+        let (summary, commits) = lines.split_last().unwrap();
+        assert_eq!(summary["type"], "summary", "{backend}");
+        assert_eq!(summary["total"], 12, "{backend}");
+        assert_eq!(summary["shallow"], false, "{backend}");
+        assert!(
+            commits.iter().all(|commit| commit["type"] == "commit"),
+            "{backend}"
+        );
+        // Commits are printed in the order they're read, like `git log`.
+        let hashes = commits
+            .iter()
+            .map(|commit| commit["hash"].as_str().unwrap())
+            .collect::<Vec<&str>>()
+            .join("\n");
+        assert_eq!(hashes, repo.git(&["rev-list", "HEAD"]), "{backend}");
+    }
+}
+
+#[test]
+fn fields_match_the_json_format() {
+    let (repo, _) = repo_with_commits(3);
+    let lines = lines(&repo, &["--all"]);
+    let document =
+        serde_json::from_str::<Value>(&repo.git_rare_stdout(&["--format", "json", "--all"]))
+            .unwrap();
+    let json_commits = document["commits"].as_array().unwrap();
+    for (line, commit) in lines.iter().zip(json_commits) {
+        let mut line = line.clone();
+        line.remove("type");
+        assert_eq!(&Value::Object(line), commit);
+    }
+    let mut summary = lines.last().unwrap().clone();
+    summary.remove("type");
+    assert_eq!(Value::Object(summary), document["counts"]);
+}
+
+#[test]
+fn count_prints_only_the_summary() {
+    let (repo, _) = repo_with_commits(5);
+    let lines = lines(&repo, &["--count"]);
+    assert_eq!(lines.len(), 1);
+    assert_eq!(lines[0]["type"], "summary");
+    assert_eq!(lines[0]["total"], 5);
+}
+
+#[test]
+fn several_repositories_are_printed_one_after_another() {
+    let (repo, _) = repo_with_commits(2);
+    let (other, _) = repo_with_commits(3);
+    let other_path = other.path().to_str().unwrap();
+    let lines = lines(&repo, &["-C", ".", "-C", other_path, "--all"]);
+    let repos = lines
+        .iter()
+        .filter(|line| line["type"] == "commit")
+        .map(|line| line["repo"].as_str().unwrap())
+        .collect::<Vec<&str>>();
+    assert_eq!(repos, [".", ".", other_path, other_path, other_path]);
+    let summary = lines.last().unwrap();
+    assert_eq!(summary["total"], 5);
+    assert_eq!(summary["repositories"].as_array().unwrap().len(), 2);
+}
+
+#[test]
+fn near_misses_are_lines_of_their_own() {
+    let (repo, _) = repo_with_commits(6);
+    let lines = lines(&repo, &["--near-misses", "--limit", "2"]);
+    assert_eq!(lines.len(), 3);
+    for near_miss in &lines[..2] {
+        assert_eq!(near_miss["type"], "near_miss");
+        assert!(near_miss["closeness"].is_f64(), "{near_miss:?}");
+    }
+    assert_eq!(lines[2]["type"], "summary");
+}
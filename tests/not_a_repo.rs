@@ -0,0 +1,73 @@
+mod common;
+
+use tempfile::TempDir;
+
+use common::{backends, git_rare, TestRepo};
+
+const MESSAGE: &str =
+    "not a git repository (or any parent up to /): run inside a repo or pass --repo <path>";
+
+#[test]
+fn outside_a_repository_a_short_message_is_shown() {
+    let dir = TempDir::new().unwrap();
+    for backend in backends() {
+        for args in [&[][..], &["--count"], &["--objects"]] {
+            let output = git_rare(dir.path(), &[&["--backend", backend][..], args].concat());
+            assert_eq!(output.status.code(), Some(2), "{backend} {args:?}");
+            let stderr = String::from_utf8(output.stderr).unwrap();
+            assert_eq!(stderr.trim(), format!("Error: {MESSAGE}"), "{backend}");
+        }
+    }
+}
+
+#[test]
+fn verbose_shows_what_git_reported() {
+    let dir = TempDir::new().unwrap();
+    let output = git_rare(dir.path(), &["--backend", "git", "--verbose"]);
+    assert_eq!(output.status.code(), Some(2));
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(
+        stderr.starts_with(&format!("Error: {MESSAGE}\n")),
+        "{stderr}"
+    );
+    assert!(stderr.contains("fatal: not a git repository"), "{stderr}");
+}
+
+#[test]
+fn git_dir_points_at_a_repository() {
+    let repo = TestRepo::new();
+    let hash = repo.commit("first");
+    let git_dir = repo.path().join(".git");
+    let elsewhere = TempDir::new().unwrap();
+    for backend in backends() {
+        let args = [
+            "--backend",
+            backend,
+            "--all",
+            "--git-dir",
+            git_dir.to_str().unwrap(),
+        ];
+        let output = git_rare(elsewhere.path(), &args);
+        assert!(output.status.success(), "{backend}: {output:?}");
+        let stdout = String::from_utf8(output.stdout).unwrap();
+        assert!(stdout.contains(&hash), "{backend}:\n{stdout}");
+
+        let plain = elsewhere.path().to_str().unwrap();
+        let output = git_rare(repo.path(), &["--backend", backend, "--git-dir", plain]);
+        assert_eq!(output.status.code(), Some(2), "{backend}");
+        let stderr = String::from_utf8(output.stderr).unwrap();
+        assert!(
+            stderr.contains(&format!("'{plain}' is not a git directory")),
+            "{backend}: {stderr}"
+        );
+    }
+}
+
+#[test]
+fn git_dir_cannot_be_combined_with_repo() {
+    let repo = TestRepo::new();
+    let output = repo.git_rare(&["--git-dir", ".git", "-C", "."]);
+    assert_eq!(output.status.code(), Some(2));
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(stderr.contains("cannot be used with"), "{stderr}");
+}
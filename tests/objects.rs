@@ -0,0 +1,146 @@
+mod common;
+
+use std::fs;
+
+use common::{backends, git_command, TestRepo};
+
+/// Hash prefix the engineered blob is searched for.
+const PREFIX: &str = "00";
+
+/// A repository whose only file, `docs/rare.txt`, has a blob hash starting
+/// with [`PREFIX`]. Candidate contents are hashed in a single
+/// `git hash-object` call, since hundreds of them are needed.
+fn engineered_repo() -> (TestRepo, String) {
+    let repo = TestRepo::new();
+    let candidates = repo.path().join(".candidates");
+    fs::create_dir(&candidates).unwrap();
+    let paths = (0..3000)
+        .map(|nonce| {
+            let path = candidates.join(nonce.to_string());
+            fs::write(&path, format!("rare blob {nonce}\n")).unwrap();
+            path.display().to_string()
+        })
+        .collect::<Vec<String>>();
+    fs::write(repo.path().join(".paths"), paths.join("\n")).unwrap();
+    let output = git_command(repo.path())
+        .args(["hash-object", "--stdin-paths"])
+        .stdin(fs::File::open(repo.path().join(".paths")).unwrap())
+        .output()
+        .unwrap();
+    assert!(output.status.success(), "{output:?}");
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    let (path, blob) = paths
+        .iter()
+        .zip(stdout.lines())
+        .find(|(_, hash)| hash.starts_with(PREFIX))
+        .expect("no candidate hashed to the prefix");
+    let blob = blob.to_string();
+    fs::create_dir(repo.path().join("docs")).unwrap();
+    fs::copy(path, repo.path().join("docs/rare.txt")).unwrap();
+    fs::remove_dir_all(&candidates).unwrap();
+    fs::remove_file(repo.path().join(".paths")).unwrap();
+    repo.git(&["add", "docs/rare.txt"]);
+    repo.commit("add a rare blob");
+    (repo, blob)
+}
+
+/// The rows of the table in `stdout`, split into their cells.
+fn rows(stdout: &str) -> Vec<Vec<String>> {
+    stdout
+        .lines()
+        .filter(|line| line.starts_with('│') && line.contains("Legendary"))
+        .map(|line| {
+            line.split('│')
+                .map(|cell| cell.trim().to_string())
+                .filter(|cell| !cell.is_empty())
+                .collect()
+        })
+        .collect()
+}
+
+#[test]
+fn objects_reports_blobs_with_their_path() {
+    let (repo, blob) = engineered_repo();
+    let rule = format!("legendary:^{PREFIX}");
+    for backend in backends() {
+        let stdout = repo.git_rare_stdout(&["--backend", backend, "--rule", &rule, "--objects"]);
+        assert!(stdout.contains("│ Object "), "{stdout}");
+        let rows = rows(&stdout);
+        let row = rows
+            .iter()
+            .find(|row| row[1] == blob)
+            .unwrap_or_else(|| panic!("{backend}: {blob} missing:\n{stdout}"));
+        assert_eq!(row[..3], ["Blob", blob.as_str(), "docs/rare.txt"]);
+        for row in &rows {
+            assert!(row[1].starts_with(PREFIX), "{backend}: {row:?}");
+        }
+    }
+}
+
+#[test]
+fn objects_without_matches_say_so() {
+    let (repo, _) = engineered_repo();
+    for backend in backends() {
+        let stdout =
+            repo.git_rare_stdout(&["--backend", backend, "--objects", "--only", "legendary"]);
+        assert!(stdout.contains("No matching objects found."), "{stdout}");
+    }
+}
+
+#[test]
+fn max_count_stops_the_scan_early() {
+    let (repo, _) = engineered_repo();
+    for i in 0..5 {
+        repo.commit(&format!("commit {i}"));
+    }
+    for backend in backends() {
+        let all =
+            repo.git_rare_stdout(&["--backend", backend, "--rule", "legendary:.", "--objects"]);
+        // Two trees, one blob and six commits.
+        assert_eq!(rows(&all).len(), 9, "{backend}:\n{all}");
+        let stdout = repo.git_rare_stdout(&[
+            "--backend",
+            backend,
+            "--rule",
+            "legendary:.",
+            "--objects",
+            "--max-count",
+            "4",
+        ]);
+        assert_eq!(rows(&stdout).len(), 4, "{backend}:\n{stdout}");
+    }
+}
+
+#[test]
+fn objects_scans_a_single_repository() {
+    let (a, _) = engineered_repo();
+    let b = TestRepo::new();
+    b.commit("empty");
+    let output = a.git_rare(&["--objects", "-C", ".", "-C", b.path().to_str().unwrap()]);
+    assert!(!output.status.success());
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(stderr.contains("one repository at a time"), "{stderr}");
+}
+
+#[cfg(unix)]
+#[test]
+fn a_chatty_git_doesnt_stall_the_scan() {
+    let (repo, blob) = engineered_repo();
+    let output = common::git_rare_with_chatty_git(
+        &repo,
+        &[
+            "--backend",
+            "git",
+            "--objects",
+            "--rule",
+            &format!("legendary:^{PREFIX}"),
+        ],
+    );
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(stdout.contains(&blob), "{stdout}");
+}
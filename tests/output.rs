@@ -0,0 +1,119 @@
+mod common;
+
+use std::fs;
+
+use serde_json::Value;
+
+use common::{repo_with_commits, TestRepo};
+
+/// The names of the files in the repository's directory, besides `.git`.
+fn files(repo: &TestRepo) -> Vec<String> {
+    let mut files = fs::read_dir(repo.path())
+        .unwrap()
+        .map(|entry| entry.unwrap().file_name().into_string().unwrap())
+        .filter(|name| name != ".git")
+        .collect::<Vec<String>>();
+    files.sort();
+    files
+}
+
+#[test]
+fn json_is_written_to_the_file() {
+    let (repo, _) = repo_with_commits(3);
+    let output = repo.git_rare(&["--format", "json", "--all", "--output", "x.json"]);
+    assert!(output.status.success(), "{output:?}");
+    assert!(output.stdout.is_empty(), "{output:?}");
+    let written = fs::read_to_string(repo.path().join("x.json")).unwrap();
+    let document: Value = serde_json::from_str(&written).unwrap();
+    assert_eq!(document["commits"].as_array().unwrap().len(), 3);
+    assert_eq!(document["counts"]["total"], 3);
+    assert_eq!(
+        written,
+        repo.git_rare_stdout(&["--format", "json", "--all"])
+    );
+    assert_eq!(files(&repo), ["x.json"]);
+}
+
+#[test]
+fn the_table_is_written_without_the_time_it_took() {
+    let (repo, _) = repo_with_commits(2);
+    let output = repo.git_rare(&["--all", "--output", "table.txt"]);
+    assert!(output.status.success(), "{output:?}");
+    assert!(output.stdout.is_empty(), "{output:?}");
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(stderr.starts_with("This operation took"), "{stderr}");
+    let table = fs::read_to_string(repo.path().join("table.txt")).unwrap();
+    assert!(table.contains("Hash"), "{table}");
+    assert!(!table.contains("This operation took"), "{table}");
+
+    // Finding nothing is said on the terminal, and leaves the file empty.
+    let output = repo.git_rare(&["--only", "legendary", "--output", "table.txt"]);
+    assert!(output.status.success(), "{output:?}");
+    assert_eq!(
+        String::from_utf8(output.stdout).unwrap(),
+        "No Legendary commits found.\n"
+    );
+    assert_eq!(
+        fs::read_to_string(repo.path().join("table.txt")).unwrap(),
+        ""
+    );
+}
+
+#[test]
+fn a_failed_scan_leaves_the_file_as_it_was() {
+    let (repo, _) = repo_with_commits(1);
+    fs::write(repo.path().join("x.json"), "before").unwrap();
+    let args = ["--format", "json", "--output", "x.json", "no-such-revision"];
+    let output = repo.git_rare(&args);
+    assert!(!output.status.success(), "{output:?}");
+    assert!(output.stdout.is_empty(), "{output:?}");
+    assert_eq!(
+        fs::read_to_string(repo.path().join("x.json")).unwrap(),
+        "before"
+    );
+    // The temporary file written instead is removed.
+    assert_eq!(files(&repo), ["x.json"]);
+}
+
+#[test]
+fn unwritable_paths_are_reported() {
+    let (repo, _) = repo_with_commits(1);
+    let output = repo.git_rare(&["--format", "json", "--output", "missing/x.json"]);
+    assert!(!output.status.success(), "{output:?}");
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(stderr.contains("couldn't write missing/x.json"), "{stderr}");
+    assert!(files(&repo).is_empty());
+}
+
+#[cfg(unix)]
+#[test]
+fn a_reader_that_stops_early_isnt_an_error() {
+    use std::process::{Command, Stdio};
+
+    let repo = TestRepo::new();
+    // Far more than a pipe holds, so git-rare is still writing once `head`
+    // has quit.
+    let hashes = (0..300)
+        .map(|i| format!("{:040x}\n", i * 7919))
+        .collect::<String>();
+    let input = repo.path().join("hashes.txt");
+    fs::write(&input, hashes).unwrap();
+    for format in ["table", "ndjson", "csv"] {
+        let mut git_rare = common::git_rare_command(repo.path())
+            .args(["--stdin", "--all", "--quiet", "--format", format])
+            .stdin(fs::File::open(&input).unwrap())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .unwrap();
+        let head = Command::new("head")
+            .arg("-1")
+            .stdin(git_rare.stdout.take().unwrap())
+            .output()
+            .unwrap();
+        assert_eq!(head.stdout.iter().filter(|&&b| b == b'\n').count(), 1);
+        let output = git_rare.wait_with_output().unwrap();
+        assert!(output.status.success(), "{format}: {output:?}");
+        assert!(output.stderr.is_empty(), "{format}: {output:?}");
+    }
+}
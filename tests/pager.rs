@@ -0,0 +1,24 @@
+mod common;
+
+use common::TestRepo;
+
+#[test]
+fn nothing_is_paged_outside_a_terminal() {
+    let repo = TestRepo::new();
+    for i in 0..3 {
+        repo.commit(&format!("commit {i}"));
+    }
+    let paged = repo.path().join("paged");
+    let pager = format!("cat > '{}'", paged.display());
+    for format in ["table", "markdown", "json", "csv"] {
+        let output = common::git_rare_command(repo.path())
+            .args(["--all", "--format", format])
+            .env("GIT_PAGER", &pager)
+            .env("PAGER", &pager)
+            .output()
+            .unwrap();
+        assert!(output.status.success(), "{output:?}");
+        assert!(!output.stdout.is_empty(), "{format}");
+        assert!(!paged.exists(), "{format}");
+    }
+}
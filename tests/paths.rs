@@ -0,0 +1,115 @@
+mod common;
+
+use std::fs;
+
+use common::{backends, git_command, scanned, TestRepo};
+
+/// Writes `contents` to `path` in the repository and commits it.
+fn commit_file(repo: &TestRepo, path: &str, contents: &str) -> String {
+    let file = repo.path().join(path);
+    fs::create_dir_all(file.parent().unwrap()).unwrap();
+    fs::write(file, contents).unwrap();
+    repo.git(&["add", path]);
+    repo.commit(&format!("change {path}"))
+}
+
+/// A repository with commits to `crates/core`, `crates/cli` and `vendor`,
+/// and a merged `topic` branch that only changed `crates/cli`.
+fn monorepo() -> TestRepo {
+    let repo = TestRepo::new();
+    for i in 0..3 {
+        commit_file(&repo, "crates/core/lib.rs", &format!("core {i}\n"));
+        commit_file(&repo, "crates/cli/main.rs", &format!("cli {i}\n"));
+    }
+    commit_file(&repo, "vendor/dep.rs", "dep\n");
+    repo.git(&["checkout", "-q", "-b", "topic"]);
+    commit_file(&repo, "crates/cli/args.rs", "args\n");
+    repo.git(&["checkout", "-q", "main"]);
+    commit_file(&repo, "crates/core/util.rs", "util\n");
+    let output = git_command(repo.path())
+        .args(["merge", "-q", "--no-ff", "-m", "merge topic", "topic"])
+        .env("GIT_AUTHOR_DATE", "2024-01-02T00:00:00Z")
+        .env("GIT_COMMITTER_DATE", "2024-01-02T00:00:00Z")
+        .output()
+        .unwrap();
+    assert!(output.status.success(), "{output:?}");
+    commit_file(&repo, "vendor/dep.rs", "dep 2\n");
+    repo
+}
+
+/// Asserts that both backends scan what `git rev-list` lists for `args`,
+/// given after `--` or with `--path`, and returns how many commits that is.
+fn assert_scans_like_rev_list(repo: &TestRepo, revisions: &[&str], paths: &[&str]) -> usize {
+    let tips: &[&str] = if revisions.is_empty() {
+        &["HEAD"]
+    } else {
+        revisions
+    };
+    let expected = repo
+        .git(&[&["rev-list"][..], tips, &["--"], paths].concat())
+        .lines()
+        .map(str::to_string)
+        .collect::<Vec<String>>();
+    let flags = paths
+        .iter()
+        .flat_map(|path| ["--path", path])
+        .collect::<Vec<&str>>();
+    for backend in backends() {
+        let args = [revisions, &["--"], paths].concat();
+        assert_eq!(
+            scanned(repo, backend, &args),
+            expected,
+            "{backend} {args:?}"
+        );
+        let args = [revisions, &flags].concat();
+        assert_eq!(
+            scanned(repo, backend, &args),
+            expected,
+            "{backend} {args:?}"
+        );
+    }
+    expected.len()
+}
+
+#[test]
+fn paths_restrict_the_scan_to_commits_modifying_them() {
+    let repo = monorepo();
+    assert_eq!(assert_scans_like_rev_list(&repo, &[], &["crates/core"]), 4);
+    // The merge didn't change `crates/cli` from `topic`, so it's simplified
+    // away.
+    assert_eq!(assert_scans_like_rev_list(&repo, &[], &["crates/cli/"]), 4);
+    assert_eq!(
+        assert_scans_like_rev_list(&repo, &[], &["crates/core", "vendor"]),
+        6
+    );
+    assert_eq!(
+        assert_scans_like_rev_list(&repo, &[], &["*.rs", ":!vendor/"]),
+        9
+    );
+}
+
+#[test]
+fn paths_compose_with_ranges() {
+    let repo = monorepo();
+    assert_eq!(
+        assert_scans_like_rev_list(&repo, &["topic..main"], &["crates"]),
+        2,
+        "the merge and crates/core/util.rs"
+    );
+    assert_eq!(
+        assert_scans_like_rev_list(&repo, &["HEAD~4.."], &[":(exclude)crates/cli"]),
+        3,
+        "crates/core/util.rs and both vendor/dep.rs changes"
+    );
+}
+
+#[test]
+fn counts_only_include_commits_modifying_the_paths() {
+    let repo = monorepo();
+    for backend in backends() {
+        let stdout = repo.git_rare_stdout(&["--backend", backend, "--count", "--", "crates/core"]);
+        assert!(stdout.contains("│ 4 "), "{backend}:\n{stdout}");
+        let stdout = repo.git_rare_stdout(&["--backend", backend, "--count", "--path", "nope"]);
+        assert!(stdout.contains("No commits found."), "{backend}:\n{stdout}");
+    }
+}
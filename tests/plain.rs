@@ -0,0 +1,131 @@
+mod common;
+
+use std::fs;
+
+use common::{backends, repo_with_commits};
+
+/// The first field of each line, like `cut -f1` prints it.
+fn cut_first(stdout: &str) -> String {
+    stdout
+        .lines()
+        .map(|line| line.split('\t').next().unwrap().to_string() + "\n")
+        .collect()
+}
+
+#[test]
+fn each_commit_is_a_line_of_fields() {
+    let (repo, hashes) = repo_with_commits(3);
+    for backend in backends() {
+        let stdout = repo.git_rare_stdout(&["--backend", backend, "--plain", "--all"]);
+        let expected = format!(
+            "\
+{}\tCommon\t\t2024-01-01T00:02:00Z\tJohn Doe\t\tjohn@example.com
+{}\tCommon\t\t2024-01-01T00:01:00Z\tJohn Doe\t\tjohn@example.com
+{}\tCommon\t\t2024-01-01T00:00:00Z\tJohn Doe\t\tjohn@example.com
+",
+            hashes[2], hashes[1], hashes[0]
+        );
+        assert_eq!(stdout, expected, "{backend}");
+        assert_eq!(
+            cut_first(&stdout),
+            repo.git(&["rev-list", "HEAD"]) + "\n",
+            "{backend}"
+        );
+    }
+}
+
+#[test]
+fn porcelain_is_the_same_as_plain() {
+    let (repo, _) = repo_with_commits(2);
+    let plain = repo.git_rare_stdout(&["--plain", "--all"]);
+    assert_eq!(repo.git_rare_stdout(&["--porcelain", "--all"]), plain);
+    assert_eq!(repo.git_rare_stdout(&["--format", "plain", "--all"]), plain);
+}
+
+#[test]
+fn header_names_the_fields() {
+    let (repo, hashes) = repo_with_commits(1);
+    let stdout = repo.git_rare_stdout(&["--plain", "--header", "--rule", "rare:^"]);
+    let explanation = repo.git_rare_stdout(&["--format", "json", "--rule", "rare:^"]);
+    let explanation: serde_json::Value = serde_json::from_str(&explanation).unwrap();
+    assert_eq!(
+        stdout,
+        format!(
+            "hash\ttier\tprobability\tdatetime\tauthor\texplanation\temail\n\
+             {}\tRare\t\t2024-01-01T00:00:00Z\tJohn Doe\t{}\tjohn@example.com\n",
+            hashes[0],
+            explanation["commits"][0]["explanation"].as_str().unwrap()
+        )
+    );
+
+    let output = repo.git_rare(&["--header"]);
+    assert!(!output.status.success(), "{output:?}");
+}
+
+#[test]
+fn tabs_and_newlines_are_escaped() {
+    let (repo, hashes) = repo_with_commits(1);
+    let config = repo.path().join(".no-config/git-rare");
+    fs::create_dir_all(&config).unwrap();
+    fs::write(
+        config.join("config.toml"),
+        "[[rules]]\nname = \"all\"\npattern = \"^\"\ntier = \"rare\"\n\
+         explanation = \"a\\ttab,\\na newline and a \\\\ backslash\"\n",
+    )
+    .unwrap();
+    let stdout = repo.git_rare_stdout(&["--plain"]);
+    assert_eq!(stdout.lines().count(), 1, "{stdout}");
+    let fields = stdout
+        .trim_end_matches('\n')
+        .split('\t')
+        .collect::<Vec<&str>>();
+    assert_eq!(fields.len(), 7, "{stdout}");
+    assert_eq!(fields[0], hashes[0]);
+    assert_eq!(fields[5], "a\\ttab,\\na newline and a \\\\ backslash");
+}
+
+#[test]
+fn optional_fields_come_after_the_others() {
+    let (repo, hashes) = repo_with_commits(1);
+    let stdout = repo.git_rare_stdout(&["--plain", "--header", "--all", "-C", ".", "-C", "."]);
+    let lines = stdout.lines().collect::<Vec<&str>>();
+    assert_eq!(
+        lines[0],
+        "hash\ttier\tprobability\tdatetime\tauthor\texplanation\trepo\temail"
+    );
+    assert_eq!(
+        lines[1],
+        format!(
+            "{}\tCommon\t\t2024-01-01T00:00:00Z\tJohn Doe\t\t.\tjohn@example.com",
+            hashes[0]
+        )
+    );
+    // The email ends the line whatever fields come before it.
+    let args = [
+        "--plain",
+        "--header",
+        "--all",
+        "--show-signatures",
+        "-C",
+        ".",
+    ];
+    let stdout = repo.git_rare_stdout(&args);
+    assert_eq!(
+        stdout.lines().next(),
+        Some("hash\ttier\tprobability\tdatetime\tauthor\texplanation\tsignature\temail")
+    );
+}
+
+#[test]
+fn counts_are_a_line() {
+    let (repo, _) = repo_with_commits(4);
+    let output = repo.git_rare(&["--plain", "--count"]);
+    assert!(output.status.success(), "{output:?}");
+    assert_eq!(
+        String::from_utf8(output.stdout).unwrap(),
+        "4\t3\t1\t0\t0\tfalse\n"
+    );
+    // Nothing found is nothing printed, not a message.
+    let stdout = repo.git_rare_stdout(&["--plain", "--only", "legendary"]);
+    assert_eq!(stdout, "");
+}
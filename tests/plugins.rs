@@ -0,0 +1,85 @@
+#![cfg(feature = "plugins")]
+
+mod common;
+
+use std::path::{Path, PathBuf};
+
+use common::{fixture, TestRepo};
+
+fn example_plugin() -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("examples")
+        .join("plugins")
+        .join("bookends.wat")
+}
+
+#[test]
+fn plugin_matches_are_reported_with_their_explanation() {
+    let repo = TestRepo::new();
+    let hashes = (0..40)
+        .map(|i| repo.commit(&format!("commit {i}")))
+        .collect::<Vec<String>>();
+    let plugin = example_plugin();
+    let stdout = repo.git_rare_stdout(&["--all", "--plugin", plugin.to_str().unwrap()]);
+
+    for hash in &hashes {
+        let row = stdout
+            .lines()
+            .find(|line| line.contains(hash.as_str()))
+            .unwrap_or_else(|| panic!("{hash} missing from output:\n{stdout}"));
+        assert_eq!(
+            row.contains("Starts and ends with the same character"),
+            hash.as_bytes()[0] == hash.as_bytes()[39],
+            "{row}"
+        );
+    }
+}
+
+#[test]
+fn trapping_plugin_is_skipped_with_a_warning() {
+    let repo = TestRepo::new();
+    let hash = repo.commit("initial");
+    let plugin = fixture("trap.wat");
+    let output = repo.git_rare(&["--all", "--plugin", plugin.to_str().unwrap()]);
+
+    assert!(output.status.success(), "{output:?}");
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(stdout.contains(&hash), "{stdout}");
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert_eq!(
+        stderr.matches("warning: skipping plugin trap.wat").count(),
+        1,
+        "{stderr}"
+    );
+}
+
+#[test]
+fn slow_plugin_is_skipped_with_a_warning() {
+    let repo = TestRepo::new();
+    let hash = repo.commit("initial");
+    repo.commit("second");
+    let plugin = fixture("spin.wat");
+    let output = repo.git_rare(&["--all", "--plugin", plugin.to_str().unwrap()]);
+
+    assert!(output.status.success(), "{output:?}");
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(stdout.contains(&hash), "{stdout}");
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert_eq!(
+        stderr.matches("warning: skipping plugin spin.wat").count(),
+        1,
+        "{stderr}"
+    );
+}
+
+#[test]
+fn invalid_plugin_is_an_error() {
+    let repo = TestRepo::new();
+    repo.commit("initial");
+    let plugin = fixture("rules.toml");
+    let output = repo.git_rare(&["--plugin", plugin.to_str().unwrap()]);
+
+    assert!(!output.status.success());
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(stderr.contains("failed to load plugin"), "{stderr}");
+}
@@ -0,0 +1,78 @@
+mod common;
+
+use common::{backends, TestRepo};
+
+/// A repository with three commits on main, the last of which was reset
+/// away, so only the reflog still records it. Returns it with the hashes of
+/// the commit that was dropped and of the new HEAD.
+fn reset_repo() -> (TestRepo, String, String) {
+    let repo = TestRepo::new();
+    repo.commit("first");
+    let head = repo.commit("second");
+    let dropped = repo.commit("dropped");
+    repo.git(&["reset", "-q", "--hard", "HEAD~1"]);
+    (repo, dropped, head)
+}
+
+/// The rows of the table in `stdout`, split into their cells.
+fn rows(stdout: &str) -> Vec<Vec<String>> {
+    stdout
+        .lines()
+        .filter(|line| line.contains("2024-"))
+        .map(|line| {
+            line.split('│')
+                .map(|cell| cell.trim().to_string())
+                .collect::<Vec<String>>()
+        })
+        .collect()
+}
+
+#[test]
+fn reflog_finds_commits_that_were_reset_away() {
+    let (repo, dropped, _) = reset_repo();
+    // Makes only the dropped commit Legendary.
+    let rule = format!("legendary:^{}", &dropped[..12]);
+    for backend in backends() {
+        let args = ["--backend", backend, "--rule", &rule, "--only", "legendary"];
+        let stdout = repo.git_rare_stdout(&args);
+        assert!(
+            stdout.contains("No Legendary commits found."),
+            "{backend}:\n{stdout}"
+        );
+
+        let stdout = repo.git_rare_stdout(&[&args[..], &["--reflog"]].concat());
+        assert!(stdout.contains("│ Reflog "), "{backend}:\n{stdout}");
+        let rows = rows(&stdout);
+        assert_eq!(rows.len(), 1, "{backend}:\n{stdout}");
+        assert_eq!(rows[0][3], dropped);
+        assert_eq!(rows[0][rows[0].len() - 3], "Legendary");
+        assert_eq!(rows[0][rows[0].len() - 2], "unreachable");
+    }
+}
+
+#[test]
+fn reflog_commits_are_listed_once() {
+    let (repo, dropped, head) = reset_repo();
+    for backend in backends() {
+        let stdout = repo.git_rare_stdout(&["--backend", backend, "--all", "--reflog"]);
+        let rows = rows(&stdout);
+        assert_eq!(rows.len(), 3, "{backend}:\n{stdout}");
+        let reachability = |hash: &str| {
+            let row = rows.iter().find(|row| row[3] == hash).unwrap();
+            row[row.len() - 2].clone()
+        };
+        assert_eq!(reachability(&dropped), "unreachable", "{backend}");
+        assert_eq!(reachability(&head), "", "{backend}");
+
+        let stdout = repo.git_rare_stdout(&["--backend", backend, "--count", "--reflog"]);
+        assert!(stdout.contains("│ 3 "), "{backend}:\n{stdout}");
+    }
+}
+
+#[test]
+fn reflog_column_is_only_shown_with_reflog() {
+    let (repo, _, _) = reset_repo();
+    let stdout = repo.git_rare_stdout(&["--all"]);
+    assert!(!stdout.contains("Reflog"), "{stdout}");
+    assert_eq!(rows(&stdout).len(), 2);
+}
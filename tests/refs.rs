@@ -0,0 +1,81 @@
+mod common;
+
+use std::collections::HashSet;
+
+use common::{backends, scanned, TestRepo};
+
+/// A repository whose branches diverge after four shared commits: `main`
+/// and `topic` locally, `origin/old` only as a remote-tracking branch, and a
+/// commit only reachable from the tag `orphan`.
+fn fixture_repo() -> TestRepo {
+    let repo = TestRepo::new();
+    for i in 0..4 {
+        repo.commit(&format!("base {i}"));
+    }
+    for (branch, commits) in [("topic", 3), ("old", 2), ("tagged", 1)] {
+        repo.git(&["checkout", "-q", "-b", branch, "main"]);
+        for i in 0..commits {
+            repo.commit(&format!("{branch} {i}"));
+        }
+    }
+    repo.git(&["checkout", "-q", "main"]);
+    for i in 0..5 {
+        repo.commit(&format!("main {i}"));
+    }
+    let old = repo.git(&["rev-parse", "old"]);
+    repo.git(&["update-ref", "refs/remotes/origin/old", &old]);
+    repo.git(&["tag", "orphan", "tagged"]);
+    repo.git(&["branch", "-q", "-D", "old", "tagged"]);
+    repo
+}
+
+fn assert_scans_like_rev_list(repo: &TestRepo, flags: &[&str], rev_list: &[&str], count: usize) {
+    let mut expected = repo
+        .git(&[&["rev-list"][..], rev_list].concat())
+        .lines()
+        .map(str::to_string)
+        .collect::<Vec<String>>();
+    expected.sort();
+    assert_eq!(expected.len(), count, "{rev_list:?}");
+    for backend in backends() {
+        let mut hashes = scanned(repo, backend, flags);
+        let unique = hashes.iter().collect::<HashSet<&String>>().len();
+        assert_eq!(unique, hashes.len(), "{backend} {flags:?} has duplicates");
+        hashes.sort();
+        assert_eq!(hashes, expected, "{backend} {flags:?}");
+    }
+}
+
+#[test]
+fn branches_scans_the_union_of_local_branches() {
+    let repo = fixture_repo();
+    assert_scans_like_rev_list(&repo, &[], &["HEAD"], 9);
+    assert_scans_like_rev_list(&repo, &["--branches"], &["--branches"], 12);
+}
+
+#[test]
+fn remotes_scans_remote_tracking_branches() {
+    let repo = fixture_repo();
+    assert_scans_like_rev_list(&repo, &["--remotes"], &["--remotes"], 6);
+    assert_scans_like_rev_list(
+        &repo,
+        &["--branches", "--remotes"],
+        &["--branches", "--remotes"],
+        14,
+    );
+}
+
+#[test]
+fn all_refs_scans_every_ref() {
+    let repo = fixture_repo();
+    assert_scans_like_rev_list(&repo, &["--all-refs"], &["--all"], 15);
+}
+
+#[test]
+fn counts_are_not_inflated_by_shared_history() {
+    let repo = fixture_repo();
+    for backend in backends() {
+        let stdout = repo.git_rare_stdout(&["--backend", backend, "--branches", "--count"]);
+        assert!(stdout.contains("│ 12 "), "{backend}:\n{stdout}");
+    }
+}
@@ -0,0 +1,59 @@
+mod common;
+
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use common::{backends, git_command, TestRepo};
+
+/// A repository with a commit 3 weeks ago and one 5 hours ago, relative to
+/// when it's made.
+fn recent_repo() -> TestRepo {
+    let repo = TestRepo::new();
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_secs();
+    for (message, ago) in [("weeks", 21 * 24 * 60 * 60), ("hours", 5 * 60 * 60)] {
+        let date = format!("@{} +0100", now - ago);
+        let output = git_command(repo.path())
+            .args(["commit", "-q", "--allow-empty", "-m", message])
+            .env("GIT_AUTHOR_DATE", &date)
+            .env("GIT_COMMITTER_DATE", &date)
+            .output()
+            .unwrap();
+        assert!(output.status.success(), "{output:?}");
+    }
+    repo
+}
+
+#[test]
+fn dates_are_shown_as_how_long_ago() {
+    let repo = recent_repo();
+    for backend in backends() {
+        let args = ["--backend", backend, "--all", "--relative-dates"];
+        let stdout = repo.git_rare_stdout(&args);
+        let dates = stdout
+            .lines()
+            .filter(|line| line.starts_with('│'))
+            .map(|line| line.split('│').nth(2).unwrap().trim().to_string())
+            .collect::<Vec<String>>();
+        assert_eq!(
+            dates,
+            ["Datetime", "5 hours ago", "3 weeks ago"],
+            "{backend}"
+        );
+    }
+
+    let args = ["--all", "--relative-dates", "--format", "markdown"];
+    let markdown = repo.git_rare_stdout(&args);
+    assert!(markdown.contains("| 5 hours ago |"), "{markdown}");
+}
+
+#[test]
+fn machine_formats_keep_rfc_3339() {
+    let repo = recent_repo();
+    let json = repo.git_rare_stdout(&["--all", "--relative-dates", "--format", "json"]);
+    assert!(!json.contains(" ago"), "{json}");
+    assert!(json.contains("+01:00\""), "{json}");
+    let csv = repo.git_rare_stdout(&["--all", "--relative-dates", "--format", "csv"]);
+    assert!(!csv.contains(" ago"), "{csv}");
+}
@@ -0,0 +1,85 @@
+mod common;
+
+use tempfile::TempDir;
+
+use common::{backends, git_rare, repo_with_commits};
+
+/// A repository with enough commits for some of them to be uncommon.
+/// Runs git-rare from an unrelated directory, asserting success.
+fn run_elsewhere(args: &[&str]) -> String {
+    let cwd = TempDir::new().unwrap();
+    let output = git_rare(cwd.path(), args);
+    assert!(
+        output.status.success(),
+        "git-rare {args:?} failed: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    String::from_utf8(output.stdout).unwrap()
+}
+
+fn stderr_elsewhere(args: &[&str]) -> String {
+    let cwd = TempDir::new().unwrap();
+    let output = git_rare(cwd.path(), args);
+    assert!(!output.status.success(), "git-rare {args:?} succeeded");
+    String::from_utf8(output.stderr).unwrap()
+}
+
+#[test]
+fn repo_flag_scans_another_directory() {
+    let (repo, hashes) = repo_with_commits(20);
+    let path = repo.path().to_str().unwrap();
+    for backend in backends() {
+        let stdout = run_elsewhere(&["--backend", backend, "-C", path, "--all"]);
+        for hash in &hashes {
+            assert!(stdout.contains(hash.as_str()), "{hash} missing:\n{stdout}");
+        }
+        let stdout = run_elsewhere(&["--backend", backend, "--repo", path, "--count"]);
+        assert!(stdout.contains("│ 20 "), "{stdout}");
+    }
+}
+
+#[test]
+fn repo_flag_composes_with_only() {
+    let (repo, _) = repo_with_commits(20);
+    let path = repo.path().to_str().unwrap();
+    let expected = repo.git_rare_stdout(&["--only", "uncommon"]);
+    let stdout = run_elsewhere(&["-C", path, "--only", "uncommon"]);
+    assert_eq!(stdout, expected);
+}
+
+#[test]
+fn repo_flag_accepts_a_subdirectory() {
+    let (repo, hashes) = repo_with_commits(20);
+    let subdir = repo.path().join("src");
+    std::fs::create_dir(&subdir).unwrap();
+    for backend in backends() {
+        let stdout = run_elsewhere(&[
+            "--backend",
+            backend,
+            "-C",
+            subdir.to_str().unwrap(),
+            "--all",
+        ]);
+        assert!(stdout.contains(hashes[0].as_str()), "{stdout}");
+    }
+}
+
+#[test]
+fn repo_flag_reports_missing_paths_and_non_repositories() {
+    let dir = TempDir::new().unwrap();
+    let missing = dir.path().join("missing");
+    let missing = missing.to_str().unwrap();
+    let plain = dir.path().to_str().unwrap();
+    for backend in backends() {
+        let stderr = stderr_elsewhere(&["--backend", backend, "-C", missing]);
+        assert!(
+            stderr.contains(&format!("'{missing}' does not exist")),
+            "{stderr}"
+        );
+        let stderr = stderr_elsewhere(&["--backend", backend, "-C", plain]);
+        assert!(
+            stderr.contains(&format!("'{plain}' is not a git repository")),
+            "{stderr}"
+        );
+    }
+}
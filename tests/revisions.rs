@@ -0,0 +1,79 @@
+mod common;
+
+use common::{backends, scanned, TestRepo};
+
+/// A repository with a tagged release and a topic branch off an older commit.
+fn fixture_repo() -> TestRepo {
+    let repo = TestRepo::new();
+    for i in 0..10 {
+        repo.commit(&format!("commit {i}"));
+        if i == 4 {
+            repo.git(&["tag", "-a", "v1.0", "-m", "Release 1.0"]);
+        }
+    }
+    repo.git(&["checkout", "-q", "-b", "topic", "HEAD~3"]);
+    for i in 0..4 {
+        repo.commit(&format!("topic {i}"));
+    }
+    repo.git(&["checkout", "-q", "main"]);
+    repo
+}
+
+#[test]
+fn revisions_select_the_same_commits_as_rev_list() {
+    let repo = fixture_repo();
+    let ranges: &[&[&str]] = &[
+        &["HEAD~4..HEAD"],
+        &["main..topic"],
+        &["topic..main"],
+        &["main...topic"],
+        &["v1.0..HEAD"],
+        &["v1.0"],
+        &["topic", "^v1.0"],
+        &["HEAD~2.."],
+    ];
+    for range in ranges {
+        let expected = repo
+            .git(&[&["rev-list"][..], range].concat())
+            .lines()
+            .map(str::to_string)
+            .collect::<Vec<String>>();
+        assert!(!expected.is_empty(), "{range:?}");
+        for backend in backends() {
+            assert_eq!(
+                scanned(&repo, backend, range),
+                expected,
+                "{backend} {range:?}"
+            );
+        }
+    }
+}
+
+#[test]
+fn without_revisions_all_of_head_is_scanned() {
+    let repo = fixture_repo();
+    let expected = repo.git(&["rev-list", "HEAD"]);
+    for backend in backends() {
+        assert_eq!(scanned(&repo, backend, &[]).join("\n"), expected);
+    }
+}
+
+#[test]
+fn invalid_revisions_are_reported() {
+    let repo = fixture_repo();
+    for backend in backends() {
+        let output = repo.git_rare(&["--backend", backend, "nope..HEAD"]);
+        assert!(!output.status.success(), "{backend}");
+        let stderr = String::from_utf8(output.stderr).unwrap();
+        assert!(stderr.contains("nope"), "{backend}: {stderr}");
+        assert!(!stderr.contains("panicked"), "{backend}: {stderr}");
+    }
+    let output = repo.git_rare(&["--backend", "git", "nope..HEAD"]);
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    let git = common::git_command(repo.path())
+        .args(["log", "nope..HEAD", "--"])
+        .output()
+        .unwrap();
+    let git_stderr = String::from_utf8(git.stderr).unwrap();
+    assert!(stderr.contains(git_stderr.trim()), "{stderr}");
+}
@@ -0,0 +1,61 @@
+mod common;
+
+use common::{backends, git_rare_with_stdin, TestRepo};
+
+/// A SHA-256 repository with a commit on a file, so that it has trees and a
+/// blob as well as commits.
+fn fixture_repo() -> (TestRepo, Vec<String>) {
+    let repo = TestRepo::sha256();
+    std::fs::write(repo.path().join("README"), "hello\n").unwrap();
+    repo.git(&["add", "README"]);
+    let hashes = (0..10)
+        .map(|i| repo.commit(&format!("commit {i}")))
+        .collect::<Vec<String>>();
+    assert!(hashes.iter().all(|hash| hash.len() == 64), "{hashes:?}");
+    (repo, hashes)
+}
+
+#[test]
+fn sha256_histories_are_scanned_by_every_backend() {
+    let (repo, hashes) = fixture_repo();
+    for backend in backends() {
+        let stdout = repo.git_rare_stdout(&["--backend", backend, "--all"]);
+        for hash in &hashes {
+            assert!(
+                stdout.contains(hash.as_str()),
+                "{backend}: {hash} missing:\n{stdout}"
+            );
+        }
+        let stdout = repo.git_rare_stdout(&["--backend", backend, "--count"]);
+        assert!(stdout.contains("│ 10 "), "{backend}:\n{stdout}");
+    }
+}
+
+#[test]
+fn sha256_objects_are_classified() {
+    let (repo, _) = fixture_repo();
+    let blob = repo.git(&["rev-parse", "HEAD:README"]);
+    for backend in backends() {
+        let stdout =
+            repo.git_rare_stdout(&["--backend", backend, "--rule", "legendary:.", "--objects"]);
+        let row = stdout
+            .lines()
+            .find(|line| line.contains(&blob))
+            .unwrap_or_else(|| panic!("{backend}: {blob} missing:\n{stdout}"));
+        assert!(row.contains("README"), "{row}");
+    }
+}
+
+#[test]
+fn sha256_hashes_are_read_from_stdin() {
+    let (repo, hashes) = fixture_repo();
+    let output = git_rare_with_stdin(
+        repo.path(),
+        &["--stdin", "--count", "--quiet"],
+        &hashes.join("\n"),
+    );
+    assert!(output.status.success(), "{output:?}");
+    assert!(output.stderr.is_empty(), "{output:?}");
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(stdout.contains("│ 10 "), "{stdout}");
+}
@@ -0,0 +1,77 @@
+mod common;
+
+use tempfile::TempDir;
+
+use common::{backends, git_command, git_rare, TestRepo};
+
+/// A ten-commit repository and a clone of its last three commits.
+fn shallow_clone() -> (TestRepo, TempDir) {
+    let repo = TestRepo::new();
+    for i in 0..10 {
+        repo.commit(&format!("commit {i}"));
+    }
+    let clone = TempDir::new().unwrap();
+    // Local clones ignore --depth unless they're cloned like remote ones.
+    let url = format!("file://{}", repo.path().display());
+    let output = git_command(repo.path())
+        .args(["clone", "-q", "--depth", "3", &url])
+        .arg(clone.path())
+        .output()
+        .unwrap();
+    assert!(output.status.success(), "{output:?}");
+    (repo, clone)
+}
+
+#[test]
+fn shallow_clones_are_warned_about() {
+    let (repo, clone) = shallow_clone();
+    for backend in backends() {
+        let output = git_rare(clone.path(), &["--backend", backend, "--count"]);
+        assert!(output.status.success(), "{backend}: {output:?}");
+        let stdout = String::from_utf8(output.stdout).unwrap();
+        assert!(stdout.contains("│ 3 "), "{backend}:\n{stdout}");
+        let stderr = String::from_utf8(output.stderr).unwrap();
+        assert!(
+            stderr.contains(
+                "warning: shallow clone — only 3 of unknown total commits scanned; \
+                 run `git fetch --unshallow` for full results"
+            ),
+            "{backend}: {stderr}"
+        );
+
+        let output = repo.git_rare(&["--backend", backend, "--count"]);
+        let stderr = String::from_utf8(output.stderr).unwrap();
+        assert!(!stderr.contains("shallow"), "{backend}: {stderr}");
+    }
+}
+
+#[test]
+fn shallow_clones_are_named_when_scanning_several() {
+    let (repo, clone) = shallow_clone();
+    let clone_path = clone.path().to_str().unwrap();
+    let output = git_rare(repo.path(), &["-C", ".", "-C", clone_path, "--count"]);
+    assert!(output.status.success(), "{output:?}");
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(
+        stderr.contains(&format!("warning: {clone_path} is a shallow clone")),
+        "{stderr}"
+    );
+    assert!(!stderr.contains("warning: . is"), "{stderr}");
+}
+
+#[test]
+fn full_history_can_be_required() {
+    let (repo, clone) = shallow_clone();
+    for backend in backends() {
+        let args = ["--backend", backend, "--require-full-history"];
+        let output = git_rare(clone.path(), &args);
+        assert!(!output.status.success(), "{backend}");
+        let stderr = String::from_utf8(output.stderr).unwrap();
+        assert!(
+            stderr.contains("shallow clone, and --require-full-history was given"),
+            "{backend}: {stderr}"
+        );
+        assert!(output.stdout.is_empty(), "{backend}");
+        repo.git_rare_stdout(&args);
+    }
+}
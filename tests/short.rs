@@ -0,0 +1,123 @@
+mod common;
+
+use tempfile::TempDir;
+
+use common::{backends, git_rare, git_rare_with_stdin, TestRepo};
+
+/// Two hashes with the same first fifteen characters, both Legendary for
+/// their runs of digits, and one that's Rare.
+const TWINS: [&str; 2] = [
+    "1234567890123450000000000000000000000000",
+    "1234567890123459999999999999999999999999",
+];
+const RARE: &str = "987654321e3a91c5db8b2f371a9e73cb5d284e6f";
+
+/// Runs git-rare on the hashes of [`TWINS`] and [`RARE`] read from stdin,
+/// asserting success, and returns its stdout.
+fn run(args: &[&str]) -> String {
+    let cwd = TempDir::new().unwrap();
+    let input = format!("{}\n{}\n{RARE}\n", TWINS[0], TWINS[1]);
+    let args = [&["--stdin", "--short"][..], args].concat();
+    let output = git_rare_with_stdin(cwd.path(), &args, &input);
+    assert!(
+        output.status.success(),
+        "git-rare {args:?} failed: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    String::from_utf8(output.stdout).unwrap()
+}
+
+#[test]
+fn hashes_are_abbreviated_in_the_table() {
+    let repo = TestRepo::new();
+    let hashes = (0..3)
+        .map(|i| repo.commit(&format!("commit {i}")))
+        .collect::<Vec<String>>();
+    for backend in backends() {
+        let args = ["--backend", backend, "--all", "--short"];
+        let stdout = repo.git_rare_stdout(&args);
+        for hash in &hashes {
+            assert!(stdout.contains(&format!("│ {} │", &hash[..12])), "{stdout}");
+            assert!(!stdout.contains(hash.as_str()), "{stdout}");
+        }
+        let stdout = repo.git_rare_stdout(&[&args[..], &["--abbrev", "8"]].concat());
+        for hash in &hashes {
+            assert!(stdout.contains(&format!("│ {} │", &hash[..8])), "{stdout}");
+        }
+    }
+    // The full hash is still what's classified.
+    let full = repo.git_rare_stdout(&["--all", "--format", "plain"]);
+    let short = repo.git_rare_stdout(&["--all", "--format", "plain", "--short"]);
+    let tiers = |plain: &str| {
+        plain
+            .lines()
+            .map(|line| line.split('\t').nth(1).unwrap().to_string())
+            .collect::<Vec<String>>()
+    };
+    assert_eq!(tiers(&short), tiers(&full));
+}
+
+#[test]
+fn shared_prefixes_are_extended_until_unique() {
+    let stdout = run(&[]);
+    assert!(stdout.contains("│ 1234567890123450 │"), "{stdout}");
+    assert!(stdout.contains("│ 1234567890123459 │"), "{stdout}");
+    assert!(stdout.contains("│ 987654321e3a     │"), "{stdout}");
+
+    let stdout = run(&["--abbrev", "4"]);
+    assert!(stdout.contains("│ 1234567890123450 │"), "{stdout}");
+    assert!(stdout.contains("│ 9876             │"), "{stdout}");
+}
+
+#[test]
+fn machine_formats_keep_the_full_hash() {
+    let json = serde_json::from_str::<serde_json::Value>(&run(&["--format", "json"])).unwrap();
+    let commits = json["commits"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|c| {
+            (
+                c["hash"].as_str().unwrap(),
+                c["short_hash"].as_str().unwrap(),
+            )
+        })
+        .collect::<Vec<(&str, &str)>>();
+    assert_eq!(
+        commits,
+        [
+            (TWINS[0], "1234567890123450"),
+            (TWINS[1], "1234567890123459"),
+            (RARE, "987654321e3a"),
+        ]
+    );
+
+    let csv = run(&["--format", "csv"]);
+    let mut lines = csv.lines();
+    assert_eq!(
+        lines.next().unwrap(),
+        "hash,short_hash,author,datetime,tier,explanation,probability,email"
+    );
+    assert!(
+        lines
+            .next()
+            .unwrap()
+            .starts_with(&format!("{},1234567890123450,", TWINS[0])),
+        "{csv}"
+    );
+
+    let ndjson = run(&["--format", "ndjson"]);
+    assert!(
+        ndjson.contains("\"short_hash\":\"1234567890123459\""),
+        "{ndjson}"
+    );
+}
+
+#[test]
+fn abbrev_needs_short_or_short_hash_rules() {
+    let cwd = TempDir::new().unwrap();
+    // The arguments are rejected before anything is read, so there's no
+    // input to write.
+    let output = git_rare(cwd.path(), &["--stdin", "--abbrev", "8"]);
+    assert!(!output.status.success());
+}
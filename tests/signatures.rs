@@ -0,0 +1,124 @@
+mod common;
+
+use std::path::Path;
+use std::process::Command;
+
+use common::{backends, git_command, git_rare_command, TestRepo};
+
+/// The Signature column of a table, newest first.
+fn signatures(stdout: &str) -> Vec<String> {
+    let lines = stdout
+        .lines()
+        .filter(|line| line.starts_with('│'))
+        .collect::<Vec<&str>>();
+    let column = lines[0]
+        .split('│')
+        .position(|cell| cell.trim() == "Signature")
+        .unwrap_or_else(|| panic!("{stdout}"));
+    lines[1..]
+        .iter()
+        .map(|line| line.split('│').nth(column).unwrap().trim().to_string())
+        .collect()
+}
+
+#[test]
+fn unsigned_commits_are_shown_as_unsigned() {
+    let repo = TestRepo::new();
+    repo.commit("first");
+    repo.commit("second");
+    for backend in backends() {
+        let args = ["--backend", backend, "--all", "--show-signatures"];
+        let stdout = repo.git_rare_stdout(&args);
+        assert_eq!(signatures(&stdout), ["unsigned", "unsigned"], "{backend}");
+    }
+    // Without asking for them, signatures aren't checked or shown.
+    let stdout = repo.git_rare_stdout(&["--all"]);
+    assert!(!stdout.contains("Signature"), "{stdout}");
+    let stdout = repo.git_rare_stdout(&["--all", "--columns", "hash,tier"]);
+    assert!(!stdout.contains("Signature"), "{stdout}");
+
+    let args = ["--all", "--show-signatures", "--format", "json"];
+    let json: serde_json::Value = serde_json::from_str(&repo.git_rare_stdout(&args)).unwrap();
+    for commit in json["commits"].as_array().unwrap() {
+        assert_eq!(commit["signature"], "N", "{json}");
+    }
+    let json = repo.git_rare_stdout(&["--all", "--format", "json"]);
+    assert!(!json.contains("\"signature\""), "{json}");
+
+    let args = ["--all", "--show-signatures", "--format", "csv"];
+    let csv = repo.git_rare_stdout(&args);
+    let mut lines = csv.lines();
+    assert_eq!(
+        lines.next(),
+        Some("hash,author,datetime,tier,explanation,probability,signature,email")
+    );
+    assert!(
+        lines
+            .take(2)
+            .all(|line| line.ends_with(",N,john@example.com")),
+        "{csv}"
+    );
+}
+
+/// Creates a key without a passphrase in `home`, returning whether gpg could.
+fn generate_key(home: &Path) -> bool {
+    Command::new("gpg")
+        .env("GNUPGHOME", home)
+        .args([
+            "--batch",
+            "--passphrase",
+            "",
+            "--quick-generate-key",
+            "John Doe <john@example.com>",
+            "ed25519",
+            "sign",
+            "never",
+        ])
+        .output()
+        .is_ok_and(|output| output.status.success())
+}
+
+#[test]
+fn signed_commits_are_checked_with_gpg() {
+    let home = tempfile::TempDir::new().unwrap();
+    if !generate_key(home.path()) {
+        eprintln!("gpg isn't available, skipping");
+        return;
+    }
+    let repo = TestRepo::new();
+    repo.commit("unsigned");
+    let output = git_command(repo.path())
+        .args(["commit", "-q", "--allow-empty", "-S", "-m", "signed"])
+        .env("GNUPGHOME", home.path())
+        .output()
+        .unwrap();
+    assert!(output.status.success(), "{output:?}");
+
+    for backend in backends() {
+        let output = git_rare_command(repo.path())
+            .args(["--backend", backend, "--all", "--show-signatures"])
+            .env("GNUPGHOME", home.path())
+            .output()
+            .unwrap();
+        assert!(output.status.success(), "{output:?}");
+        let stdout = String::from_utf8(output.stdout).unwrap();
+        let found = signatures(&stdout);
+        // A key gpg made is trusted as one's own.
+        assert_eq!(found, ["signed", "unsigned"], "{backend}: {stdout}");
+    }
+}
+
+#[test]
+fn signatures_conflict_with_what_has_no_commits_to_check() {
+    let repo = TestRepo::new();
+    repo.commit("first");
+    for args in [
+        ["--show-signatures", "--count"].as_slice(),
+        ["--show-signatures", "--near-misses"].as_slice(),
+        ["--show-signatures", "--objects"].as_slice(),
+        ["--show-signatures", "--lost"].as_slice(),
+    ] {
+        let output = repo.git_rare(args);
+        assert!(!output.status.success(), "{args:?}");
+    }
+}
@@ -0,0 +1,131 @@
+mod common;
+
+use common::{backends, scanned, TestRepo};
+
+/// A repository tagged `v1.0` after three commits and `v2.0`, a lightweight
+/// tag, two commits later, with three more commits since.
+fn released_repo() -> TestRepo {
+    let repo = TestRepo::new();
+    for i in 0..8 {
+        repo.commit(&format!("commit {i}"));
+        match i {
+            2 => drop(repo.git(&["tag", "-a", "v1.0", "-m", "Release 1.0"])),
+            4 => drop(repo.git(&["tag", "v2.0"])),
+            _ => {}
+        }
+    }
+    repo
+}
+
+/// What git-rare printed to stderr for `args`.
+fn stderr(repo: &TestRepo, backend: &str, args: &[&str]) -> String {
+    let args = [&["--backend", backend][..], args].concat();
+    let output = repo.git_rare(&args);
+    assert!(output.status.success(), "{args:?}: {output:?}");
+    String::from_utf8(output.stderr).unwrap()
+}
+
+fn rev_list(repo: &TestRepo, range: &str) -> Vec<String> {
+    repo.git(&["rev-list", range])
+        .lines()
+        .map(str::to_string)
+        .collect()
+}
+
+#[test]
+fn the_most_recent_tag_is_used_by_default() {
+    let repo = released_repo();
+    let expected = rev_list(&repo, "v2.0..HEAD");
+    assert_eq!(expected.len(), 3);
+    for backend in backends() {
+        assert_eq!(
+            scanned(&repo, backend, &["--since-tag"]),
+            expected,
+            "{backend}"
+        );
+        let stderr = stderr(&repo, backend, &["--since-tag"]);
+        assert!(stderr.contains("Scanning commits since v2.0"), "{stderr}");
+    }
+}
+
+#[test]
+fn a_given_tag_is_used_instead() {
+    let repo = released_repo();
+    let expected = rev_list(&repo, "v1.0..HEAD");
+    assert_eq!(expected.len(), 5);
+    for backend in backends() {
+        assert_eq!(
+            scanned(&repo, backend, &["--since-tag", "v1.0"]),
+            expected,
+            "{backend}"
+        );
+        let stderr = stderr(&repo, backend, &["--since-tag", "v1.0"]);
+        assert!(stderr.contains("Scanning commits since v1.0"), "{stderr}");
+
+        let stdout = repo.git_rare_stdout(&["--backend", backend, "--since-tag=v1.0", "--count"]);
+        assert!(stdout.contains("│ 5 "), "{backend}:\n{stdout}");
+    }
+}
+
+#[test]
+fn missing_tags_are_errors() {
+    let repo = released_repo();
+    for backend in backends() {
+        let output = repo.git_rare(&["--backend", backend, "--since-tag", "v9.9"]);
+        assert!(!output.status.success());
+        let stderr = String::from_utf8(output.stderr).unwrap();
+        assert!(
+            stderr.contains("tag 'v9.9' doesn't exist"),
+            "{backend}: {stderr}"
+        );
+    }
+
+    let untagged = TestRepo::new();
+    untagged.commit("only");
+    for backend in backends() {
+        let output = untagged.git_rare(&["--backend", backend, "--since-tag"]);
+        assert!(!output.status.success());
+        let stderr = String::from_utf8(output.stderr).unwrap();
+        assert!(
+            stderr.contains("found no tag reachable from HEAD"),
+            "{backend}: {stderr}"
+        );
+    }
+}
+
+#[test]
+fn a_branch_named_like_the_tag_is_passed_over() {
+    let repo = released_repo();
+    // Git would take `heads/release` for the branch `release`, which comes
+    // before the tag of that name.
+    repo.git(&["tag", "heads/release", "v1.0"]);
+    repo.git(&["branch", "release", "HEAD~1"]);
+    let expected = rev_list(&repo, "v1.0..HEAD");
+    assert_eq!(expected.len(), 5);
+    for backend in backends() {
+        let scanned = scanned(&repo, backend, &["--since-tag", "heads/release"]);
+        assert_eq!(scanned, expected, "{backend}");
+    }
+}
+
+#[test]
+fn each_repository_is_scanned_since_its_own_tag() {
+    let (first, second) = (released_repo(), released_repo());
+    second.commit("after the release");
+    let (first_path, second_path) = (
+        first.path().to_str().unwrap(),
+        second.path().to_str().unwrap(),
+    );
+    for backend in backends() {
+        let args = ["--since-tag", "-C", first_path, "-C", second_path];
+        let listed = scanned(&first, backend, &args);
+        assert_eq!(listed.len(), 3 + 4, "{backend}");
+        let stderr = stderr(&first, backend, &args);
+        for path in [first_path, second_path] {
+            assert!(
+                stderr.contains(&format!("Scanning commits since v2.0 in {path}")),
+                "{backend}: {stderr}"
+            );
+        }
+    }
+}
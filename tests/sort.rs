@@ -0,0 +1,101 @@
+mod common;
+
+use common::{backends, git_command, TestRepo};
+
+/// A repository whose commits were authored in another order than they
+/// were committed in, by authors whose names are in yet another.
+fn shuffled_repo() -> TestRepo {
+    let repo = TestRepo::new();
+    let commits = [("carol", 3), ("alice", 1), ("Bob", 4), ("dave", 2)];
+    for (i, (author, minute)) in commits.into_iter().enumerate() {
+        let output = git_command(repo.path())
+            .args(["commit", "-q", "--allow-empty", "-m", author])
+            .env("GIT_AUTHOR_NAME", author)
+            .env(
+                "GIT_AUTHOR_EMAIL",
+                format!("{}@example.com", author.to_lowercase()),
+            )
+            .env("GIT_AUTHOR_DATE", format!("2024-01-01T00:0{minute}:00Z"))
+            .env("GIT_COMMITTER_DATE", format!("2024-01-02T00:0{i}:00Z"))
+            .output()
+            .unwrap();
+        assert!(output.status.success(), "{output:?}");
+    }
+    repo
+}
+
+/// The authors of the commits printed for `args`, in their order.
+fn authors(repo: &TestRepo, args: &[&str]) -> Vec<String> {
+    let args = [&["--all", "--format", "json"][..], args].concat();
+    let json = serde_json::from_str::<serde_json::Value>(&repo.git_rare_stdout(&args)).unwrap();
+    json["commits"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|commit| commit["author"].as_str().unwrap().to_string())
+        .collect()
+}
+
+#[test]
+fn commits_are_sorted_by_date_and_author() {
+    let repo = shuffled_repo();
+    for backend in backends() {
+        let args = ["--backend", backend];
+        assert_eq!(authors(&repo, &args), ["dave", "Bob", "alice", "carol"]);
+        let sorted = |extra: &[&str]| authors(&repo, &[&args[..], extra].concat());
+        assert_eq!(
+            sorted(&["--sort-by", "date"]),
+            ["Bob", "carol", "dave", "alice"]
+        );
+        assert_eq!(
+            sorted(&["--sort-by", "date", "--reverse"]),
+            ["alice", "dave", "carol", "Bob"]
+        );
+        assert_eq!(
+            sorted(&["--sort-by", "author"]),
+            ["alice", "Bob", "carol", "dave"]
+        );
+        assert_eq!(sorted(&["--reverse"]), ["carol", "alice", "Bob", "dave"]);
+    }
+}
+
+#[test]
+fn sorting_follows_filtering() {
+    let repo = shuffled_repo();
+    let hashes = repo.git(&["rev-list", "HEAD"]);
+    let rare = hashes
+        .lines()
+        .filter(|hash| hash.starts_with(|c: char| c.is_ascii_digit()))
+        .count();
+    let table = repo.git_rare_stdout(&[
+        "--only",
+        "rare",
+        "--rule",
+        "rare:^[0-9]",
+        "--sort-by",
+        "date",
+        "--reverse",
+    ]);
+    let rows = table
+        .lines()
+        .filter(|line| line.starts_with("│ ") && !line.contains("Author"))
+        .collect::<Vec<&str>>();
+    assert!(rare > 1, "{hashes}");
+    assert_eq!(rows.len(), rare, "{table}");
+    let mut dates = rows
+        .iter()
+        .map(|row| row.split('│').nth(2).unwrap().trim())
+        .collect::<Vec<&str>>();
+    let printed = dates.clone();
+    dates.sort();
+    assert_eq!(printed, dates, "{table}");
+}
+
+#[test]
+fn sorting_needs_what_is_found() {
+    let repo = shuffled_repo();
+    let count = repo.git_rare(&["--count", "--sort-by", "tier"]);
+    assert!(!count.status.success());
+    let near_misses = repo.git_rare(&["--near-misses", "--reverse"]);
+    assert!(!near_misses.status.success());
+}
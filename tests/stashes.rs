@@ -0,0 +1,107 @@
+mod common;
+
+use std::fs;
+
+use common::{backends, git_command, TestRepo};
+
+/// Runs `git stash` in `repo` with a fixed date, so its commits are dated
+/// like the others.
+fn stash(repo: &TestRepo, args: &[&str], date: &str) {
+    let output = git_command(repo.path())
+        .args([&["stash", "push", "-q"][..], args].concat())
+        .env("GIT_AUTHOR_DATE", date)
+        .env("GIT_COMMITTER_DATE", date)
+        .output()
+        .unwrap();
+    assert!(output.status.success(), "{output:?}");
+}
+
+/// A repository with three commits and two stashes, the older of which also
+/// stashed an untracked file. Returns it with the name and hash of each
+/// commit the stashes are made of.
+fn stash_repo() -> (TestRepo, Vec<(String, String)>) {
+    let repo = TestRepo::new();
+    fs::write(repo.path().join("notes.txt"), "first\n").unwrap();
+    repo.git(&["add", "notes.txt"]);
+    for i in 0..3 {
+        repo.commit(&format!("commit {i}"));
+    }
+    fs::write(repo.path().join("notes.txt"), "second\n").unwrap();
+    fs::write(repo.path().join("untracked.txt"), "new\n").unwrap();
+    stash(&repo, &["--include-untracked"], "2024-02-01T00:00:00Z");
+    fs::write(repo.path().join("notes.txt"), "third\n").unwrap();
+    stash(&repo, &[], "2024-02-02T00:00:00Z");
+    let commits = [
+        "stash@{0}",
+        "stash@{0}^2",
+        "stash@{1}",
+        "stash@{1}^2",
+        "stash@{1}^3",
+    ]
+    .into_iter()
+    .map(|name| (name.to_string(), repo.git(&["rev-parse", name])))
+    .collect();
+    (repo, commits)
+}
+
+/// The rows of the table in `stdout`, split into their cells.
+fn rows(stdout: &str) -> Vec<Vec<String>> {
+    stdout
+        .lines()
+        .filter(|line| line.contains("2024-"))
+        .map(|line| {
+            line.split('│')
+                .map(|cell| cell.trim().to_string())
+                .collect::<Vec<String>>()
+        })
+        .collect()
+}
+
+#[test]
+fn stashes_are_labelled_with_their_name() {
+    let (repo, stashed) = stash_repo();
+    for backend in backends() {
+        let stdout = repo.git_rare_stdout(&["--backend", backend, "--all", "--stashes"]);
+        assert!(stdout.contains("│ Stash "), "{backend}:\n{stdout}");
+        let rows = rows(&stdout);
+        assert_eq!(rows.len(), 3 + stashed.len(), "{backend}:\n{stdout}");
+        for (name, hash) in &stashed {
+            let row = rows
+                .iter()
+                .find(|row| row[3] == *hash)
+                .unwrap_or_else(|| panic!("{backend}: {name} missing:\n{stdout}"));
+            assert_eq!(row[row.len() - 2], *name, "{backend}");
+        }
+        let unlabelled = rows.iter().filter(|row| row[row.len() - 2].is_empty());
+        assert_eq!(unlabelled.count(), 3, "{backend}:\n{stdout}");
+    }
+}
+
+#[test]
+fn stashes_are_only_scanned_with_the_flag() {
+    let (repo, stashed) = stash_repo();
+    for backend in backends() {
+        let stdout = repo.git_rare_stdout(&["--backend", backend, "--all", "--all-refs"]);
+        assert!(!stdout.contains("Stash"), "{backend}:\n{stdout}");
+        for (name, hash) in &stashed {
+            assert!(!stdout.contains(hash), "{backend}: {name}:\n{stdout}");
+        }
+
+        let stdout = repo.git_rare_stdout(&["--backend", backend, "--count", "--all-refs"]);
+        assert!(stdout.contains("│ 3 "), "{backend}:\n{stdout}");
+        let stdout = repo.git_rare_stdout(&["--backend", backend, "--count", "--stashes"]);
+        assert!(stdout.contains("│ 8 "), "{backend}:\n{stdout}");
+    }
+}
+
+#[test]
+fn stash_commits_are_counted_once() {
+    let (repo, _) = stash_repo();
+    for backend in backends() {
+        let args = ["--backend", backend, "--count", "--stashes"];
+        for extra in ["--all-refs", "--reflog"] {
+            let stdout = repo.git_rare_stdout(&[&args[..], &[extra]].concat());
+            assert!(stdout.contains("│ 8 "), "{backend} {extra}:\n{stdout}");
+        }
+    }
+}
@@ -0,0 +1,90 @@
+mod common;
+
+use tempfile::TempDir;
+
+use common::{git_rare_with_stdin, TestRepo};
+
+/// Runs git-rare with `input` on stdin, asserting success, and returns its
+/// stdout and stderr.
+fn run(args: &[&str], input: &str) -> (String, String) {
+    let cwd = TempDir::new().unwrap();
+    let args = [&["--stdin"][..], args].concat();
+    let output = git_rare_with_stdin(cwd.path(), &args, input);
+    assert!(
+        output.status.success(),
+        "git-rare {args:?} failed: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    (
+        String::from_utf8(output.stdout).unwrap(),
+        String::from_utf8(output.stderr).unwrap(),
+    )
+}
+
+/// All digits, so Legendary.
+const LEGENDARY: &str = "1234567890123456789012345678901234567890";
+/// A descending run of nine digits, so Rare.
+const RARE: &str = "987654321e3a91c5db8b2f371a9e73cb5d284e6f";
+/// No notable patterns, so Common.
+const COMMON: &str = "7e3a91c5d48b2f371a9e73cb5d284e6f10a9c3b7";
+
+#[test]
+fn piped_hashes_are_classified_without_author_columns() {
+    let input = format!("{LEGENDARY}\n{RARE} refs/heads/main\n{COMMON}\n");
+    // Without the time it took, there's nothing to say on stderr.
+    let (stdout, stderr) = run(&["--quiet"], &input);
+    assert!(stdout.contains(LEGENDARY), "{stdout}");
+    assert!(stdout.contains(RARE), "{stdout}");
+    assert!(!stdout.contains(COMMON), "{stdout}");
+    assert!(!stdout.contains("Author"), "{stdout}");
+    assert!(stderr.is_empty(), "{stderr}");
+
+    let (stdout, _) = run(&["--only", "legendary"], &input);
+    assert!(stdout.contains(LEGENDARY), "{stdout}");
+    assert!(!stdout.contains(RARE), "{stdout}");
+
+    let (stdout, _) = run(&["--count"], &input);
+    assert!(stdout.contains("│ 3 "), "{stdout}");
+}
+
+#[test]
+fn invalid_lines_are_reported_after_the_table() {
+    let input = format!("{LEGENDARY}\nnot a hash\n\nabc1234 short\n{COMMON}\n");
+    let (stdout, stderr) = run(&["--all"], &input);
+    assert!(stdout.contains(LEGENDARY), "{stdout}");
+    assert!(stdout.contains(COMMON), "{stdout}");
+    assert!(stderr.contains("skipped 2 lines"), "{stderr}");
+}
+
+#[test]
+fn rev_list_output_matches_a_scan_of_the_repository() {
+    let repo = TestRepo::new();
+    for i in 0..20 {
+        repo.commit(&format!("commit {i}"));
+    }
+    let hashes = repo.git(&["rev-list", "HEAD"]);
+    let (stdout, _) = run(&["--all"], &hashes);
+    for hash in hashes.lines() {
+        assert!(stdout.contains(hash), "{hash} missing:\n{stdout}");
+    }
+    let count = |s: &str| {
+        s.lines()
+            .find(|line| line.contains("│ 20 "))
+            .map(str::to_string)
+            .unwrap_or_else(|| panic!("no count row:\n{s}"))
+    };
+    let (stdout, _) = run(&["--count"], &hashes);
+    assert_eq!(count(&stdout), count(&repo.git_rare_stdout(&["--count"])));
+}
+
+#[test]
+fn lines_with_commit_fields_keep_their_columns() {
+    let repo = TestRepo::new();
+    for i in 0..5 {
+        repo.commit(&format!("commit {i}"));
+    }
+    let log = repo.git(&["log", "--pretty=format:%H %aI %an"]);
+    let (stdout, _) = run(&["--all"], &log);
+    assert!(stdout.contains("│ Author "), "{stdout}");
+    assert!(stdout.contains("John Doe"), "{stdout}");
+}
@@ -0,0 +1,86 @@
+mod common;
+
+use std::fs;
+
+use common::{git_rare_unstyled, repo_with_commits, TestRepo};
+
+/// The table of two columns printed for `args`, in the style git-rare
+/// picks for a pipe unless they choose one, without the footer that follows
+/// it.
+fn table(repo: &TestRepo, args: &[&str]) -> String {
+    let args = [&["--all", "--columns", "hash,tier"][..], args].concat();
+    let output = git_rare_unstyled(repo.path()).args(&args).output().unwrap();
+    assert!(output.status.success(), "{output:?}");
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    let Some((table, footer)) = stdout.trim_end().rsplit_once('\n') else {
+        panic!("{stdout}");
+    };
+    assert_eq!(footer, "2 commits shown: 2 Common", "{stdout}");
+    format!("{table}\n")
+}
+
+const ASCII: &str = "\
++------------------------------------------+--------+
+| Hash                                     | Tier   |
++------------------------------------------+--------+
+| b9e7d8a992f3c552a72ad3f72e022bbfbdc73f2b | Common |
++------------------------------------------+--------+
+| 9fa901b44f74464eb37eace36fe0ce2e6df9a20b | Common |
++------------------------------------------+--------+
+";
+
+#[test]
+fn styles_draw_the_borders() {
+    let (repo, _) = repo_with_commits(2);
+    assert_eq!(
+        table(&repo, &["--style", "rounded"]),
+        "\
+╭──────────────────────────────────────────┬────────╮
+│ Hash                                     │ Tier   │
+├──────────────────────────────────────────┼────────┤
+│ b9e7d8a992f3c552a72ad3f72e022bbfbdc73f2b │ Common │
+│ 9fa901b44f74464eb37eace36fe0ce2e6df9a20b │ Common │
+╰──────────────────────────────────────────┴────────╯
+"
+    );
+    assert_eq!(table(&repo, &["--style", "ascii"]), ASCII);
+    assert_eq!(
+        table(&repo, &["--style", "psql"]),
+        " Hash                                     | Tier   \n\
+         ------------------------------------------+--------\n \
+         b9e7d8a992f3c552a72ad3f72e022bbfbdc73f2b | Common \n \
+         9fa901b44f74464eb37eace36fe0ce2e6df9a20b | Common \n"
+    );
+    assert_eq!(
+        table(&repo, &["--style", "markdown"]),
+        "\
+| Hash                                     | Tier   |
+|------------------------------------------|--------|
+| b9e7d8a992f3c552a72ad3f72e022bbfbdc73f2b | Common |
+| 9fa901b44f74464eb37eace36fe0ce2e6df9a20b | Common |
+"
+    );
+    assert_eq!(
+        table(&repo, &["--style", "compact"]),
+        table(&repo, &["--style", "blank"])
+    );
+    assert_eq!(
+        table(&repo, &["--style", "blank"]),
+        " Hash                                       Tier   \n \
+         b9e7d8a992f3c552a72ad3f72e022bbfbdc73f2b   Common \n \
+         9fa901b44f74464eb37eace36fe0ce2e6df9a20b   Common \n"
+    );
+}
+
+#[test]
+fn a_pipe_gets_ascii_unless_the_config_says_otherwise() {
+    let (repo, _) = repo_with_commits(2);
+    assert_eq!(table(&repo, &[]), ASCII);
+
+    let config = repo.path().join(".no-config/git-rare");
+    fs::create_dir_all(&config).unwrap();
+    fs::write(config.join("config.toml"), "style = \"markdown\"\n").unwrap();
+    assert_eq!(table(&repo, &[]), table(&repo, &["--style", "markdown"]));
+    // The command line takes precedence over the config.
+    assert_eq!(table(&repo, &["--style", "ascii"]), ASCII);
+}
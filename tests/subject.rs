@@ -0,0 +1,120 @@
+mod common;
+
+use common::{backends, git_command, TestRepo};
+
+const LONG: &str =
+    "Rewrite the parser so that deeply nested expressions no longer overflow the stack";
+
+/// A repository with commits whose subjects have a pipe, quotes, and far too
+/// many characters, oldest first, each message with a body after it.
+fn subject_repo() -> TestRepo {
+    let repo = TestRepo::new();
+    let subjects = ["Split a | b into two", "Say \"hello\", it's 'polite'", LONG];
+    for (i, subject) in subjects.iter().enumerate() {
+        let date = format!("2024-01-01T00:0{i}:00Z");
+        let message = format!("{subject}\n\nThe body isn't shown.");
+        let output = git_command(repo.path())
+            .args(["commit", "-q", "--allow-empty", "-m", &message])
+            .env("GIT_AUTHOR_DATE", &date)
+            .env("GIT_COMMITTER_DATE", &date)
+            .output()
+            .unwrap();
+        assert!(output.status.success(), "{output:?}");
+    }
+    repo
+}
+
+/// The Subject column of a table, newest first.
+fn subjects(stdout: &str) -> Vec<String> {
+    let lines = stdout
+        .lines()
+        .filter(|line| line.starts_with('│'))
+        .collect::<Vec<&str>>();
+    let column = lines[0]
+        .split('│')
+        .position(|cell| cell.trim() == "Subject")
+        .unwrap_or_else(|| panic!("{stdout}"));
+    lines[1..]
+        .iter()
+        .map(|line| line.split('│').nth(column).unwrap().trim().to_string())
+        .collect()
+}
+
+#[test]
+fn subjects_are_cut_short_in_the_table() {
+    let repo = subject_repo();
+    for backend in backends() {
+        let args = ["--backend", backend, "--all", "--show-subject"];
+        let stdout = repo.git_rare_stdout(&args);
+        assert_eq!(
+            subjects(&stdout),
+            [
+                "Rewrite the parser so that deeply nested expressi…",
+                "Say \"hello\", it's 'polite'",
+                "Split a | b into two",
+            ],
+            "{backend}"
+        );
+    }
+
+    let args = ["--all", "--show-subject", "--subject-width", "12"];
+    let stdout = repo.git_rare_stdout(&args);
+    assert_eq!(
+        subjects(&stdout),
+        ["Rewrite the…", "Say \"hello\"…", "Split a | b…"]
+    );
+    // Without asking for them, there's no Subject column.
+    let stdout = repo.git_rare_stdout(&["--all"]);
+    assert!(!stdout.contains("Subject"), "{stdout}");
+    let stdout = repo.git_rare_stdout(&["--all", "--columns", "hash,subject"]);
+    assert_eq!(subjects(&stdout)[2], "Split a | b into two");
+}
+
+#[test]
+fn machine_formats_have_the_whole_subject() {
+    let repo = subject_repo();
+    let args = ["--all", "--show-subject", "--format", "json"];
+    let json: serde_json::Value = serde_json::from_str(&repo.git_rare_stdout(&args)).unwrap();
+    let found = json["commits"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|commit| commit["subject"].as_str().unwrap())
+        .collect::<Vec<&str>>();
+    assert_eq!(
+        found,
+        [LONG, "Say \"hello\", it's 'polite'", "Split a | b into two"]
+    );
+    let json = repo.git_rare_stdout(&["--all", "--format", "json"]);
+    assert!(!json.contains("\"subject\""), "{json}");
+
+    let args = ["--all", "--show-subject", "--format", "csv"];
+    let csv = repo.git_rare_stdout(&args);
+    let mut lines = csv.lines();
+    assert_eq!(
+        lines.next(),
+        Some("hash,author,datetime,subject,tier,explanation,probability,email")
+    );
+    assert!(csv.contains(&format!(",{LONG},")), "{csv}");
+    assert!(
+        csv.contains(",\"Say \"\"hello\"\", it's 'polite'\","),
+        "{csv}"
+    );
+
+    let args = ["--all", "--show-subject", "--format", "markdown"];
+    let markdown = repo.git_rare_stdout(&args);
+    assert!(markdown.contains("| Split a \\| b into two "), "{markdown}");
+}
+
+#[test]
+fn invalid_widths_and_conflicts_are_rejected() {
+    let repo = subject_repo();
+    for args in [
+        ["--show-subject", "--subject-width", "1"].as_slice(),
+        ["--show-subject", "--subject-width", "wide"].as_slice(),
+        ["--show-subject", "--columns", "hash"].as_slice(),
+    ] {
+        let output = repo.git_rare(args);
+        assert!(!output.status.success(), "{args:?}");
+    }
+}
@@ -0,0 +1,114 @@
+mod common;
+
+use std::path::Path;
+
+use common::{backends, git_command, TestRepo};
+
+/// Runs git in `repo` like [`TestRepo::git`], allowing submodules to be
+/// cloned from local paths.
+fn git_with_file_protocol(repo: &TestRepo, args: &[&str]) {
+    let output = git_command(repo.path())
+        .args(["-c", "protocol.file.allow=always"])
+        .args(args)
+        .output()
+        .unwrap();
+    assert!(output.status.success(), "{output:?}");
+}
+
+/// A superproject with two commits, a submodule `libs/core` with three and
+/// one `vendor/old` that was deinitialized. Returns them with the hash of
+/// the submodule's newest commit.
+fn superproject() -> (TestRepo, TestRepo, String) {
+    let core = TestRepo::new();
+    core.commit("core 0");
+    core.commit("core 1");
+    let rare = core.commit("core 2");
+    let old = TestRepo::new();
+    old.commit("old");
+
+    let repo = TestRepo::new();
+    repo.commit("super 0");
+    for (source, path) in [(&core, "libs/core"), (&old, "vendor/old")] {
+        let source = source.path().to_str().unwrap();
+        git_with_file_protocol(&repo, &["submodule", "add", "-q", source, path]);
+    }
+    repo.commit("add submodules");
+    repo.git(&["submodule", "deinit", "-q", "-f", "vendor/old"]);
+    (repo, core, rare)
+}
+
+/// The row for `hash` in `stdout`, split into its cells.
+fn row(stdout: &str, hash: &str) -> Vec<String> {
+    let line = stdout
+        .lines()
+        .find(|line| line.contains(hash))
+        .unwrap_or_else(|| panic!("{hash} missing from output:\n{stdout}"));
+    line.split('│')
+        .map(|cell| cell.trim().to_string())
+        .filter(|cell| !cell.is_empty())
+        .collect()
+}
+
+#[test]
+fn submodule_commits_are_named_by_their_path() {
+    let (repo, _core, rare) = superproject();
+    let rule = format!("legendary:^{}", &rare[..12]);
+    for backend in backends() {
+        let args = ["--backend", backend, "--rule", &rule, "--only", "legendary"];
+        let stdout = repo.git_rare_stdout(&args);
+        assert!(
+            stdout.contains("No Legendary commits found."),
+            "{backend}:\n{stdout}"
+        );
+
+        let output = repo.git_rare(&[&args[..], &["--recurse-submodules"]].concat());
+        assert!(output.status.success(), "{output:?}");
+        let stdout = String::from_utf8(output.stdout).unwrap();
+        assert!(stdout.contains("│ Repository "), "{backend}:\n{stdout}");
+        assert_eq!(row(&stdout, &rare)[0], "libs/core", "{backend}:\n{stdout}");
+        let stderr = String::from_utf8(output.stderr).unwrap();
+        assert!(
+            stderr.contains("skipping vendor/old: submodule isn't initialized"),
+            "{backend}: {stderr}"
+        );
+    }
+}
+
+#[test]
+fn counts_are_per_submodule_with_a_total() {
+    let (repo, _core, _) = superproject();
+    for backend in backends() {
+        let stdout =
+            repo.git_rare_stdout(&["--backend", backend, "--count", "--recurse-submodules"]);
+        assert_eq!(row(&stdout, "│ . ")[..2], [".", "2"], "{backend}");
+        assert_eq!(row(&stdout, "libs/core")[..2], ["libs/core", "3"]);
+        assert_eq!(
+            row(&stdout, "All repositories")[..2],
+            ["All repositories", "5"]
+        );
+    }
+}
+
+#[test]
+fn submodules_are_found_under_each_repo() {
+    let (repo, _core, rare) = superproject();
+    let dir = tempfile::TempDir::new().unwrap();
+    let path = repo.path().to_str().unwrap();
+    for backend in backends() {
+        let output = common::git_rare(
+            dir.path(),
+            &[
+                "--backend",
+                backend,
+                "--all",
+                "-C",
+                path,
+                "--recurse-submodules",
+            ],
+        );
+        assert!(output.status.success(), "{output:?}");
+        let stdout = String::from_utf8(output.stdout).unwrap();
+        let submodule = Path::new(path).join("libs/core");
+        assert_eq!(row(&stdout, &rare)[0], submodule.display().to_string());
+    }
+}
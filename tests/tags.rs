@@ -0,0 +1,103 @@
+mod common;
+
+use common::{backends, git_command, TestRepo};
+
+/// Creates an annotated tag with a fixed tagger date.
+fn annotated_tag(repo: &TestRepo, name: &str, target: &str, date: &str) {
+    let output = git_command(repo.path())
+        .args(["tag", "-a", name, "-m", &format!("Release {name}"), target])
+        .env("GIT_COMMITTER_DATE", date)
+        .output()
+        .unwrap();
+    assert!(output.status.success(), "{output:?}");
+}
+
+/// A repository with an annotated tag on HEAD's history, and a lightweight
+/// and an annotated tag on commits HEAD can't reach.
+fn fixture_repo() -> TestRepo {
+    let repo = TestRepo::new();
+    for i in 0..4 {
+        repo.commit(&format!("commit {i}"));
+    }
+    annotated_tag(&repo, "v1.0", "HEAD~1", "2024-02-03T04:05:06Z");
+    repo.git(&["checkout", "-q", "-b", "gone"]);
+    repo.commit("lightweight");
+    repo.git(&["tag", "light"]);
+    repo.commit("annotated");
+    annotated_tag(&repo, "v2.0-rc", "HEAD", "2024-03-04T05:06:07+02:00");
+    repo.git(&["checkout", "-q", "main"]);
+    repo.git(&["branch", "-q", "-D", "gone"]);
+    repo
+}
+
+/// The row for `hash` in `stdout`, split into its cells.
+fn row(stdout: &str, hash: &str) -> Vec<String> {
+    let line = stdout
+        .lines()
+        .find(|line| line.contains(hash))
+        .unwrap_or_else(|| panic!("{hash} missing from output:\n{stdout}"));
+    line.split('│')
+        .map(|cell| cell.trim().to_string())
+        .filter(|cell| !cell.is_empty())
+        .collect()
+}
+
+#[test]
+fn tags_include_unreachable_commits_and_tag_objects() {
+    let repo = fixture_repo();
+    let light = repo.git(&["rev-parse", "light"]);
+    let annotated = repo.git(&["rev-parse", "v2.0-rc^{commit}"]);
+    let v1 = repo.git(&["rev-parse", "v1.0"]);
+    let v2 = repo.git(&["rev-parse", "v2.0-rc"]);
+    for backend in backends() {
+        let stdout = repo.git_rare_stdout(&["--backend", backend, "--tags", "--all"]);
+        assert!(stdout.contains("│ Object "), "{stdout}");
+        assert_eq!(row(&stdout, &light)[0], "Commit");
+        assert_eq!(row(&stdout, &annotated)[0], "Commit");
+        assert_eq!(row(&stdout, &annotated)[1], "John Doe");
+
+        let v1_row = row(&stdout, &v1);
+        assert_eq!(v1_row[..3], ["Tag", "v1.0", "2024-02-03 04:05:06 +00:00"]);
+        let v2_row = row(&stdout, &v2);
+        assert_eq!(
+            v2_row[..3],
+            ["Tag", "v2.0-rc", "2024-03-04 05:06:07 +02:00"]
+        );
+        // Six commits reachable from the tags, plus the two tag objects.
+        let rows = stdout.lines().filter(|line| line.contains("2024-")).count();
+        assert_eq!(rows, 8, "{backend}:\n{stdout}");
+    }
+}
+
+#[test]
+fn tags_are_counted_with_the_commits() {
+    let repo = fixture_repo();
+    for backend in backends() {
+        let stdout = repo.git_rare_stdout(&["--backend", backend, "--tags", "--count"]);
+        assert!(stdout.contains("│ 8 "), "{backend}:\n{stdout}");
+    }
+}
+
+#[test]
+fn object_column_is_only_shown_with_tags() {
+    let repo = fixture_repo();
+    let stdout = repo.git_rare_stdout(&["--all"]);
+    assert!(!stdout.contains("Object"), "{stdout}");
+    assert!(!stdout.contains(&repo.git(&["rev-parse", "v1.0"])));
+}
+
+#[test]
+fn tags_are_scanned_as_well_as_head() {
+    let repo = fixture_repo();
+    let untagged = repo.commit("untagged");
+    for backend in backends() {
+        let stdout = repo.git_rare_stdout(&["--backend", backend, "--tags", "--all"]);
+        assert_eq!(row(&stdout, &untagged)[0], "Commit", "{backend}");
+        assert!(stdout.contains(&repo.git(&["rev-parse", "light"])));
+
+        // Given revisions take HEAD's place.
+        let args = ["--backend", backend, "--tags", "--all", "HEAD~1"];
+        let stdout = repo.git_rare_stdout(&args);
+        assert!(!stdout.contains(&untagged), "{backend}:\n{stdout}");
+    }
+}
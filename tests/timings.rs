@@ -0,0 +1,112 @@
+mod common;
+
+use common::{backends, git_command, TestRepo};
+
+const PHASES: [&str; 5] = ["git", "parsing", "classification", "rendering", "total"];
+
+/// A repository with three commits, each of which `--rule` finds rare.
+fn timed_repo() -> TestRepo {
+    let repo = TestRepo::new();
+    for i in 0..3 {
+        let date = format!("2024-01-01T00:0{i}:00Z");
+        let output = git_command(repo.path())
+            .args([
+                "commit",
+                "-q",
+                "--allow-empty",
+                "-m",
+                &format!("commit {i}"),
+            ])
+            .env("GIT_AUTHOR_DATE", &date)
+            .env("GIT_COMMITTER_DATE", &date)
+            .output()
+            .unwrap();
+        assert!(output.status.success(), "{output:?}");
+    }
+    repo
+}
+
+/// The stdout and stderr of `git-rare` run with `args`, which finds every
+/// commit rare.
+fn run(repo: &TestRepo, args: &[&str]) -> (String, String) {
+    let args = [&["--rule", "rare:^[0-9a-f]"], args].concat();
+    let output = repo.git_rare(&args);
+    assert!(output.status.success(), "{output:?}");
+    (
+        String::from_utf8(output.stdout).unwrap(),
+        String::from_utf8(output.stderr).unwrap(),
+    )
+}
+
+#[test]
+fn the_time_it_took_is_printed_to_stderr() {
+    let repo = timed_repo();
+    for backend in backends() {
+        let (stdout, stderr) = run(&repo, &["--backend", backend]);
+        assert!(stdout.contains("3 commits shown: 3 Rare"), "{stdout}");
+        assert!(!stdout.contains("took"), "{backend}: {stdout}");
+        assert!(
+            stderr.starts_with("This operation took"),
+            "{backend}: {stderr}"
+        );
+        assert_eq!(stderr.lines().count(), 1, "{backend}: {stderr}");
+    }
+    let (stdout, stderr) = run(&repo, &["--count"]);
+    assert!(!stdout.contains("took"), "{stdout}");
+    assert!(stderr.starts_with("This operation took"), "{stderr}");
+
+    for args in [["--quiet"], ["-q"]] {
+        let (stdout, stderr) = run(&repo, &args);
+        assert!(stdout.contains("3 commits shown: 3 Rare"), "{stdout}");
+        assert!(stderr.is_empty(), "{stderr}");
+    }
+    for format in ["json", "ndjson", "csv", "markdown", "plain"] {
+        let (stdout, stderr) = run(&repo, &["--format", format]);
+        assert!(!stdout.contains("took"), "{format}: {stdout}");
+        assert!(stderr.is_empty(), "{format}: {stderr}");
+    }
+}
+
+#[test]
+fn timings_report_each_phase_on_stderr() {
+    let repo = timed_repo();
+    for backend in backends() {
+        for format in ["table", "json"] {
+            let args = ["--backend", backend, "--format", format, "--timings"];
+            let (stdout, stderr) = run(&repo, &args);
+            assert!(
+                !stdout.contains("commits/s"),
+                "{backend}, {format}: {stdout}"
+            );
+            assert!(!stdout.contains("took"), "{backend}, {format}: {stdout}");
+            let report = stderr
+                .lines()
+                .filter(|line| !line.starts_with("This operation took"))
+                .collect::<Vec<&str>>();
+            let labels = report
+                .iter()
+                .map(|line| line.split_whitespace().next().unwrap())
+                .collect::<Vec<&str>>();
+            assert_eq!(labels, PHASES, "{backend}, {format}: {stderr}");
+            assert!(report[4].ends_with("(3 commits)"), "{stderr}");
+            assert!(report[4].contains(" commits/s"), "{stderr}");
+        }
+    }
+    // Quiet only drops the time after the table, not what was asked for.
+    let (_, stderr) = run(&repo, &["--quiet", "--timings"]);
+    assert!(!stderr.contains("This operation took"), "{stderr}");
+    assert!(stderr.starts_with("git "), "{stderr}");
+}
+
+#[test]
+fn classifying_piped_hashes_is_timed() {
+    let repo = timed_repo();
+    let hashes = repo.git(&["rev-list", "HEAD"]);
+    let output = common::git_rare_with_stdin(repo.path(), &["--stdin", "--timings", "-q"], &hashes);
+    assert!(output.status.success(), "{output:?}");
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    for phase in PHASES {
+        assert!(stderr.contains(&format!("{phase} ")), "{stderr}");
+    }
+    assert!(stderr.trim_end().ends_with("(3 commits)"), "{stderr}");
+}
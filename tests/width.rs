@@ -0,0 +1,126 @@
+mod common;
+
+use common::{backends, git_command, TestRepo};
+
+/// A repository with two commits by an author with a long name, which the
+/// rule of [`table`] explains at length.
+fn long_repo() -> TestRepo {
+    let repo = TestRepo::new();
+    for i in 0..2 {
+        let date = format!("2024-01-01T00:0{i}:00Z");
+        let output = git_command(repo.path())
+            .args([
+                "commit",
+                "-q",
+                "--allow-empty",
+                "-m",
+                &format!("commit {i}"),
+            ])
+            .env("GIT_AUTHOR_NAME", "Jonathan Smithington-Wells")
+            .env("GIT_AUTHOR_DATE", &date)
+            .env("GIT_COMMITTER_DATE", &date)
+            .output()
+            .unwrap();
+        assert!(output.status.success(), "{output:?}");
+    }
+    repo
+}
+
+/// The table printed for `args` with a rule that matches every commit,
+/// without its footer.
+fn table(repo: &TestRepo, args: &[&str]) -> String {
+    let rule = ["--rule", "rare:^([0-9a-f])"];
+    let args = [&rule[..], &["--no-footer"], args].concat();
+    repo.git_rare_stdout(&args)
+}
+
+const COLUMNS: [&str; 2] = ["--columns", "hash,author,explanation,tier"];
+
+#[test]
+fn explanations_are_cut_before_authors() {
+    let repo = long_repo();
+    let args = [&COLUMNS[..], &["--max-width", "60"]].concat();
+    assert_eq!(
+        table(&repo, &args),
+        "\
+╭──────────────────────────────────────────┬────────┬────────┬──────╮
+│ Hash                                     │ Author │ Expla… │ Tier │
+├──────────────────────────────────────────┼────────┼────────┼──────┤
+│ b807eee910b8bd4df505fd750ccf7b9c8cc364ae │ Jonat… │ ^([0-… │ Rare │
+│ 9c86c5ce79254b3ad7993adcf0918711f265c1a6 │ Jonat… │ ^([0-… │ Rare │
+╰──────────────────────────────────────────┴────────┴────────┴──────╯
+"
+    );
+    let args = [&COLUMNS[..], &["--max-width", "80"]].concat();
+    assert_eq!(
+        table(&repo, &args),
+        "\
+╭──────────────────────────────────────────┬───────────────────┬────────┬──────╮
+│ Hash                                     │ Author            │ Expla… │ Tier │
+├──────────────────────────────────────────┼───────────────────┼────────┼──────┤
+│ b807eee910b8bd4df505fd750ccf7b9c8cc364ae │ Jonathan Smithin… │ ^([0-… │ Rare │
+│ 9c86c5ce79254b3ad7993adcf0918711f265c1a6 │ Jonathan Smithin… │ ^([0-… │ Rare │
+╰──────────────────────────────────────────┴───────────────────┴────────┴──────╯
+"
+    );
+    let args = [&COLUMNS[..], &["--max-width", "120"]].concat();
+    assert_eq!(
+        table(&repo, &args),
+        "\
+╭──────────────────────────────────────────┬────────────────────────────┬──────────────────────────────┬──────╮
+│ Hash                                     │ Author                     │ Explanation                  │ Tier │
+├──────────────────────────────────────────┼────────────────────────────┼──────────────────────────────┼──────┤
+│ b807eee910b8bd4df505fd750ccf7b9c8cc364ae │ Jonathan Smithington-Wells │ ^([0-9a-f]): 'b' at offset 0 │ Rare │
+│ 9c86c5ce79254b3ad7993adcf0918711f265c1a6 │ Jonathan Smithington-Wells │ ^([0-9a-f]): '9' at offset 0 │ Rare │
+╰──────────────────────────────────────────┴────────────────────────────┴──────────────────────────────┴──────╯
+"
+    );
+}
+
+#[test]
+fn hashes_and_tiers_are_never_cut() {
+    let repo = long_repo();
+    for backend in backends() {
+        for width in ["60", "80", "120"] {
+            let stdout = table(&repo, &["--backend", backend, "--max-width", width]);
+            let rows = stdout
+                .lines()
+                .filter(|line| line.starts_with('│'))
+                .skip(1)
+                .collect::<Vec<&str>>();
+            assert_eq!(rows.len(), 2, "{stdout}");
+            for row in rows {
+                let cells = row.split('│').map(str::trim).collect::<Vec<&str>>();
+                assert_eq!(cells[3].len(), 40, "{backend} at {width}: {stdout}");
+                assert_eq!(cells[6], "Rare", "{backend} at {width}: {stdout}");
+            }
+        }
+    }
+    // At 120 the longest of the others only loses its end.
+    let stdout = table(&repo, &["--max-width", "120"]);
+    assert!(stdout.contains("│ Jonathan Smithington… │"), "{stdout}");
+    assert!(
+        stdout.lines().all(|line| line.chars().count() <= 120),
+        "{stdout}"
+    );
+}
+
+#[test]
+fn tables_are_whole_without_a_terminal() {
+    let repo = long_repo();
+    let stdout = table(&repo, &[]);
+    assert!(
+        stdout.contains("│ Jonathan Smithington-Wells │"),
+        "{stdout}"
+    );
+    assert!(!stdout.contains('…'), "{stdout}");
+    // Nor is more than the table cut.
+    let args = ["--all", "--max-width", "60", "--format", "csv"];
+    let csv = repo.git_rare_stdout(&args);
+    assert!(csv.contains(",Jonathan Smithington-Wells,"), "{csv}");
+
+    for width in ["0", "wide"] {
+        let output = repo.git_rare(&["--max-width", width]);
+        assert!(!output.status.success(), "{width}");
+    }
+}
@@ -0,0 +1,102 @@
+mod common;
+
+use std::path::{Path, PathBuf};
+
+use common::{backends, git_command, git_rare, TestRepo};
+
+/// Commits in the worktree at `dir`, with a date of its own so the hash is
+/// reproducible.
+fn commit_in(dir: &Path, message: &str, minute: u32) {
+    let date = format!("2024-02-01T00:{minute:02}:00Z");
+    let output = git_command(dir)
+        .args(["commit", "-q", "--allow-empty", "-m", message])
+        .env("GIT_AUTHOR_DATE", &date)
+        .env("GIT_COMMITTER_DATE", &date)
+        .output()
+        .unwrap();
+    assert!(output.status.success(), "{output:?}");
+}
+
+/// A repository with four commits on main, a linked worktree on a branch
+/// `side` with two more, and another whose detached HEAD has a commit no ref
+/// points to. Returns it with the paths of the two worktrees.
+fn worktree_repo() -> (TestRepo, PathBuf, PathBuf) {
+    let repo = TestRepo::new();
+    for i in 0..4 {
+        repo.commit(&format!("main {i}"));
+    }
+    let linked = repo.path().join("worktrees/linked");
+    let detached = repo.path().join("worktrees/detached");
+    let path = |path: &Path| path.to_str().unwrap().to_string();
+    repo.git(&[
+        "worktree",
+        "add",
+        "-q",
+        "-b",
+        "side",
+        &path(&linked),
+        "HEAD~1",
+    ]);
+    repo.git(&["worktree", "add", "-q", "--detach", &path(&detached)]);
+    commit_in(&linked, "side 0", 0);
+    commit_in(&linked, "side 1", 1);
+    commit_in(&detached, "detached", 2);
+    (repo, linked, detached)
+}
+
+/// The output of git-rare run in `dir` with `args`.
+fn scan(dir: &Path, args: &[&str]) -> String {
+    let output = git_rare(dir, args);
+    assert!(output.status.success(), "{args:?}: {output:?}");
+    String::from_utf8(output.stdout).unwrap()
+}
+
+#[test]
+fn a_linked_worktree_scans_its_own_head() {
+    let (repo, linked, _) = worktree_repo();
+    let expected = git_command(&linked)
+        .args(["rev-list", "--count", "HEAD"])
+        .output()
+        .unwrap();
+    let expected = String::from_utf8(expected.stdout).unwrap();
+    assert_eq!(expected.trim(), "5");
+    let linked_path = linked.to_str().unwrap();
+    for backend in backends() {
+        let args = ["--backend", backend, "--rule", "legendary:.", "--all"];
+        let inside = scan(&linked, &args);
+        assert_eq!(
+            inside.matches("│ Legendary").count(),
+            5,
+            "{backend}:\n{inside}"
+        );
+        let from_main = [&args[..], &["-C", linked_path]].concat();
+        assert_eq!(scan(repo.path(), &from_main), inside, "{backend}");
+        let git_file = linked.join(".git");
+        let git_dir = [&args[..], &["--git-dir", git_file.to_str().unwrap()]].concat();
+        assert_eq!(scan(repo.path(), &git_dir), inside, "{backend}");
+    }
+}
+
+#[test]
+fn every_worktree_sees_the_same_refs() {
+    let (repo, linked, detached) = worktree_repo();
+    let scans: [&[&str]; 3] = [&["--all-refs"], &["--reflog"], &["--lost"]];
+    for backend in backends() {
+        for flags in scans {
+            let args = [
+                &["--backend", backend, "--rule", "legendary:.", "--count"][..],
+                flags,
+            ]
+            .concat();
+            let expected = scan(repo.path(), &args);
+            for dir in [&linked, &detached] {
+                assert_eq!(scan(dir, &args), expected, "{backend} {flags:?} in {dir:?}");
+            }
+        }
+        // The detached worktree's commit is reachable from its HEAD alone.
+        let args = ["--backend", backend, "--count", "--all-refs"];
+        assert!(scan(repo.path(), &args).contains("│ 7 "), "{backend}");
+        let args = ["--backend", backend, "--lost"];
+        assert!(scan(repo.path(), &args).contains("No lost commits found."));
+    }
+}
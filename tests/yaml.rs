@@ -0,0 +1,184 @@
+mod common;
+
+use std::fs;
+
+use serde::Deserialize;
+
+use common::{backends, git_command, TestRepo};
+
+#[derive(Deserialize, Debug, PartialEq)]
+struct Document {
+    parameters: Parameters,
+    commits: Vec<Commit>,
+    counts: Counts,
+}
+
+#[derive(Deserialize, Debug, PartialEq)]
+struct Parameters {
+    revisions: Vec<String>,
+    all: bool,
+    rules: Vec<String>,
+}
+
+#[derive(Deserialize, Debug, PartialEq)]
+struct Commit {
+    hash: String,
+    author: Option<String>,
+    datetime: Option<String>,
+    tier: String,
+    explanation: String,
+    probability: Option<f64>,
+}
+
+#[derive(Deserialize, Debug, PartialEq)]
+struct Counts {
+    total: u64,
+    common: u64,
+    uncommon: u64,
+    rare: u64,
+    legendary: u64,
+    shallow: bool,
+}
+
+const AUTHOR: &str = "Doe: \"JD\" #1, O'Reilly & [co] - yes";
+
+/// A repository with two commits by an author whose name YAML would read as
+/// something else unquoted, and a config with a rule whose explanation spans
+/// lines.
+fn quoted_repo() -> (TestRepo, Vec<String>) {
+    let repo = TestRepo::new();
+    let mut hashes = vec![];
+    for i in 0..2 {
+        let date = format!("2024-01-01T00:0{i}:00Z");
+        let output = git_command(repo.path())
+            .args(["commit", "-q", "--allow-empty", "-m", "quoted"])
+            .env("GIT_AUTHOR_NAME", AUTHOR)
+            .env("GIT_AUTHOR_DATE", &date)
+            .env("GIT_COMMITTER_DATE", &date)
+            .output()
+            .unwrap();
+        assert!(output.status.success(), "{output:?}");
+        hashes.push(repo.git(&["rev-parse", "HEAD"]));
+    }
+    let config = repo.path().join(".no-config/git-rare");
+    fs::create_dir_all(&config).unwrap();
+    fs::write(
+        config.join("config.toml"),
+        "[[rules]]\nname = \"all\"\npattern = \"^\"\ntier = \"rare\"\n\
+         explanation = \"key: \\\"value\\\"\\n- not a list\"\nprobability = 1.52587890625e-5\n",
+    )
+    .unwrap();
+    (repo, hashes)
+}
+
+#[test]
+fn commits_read_back_as_they_were_found() {
+    let (repo, hashes) = quoted_repo();
+    for backend in backends() {
+        let args = ["--backend", backend, "--all"];
+        let yaml = repo.git_rare_stdout(&[&["--format", "yaml"], &args[..]].concat());
+        let document: Document =
+            serde_yaml::from_str(&yaml).unwrap_or_else(|e| panic!("{e}: {yaml}"));
+        let json = repo.git_rare_stdout(&[&["--format", "json"], &args[..]].concat());
+        assert_eq!(
+            document,
+            serde_json::from_str::<Document>(&json).unwrap(),
+            "{backend}"
+        );
+
+        let listed = document
+            .commits
+            .iter()
+            .map(|commit| commit.hash.as_str())
+            .collect::<Vec<&str>>();
+        assert_eq!(
+            listed,
+            [hashes[1].as_str(), hashes[0].as_str()],
+            "{backend}"
+        );
+        let first = &document.commits[1];
+        assert_eq!(first.author.as_deref(), Some(AUTHOR), "{backend}");
+        assert_eq!(
+            first.datetime.as_deref(),
+            Some("2024-01-01T00:00:00Z"),
+            "{backend}"
+        );
+        assert_eq!(first.tier, "Rare", "{backend}");
+        assert_eq!(
+            first.explanation, "key: \"value\"\n- not a list",
+            "{backend}"
+        );
+        assert!(first.probability.unwrap() <= 1.52587890625e-5, "{backend}");
+        assert!(document.parameters.revisions.is_empty(), "{backend}");
+        assert_eq!(document.parameters.rules, ["^"], "{backend}");
+        assert!(document.parameters.all, "{backend}");
+        assert_eq!(document.counts.total, 2, "{backend}");
+        assert!(!document.counts.shallow, "{backend}");
+    }
+}
+
+#[test]
+fn strings_read_back_as_strings() {
+    let repo = TestRepo::new();
+    for (i, name) in ["true", "null", "1.0"].into_iter().enumerate() {
+        let date = format!("2024-01-01T00:0{i}:00Z");
+        let output = git_command(repo.path())
+            .args(["commit", "-q", "--allow-empty", "-m", name])
+            .env("GIT_AUTHOR_NAME", name)
+            .env("GIT_AUTHOR_DATE", &date)
+            .env("GIT_COMMITTER_DATE", &date)
+            .output()
+            .unwrap();
+        assert!(output.status.success(), "{output:?}");
+    }
+    let yaml = repo.git_rare_stdout(&["--format", "yaml", "--all"]);
+    let document: Document = serde_yaml::from_str(&yaml).unwrap_or_else(|e| panic!("{e}: {yaml}"));
+    let authors = document
+        .commits
+        .iter()
+        .map(|commit| commit.author.as_deref())
+        .collect::<Vec<Option<&str>>>();
+    assert_eq!(authors, [Some("1.0"), Some("null"), Some("true")], "{yaml}");
+    assert!(
+        document
+            .commits
+            .iter()
+            .all(|commit| commit.datetime.is_some()),
+        "{yaml}"
+    );
+}
+
+#[test]
+fn count_prints_only_the_summary() {
+    let (repo, _) = quoted_repo();
+    let output = repo.git_rare(&["--format", "yaml", "--count"]);
+    assert!(output.status.success(), "{output:?}");
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(!stdout.contains("This operation took"), "{stdout}");
+    let counts: Counts = serde_yaml::from_str(&stdout).unwrap();
+    assert_eq!(
+        counts,
+        Counts {
+            total: 2,
+            common: 0,
+            uncommon: 0,
+            rare: 2,
+            legendary: 0,
+            shallow: false,
+        }
+    );
+}
+
+#[test]
+fn nothing_found_is_an_empty_list() {
+    let repo = TestRepo::new();
+    repo.commit("initial");
+    let output = repo.git_rare(&["--format", "yaml", "--only", "legendary"]);
+    assert!(output.status.success(), "{output:?}");
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(!stdout.contains("This operation took"), "{stdout}");
+    assert!(stdout.contains("\ncommits: []\n"), "{stdout}");
+    let document: Document = serde_yaml::from_str(&stdout).unwrap();
+    assert!(document.commits.is_empty());
+    assert_eq!(document.counts.total, 1);
+}